@@ -0,0 +1,74 @@
+use components_lib::core::documents::StoredDocument;
+use serde::{Deserialize, Serialize};
+
+use crate::preferences::local_storage;
+
+/// localStorage key holding the last autosave
+const AUTOSAVE_KEY: &str = "collab_hub.autosave";
+/// localStorage key holding when the user last saved, in ms since the epoch
+const LAST_SAVE_KEY: &str = "collab_hub.last_save";
+/// localStorage key overriding how often to autosave, in seconds
+const AUTOSAVE_INTERVAL_KEY: &str = "collab_hub.autosave_interval";
+const DEFAULT_AUTOSAVE_INTERVAL_SECS: u32 = 30;
+
+/// Every document that had unsaved changes at the time of an autosave
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Autosave {
+    /// ms since the epoch
+    pub saved_at: f64,
+    pub documents: Vec<StoredDocument>,
+}
+
+impl Autosave {
+    /// When the autosave happened, in the user's locale
+    pub fn saved_at_label(&self) -> String {
+        js_sys::Date::new(&self.saved_at.into())
+            .to_locale_string("default", &wasm_bindgen::JsValue::UNDEFINED)
+            .into()
+    }
+}
+
+pub fn autosave_interval_ms() -> i32 {
+    let secs = local_storage()
+        .and_then(|storage| storage.get_item(AUTOSAVE_INTERVAL_KEY).ok().flatten())
+        .and_then(|value| value.parse::<u32>().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_AUTOSAVE_INTERVAL_SECS);
+
+    secs.saturating_mul(1000).min(i32::MAX as u32) as i32
+}
+
+pub fn write_autosave(documents: Vec<StoredDocument>) {
+    let autosave = Autosave { saved_at: js_sys::Date::now(), documents };
+
+    if let (Some(storage), Ok(json)) = (local_storage(), serde_json::to_string(&autosave)) {
+        let _ = storage.set_item(AUTOSAVE_KEY, &json);
+    }
+}
+
+/// The last autosave, unless the user has saved explicitly since
+pub fn load_autosave() -> Option<Autosave> {
+    let storage = local_storage()?;
+    let autosave: Autosave = serde_json::from_str(&storage.get_item(AUTOSAVE_KEY).ok()??).ok()?;
+    let last_save = storage
+        .get_item(LAST_SAVE_KEY).ok().flatten()
+        .and_then(|value| value.parse::<f64>().ok())
+        .unwrap_or(0.0);
+
+    (autosave.saved_at > last_save && !autosave.documents.is_empty()).then_some(autosave)
+}
+
+pub fn discard_autosave() {
+    if let Some(storage) = local_storage() {
+        let _ = storage.remove_item(AUTOSAVE_KEY);
+    }
+}
+
+/// Record an explicit save, which makes the last autosave obsolete
+pub fn clear_autosave() {
+    discard_autosave();
+
+    if let Some(storage) = local_storage() {
+        let _ = storage.set_item(LAST_SAVE_KEY, &js_sys::Date::now().to_string());
+    }
+}
@@ -0,0 +1,85 @@
+use wasm_bindgen::prelude::*;
+use crate::code_editor_view::editor_textarea_id;
+
+/// The async Clipboard API, if the page may use it. Browsers leave it out of
+/// insecure contexts, and some only support writing.
+fn clipboard_with(method: &str) -> Option<web_sys::Clipboard> {
+    let clipboard = web_sys::window()?.navigator().clipboard();
+    if clipboard.is_undefined() || !js_sys::Reflect::has(&clipboard, &JsValue::from_str(method)).unwrap_or(false) {
+        return None;
+    }
+    Some(clipboard)
+}
+
+/// What to tell the user when the browser refuses clipboard access
+fn failure_message(err: JsValue) -> String {
+    let name = js_sys::Reflect::get(&err, &JsValue::from_str("name"))
+        .ok()
+        .and_then(|name| name.as_string());
+
+    if name.as_deref() == Some("NotAllowedError") {
+        "Clipboard access was denied. Use Ctrl+C, Ctrl+X and Ctrl+V instead.".to_string()
+    } else {
+        let message = js_sys::Reflect::get(&err, &JsValue::from_str("message"))
+            .ok()
+            .and_then(|message| message.as_string())
+            .unwrap_or_else(|| "unknown error".to_string());
+        format!("Couldn't use the clipboard: {message}")
+    }
+}
+
+fn settle(
+    promise: js_sys::Promise,
+    on_done: impl FnOnce(JsValue) + 'static,
+    on_failed: impl FnOnce(String) + 'static,
+) {
+    let done = Closure::once(on_done);
+    let failed = Closure::once(move |err: JsValue| on_failed(failure_message(err)));
+    let _ = promise.then2(&done, &failed);
+
+    // Only one of them ever runs, so neither can be dropped here
+    done.forget();
+    failed.forget();
+}
+
+/// Run the textarea's own `copy`, `cut` or `paste`, for browsers without the
+/// Clipboard API. Cutting this way goes through the textarea's input event.
+pub fn exec_command(command: &str) {
+    let _ = js_sys::eval(&format!(
+        "document.getElementById('{}')?.focus(); document.execCommand('{command}');",
+        editor_textarea_id()
+    ));
+}
+
+/// Put `text` on the clipboard. Returns false without trying if there's no
+/// Clipboard API to use.
+pub fn write_clipboard(
+    text: &str,
+    on_written: impl FnOnce() + 'static,
+    on_failed: impl FnOnce(String) + 'static,
+) -> bool {
+    let Some(clipboard) = clipboard_with("writeText") else {
+        return false;
+    };
+
+    settle(clipboard.write_text(text), move |_| on_written(), on_failed);
+    true
+}
+
+/// Read the clipboard's text. Returns false without trying if there's no
+/// Clipboard API to use.
+pub fn read_clipboard(
+    on_read: impl FnOnce(String) + 'static,
+    on_failed: impl FnOnce(String) + 'static,
+) -> bool {
+    let Some(clipboard) = clipboard_with("readText") else {
+        return false;
+    };
+
+    settle(
+        clipboard.read_text(),
+        move |text: JsValue| on_read(text.as_string().unwrap_or_default()),
+        on_failed,
+    );
+    true
+}
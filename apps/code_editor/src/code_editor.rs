@@ -2,13 +2,51 @@ use dioxus::prelude::*;
 use components_lib::editor::{
     editor_core::{
         Buffer,
+        BufferEncoding,
         CursorPosition,
+        Diagnostic,
+        Edit,
+        EditorSettings,
+        IndentStyle,
+        MONOSPACE_FONTS,
+        OpenDocument,
+        OpenDocuments,
+        Selection,
+        ValidationError,
+        bracket_jump,
+        encoding::{decode_latin1, decode_utf8, is_binary},
+        find_all,
+        detect_language,
+        find_suspicious_chars,
+        is_prose,
+        language_from_filename,
+        next_zoom,
+        template_for,
+        validator_for,
+    },
+    dialogs::{
+        GoToLineDialog,
+        KeybindingsDialog,
+        NewFileDialog,
+        RestoreAutosaveDialog,
+        SettingsDialog,
+        ThemeContrastDialog,
     },
-    dialogs::file_dialog::NewFileDialog,
     panels::{
+        Banner,
+        DiffView,
+        FileTree,
+        FileTreeEntry,
+        FindBar,
+        PresencePanel,
+        folder_entries,
         StatusBar,
+        TabBar,
         menus::{
-            menu_config::get_default_editor_menus,
+            ContextMenu,
+            Keymap,
+            menu_commands,
+            menu_config::{get_default_editor_menus, get_editor_context_menu},
             menu::{
                 MenuBar,
                 MenuHandler,
@@ -16,8 +54,67 @@ use components_lib::editor::{
         }
     }
 };
-use components_lib::available_themes;
-use crate::code_editor_view::EditorView;
+use components_lib::available_themes_with;
+use components_lib::core::Theme;
+use components_lib::core::collab::{CollabMessage, RemoteCursor};
+use components_lib::core::documents::StoredDocument;
+use components_lib::core::users::User;
+use crate::autosave::{
+    autosave_interval_ms,
+    clear_autosave,
+    discard_autosave,
+    load_autosave,
+    write_autosave,
+};
+use crate::collab::CollabSession;
+use crate::js::js_string;
+use crate::lint::lint_document;
+use crate::session::{clear_session, load_session, write_session, Session};
+use crate::server_docs::{
+    create_document,
+    doc_id_from_location,
+    load_document,
+    set_location_doc_id,
+    update_document,
+};
+use crate::clipboard::{exec_command, read_clipboard, write_clipboard};
+use crate::code_editor_view::{
+    EditorView,
+    editor_textarea_id,
+    keep_selection_mapped,
+    keep_selection_through,
+    select_in_textarea,
+    set_active_pane,
+};
+use crate::url_files::{fetch_url_file, file_name_from_url, file_url_from_location};
+use crate::recent_files::{
+    load_recent_files,
+    push_recent_file,
+    recent_local_file,
+    reopen_local_file,
+    save_recent_files,
+    RecentFile,
+    RecentSource,
+};
+use crate::preferences::{
+    picked_theme_index,
+    save_key_overrides,
+    save_settings,
+    save_theme,
+    saved_key_overrides,
+    saved_settings,
+    saved_themes,
+    theme_index_for,
+};
+use crate::operations::{
+    abort_operation,
+    register_abort_controller,
+    release_abort_controller,
+    OperationKind,
+    OperationTracker,
+};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::rc::Rc;
 use wasm_bindgen::{
     prelude::*,
     JsCast,
@@ -25,23 +122,146 @@ use wasm_bindgen::{
 
 
 
+/// How long after a remote user's last edit they still show as editing
+const EDITING_INDICATOR_MS: i32 = 3000;
+
+/// Documents longer than this many chars wait for a pause in typing before
+/// they're searched
+const SEARCH_DEBOUNCE_CHARS: usize = 200_000;
+const SEARCH_DEBOUNCE_MS: i32 = 200;
+
+/// How long typing has to pause before the document is checked for parse errors
+const VALIDATE_DEBOUNCE_MS: i32 = 300;
+
+/// How long typing has to pause before the server lints the document. Longer
+/// than for parsing, linting can mean running a compiler.
+const LINT_DEBOUNCE_MS: i32 = 1000;
+
+/// How long a message stays in the status bar
+const STATUS_MESSAGE_MS: i32 = 2000;
+
+/// How long the tabs have to stay unchanged before they're saved for next time
+const SESSION_DEBOUNCE_MS: i32 = 1000;
+
+/// Menu shortcuts the textarea already handles itself, left to the browser
+/// and not rebindable
+const NATIVE_SHORTCUTS: [&str; 3] = ["edit.cut", "edit.copy", "edit.paste"];
+
+/// What Save All tells the user once it's done
+fn save_all_summary(saved: u32, total: u32) -> String {
+    let files = if total == 1 { "file" } else { "files" };
+    if saved == total {
+        format!("Saved {total} {files}")
+    } else {
+        format!("Saved {saved} of {total} {files}")
+    }
+}
+
+/// Ask the user before closing `documents` if any of them have unsaved changes
+fn confirm_closing(documents: &[&OpenDocument]) -> bool {
+    let unsaved: Vec<String> = documents
+        .iter()
+        .filter(|document| document.has_unsaved_changes())
+        .map(|document| document.title())
+        .collect();
+
+    let message = match unsaved.as_slice() {
+        [] => return true,
+        [title] => format!("{title} has unsaved changes. Close it anyway?"),
+        titles => format!("{} documents have unsaved changes: {}. Close them anyway?", titles.len(), titles.join(", ")),
+    };
+
+    let window = web_sys::window().expect("no global window exists");
+    window.confirm_with_message(&message).unwrap_or(false)
+}
+
+/// Hand `contents` to the browser as a download named `filename`
+fn download_text(filename: &str, contents: &str) {
+    // Create a Blob and download link
+    let js_code = "
+        (function() {
+            const blob = new Blob([window._contentToSave], {type: 'text/plain'});
+            const url = URL.createObjectURL(blob);
+            const a = document.createElement('a');
+            a.href = url;
+            a.download = window._suggestedFilename;
+            document.body.appendChild(a);
+            a.click();
+            document.body.removeChild(a);
+            URL.revokeObjectURL(url);
+        })();
+    ";
+
+    // Set up global variables for the JavaScript to use
+    let window = web_sys::window().expect("no global window exists");
+    let window_any = window.dyn_into::<js_sys::Object>().expect("window should be an object");
+
+    js_sys::Reflect::set(
+        &window_any,
+        &JsValue::from_str("_contentToSave"),
+        &JsValue::from_str(contents)
+    ).expect("Failed to set content");
+
+    js_sys::Reflect::set(
+        &window_any,
+        &JsValue::from_str("_suggestedFilename"),
+        &JsValue::from_str(filename)
+    ).expect("Failed to set filename");
+
+    // Execute the JavaScript
+    let _ = js_sys::eval(js_code);
+}
+
+/// The View menu's on/off switches that aren't saved settings
+#[derive(Clone, Copy, PartialEq)]
+pub struct ViewToggles {
+    pub reveal_hidden_chars: Signal<bool>,
+    pub render_whitespace: Signal<bool>,
+    /// Follows the active document's language unless toggled
+    pub spellcheck: Signal<bool>,
+    pub split_editor: Signal<bool>,
+    /// Whether split panes sit one above the other rather than side by side
+    pub stack_panes: Signal<bool>,
+}
+
+impl Default for ViewToggles {
+    fn default() -> Self {
+        Self {
+            reveal_hidden_chars: Signal::new(false),
+            render_whitespace: Signal::new(false),
+            spellcheck: Signal::new(false),
+            split_editor: Signal::new(false),
+            stack_panes: Signal::new(false),
+        }
+    }
+}
+
 /// Handles the Editor menu components State and Events
 #[derive(Clone, PartialEq)]
 pub struct EditorMenuHandler {
     // State fields for menu operations
     pub buffer_has_changes: bool,
+    // Whether any open document has changes, not just the active one
+    pub any_buffer_has_changes: bool,
     pub has_filename: bool,
+    pub read_only: bool,
     pub theme_is_light: Signal<bool>, 
     pub theme_is_dark: Signal<bool>,
+    pub view: ViewToggles,
+    pub settings: Signal<EditorSettings>,
 }
 
 impl Default for EditorMenuHandler {
     fn default() -> Self {
         Self {
             buffer_has_changes: false,
+            any_buffer_has_changes: false,
             has_filename: false,
+            read_only: false,
             theme_is_light: Signal::new(false),
             theme_is_dark: Signal::new(true),
+            view: ViewToggles::default(),
+            settings: Signal::new(EditorSettings::default()),
         }
     }
 }
@@ -49,12 +269,20 @@ impl Default for EditorMenuHandler {
 impl EditorMenuHandler {
     pub fn new(
         buffer_has_changes: bool,
+        any_buffer_has_changes: bool,
         has_filename: bool,
+        read_only: bool,
         theme_is_light: bool,
+        view: ViewToggles,
+        settings: Signal<EditorSettings>,
     ) -> Self {
         let mut new_handler = Self {
             buffer_has_changes,
+            any_buffer_has_changes,
             has_filename,
+            read_only,
+            view,
+            settings,
             ..Default::default()
         };
 
@@ -79,43 +307,151 @@ impl MenuHandler for EditorMenuHandler {
             "file.new" => {
                 let _ = js_sys::eval("window._editorActions && window._editorActions.newFile()");
             },
+            "file.new_scratch" => {
+                let _ = js_sys::eval("window._editorActions && window._editorActions.newScratch()");
+            },
             "file.open" => {
                 let _ = js_sys::eval("window._editorActions && window._editorActions.openFile()");
             },
+            "file.open_folder" => {
+                let _ = js_sys::eval("window._editorActions && window._editorActions.openFolder()");
+            },
             "file.save" => {
                 let _ = js_sys::eval("window._editorActions && window._editorActions.saveFile()");
             },
             "file.save_as" => {
                 let _ = js_sys::eval("window._editorActions && window._editorActions.saveFileAs()");
             },
+            "file.save_all" => {
+                let _ = js_sys::eval("window._editorActions && window._editorActions.saveAll()");
+            },
+            "file.clear_recent" => {
+                let _ = js_sys::eval("window._editorActions && window._editorActions.clearRecent()");
+            },
+            id if id.starts_with("file.open_recent.") => {
+                if let Ok(index) = id["file.open_recent.".len()..].parse::<usize>() {
+                    let _ = js_sys::eval(&format!("window._editorActions && window._editorActions.openRecent({index})"));
+                }
+            },
+            "file.save_to_server" => {
+                let _ = js_sys::eval("window._editorActions && window._editorActions.saveToServer()");
+            },
             "file.exit" => {
                 let _ = js_sys::eval("window.close();");
             },
+            "edit.undo" => {
+                let _ = js_sys::eval("window._editorActions && window._editorActions.undo(false)");
+            },
+            "edit.redo" => {
+                let _ = js_sys::eval("window._editorActions && window._editorActions.undo(true)");
+            },
             "edit.cut" => {
-                let _ = js_sys::eval("document.execCommand('cut');");
+                let _ = js_sys::eval("window._editorActions && window._editorActions.copySelection(true)");
             },
             "edit.copy" => {
-                let _ = js_sys::eval("document.execCommand('copy');");
+                let _ = js_sys::eval("window._editorActions && window._editorActions.copySelection(false)");
             },
             "edit.paste" => {
-                let _ = js_sys::eval("document.execCommand('paste');");
+                let _ = js_sys::eval("window._editorActions && window._editorActions.paste()");
+            },
+            "edit.select_all" => {
+                let _ = js_sys::eval(&format!("document.getElementById('{}')?.select();", editor_textarea_id()));
+            },
+            "edit.duplicate_lines" => {
+                let _ = js_sys::eval("window._editorActions && window._editorActions.duplicateLines()");
+            },
+            "edit.move_lines_up" => {
+                let _ = js_sys::eval("window._editorActions && window._editorActions.moveLines(true)");
             },
-            "view.theme.light" => {
-                if !*self.theme_is_light.read() {
-                    self.theme_is_light.set(true);
-                    self.theme_is_dark.set(false);
+            "edit.move_lines_down" => {
+                let _ = js_sys::eval("window._editorActions && window._editorActions.moveLines(false)");
+            },
+            "edit.find" => {
+                let _ = js_sys::eval("window._editorActions && window._editorActions.find()");
+            },
+            "edit.goto_line" => {
+                let _ = js_sys::eval("window._editorActions && window._editorActions.goToLine()");
+            },
+            "edit.goto_bracket" => {
+                let _ = js_sys::eval("window._editorActions && window._editorActions.goToBracket()");
+            },
+            "view.theme.light" if !*self.theme_is_light.read() => {
+                self.theme_is_light.set(true);
+                self.theme_is_dark.set(false);
 
-                    let _ = js_sys::eval("window._editorActions && window._editorActions.setTheme('light')");
-                }
+                let _ = js_sys::eval("window._editorActions && window._editorActions.setTheme('light')");
             },
-            "view.theme.dark" => {
-                if !*self.theme_is_dark.read() {
-                    self.theme_is_dark.set(true);
-                    self.theme_is_light.set(false);
+            "view.theme.dark" if !*self.theme_is_dark.read() => {
+                self.theme_is_dark.set(true);
+                self.theme_is_light.set(false);
 
-                    let _ = js_sys::eval("window._editorActions && window._editorActions.setTheme('dark')");
+                let _ = js_sys::eval("window._editorActions && window._editorActions.setTheme('dark')");
+            },
+            "view.import_theme" => {
+                let _ = js_sys::eval("window._editorActions && window._editorActions.importTheme()");
+            },
+            "view.export_theme" => {
+                let _ = js_sys::eval("window._editorActions && window._editorActions.exportTheme()");
+            },
+            "view.reveal_hidden_chars" => {
+                let reveal = !*self.view.reveal_hidden_chars.read();
+                self.view.reveal_hidden_chars.set(reveal);
+            },
+            id if id.starts_with("view.font.") => {
+                if let Some((_, family)) = id["view.font.".len()..].parse::<usize>().ok().and_then(|index| MONOSPACE_FONTS.get(index)) {
+                    self.settings.write().font.family = family.to_string();
                 }
             },
+            "view.font_bigger" => {
+                let font = self.settings.read().font.resized(1);
+                self.settings.write().font = font;
+            },
+            "view.font_smaller" => {
+                let font = self.settings.read().font.resized(-1);
+                self.settings.write().font = font;
+            },
+            "view.zoom_in" | "view.zoom_out" => {
+                let zoom = next_zoom(self.settings.read().zoom, action_id == "view.zoom_in");
+                self.settings.write().zoom = zoom;
+            },
+            "view.zoom_reset" => self.settings.write().zoom = EditorSettings::default().zoom,
+            "view.render_whitespace" => {
+                let render = !*self.view.render_whitespace.read();
+                self.view.render_whitespace.set(render);
+            },
+            "view.spellcheck" => {
+                let spellcheck = !*self.view.spellcheck.read();
+                self.view.spellcheck.set(spellcheck);
+
+                // The menu took focus. Giving it back brings back the caret and
+                // makes the browser check (or clear) the text straight away.
+                let _ = js_sys::eval(&format!("setTimeout(() => document.getElementById('{}')?.focus())", editor_textarea_id()));
+            },
+            "view.show_changes" => {
+                let _ = js_sys::eval("window._editorActions && window._editorActions.showChanges()");
+            },
+            "view.word_wrap" => {
+                let word_wrap = !self.settings.read().word_wrap;
+                self.settings.write().word_wrap = word_wrap;
+            },
+            "view.read_only" => {
+                let _ = js_sys::eval("window._editorActions && window._editorActions.toggleReadOnly()");
+            },
+            "view.minimap" => {
+                let show_minimap = !self.settings.read().show_minimap;
+                self.settings.write().show_minimap = show_minimap;
+            },
+            "view.split_editor" => {
+                let split = !*self.view.split_editor.read();
+                self.view.split_editor.set(split);
+            },
+            "view.stack_panes" => {
+                let stack = !*self.view.stack_panes.read();
+                self.view.stack_panes.set(stack);
+            },
+            "view.settings" => {
+                let _ = js_sys::eval("window._editorActions && window._editorActions.showSettings()");
+            },
             "help.about" => {
                 let _ = js_sys::eval(
                     "alert('Collab Hub - Code Editor\\nA lightweight code editor built with Rust, Dioxus, and WebAssembly.');"
@@ -129,6 +465,12 @@ impl MenuHandler for EditorMenuHandler {
         match item_id {
             // Disable Save if nothing has changed or no file is open
             "file.save" => self.buffer_has_changes && self.has_filename,
+            "file.save_all" => self.any_buffer_has_changes,
+            // Needs language support the editor doesn't have yet
+            "edit.goto_definition" => false,
+            "edit.undo" | "edit.redo" | "edit.cut" | "edit.paste"
+            | "edit.duplicate_lines" | "edit.move_lines_up" | "edit.move_lines_down" => !self.read_only,
+            "view.stack_panes" => *self.view.split_editor.read(),
             _ => true,
         }
     }
@@ -137,6 +479,18 @@ impl MenuHandler for EditorMenuHandler {
         match item_id {
             "view.theme.light" => Some(*self.theme_is_light.read()),
             "view.theme.dark" => Some(*self.theme_is_dark.read()),
+            "view.reveal_hidden_chars" => Some(*self.view.reveal_hidden_chars.read()),
+            "view.render_whitespace" => Some(*self.view.render_whitespace.read()),
+            "view.spellcheck" => Some(*self.view.spellcheck.read()),
+            id if id.starts_with("view.font.") => {
+                let index: usize = id["view.font.".len()..].parse().ok()?;
+                MONOSPACE_FONTS.get(index).map(|(_, family)| self.settings.read().font.family == *family)
+            },
+            "view.word_wrap" => Some(self.settings.read().word_wrap),
+            "view.read_only" => Some(self.read_only),
+            "view.minimap" => Some(self.settings.read().show_minimap),
+            "view.split_editor" => Some(*self.view.split_editor.read()),
+            "view.stack_panes" => Some(*self.view.stack_panes.read()),
             _ => None,
         }
     }
@@ -145,196 +499,1071 @@ impl MenuHandler for EditorMenuHandler {
 #[component]
 pub fn CodeEditor() -> Element {
     // Application State
-    let mut buffer = use_signal(|| Buffer::new());
-    let mut cursor_position = use_signal(|| CursorPosition::default());
-    let mut filename = use_signal(|| None::<String>);
-    let mut language = use_signal(|| Some("plaintext".to_string()));
-    let mut file_handle = use_signal(|| None::<web_sys::FileSystemFileHandle>);
+    // The tabs left open last time, reopened unless the user turned that off
+    let restored_session = use_hook(|| {
+        Rc::new(saved_settings().restore_session.then(load_session).flatten().map(Session::restore))
+    });
+    let restored = restored_session.is_some();
+    let mut documents = use_signal(|| {
+        restored_session.as_ref().as_ref().map(|(documents, _)| documents.clone()).unwrap_or_default()
+    });
+    let mut cursor_position = use_signal(CursorPosition::default);
+    let mut selection = use_signal(Selection::default);
+    // Where each open document lives, keyed by document id
+    let mut file_handles = use_signal(HashMap::<u32, web_sys::FileSystemFileHandle>::new);
+    // The folder open in the sidebar, the entries of each of its folders read
+    // so far by path, and which of them are expanded
+    let mut folder_handle = use_signal(|| None::<web_sys::FileSystemDirectoryHandle>);
+    let mut folder_tree = use_signal(HashMap::<String, Vec<FileTreeEntry>>::new);
+    let mut expanded_folders = use_signal(BTreeSet::<String>::new);
+    let mut server_doc_ids = use_signal(|| {
+        restored_session.as_ref().as_ref().map(|(_, server_ids)| server_ids.clone()).unwrap_or_default()
+    });
+    // The document the pending save writes out
+    let mut operation_document = use_signal(|| None::<u32>);
+    let mut opened_file_handle = use_signal(|| None::<web_sys::FileSystemFileHandle>);
+    // The last document opened with invalid UTF-8, along with its original bytes
+    let mut invalid_utf8_document = use_signal(|| None::<(u32, Vec<u8>)>);
+    // Why the file from the `?file=` URL couldn't be opened
+    let mut url_file_error = use_signal(|| None::<String>);
+    // How the last Save All went
+    let mut save_all_report = use_signal(|| None::<String>);
+    // Why the last menu cut, copy or paste couldn't reach the clipboard
+    let mut clipboard_error = use_signal(|| None::<String>);
     let mut show_new_file_dialog = use_signal(|| false);
-    let menu_items = get_default_editor_menus();
+    let mut show_goto_line_dialog = use_signal(|| false);
+    // Why the last thing asked for couldn't be done, shown in the status bar
+    // until the timeout with the same generation clears it
+    let mut status_message = use_signal(|| None::<String>);
+    let mut status_message_generation = use_signal(|| 0u32);
+    let mut show_diff_view = use_signal(|| false);
+    let mut show_find_bar = use_signal(|| false);
+    // The term being highlighted, which lags behind the find bar while a
+    // large document waits out the debounce
+    let mut search_term = use_signal(String::new);
+    // Index into `search_matches` of the match the find bar is on
+    let mut active_match = use_signal(|| None::<usize>);
+    // Bumped on each keystroke in the find bar, so only the last one searches
+    let mut search_generation = use_signal(|| 0u32);
+    // Whether the document with this id parsed when it was last checked
+    let mut validation = use_signal(|| None::<(u32, Result<(), ValidationError>)>);
+    // Bumped on each change to the active document, so only the last one is checked
+    let mut validation_generation = use_signal(|| 0u32);
+    // What the server's linters found in the document with this id when it was last linted
+    let mut diagnostics = use_signal(|| None::<(u32, Vec<Diagnostic>)>);
+    // Bumped on each change to the active document, so only the last one is linted
+    let mut lint_generation = use_signal(|| 0u32);
+    // Languages the server can't lint, which aren't sent again
+    let mut unlintable_languages = use_signal(HashSet::<String>::new);
+    // Bumped on every change to the tabs, so only the last of a burst is saved
+    let mut session_generation = use_signal(|| 0u32);
+    let mut dragging_files = use_signal(|| false);
+    // Where the editor's context menu is open, in viewport coordinates
+    let mut context_menu_at = use_signal(|| None::<(f64, f64)>);
+    let reveal_hidden_chars = use_signal(|| false);
+    let render_whitespace = use_signal(|| false);
+    let mut spellcheck = use_signal(|| false);
+    let mut settings = use_signal(saved_settings);
+    let mut show_settings_dialog = use_signal(|| false);
+    // Shortcuts the user rebound, by command id
+    let mut key_overrides = use_signal(saved_key_overrides);
+    let mut show_keybindings_dialog = use_signal(|| false);
+    let split_editor = use_signal(|| false);
+    let stack_panes = use_signal(|| false);
+    // The document each pane shows. The second pane is only shown while split.
+    let mut pane_documents = use_signal(|| [0u32; 2]);
+    // The pane showing the active document, which the editor's commands act on
+    let mut active_pane = use_signal(|| 0usize);
+    let mut remote_cursors = use_signal(Vec::<RemoteCursor>::new);
+    // Everyone in the collaboration room, and when each last sent an edit
+    let mut connected_users = use_signal(Vec::<User>::new);
+    let mut last_edit_at = use_signal(HashMap::<String, f64>::new);
+    let mut operations = use_signal(OperationTracker::default);
+    let mut recent_files = use_signal(load_recent_files);
+    // Work left unsaved last time, until the user restores or discards it.
+    // Reopened tabs already have it.
+    let mut restorable_autosave = use_signal(|| if restored { None } else { load_autosave() });
+    let recent_names: Vec<String> = recent_files.read().iter().map(|recent| recent.name.clone()).collect();
+    // Folders can only be opened where the browser can list them
+    let can_open_folders = use_hook(|| {
+        js_sys::eval("'showDirectoryPicker' in window").unwrap_or(JsValue::FALSE).as_bool().unwrap_or(false)
+    });
+    let mut menu_items = get_default_editor_menus(&recent_names, can_open_folders);
+    // The shortcuts the menus come with, then the ones the user picked instead
+    let default_keymap = Keymap::from_menus(&menu_items);
+    let keymap = default_keymap.clone().with_overrides(&key_overrides.read());
+    keymap.label_menus(&mut menu_items);
+    let key_commands = menu_commands(&menu_items);
 
     // Theme State
-    let themes = available_themes();
-    let current_theme_idx = use_signal(|| 0);
+    // Themes the user imported are appended to the built-in ones
+    let mut themes = use_signal(|| available_themes_with(&saved_themes()));
+    // An imported theme with low contrast colors, until the user decides whether to use it
+    let mut low_contrast_theme = use_signal(|| None::<Theme>);
+    let mut current_theme_idx = use_signal(|| picked_theme_index(&themes.peek(), settings.peek().theme.as_deref()));
+
+    // Follow the system's light/dark preference as it changes
+    use_hook(|| {
+        let _ = js_sys::eval(r#"
+            window.matchMedia('(prefers-color-scheme: dark)').addEventListener('change', (event) => {
+                window._editorActions && window._editorActions.systemThemeChanged(event.matches);
+            });
+        "#);
+    });
+
+    // Warn before the tab closes while there are unsaved changes
+    use_hook(|| {
+        let _ = js_sys::eval(r#"
+            window.addEventListener('beforeunload', (event) => {
+                if (window._editorHasUnsavedChanges) {
+                    event.preventDefault();
+                    event.returnValue = '';
+                }
+            });
+        "#);
+    });
+
+    use_effect(move || {
+        let has_unsaved_changes = documents.read().has_unsaved_changes();
+        let window = web_sys::window().expect("no global window exists");
+        let _ = js_sys::Reflect::set(
+            &window,
+            &JsValue::from_str("_editorHasUnsavedChanges"),
+            &JsValue::from_bool(has_unsaved_changes)
+        );
+    });
+
+    // Periodically keep a copy of every document with unsaved changes
+    use_effect(move || {
+        let autosave = Closure::wrap(Box::new(move || {
+            let unsaved: Vec<StoredDocument> = documents
+                .read()
+                .documents()
+                .iter()
+                .filter(|document| document.has_unsaved_changes())
+                .map(|document| StoredDocument {
+                    filename: document.filename.clone(),
+                    language: document.language.clone(),
+                    content: document.buffer.text(),
+                })
+                .collect();
+
+            if !unsaved.is_empty() {
+                write_autosave(unsaved);
+            }
+        }) as Box<dyn FnMut()>);
+
+        let window = web_sys::window().expect("no global window exists");
+        let _ = window.set_interval_with_callback_and_timeout_and_arguments_0(
+            autosave.as_ref().unchecked_ref(),
+            autosave_interval_ms(),
+        );
+
+        // The timer runs for the rest of the page's life
+        autosave.forget();
+    });
+
+    // Prose gets spell checked, code doesn't. Switching to a document in
+    // another language undoes the View menu's override.
+    let active_language = use_memo(move || documents.read().active().language.clone());
+    use_effect(move || spellcheck.set(is_prose(&active_language.read())));
+
+    use_effect(move || save_settings(&settings.read()));
+    use_effect(move || save_key_overrides(&key_overrides.read()));
+    use_effect(move || save_recent_files(&recent_files.read()));
+
+    let document_stats = use_memo(move || documents.read().active().buffer.stats());
+
+    // The active pane shows whichever document is made active, and a pane
+    // whose document is closed falls back to it
+    use_effect(move || {
+        let documents = documents.read();
+        let active_id = documents.active().id;
+        let mut panes = *pane_documents.peek();
+
+        panes[*active_pane.peek()] = active_id;
+        for id in &mut panes {
+            if !documents.documents().iter().any(|document| document.id == *id) {
+                *id = active_id;
+            }
+        }
+        if panes != *pane_documents.peek() {
+            pane_documents.set(panes);
+        }
+    });
+
+    // Splitting opens the active document in the second pane as well, and
+    // unsplitting keeps it in the one pane left
+    use_effect(move || {
+        let active_id = documents.peek().active().id;
+        if split_editor() {
+            pane_documents.write()[1] = active_id;
+        } else {
+            pane_documents.write()[0] = active_id;
+            active_pane.set(0);
+            set_active_pane(0);
+        }
+    });
+
+    // Save the open tabs for next time once they've stopped changing, or
+    // forget them if the user doesn't want them back
+    use_effect(move || {
+        // Read for the rerun on every edit; the tabs are copied once it's time to save
+        let _ = (documents.read(), server_doc_ids.read());
+        let restore = settings.read().restore_session;
+        *session_generation.write() += 1;
+        let generation = *session_generation.peek();
+
+        if !restore {
+            clear_session();
+            return;
+        }
+
+        let write = Closure::once_into_js(move || {
+            if *session_generation.peek() == generation {
+                write_session(&Session::of(&documents.peek(), &server_doc_ids.peek()));
+            }
+        });
+        let window = web_sys::window().expect("no global window exists");
+        let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(write.unchecked_ref(), SESSION_DEBOUNCE_MS);
+    });
+
+    // Check that documents in a language with a validator parse, once typing
+    // pauses so large files aren't parsed on every keystroke
+    use_effect(move || {
+        // Reading the documents reruns this on every edit, not just on switching tabs
+        let (id, language) = {
+            let documents = documents.read();
+            let document = documents.active();
+            (document.id, document.language.clone())
+        };
+        *validation_generation.write() += 1;
+        let generation = *validation_generation.peek();
+
+        let Some(validator) = validator_for(&language) else {
+            validation.set(None);
+            return;
+        };
+
+        let validate = Closure::once_into_js(move || {
+            if *validation_generation.peek() != generation {
+                return;
+            }
+            let text = documents.peek().active().buffer.text();
+            validation.set(Some((id, validator.validate(&text))));
+        });
+        let window = web_sys::window().expect("no global window exists");
+        let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(validate.unchecked_ref(), VALIDATE_DEBOUNCE_MS);
+    });
+
+    // Have the server lint documents in languages without a validator here,
+    // once typing pauses
+    use_effect(move || {
+        let (id, language) = {
+            let documents = documents.read();
+            let document = documents.active();
+            (document.id, document.language.clone())
+        };
+        *lint_generation.write() += 1;
+        let generation = *lint_generation.peek();
+
+        let lintable = settings.read().server_linting
+            && validator_for(&language).is_none()
+            && !unlintable_languages.read().contains(&language);
+        if !lintable {
+            if diagnostics.peek().is_some() {
+                diagnostics.set(None);
+            }
+            return;
+        }
+
+        let lint = Closure::once_into_js(move || {
+            if *lint_generation.peek() != generation {
+                return;
+            }
+            let text = documents.peek().active().buffer.text();
+            lint_document(generation, id, &language, &text);
+        });
+        let window = web_sys::window().expect("no global window exists");
+        let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(lint.unchecked_ref(), LINT_DEBOUNCE_MS);
+    });
+
+    // Only recount the document when its text changes, not on every cursor move
+    let document_counts = use_memo(move || {
+        let documents = documents.read();
+        let buffer = &documents.active().buffer;
+        (buffer.word_count(), buffer.char_count())
+    });
+
+    // Join a collaboration room if the page was opened with one. The document
+    // open at the time is the one shared with the room.
+    let collab_document = use_hook(|| documents.peek().active().id);
+    let shared_text = move || {
+        documents.peek()
+            .documents()
+            .iter()
+            .find(|document| document.id == collab_document)
+            .map(|document| document.buffer.text())
+            .unwrap_or_default()
+    };
+    let collab = use_hook(|| CollabSession::from_location(shared_text, move |message| match message {
+        CollabMessage::Cursor { user, line, column } => {
+            let mut cursors = remote_cursors.write();
+            match cursors.iter_mut().find(|cursor| cursor.user.id == user.id) {
+                Some(cursor) => {
+                    cursor.line = line;
+                    cursor.column = column;
+                }
+                None => cursors.push(RemoteCursor { user, line, column }),
+            }
+        }
+        CollabMessage::Join { user } => {
+            let mut users = connected_users.write();
+            if !users.iter().any(|connected| connected.id == user.id) {
+                users.push(user);
+            }
+        }
+        CollabMessage::Presence { users } => connected_users.set(users),
+        CollabMessage::Leave { user_id } => {
+            remote_cursors.write().retain(|cursor| cursor.user.id != user_id);
+            connected_users.write().retain(|user| user.id != user_id);
+            last_edit_at.write().remove(&user_id);
+        }
+        CollabMessage::Edit { user_id, edit, .. } => {
+            last_edit_at.write().insert(user_id, js_sys::Date::now());
+
+            // Render again once the editing indicator has run out
+            let expire = Closure::once_into_js(move || {
+                last_edit_at.write();
+            });
+            let window = web_sys::window().expect("no global window exists");
+            let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(expire.unchecked_ref(), EDITING_INDICATOR_MS);
+
+            let mut documents = documents.write();
+            let is_active = documents.active().id == collab_document;
+            let Some(document) = documents.get_mut(collab_document) else {
+                return;
+            };
+
+            let old_text = document.buffer.text();
+            if document.buffer.apply(&edit).is_ok() && is_active {
+                keep_selection_through(&edit, &old_text, document.buffer.text());
+            }
+        }
+        // Handled by the session itself
+        CollabMessage::Ack { .. } | CollabMessage::Sync { .. } => {}
+    }));
+
+    let in_collab_room = collab.is_some();
+
+    // Send local changes to the shared document, however they were made
+    {
+        let collab = collab.clone();
+        use_effect(move || {
+            let text = documents.read()
+                .documents()
+                .iter()
+                .find(|document| document.id == collab_document)
+                .map(|document| document.buffer.text());
+
+            if let (Some(session), Some(text)) = (&collab, text) {
+                session.share_text(&text);
+            }
+        });
+    }
 
     // Event Handlers
-    let handle_buffer_change = move |new_buffer: Buffer| {
-        buffer.set(new_buffer);
+    // Either pane can edit, and the document it shows needn't be the active one
+    let mut handle_buffer_change = move |id: u32, new_buffer: Buffer| {
+        if let Some(document) = documents.write().get_mut(id) {
+            document.buffer = new_buffer;
+        }
+    };
+
+    // Make the document in the focused pane the active one, keeping the caret
+    // the pane reports next
+    let mut handle_focus_pane = move |pane: usize| {
+        set_active_pane(pane);
+        if *active_pane.peek() == pane {
+            return;
+        }
+        active_pane.set(pane);
+
+        let id = pane_documents.peek()[pane];
+        let index = documents.peek().documents().iter().position(|document| document.id == id);
+        if let Some(index) = index.filter(|index| *index != documents.peek().active_index()) {
+            documents.write().set_active(index);
+        }
+    };
+
+    let handle_select_tab = move |index: usize| {
+        documents.write().set_active(index);
+        cursor_position.set(CursorPosition::default());
+        selection.set(Selection::default());
+    };
+
+    // Let go of what was kept about documents that have been closed
+    let mut forget_closed = move |closed: &[OpenDocument]| {
+        for document in closed {
+            if invalid_utf8_document.read().as_ref().is_some_and(|(id, _)| *id == document.id) {
+                invalid_utf8_document.set(None);
+            }
+            file_handles.write().remove(&document.id);
+            server_doc_ids.write().remove(&document.id);
+        }
+    };
+
+    let handle_close_tab = move |index: usize| {
+        let Some(document) = documents.read().documents().get(index).cloned() else {
+            return;
+        };
+
+        if !confirm_closing(&[&document]) {
+            return;
+        }
+
+        documents.write().close(index);
+        cursor_position.set(CursorPosition::default());
+        selection.set(Selection::default());
+        forget_closed(&[document]);
+    };
+
+    // Close the tabs around the one at `index` that `close` closes, once the
+    // user agrees to lose any unsaved changes in them
+    let mut close_tabs = move |
+        index: usize,
+        closing: fn(&OpenDocuments, usize) -> Vec<&OpenDocument>,
+        close: fn(&mut OpenDocuments, usize) -> Vec<OpenDocument>,
+    | {
+        if !confirm_closing(&closing(&documents.read(), index)) {
+            return;
+        }
+
+        let active_id = documents.read().active().id;
+        let closed = close(&mut documents.write(), index);
+        if documents.read().active().id != active_id {
+            cursor_position.set(CursorPosition::default());
+            selection.set(Selection::default());
+        }
+        forget_closed(&closed);
     };
 
-    let handle_cursor_move = move |new_cursor: CursorPosition| {
+    let handle_cursor_move = use_callback(move |new_cursor: CursorPosition| {
         cursor_position.set(new_cursor);
+
+        if let Some(session) = &collab {
+            session.send(&CollabMessage::Cursor {
+                user: session.user.clone(),
+                line: new_cursor.line,
+                column: new_cursor.column,
+            });
+        }
+    });
+
+    let mut handle_selection_change = move |new_selection: Selection| {
+        selection.set(new_selection);
+    };
+
+    let handle_language_change = move |language: String| {
+        documents.write().active_mut().language = language;
+    };
+
+    let handle_jump_to_hidden_char = move |_: ()| {
+        let text = documents.read().active().buffer.text();
+        let hidden_chars = find_suspicious_chars(&text);
+        let cursor_offset = cursor_position().offset;
+
+        // Jump to the next one after the caret, wrapping around to the first
+        let next = hidden_chars.iter()
+            .find(|(offset, _)| *offset > cursor_offset)
+            .or(hidden_chars.first());
+
+        if let Some(&(offset, _)) = next {
+            select_in_textarea(&text, offset, offset + 1);
+        }
+    };
+
+    let handle_jump_to_error = move |_: ()| {
+        let validation = validation.read();
+        if let Some((_, Err(error))) = validation.as_ref() {
+            let text = documents.read().active().buffer.text();
+            select_in_textarea(&text, error.offset, error.offset + error.len);
+        }
     };
 
     let handle_new_file = use_callback(move |_| {
         show_new_file_dialog.set(true);
     });
 
-    let handle_create_file = use_callback(move |(new_filename, new_language): (String, String)| {
-        buffer.set(Buffer::new());
-        filename.set(Some(new_filename));
-        language.set(Some(new_language));
+    let handle_new_scratch = use_callback(move |_| {
+        documents.write().new_scratch();
+    });
+
+    let handle_create_file = use_callback(move |(new_filename, new_language, use_template): (String, String, bool)| {
         show_new_file_dialog.set(false);
+
+        let Some(template) = template_for(&new_language).filter(|_| use_template) else {
+            documents.write().new_document(Some(new_filename), new_language);
+            return;
+        };
+
+        let buffer = Buffer::from_str(&template.text, Some(new_filename.clone()));
+        documents.write().open(buffer, Some(new_filename), new_language);
+
+        // The new document's textarea only exists once the editor has rendered
+        let place_caret = Closure::once_into_js(move || {
+            select_in_textarea(&template.text, template.cursor, template.cursor);
+        });
+        let window = web_sys::window().expect("no global window exists");
+        let _ = window.set_timeout_with_callback(place_caret.unchecked_ref());
     });
 
     let handle_cancel_new_file = use_callback(move |_: ()| {
         show_new_file_dialog.set(false);
     });
 
-    let handle_open_file = use_callback(move |_: ()| {
+    let handle_goto_line = use_callback(move |_: ()| {
+        show_goto_line_dialog.set(true);
+    });
+
+    let handle_go_to = move |line: usize| {
+        show_goto_line_dialog.set(false);
+
+        let buffer = documents.read().active().buffer.clone();
+        let offset = buffer.line_start(line.saturating_sub(1));
+        select_in_textarea(&buffer.text(), offset, offset);
+    };
+
+    let mut flash_status = move |message: &str| {
+        status_message.set(Some(message.to_string()));
+        *status_message_generation.write() += 1;
+        let generation = *status_message_generation.peek();
+
+        let clear = Closure::once_into_js(move || {
+            if *status_message_generation.peek() == generation {
+                status_message.set(None);
+            }
+        });
         let window = web_sys::window().expect("no global window exists");
-        
-        // JavaScript to open a file using the File System Access API
-        let js_open = r#"
-        (async function() {
-            try {
-                // Check if the File System Access API is supported
-                if (!('showOpenFilePicker' in window)) {
-                    throw new Error('File System Access API not supported');
-                }
-                
-                const options = {
-                    types: [
-                        {
-                            description: 'Text Files',
-                            accept: {'text/plain': ['.txt', '.rs', '.js', '.html', '.css', '.md', '.json', '.toml', '.yaml', '.yml']}
-                        }
-                    ],
-                    multiple: false
-                };
-                
-                const [handle] = await window.showOpenFilePicker(options);
-                const file = await handle.getFile();
-                const contents = await file.text();
-                
-                // Store the file handle for later use
-                window._openedFileHandle = handle;
-                
-                // Determine language from extension
-                const ext = handle.name.split('.').pop().toLowerCase();
-                let lang = 'plain';
-                switch (ext) {
-                    case 'rs': lang = 'rust'; break;
-                    case 'js': lang = 'javascript'; break;
-                    case 'html': lang = 'html'; break;
-                    case 'css': lang = 'css'; break;
-                    case 'md': lang = 'markdown'; break;
-                    case 'json': lang = 'json'; break;
-                    case 'toml': lang = 'toml'; break;
-                    case 'yaml':
-                    case 'yml': lang = 'yaml'; break;
-                }
-                
-                return { success: true, name: handle.name, contents, language: lang, handle };
-            } catch (e) {
-                console.error("Error opening file:", e);
-                
-                // If File System Access API is not supported, fall back to file input
-                if (e.message === 'File System Access API not supported') {
-                    return { success: false, fallback: true, error: e.toString() };
-                }
-                
-                return { success: false, error: e.toString() };
+        let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(clear.unchecked_ref(), STATUS_MESSAGE_MS);
+    };
+
+    // Jump to the partner of the bracket at the caret, or to the start of the
+    // block the caret is in
+    let handle_goto_bracket = use_callback(move |_: ()| {
+        let text = documents.read().active().buffer.text();
+        match bracket_jump(&text, cursor_position().offset) {
+            Some(offset) => select_in_textarea(&text, offset, offset),
+            None => flash_status("No matching bracket"),
+        }
+    });
+
+    let search_matches = use_memo(move || documents.read().active().buffer.find_all(&search_term.read()));
+
+    let handle_find = use_callback(move |_: ()| {
+        show_find_bar.set(true);
+        let _ = js_sys::eval("document.getElementById('find-input')?.select()");
+    });
+
+    // Highlight `term`, starting from the first match after the caret
+    let mut apply_search = move |term: String| {
+        let text = documents.read().active().buffer.text();
+        let caret = cursor_position().offset;
+        let matches = find_all(&text, &term);
+
+        let first = matches.iter().position(|offset| *offset >= caret).or((!matches.is_empty()).then_some(0));
+        active_match.set(first);
+        search_term.set(term);
+    };
+
+    // Replace every match of the search term in the active document
+    let handle_replace_all = move |replacement: String| {
+        let term = search_term();
+        let replaced = {
+            let mut documents = documents.write();
+            let active = documents.active_mut();
+            if active.read_only {
+                return;
             }
-        })()
-        "#;
-        
-        // Execute the JavaScript
-        let _ = js_sys::eval(js_open);
-        
-        // Use a script to check results and call back to our Rust code
-        let document = window.document().expect("should have a document on window");
-        let script = document.create_element("script").expect("couldn't create script");
-        
-        script.set_text_content(Some(&format!(
-            r#"
-            (async function() {{
-                try {{
-                    const result = await {};
-                    
-                    if (result && result.success) {{
-                        // Call back to Rust with the file contents and info
-                        window._handleOpenedFile && window._handleOpenedFile(
-                            result.contents, 
-                            result.name,
-                            result.language
-                        );
-                        
+            active.buffer.replace_all(&term, &replacement)
+        };
+
+        active_match.set(None);
+        flash_status(&format!("Replaced {replaced} {}", if replaced == 1 { "match" } else { "matches" }));
+    };
+
+    let handle_search = move |term: String| {
+        *search_generation.write() += 1;
+        let generation = *search_generation.peek();
+
+        if term.is_empty() || documents.read().active().buffer.char_count() <= SEARCH_DEBOUNCE_CHARS {
+            apply_search(term);
+            return;
+        }
+
+        let search = Closure::once_into_js(move || {
+            if *search_generation.peek() == generation {
+                apply_search(term);
+            }
+        });
+        let window = web_sys::window().expect("no global window exists");
+        let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(search.unchecked_ref(), SEARCH_DEBOUNCE_MS);
+    };
+
+    // Select the match `step` away from the active one, wrapping around, and
+    // keep typing in the find bar
+    let mut step_match = move |step: isize| {
+        let matches = search_matches.read();
+        if matches.is_empty() {
+            return;
+        }
+
+        let index = match active_match() {
+            Some(index) if index < matches.len() => (index as isize + step).rem_euclid(matches.len() as isize) as usize,
+            _ => 0,
+        };
+        let start = matches[index];
+        drop(matches);
+
+        active_match.set(Some(index));
+        let text = documents.read().active().buffer.text();
+        select_in_textarea(&text, start, start + search_term.read().chars().count());
+        let _ = js_sys::eval("document.getElementById('find-input')?.focus()");
+    };
+
+    let handle_close_find = move |_: ()| {
+        *search_generation.write() += 1;
+        show_find_bar.set(false);
+        search_term.set(String::new());
+        active_match.set(None);
+        let _ = js_sys::eval(&format!("document.getElementById('{}')?.focus()", editor_textarea_id()));
+    };
+
+    // The lines the selection covers. One ending at the start of a line
+    // doesn't take that line with it.
+    let selected_lines = move || {
+        let selected = selection().normalized();
+        let mut end = selected.head.line;
+        if end > selected.anchor.line && selected.head.column == 0 {
+            end -= 1;
+        }
+        (selected.anchor.line, end)
+    };
+
+    // Apply a whole-line edit, then select the same text `line_delta` lines away
+    let mut edit_lines = move |line_delta: isize, edit: &dyn Fn(&mut Buffer) -> Result<(), String>| {
+        if documents.read().active().read_only {
+            return;
+        }
+
+        let mut buffer = documents.read().active().buffer.clone();
+        if edit(&mut buffer).is_err() {
+            return;
+        }
+
+        let selected = selection().normalized();
+        let place = |position: CursorPosition| {
+            let line = position.line.saturating_add_signed(line_delta);
+            buffer.char_idx_of(line, position.column).unwrap_or_else(|| buffer.line_start(line))
+        };
+        let (start, end) = (place(selected.anchor), place(selected.head));
+
+        let text = buffer.text();
+        documents.write().active_mut().buffer = buffer;
+        select_in_textarea(&text, start, end);
+    };
+
+    // The selection as a char range of the active document
+    let selected_range = move || selection().char_range();
+
+    // Copy the selection to the clipboard, deleting it afterwards if `cut`
+    let handle_copy_selection = move |cut: bool| {
+        let (start, end) = selected_range();
+        if start == end {
+            return;
+        }
+
+        let text = documents.read().active().buffer.text();
+        let selected: String = text.chars().skip(start).take(end - start).collect();
+        let id = documents.read().active().id;
+
+        let on_written = move || {
+            let (new_text, selection_end) = {
+                let mut documents = documents.write();
+                let Some(document) = documents.get_mut(id) else {
+                    return;
+                };
+
+                // Leave text that changed while the clipboard was being written
+                let unchanged = document.buffer.text() == text;
+                if cut && unchanged && !document.read_only && document.buffer.delete(start, end - start).is_ok() {
+                    (document.buffer.text(), start)
+                } else {
+                    (document.buffer.text(), end)
+                }
+            };
+
+            // The menu took focus, so hand it back along with the selection
+            select_in_textarea(&new_text, start, selection_end);
+        };
+
+        if !write_clipboard(&selected, on_written, move |message| clipboard_error.set(Some(message))) {
+            exec_command(if cut { "cut" } else { "copy" });
+        }
+    };
+
+    // Replace the selection with the clipboard's text
+    let handle_paste = move || {
+        let id = documents.read().active().id;
+
+        let on_read = move |pasted: String| {
+            let (start, end) = selected_range();
+            let mut documents = documents.write();
+            let active = documents.active_mut();
+            if active.id != id || active.read_only {
+                return;
+            }
+
+            let pasted = pasted.replace("\r\n", "\n");
+            if active.buffer.replace_range(start, end, &pasted).is_err() {
+                return;
+            }
+            let new_text = active.buffer.text();
+            drop(documents);
+
+            let caret = start + pasted.chars().count();
+            select_in_textarea(&new_text, caret, caret);
+        };
+
+        if !read_clipboard(on_read, move |message| clipboard_error.set(Some(message))) {
+            exec_command("paste");
+        }
+    };
+
+    // Take back the active document's last change, or make the last one
+    // taken back again if `redo`, with the caret where the text changed
+    let mut handle_undo = move |redo: bool| {
+        if documents.read().active().read_only {
+            return;
+        }
+
+        let mut buffer = documents.read().active().buffer.clone();
+        let old_text = buffer.text();
+        let stepped = if redo { buffer.redo() } else { buffer.undo() };
+        if stepped.is_err() {
+            return;
+        }
+
+        let text = buffer.text();
+        let caret = Edit::diff(&old_text, &text)
+            .iter()
+            .map(|edit| match edit {
+                Edit::Insert { pos, text } => pos + text.chars().count(),
+                Edit::Delete { pos, .. } => *pos,
+            })
+            .max()
+            .unwrap_or(0);
+
+        documents.write().active_mut().buffer = buffer;
+        select_in_textarea(&text, caret, caret);
+    };
+
+    let mut handle_duplicate_lines = move || {
+        let (start, end) = selected_lines();
+        edit_lines((end - start + 1) as isize, &|buffer| buffer.duplicate_lines(start, end));
+    };
+
+    let mut handle_move_lines = move |up: bool| {
+        let (start, end) = selected_lines();
+        let line_count = documents.read().active().buffer.line_count();
+        let moves = if up { start > 0 } else { end + 1 < line_count };
+        if !moves {
+            return;
+        }
+
+        edit_lines(if up { -1 } else { 1 }, &|buffer| buffer.move_lines(start, end, up));
+    };
+
+    let handle_reopen_as_latin1 = move |_: ()| {
+        let Some((id, bytes)) = invalid_utf8_document.write().take() else {
+            return;
+        };
+
+        if let Some(document) = documents.write().get_mut(id) {
+            document.buffer = Buffer::from_str(&decode_latin1(&bytes), document.filename.clone())
+                .with_encoding(BufferEncoding::Latin1);
+        }
+    };
+
+    let handle_restore_autosave = move |_: ()| {
+        let Some(autosave) = restorable_autosave.write().take() else {
+            return;
+        };
+
+        for document in autosave.documents {
+            // Restored work hasn't been saved anywhere yet
+            let mut buffer = Buffer::from_str("", document.filename.clone());
+            buffer.set_text(&document.content);
+            documents.write().open(buffer, document.filename, document.language);
+        }
+    };
+
+    let handle_discard_autosave = move |_: ()| {
+        restorable_autosave.set(None);
+        discard_autosave();
+    };
+
+    let handle_cancel_operation = move |_: ()| {
+        if let Some(token) = operations.write().cancel() {
+            abort_operation(token);
+        }
+    };
+
+    // Put a file's raw bytes in a tab, the shared end of every way of opening a file.
+    // The language is worked out from the file's name and contents unless given.
+    // Returns the document's id, or None if the file isn't text.
+    let mut open_bytes = move |bytes: Vec<u8>, name: String, lang: Option<String>| -> Option<u32> {
+        if is_binary(&bytes) {
+            let window = web_sys::window().expect("no global window exists");
+            let _ = window.alert_with_message(&format!("{name} looks like a binary file and can't be opened as text."));
+            return None;
+        }
+
+        let (content, encoding) = decode_utf8(&bytes);
+        let lang = lang.unwrap_or_else(|| detect_language(Some(&name), &content));
+        let buffer = Buffer::from_str(&content, Some(name.clone())).with_encoding(encoding);
+        let id = documents.write().open(buffer, Some(name), lang);
+
+        // Keep the raw bytes so the file can be reopened as Latin-1
+        if encoding == BufferEncoding::Utf8Lossy {
+            invalid_utf8_document.set(Some((id, bytes)));
+        }
+
+        Some(id)
+    };
+
+    // Dropped files each open in their own tab
+    let mut handle_file_dropped = move |contents: js_sys::Uint8Array, name: String| {
+        if let Some(id) = open_bytes(contents.to_vec(), name, None) {
+            file_handles.write().remove(&id);
+            server_doc_ids.write().remove(&id);
+        }
+    };
+
+    // Accept files dragged anywhere onto the page
+    use_hook(|| {
+        let _ = js_sys::eval(r#"
+            (function() {
+                const hasFiles = (event) => event.dataTransfer && event.dataTransfer.types.includes('Files');
+                const actions = () => window._editorActions;
+                // dragenter/dragleave fire for every child element, so count them
+                let depth = 0;
+
+                window.addEventListener('dragenter', (event) => {
+                    if (!hasFiles(event)) return;
+                    depth++;
+                    actions() && actions().setDragging(true);
+                });
+                window.addEventListener('dragleave', (event) => {
+                    if (!hasFiles(event)) return;
+                    depth = Math.max(depth - 1, 0);
+                    if (depth === 0) {
+                        actions() && actions().setDragging(false);
+                    }
+                });
+                window.addEventListener('dragover', (event) => {
+                    // Without this the browser navigates to the file instead
+                    if (hasFiles(event)) event.preventDefault();
+                });
+                window.addEventListener('drop', async (event) => {
+                    if (!hasFiles(event)) return;
+                    event.preventDefault();
+                    depth = 0;
+                    actions() && actions().setDragging(false);
+
+                    for (const file of event.dataTransfer.files) {
+                        try {
+                            const contents = new Uint8Array(await file.arrayBuffer());
+                            actions() && actions().fileDropped(contents, file.name);
+                        } catch (err) {
+                            console.error('Error reading dropped file:', err);
+                        }
+                    }
+                });
+            })();
+        "#);
+    });
+
+    let handle_open_file = use_callback(move |_: ()| {
+        let window = web_sys::window().expect("no global window exists");
+        opened_file_handle.set(None);
+
+        // Any open still in flight is now stale and its result will be dropped
+        let token = operations.write().begin(OperationKind::Open);
+        register_abort_controller(token);
+
+        // JavaScript to open a file using the File System Access API
+        let js_open = r#"
+        (async function() {
+            try {
+                // Check if the File System Access API is supported
+                if (!('showOpenFilePicker' in window)) {
+                    throw new Error('File System Access API not supported');
+                }
+
+                const options = {
+                    types: [
+                        {
+                            description: 'Text Files',
+                            accept: {'text/plain': ['.txt', '.rs', '.py', '.js', '.html', '.css', '.md', '.json', '.toml', '.yaml', '.yml']}
+                        }
+                    ],
+                    multiple: false
+                };
+
+                const [handle] = await window.showOpenFilePicker(options);
+                const file = await handle.getFile();
+                // Decoding happens in Rust so invalid UTF-8 can be reported
+                const contents = new Uint8Array(await file.arrayBuffer());
+
+                // Store the file handle for later use
+                window._openedFileHandle = handle;
+
+                return { success: true, name: handle.name, contents, handle };
+            } catch (e) {
+                console.error("Error opening file:", e);
+
+                // If File System Access API is not supported, fall back to file input
+                if (e.message === 'File System Access API not supported') {
+                    return { success: false, fallback: true, error: e.toString() };
+                }
+
+                return { success: false, error: e.toString() };
+            }
+        })()
+        "#;
+
+        // Use a script to check results and call back to our Rust code
+        let document = window.document().expect("should have a document on window");
+        let script = document.create_element("script").expect("couldn't create script");
+
+        script.set_text_content(Some(&format!(
+            r#"
+            (async function() {{
+                const token = {token};
+                const controller = window._editorAbortControllers && window._editorAbortControllers[token];
+                const signal = controller ? controller.signal : undefined;
+                const done = () => window._editorActions && window._editorActions.operationDone(token);
+
+                try {{
+                    const result = await {js_open};
+
+                    // The user cancelled while the file was loading
+                    if (signal && signal.aborted) return;
+
+                    if (result && result.success) {{
                         // Store file handle
-                        window._storeOpenedFileHandle && window._storeOpenedFileHandle(window._openedFileHandle);
+                        window._storeOpenedFileHandle && window._storeOpenedFileHandle(token, window._openedFileHandle);
+
+                        // Call back to Rust with the file contents and info
+                        window._handleOpenedFile && window._handleOpenedFile(
+                            token,
+                            result.contents,
+                            result.name
+                        );
                     }} else if (result && result.fallback) {{
                         // Fall back to file input
                         const input = document.createElement('input');
                         input.type = 'file';
-                        input.accept = '.txt,.rs,.js,.html,.css,.md,.json,.toml,.yaml,.yml';
+                        input.accept = '.txt,.rs,.py,.js,.html,.css,.md,.json,.toml,.yaml,.yml';
+                        input.oncancel = done;
                         
                         input.onchange = (event) => {{
                             const file = event.target.files[0];
-                            if (!file) return;
+                            if (!file) {{
+                                done();
+                                return;
+                            }}
                             
                             const reader = new FileReader();
+                            if (signal) {{
+                                signal.addEventListener('abort', () => reader.abort());
+                            }}
+                            reader.onerror = done;
                             reader.onload = (e) => {{
-                                const contents = e.target.result;
-                                
-                                // Determine language from extension
-                                const ext = file.name.split('.').pop().toLowerCase();
-                                let lang = 'plain';
-                                switch (ext) {{
-                                    case 'rs': lang = 'rust'; break;
-                                    case 'js': lang = 'javascript'; break;
-                                    case 'html': lang = 'html'; break;
-                                    case 'css': lang = 'css'; break;
-                                    case 'md': lang = 'markdown'; break;
-                                    case 'json': lang = 'json'; break;
-                                    case 'toml': lang = 'toml'; break;
-                                    case 'yaml':
-                                    case 'yml': lang = 'yaml'; break;
-                                }}
+                                const contents = new Uint8Array(e.target.result);
                                 
                                 window._handleOpenedFile && window._handleOpenedFile(
+                                    token,
                                     contents, 
-                                    file.name,
-                                    lang
+                                    file.name
                                 );
                             }};
-                            reader.readAsText(file);
+                            reader.readAsArrayBuffer(file);
                         }};
                         
                         input.click();
+                    }} else {{
+                        done();
                     }}
                 }} catch (e) {{
                     console.error("Error processing open result:", e);
+                    done();
                 }}
             }})();
-            "#,
-            js_open
+            "#
         )));
         
         document.body().expect("no body").append_child(&script).expect("couldn't append script");
         
         // Create callback functions for JavaScript to call
-        let handle_opened_file = Closure::wrap(Box::new(move |content: String, name: String, lang: String| {
-            buffer.set(Buffer::from_str(&content, Some(name.clone())));
-            filename.set(Some(name));
-            language.set(Some(lang));
-        }) as Box<dyn FnMut(String, String, String)>);
+        let handle_opened_file = Closure::wrap(Box::new(move |token: u32, contents: js_sys::Uint8Array, name: String| {
+            release_abort_controller(token);
+
+            // Drop results from opens that were cancelled or superseded
+            if !operations.write().complete(token) {
+                return;
+            }
+
+            let Some(id) = open_bytes(contents.to_vec(), name, None) else {
+                return;
+            };
+
+            // The handle arrives first, while the document doesn't have a tab yet
+            match opened_file_handle.write().take() {
+                Some(handle) => {
+                    // Only files with a handle can be reopened later
+                    let document = documents.read().active().clone();
+                    let recent = recent_local_file(&recent_files.read(), &document.title(), &document.language, &handle);
+                    push_recent_file(&mut recent_files.write(), recent);
+
+                    file_handles.write().insert(id, handle);
+                },
+                None => {
+                    file_handles.write().remove(&id);
+                },
+            }
+            server_doc_ids.write().remove(&id);
+        }) as Box<dyn FnMut(u32, js_sys::Uint8Array, String)>);
         
-        let store_file_handle = Closure::wrap(Box::new(move |handle: web_sys::FileSystemFileHandle| {
-            file_handle.set(Some(handle));
-        }) as Box<dyn FnMut(web_sys::FileSystemFileHandle)>);
+        let store_file_handle = Closure::wrap(Box::new(move |token: u32, handle: web_sys::FileSystemFileHandle| {
+            if operations.read().is_current(token) {
+                opened_file_handle.set(Some(handle));
+            }
+        }) as Box<dyn FnMut(u32, web_sys::FileSystemFileHandle)>);
         
         // Attach callbacks to window
         let window_any = window.dyn_into::<web_sys::js_sys::Object>().expect("window should be an object");
         js_sys::Reflect::set(
             &window_any, 
             &JsValue::from_str("_handleOpenedFile"), 
-            &handle_opened_file.as_ref()
+            handle_opened_file.as_ref()
         ).expect("Failed to set window._handleOpenedFile");
         
         js_sys::Reflect::set(
             &window_any, 
             &JsValue::from_str("_storeOpenedFileHandle"), 
-            &store_file_handle.as_ref()
+            store_file_handle.as_ref()
         ).expect("Failed to set window._storeOpenedFileHandle");
         
         // Prevent the callbacks from being dropped
@@ -342,60 +1571,188 @@ pub fn CodeEditor() -> Element {
         store_file_handle.forget();
     });
 
-    let fallback_save_download = {
-        let buffer = buffer.clone();
-        let filename = filename.clone();
-    
-        move || {
-            let current_text = buffer.read().text();
-            let current_filename = filename.read().clone().unwrap_or_else(|| "untitled.txt".to_string());
-    
-            // Create a Blob and download link
-            let js_code = "
-                (function() {
-                    const blob = new Blob([window._contentToSave], {type: 'text/plain'});
-                    const url = URL.createObjectURL(blob);
-                    const a = document.createElement('a');
-                    a.href = url;
-                    a.download = window._suggestedFilename;
-                    document.body.appendChild(a);
-                    a.click();
-                    document.body.removeChild(a);
-                    URL.revokeObjectURL(url);
-                })();
-            ";
-    
-            // Set up global variables for the JavaScript to use
-            let window = web_sys::window().expect("no global window exists");
-            let window_any = window.dyn_into::<js_sys::Object>().expect("window should be an object");
-            
-            js_sys::Reflect::set(
-                &window_any,
-                &JsValue::from_str("_contentToSave"),
-                &JsValue::from_str(&current_text)
-            ).expect("Failed to set content");
-            
-            js_sys::Reflect::set(
-                &window_any,
-                &JsValue::from_str("_suggestedFilename"),
-                &JsValue::from_str(&current_filename)
-            ).expect("Failed to set filename");
-            
-            // Execute the JavaScript
-            let _ = js_sys::eval(js_code);
+    let handle_open_folder = use_callback(move |_: ()| {
+        let _ = js_sys::eval(r#"
+            (async function() {
+                try {
+                    const handle = await window.showDirectoryPicker();
+                    window._editorActions && window._editorActions.folderOpened(handle);
+                } catch (err) {
+                    // Closing the picker rejects as well
+                    if (err.name !== 'AbortError') {
+                        console.error('Error opening folder:', err);
+                    }
+                }
+            })();
+        "#);
+    });
+
+    // Run `js_code`, a JavaScript function taking the open folder and a path
+    // within it, on `path`
+    let find_in_folder = move |path: &str, js_code: &str| {
+        let Some(handle) = folder_handle.peek().clone() else {
+            return;
+        };
+
+        let window = web_sys::window().expect("no global window exists");
+        let window_any = window.dyn_into::<js_sys::Object>().expect("window should be an object");
+        js_sys::Reflect::set(
+            &window_any,
+            &JsValue::from_str("_folderToRead"),
+            &handle
+        ).expect("Failed to set folder to read");
+
+        let path = js_string(path);
+        let _ = js_sys::eval(&format!("({js_code})(window._folderToRead, {path});"));
+    };
+
+    // List the folder at `path` in the open folder, "" for the folder itself.
+    // The entries arrive through `folderRead`.
+    let read_folder = move |path: &str| {
+        find_in_folder(path, r#"
+            async function(root, path) {
+                try {
+                    let folder = root;
+                    for (const name of path.split('/').filter(Boolean)) {
+                        folder = await folder.getDirectoryHandle(name);
+                    }
+
+                    const entries = [];
+                    for await (const [name, handle] of folder.entries()) {
+                        entries.push({ name, is_dir: handle.kind === 'directory' });
+                    }
+                    window._editorActions && window._editorActions.folderRead(path, JSON.stringify(entries));
+                } catch (err) {
+                    console.error('Error reading folder:', err);
+                }
+            }
+        "#);
+    };
+
+    let mut handle_folder_opened = move |handle: web_sys::FileSystemDirectoryHandle| {
+        folder_handle.set(Some(handle));
+        folder_tree.set(HashMap::new());
+        expanded_folders.set(BTreeSet::new());
+        read_folder("");
+    };
+
+    // Folders are read the first time they're expanded
+    let handle_toggle_folder = move |path: String| {
+        if expanded_folders.write().remove(&path) {
+            return;
+        }
+        if !folder_tree.read().contains_key(&path) {
+            read_folder(&path);
+        }
+        expanded_folders.write().insert(path);
+    };
+
+    // Open the file at `path` in the open folder. It arrives through `folderFileOpened`.
+    let handle_open_folder_file = move |path: String| {
+        find_in_folder(&path, r#"
+            async function(root, path) {
+                try {
+                    const names = path.split('/');
+                    let folder = root;
+                    for (const name of names.slice(0, -1)) {
+                        folder = await folder.getDirectoryHandle(name);
+                    }
+
+                    const handle = await folder.getFileHandle(names[names.length - 1]);
+                    const file = await handle.getFile();
+                    const contents = new Uint8Array(await file.arrayBuffer());
+                    window._editorActions && window._editorActions.folderFileOpened(handle, contents, file.name);
+                } catch (err) {
+                    console.error('Error opening file from folder:', err);
+                }
+            }
+        "#);
+    };
+
+    // Files opened from the folder save back to it like any opened file
+    let mut handle_folder_file_opened = move |handle: web_sys::FileSystemFileHandle, contents: js_sys::Uint8Array, name: String| {
+        let Some(id) = open_bytes(contents.to_vec(), name, None) else {
+            return;
+        };
+
+        let document = documents.read().active().clone();
+        let recent = recent_local_file(&recent_files.read(), &document.title(), &document.language, &handle);
+        push_recent_file(&mut recent_files.write(), recent);
+
+        file_handles.write().insert(id, handle);
+        server_doc_ids.write().remove(&id);
+    };
+
+    // A save of document `id` went through, wherever it went
+    let mut document_saved = move |id: u32| {
+        if let Some(document) = documents.write().get_mut(id) {
+            document.buffer.mark_saved();
+        }
+        clear_autosave();
+    };
+
+    // Tidy document `id` up the way the settings ask before it's written out,
+    // keeping the caret on the same line
+    let mut tidy_for_save = move |id: u32| {
+        let (trim, final_newline) = {
+            let settings = settings.read();
+            (settings.trim_trailing_whitespace, settings.insert_final_newline)
+        };
+        if !trim && !final_newline {
+            return;
+        }
+
+        let mut documents = documents.write();
+        let is_active = documents.active().id == id;
+        let Some(document) = documents.get_mut(id).filter(|document| !document.read_only) else {
+            return;
+        };
+
+        let old_buffer = document.buffer.clone();
+        let trimmed = trim && document.buffer.trim_trailing_whitespace() > 0;
+        let newline_fixed = final_newline && document.buffer.ensure_final_newline();
+        if !is_active || !(trimmed || newline_fixed) {
+            return;
         }
+
+        let new_buffer = document.buffer.clone();
+        keep_selection_mapped(&old_buffer.text(), new_buffer.text(), |offset| {
+            let (line, column) = old_buffer.position_of(offset);
+            if line >= new_buffer.line_count() {
+                return new_buffer.char_count();
+            }
+            // Only whitespace went, so a caret in it moves back to the line's end
+            let line_len = new_buffer.line_len_chars(line).unwrap_or_default();
+            new_buffer.line_start(line) + column.min(line_len)
+        });
+    };
+
+    let mut fallback_save_download = move || {
+        let current_text = documents.read().active().buffer.text();
+        let current_filename = documents.read().active().filename.clone().unwrap_or_else(|| "untitled.txt".to_string());
+
+        download_text(&current_filename, &current_text);
+
+        // There's no way to tell whether the download was kept, so starting it counts as saving
+        let id = documents.read().active().id;
+        document_saved(id);
     };
 
     let handle_save_as = use_callback(move |_| {
+        tidy_for_save(documents.read().active().id);
         let window = web_sys::window().expect("no global window exists");
-        let current_text = buffer.read().text();
-        let current_filename = filename.read().clone().unwrap_or_else(|| "untitled.txt".to_string());
+        let current_text = documents.read().active().buffer.text();
+        let current_filename = documents.read().active().filename.clone().unwrap_or_else(|| "untitled.txt".to_string());
         
         // Check if File System Access API is supported
         let is_fsapi_supported = js_sys::eval("'showSaveFilePicker' in window")
             .unwrap_or(JsValue::FALSE).as_bool().unwrap_or(false);
         
         if is_fsapi_supported {
+            let token = operations.write().begin(OperationKind::SaveAs);
+            register_abort_controller(token);
+            operation_document.set(Some(documents.read().active().id));
+
             // Store content and filename in global variables first
             let window_any = window.dyn_into::<js_sys::Object>().expect("window should be an object");
             
@@ -412,51 +1769,88 @@ pub fn CodeEditor() -> Element {
             ).expect("Failed to set filename");
             
             // Set up our callbacks
-            let update_info = Closure::wrap(Box::new(move |name: String, lang: String| {
-                filename.set(Some(name));
-                language.set(Some(lang));
-            }) as Box<dyn FnMut(String, String)>);
+            let update_info = Closure::wrap(Box::new(move |token: u32, name: String, lang: String| {
+                release_abort_controller(token);
+
+                // A newer operation owns the filename now
+                if !operations.write().complete(token) {
+                    return;
+                }
+
+                // The tab may have been closed while the picker was open
+                let Some(id) = operation_document() else {
+                    return;
+                };
+                if let Some(document) = documents.write().get_mut(id) {
+                    document.filename = Some(name);
+                    document.language = lang;
+                    // Once it's a file on disk it's kept like any other
+                    document.scratch = false;
+                }
+
+                // The file on disk is now where this document lives
+                server_doc_ids.write().remove(&id);
+                document_saved(id);
+            }) as Box<dyn FnMut(u32, String, String)>);
             
-            let store_handle = Closure::wrap(Box::new(move |handle: web_sys::FileSystemFileHandle| {
-                file_handle.set(Some(handle));
-            }) as Box<dyn FnMut(web_sys::FileSystemFileHandle)>);
+            let store_handle = Closure::wrap(Box::new(move |token: u32, handle: web_sys::FileSystemFileHandle| {
+                if operations.read().is_current(token) {
+                    if let Some(id) = operation_document() {
+                        let name = handle.name();
+                        let recent = recent_local_file(&recent_files.read(), &name, language_from_filename(&name), &handle);
+                        push_recent_file(&mut recent_files.write(), recent);
+
+                        file_handles.write().insert(id, handle);
+                    }
+                }
+            }) as Box<dyn FnMut(u32, web_sys::FileSystemFileHandle)>);
             
             js_sys::Reflect::set(
                 &window_any, 
                 &JsValue::from_str("_updateFileInfo"), 
-                &update_info.as_ref()
+                update_info.as_ref()
             ).expect("Failed to set update callback");
             
             js_sys::Reflect::set(
                 &window_any, 
                 &JsValue::from_str("_storeFileHandle"), 
-                &store_handle.as_ref()
+                store_handle.as_ref()
             ).expect("Failed to set store handle callback");
             
-            // Single JavaScript code block
+            // Single JavaScript code block, invoked with the operation token
             let js_code = "
-                (async function() {
+                async function(token) {
+                    const controller = window._editorAbortControllers && window._editorAbortControllers[token];
+                    const signal = controller ? controller.signal : undefined;
+
                     try {
                         const options = {
                             suggestedName: window._suggestedFilename || 'untitled.txt',
                             types: [{
                                 description: 'Text Files',
-                                accept: {'text/plain': ['.txt', '.rs', '.js', '.html', '.css', '.md', '.json', '.toml', '.yaml', '.yml']}
+                                accept: {'text/plain': ['.txt', '.rs', '.py', '.js', '.html', '.css', '.md', '.json', '.toml', '.yaml', '.yml']}
                             }]
                         };
                         
                         const handle = await window.showSaveFilePicker(options);
+                        if (signal && signal.aborted) return;
+
                         const writable = await handle.createWritable();
                         await writable.write(window._contentToSave || '');
+
+                        // Discard the write if the user cancelled in the meantime
+                        if (signal && signal.aborted) {
+                            await writable.abort();
+                            return;
+                        }
                         await writable.close();
                         
-                        window._savedFileHandle = handle;
-                        
                         // Determine language from extension
                         const ext = handle.name.split('.').pop().toLowerCase();
                         let lang = 'plain';
                         switch (ext) {
                             case 'rs': lang = 'rust'; break;
+                            case 'py': lang = 'python'; break;
                             case 'js': lang = 'javascript'; break;
                             case 'html': lang = 'html'; break;
                             case 'css': lang = 'css'; break;
@@ -466,20 +1860,21 @@ pub fn CodeEditor() -> Element {
                             case 'yaml': case 'yml': lang = 'yaml'; break;
                         }
                         
-                        if (window._updateFileInfo) {
-                            window._updateFileInfo(handle.name, lang);
-                        }
                         if (window._storeFileHandle) {
-                            window._storeFileHandle(handle);
+                            window._storeFileHandle(token, handle);
+                        }
+                        if (window._updateFileInfo) {
+                            window._updateFileInfo(token, handle.name, lang);
                         }
                     } catch (err) {
                         console.error('Error in save as:', err);
+                        window._editorActions && window._editorActions.operationDone(token);
                     }
-                })();
+                }
             ";
             
             // Execute the JavaScript
-            let _ = js_sys::eval(js_code);
+            let _ = js_sys::eval(&format!("({js_code})({token});"));
             
             // Prevent callbacks from being dropped
             update_info.forget();
@@ -490,69 +1885,310 @@ pub fn CodeEditor() -> Element {
         }
     });
     
+    // The current document in the shape the document server stores
+    let current_document = move || {
+        let documents = documents.read();
+        let active = documents.active();
+
+        StoredDocument {
+            filename: active.filename.clone(),
+            language: active.language.clone(),
+            content: active.buffer.text(),
+        }
+    };
+
+    let handle_save_to_server = use_callback(move |_: ()| {
+        tidy_for_save(documents.read().active().id);
+        let token = operations.write().begin(OperationKind::Save);
+        register_abort_controller(token);
+
+        let id = documents.read().active().id;
+        operation_document.set(Some(id));
+
+        // Documents that already live on the server are overwritten in place
+        match server_doc_ids.read().get(&id) {
+            Some(server_id) => update_document(token, server_id, &current_document()),
+            None => create_document(token, &current_document()),
+        }
+    });
+
     let handle_save_file = use_callback(move |_| {
+        let active_id = documents.read().active().id;
+        tidy_for_save(active_id);
+
+        // Server documents save back to the server
+        if server_doc_ids.read().contains_key(&active_id) {
+            handle_save_to_server(());
+            return;
+        }
+
         let window = web_sys::window().expect("no global window exists");
-        let current_text = buffer.read().text();
-        
+        let current_text = documents.read().active().buffer.text();
+        let active_handle = file_handles.read().get(&active_id).cloned();
+
         // Check if File System Access API is supported and we have a file handle
         let is_fsapi_supported = js_sys::eval("'showSaveFilePicker' in window")
             .unwrap_or(JsValue::FALSE).as_bool().unwrap_or(false);
         
-        if is_fsapi_supported && file_handle.read().is_some() {
-            // Set up the content to save
+        if let Some(handle) = active_handle.filter(|_| is_fsapi_supported) {
+            let token = operations.write().begin(OperationKind::Save);
+            register_abort_controller(token);
+            operation_document.set(Some(active_id));
+
+            // Set up the content to save and the file it goes to
             let window_any = window.dyn_into::<js_sys::Object>().expect("window should be an object");
             js_sys::Reflect::set(
                 &window_any,
                 &JsValue::from_str("_contentToSave"),
                 &JsValue::from_str(&current_text)
             ).expect("Failed to set content");
+
+            js_sys::Reflect::set(
+                &window_any,
+                &JsValue::from_str("_fileHandleToSave"),
+                &handle
+            ).expect("Failed to set file handle");
             
-            // Single JavaScript code block
+            // Single JavaScript code block, invoked with the operation token
             let js_code = "
-                (async function() {
+                async function(token) {
+                    const controller = window._editorAbortControllers && window._editorAbortControllers[token];
+                    const signal = controller ? controller.signal : undefined;
+
                     try {
-                        const handle = window._savedFileHandle;
+                        const handle = window._fileHandleToSave;
                         if (!handle) {
                             throw new Error('No file handle available');
                         }
                         
                         const writable = await handle.createWritable();
                         await writable.write(window._contentToSave || '');
+
+                        // Discard the write if the user cancelled in the meantime
+                        if (signal && signal.aborted) {
+                            await writable.abort();
+                            return false;
+                        }
                         await writable.close();
+                        window._editorActions && window._editorActions.fileSaved(token);
                         return true;
                     } catch (err) {
                         console.error('Error saving file:', err);
                         return false;
+                    } finally {
+                        window._editorActions && window._editorActions.operationDone(token);
                     }
-                })();
+                }
             ";
             
             // Execute the JavaScript
-            let _ = js_sys::eval(js_code);
+            let _ = js_sys::eval(&format!("({js_code})({token});"));
         } else {
             // No file handle or API not supported, do Save As
             handle_save_as(());
         }
     });
 
+    // Save every document with changes: back to its file or the server where
+    // it has one, otherwise through a save picker each. Without the File
+    // System Access API, local documents are downloaded instead.
+    let handle_save_all = use_callback(move |_: ()| {
+        let window = web_sys::window().expect("no global window exists");
+        let is_fsapi_supported = js_sys::eval("'showSaveFilePicker' in window")
+            .unwrap_or(JsValue::FALSE).as_bool().unwrap_or(false);
+
+        let modified_ids: Vec<u32> = documents
+            .read()
+            .documents()
+            .iter()
+            .filter(|document| document.has_unsaved_changes())
+            .map(|document| document.id)
+            .collect();
+        for id in modified_ids {
+            tidy_for_save(id);
+        }
+
+        let modified: Vec<_> = documents
+            .read()
+            .documents()
+            .iter()
+            .filter(|document| document.has_unsaved_changes())
+            .cloned()
+            .collect();
+        let total = modified.len() as u32;
+        if total == 0 {
+            return;
+        }
+
+        let mut saved = 0;
+        let files = js_sys::Array::new();
+        for document in modified {
+            let file = js_sys::Object::new();
+            let set = |key: &str, value: &JsValue| {
+                js_sys::Reflect::set(&file, &JsValue::from_str(key), value).expect("Failed to set file to save");
+            };
+            set("id", &JsValue::from(document.id));
+            set("name", &JsValue::from_str(&document.title()));
+            set("content", &JsValue::from_str(&document.buffer.text()));
+
+            if let Some(server_id) = server_doc_ids.read().get(&document.id) {
+                let stored = StoredDocument {
+                    filename: document.filename.clone(),
+                    language: document.language.clone(),
+                    content: document.buffer.text(),
+                };
+                let url = format!("/api/docs/{}", js_sys::encode_uri_component(server_id));
+                set("serverUrl", &JsValue::from_str(&url));
+                set("body", &JsValue::from_str(&serde_json::to_string(&stored).expect("documents always serialize")));
+            } else if is_fsapi_supported {
+                let handle = file_handles.read().get(&document.id).cloned();
+                set("handle", &handle.map(JsValue::from).unwrap_or(JsValue::NULL));
+            } else {
+                download_text(&document.title(), &document.buffer.text());
+                document_saved(document.id);
+                saved += 1;
+                continue;
+            }
+
+            files.push(&file);
+        }
+
+        if files.length() == 0 {
+            save_all_report.set(Some(save_all_summary(saved, total)));
+            return;
+        }
+
+        let token = operations.write().begin(OperationKind::SaveAll);
+        register_abort_controller(token);
+
+        let window_any = window.dyn_into::<js_sys::Object>().expect("window should be an object");
+        js_sys::Reflect::set(
+            &window_any,
+            &JsValue::from_str("_filesToSave"),
+            &files
+        ).expect("Failed to set files to save");
+
+        // One file at a time, so each save picker opens after the last closes.
+        // A failed or cancelled file is skipped rather than ending the run.
+        let js_code = "
+            async function(token, files, saved, total) {
+                const controller = window._editorAbortControllers && window._editorAbortControllers[token];
+                const signal = controller ? controller.signal : undefined;
+                const actions = window._editorActions;
+
+                for (const file of files) {
+                    if (signal && signal.aborted) break;
+
+                    try {
+                        if (file.serverUrl) {
+                            const response = await fetch(file.serverUrl, {
+                                method: 'PUT',
+                                body: file.body,
+                                headers: { 'Content-Type': 'application/json' },
+                                signal,
+                            });
+                            if (!response.ok) {
+                                throw new Error(`${response.status} ${response.statusText}`);
+                            }
+                            actions && actions.saveAllFileSaved(token, file.id, null);
+                        } else {
+                            const handle = file.handle || await window.showSaveFilePicker({ suggestedName: file.name });
+                            if (signal && signal.aborted) break;
+
+                            const writable = await handle.createWritable();
+                            await writable.write(file.content);
+
+                            // Discard the write if the user cancelled in the meantime
+                            if (signal && signal.aborted) {
+                                await writable.abort();
+                                break;
+                            }
+                            await writable.close();
+
+                            // A picked file is where the document lives from now on
+                            actions && actions.saveAllFileSaved(token, file.id, file.handle ? null : handle);
+                        }
+                        saved++;
+                    } catch (err) {
+                        if (err.name !== 'AbortError') {
+                            console.error('Error saving ' + file.name + ':', err);
+                        }
+                    }
+                }
+
+                actions && actions.saveAllDone(token, saved, total);
+            }
+        ";
+
+        let _ = js_sys::eval(&format!("({js_code})({token}, window._filesToSave, {saved}, {total});"));
+    });
+
+    let handle_export_theme = use_callback(move |_: ()| {
+        let theme = themes.read()[current_theme_idx()].clone();
+        download_text(&format!("{}.json", theme.name), &theme.to_json());
+    });
+
+    let handle_import_theme = use_callback(move |_: ()| {
+        let _ = js_sys::eval(r#"
+            (function() {
+                const input = document.createElement('input');
+                input.type = 'file';
+                input.accept = '.json,application/json';
+                input.onchange = async (event) => {
+                    const file = event.target.files[0];
+                    if (file) {
+                        window._editorActions && window._editorActions.themeImported(await file.text());
+                    }
+                };
+                input.click();
+            })();
+        "#);
+    });
+
+    // Apply settings from the settings dialog, switching theme if a different one was picked
+    let handle_settings_change = move |new_settings: EditorSettings| {
+        if new_settings.theme != settings.peek().theme {
+            current_theme_idx.set(picked_theme_index(&themes.read(), new_settings.theme.as_deref()));
+        }
+        settings.set(new_settings);
+    };
+
+    let mut use_imported_theme = move |theme: Theme| {
+        settings.write().theme = Some(theme.name.clone());
+        save_theme(&theme);
+        themes.write().push(theme);
+        current_theme_idx.set(themes.read().len() - 1);
+    };
+
+    // Themes with hard to read colors wait for the user to accept them. VS Code
+    // themes are converted, anything else is reported as one of ours.
+    let handle_theme_imported = use_callback(move |json: String| {
+        match Theme::from_json(&json).or_else(|err| Theme::from_vscode(&json).map_err(|_| err)) {
+            Ok(theme) if !theme.contrast_report().is_empty() => low_contrast_theme.set(Some(theme)),
+            Ok(theme) => use_imported_theme(theme),
+            Err(err) => {
+                let window = web_sys::window().expect("no global window exists");
+                let _ = window.alert_with_message(&format!("Couldn't import theme: {err}"));
+            },
+        }
+    });
+
     // Get current theme
-    let current_theme = &themes[current_theme_idx()];
+    let current_theme = themes.read()[current_theme_idx()].clone();
 
     // Set up global JavaScript handlers to bridge between menu and component
 let setup_js_handlers = {
-    let handle_new_file = handle_new_file.clone();
-    let handle_open_file = handle_open_file.clone();
-    let handle_save_file = handle_save_file.clone();
-    let handle_save_as = handle_save_as.clone();
-    let current_theme_idx = current_theme_idx.clone();
-    let themes = themes.clone();
-    
     move || {
         // Create handler for new file
         let new_file_handler = Closure::wrap(Box::new(move || {
             handle_new_file(());
         }) as Box<dyn FnMut()>);
         
+        // Create handler for new scratch buffer
+        let new_scratch_handler = Closure::wrap(Box::new(move || {
+            handle_new_scratch(());
+        }) as Box<dyn FnMut()>);
+
         // Create handler for open file
         let open_file_handler = Closure::wrap(Box::new(move || {
             handle_open_file(());
@@ -568,19 +2204,328 @@ let setup_js_handlers = {
             handle_save_as(());
         }) as Box<dyn FnMut()>);
         
+        // Create handler for saves to the document server
+        let server_doc_saved_handler = Closure::wrap(Box::new(move |token: u32, id: String| {
+            release_abort_controller(token);
+
+            if !operations.write().complete(token) {
+                return;
+            }
+
+            let Some(document_id) = operation_document() else {
+                return;
+            };
+
+            // Only point the address bar at the document if it's still the one on screen
+            if documents.read().active().id == document_id {
+                set_location_doc_id(&id);
+            }
+
+            if let Some(document) = documents.read().documents().iter().find(|document| document.id == document_id) {
+                push_recent_file(&mut recent_files.write(), RecentFile {
+                    name: document.title(),
+                    language: document.language.clone(),
+                    source: RecentSource::Server { id: id.clone() },
+                });
+            }
+            server_doc_ids.write().insert(document_id, id);
+            document_saved(document_id);
+        }) as Box<dyn FnMut(u32, String)>);
+
+        // Create handler for documents loaded from the document server
+        let server_doc_loaded_handler = Closure::wrap(Box::new(move |token: u32, id: String, json: String| {
+            release_abort_controller(token);
+
+            if !operations.write().complete(token) {
+                return;
+            }
+
+            match serde_json::from_str::<StoredDocument>(&json) {
+                Ok(document) => {
+                    push_recent_file(&mut recent_files.write(), RecentFile {
+                        name: document.filename.clone().unwrap_or_else(|| "untitled".to_string()),
+                        language: document.language.clone(),
+                        source: RecentSource::Server { id: id.clone() },
+                    });
+
+                    let buffer = Buffer::from_str(&document.content, document.filename.clone());
+                    let document_id = documents.write().open(buffer, document.filename, document.language);
+                    file_handles.write().remove(&document_id);
+                    server_doc_ids.write().insert(document_id, id);
+                },
+                Err(err) => web_sys::console::error_1(&format!("Invalid server document: {err}").into()),
+            }
+        }) as Box<dyn FnMut(u32, String, String)>);
+
+        // Create handlers for files dragged onto the page
+        let dragging_handler = Closure::wrap(Box::new(move |dragging: bool| {
+            dragging_files.set(dragging);
+        }) as Box<dyn FnMut(bool)>);
+
+        let file_dropped_handler = Closure::wrap(Box::new(move |contents: js_sys::Uint8Array, name: String| {
+            handle_file_dropped(contents, name);
+        }) as Box<dyn FnMut(js_sys::Uint8Array, String)>);
+
+        // Create handler for go to line
+        let goto_line_handler = Closure::wrap(Box::new(move || {
+            handle_goto_line(());
+        }) as Box<dyn FnMut()>);
+
+        let goto_bracket_handler = Closure::wrap(Box::new(move || {
+            handle_goto_bracket(());
+        }) as Box<dyn FnMut()>);
+
+        let find_handler = Closure::wrap(Box::new(move || {
+            handle_find(());
+        }) as Box<dyn FnMut()>);
+
+        let show_settings_handler = Closure::wrap(Box::new(move || {
+            show_settings_dialog.set(true);
+        }) as Box<dyn FnMut()>);
+
+        let show_changes_handler = Closure::wrap(Box::new(move || {
+            show_diff_view.set(true);
+        }) as Box<dyn FnMut()>);
+
+        let copy_selection_handler = Closure::wrap(Box::new(move |cut: bool| {
+            handle_copy_selection(cut);
+        }) as Box<dyn FnMut(bool)>);
+
+        let paste_handler = Closure::wrap(Box::new(move || {
+            handle_paste();
+        }) as Box<dyn FnMut()>);
+
+        let undo_handler = Closure::wrap(Box::new(move |redo: bool| {
+            handle_undo(redo);
+        }) as Box<dyn FnMut(bool)>);
+
+        let duplicate_lines_handler = Closure::wrap(Box::new(move || {
+            handle_duplicate_lines();
+        }) as Box<dyn FnMut()>);
+
+        let move_lines_handler = Closure::wrap(Box::new(move |up: bool| {
+            handle_move_lines(up);
+        }) as Box<dyn FnMut(bool)>);
+
+        // Create handlers for the recent files list
+        let open_recent_handler = Closure::wrap(Box::new(move |index: usize| {
+            let Some(recent) = recent_files.read().get(index).cloned() else {
+                return;
+            };
+
+            let token = operations.write().begin(OperationKind::Open);
+            register_abort_controller(token);
+
+            match &recent.source {
+                RecentSource::Local { handle_key } => reopen_local_file(token, handle_key, &recent.name, &recent.language),
+                RecentSource::Server { id } => load_document(token, id),
+            }
+        }) as Box<dyn FnMut(usize)>);
+
+        let recent_file_opened_handler = Closure::wrap(Box::new(move |token: u32, contents: js_sys::Uint8Array, name: String, lang: String, handle: web_sys::FileSystemFileHandle| {
+            release_abort_controller(token);
+
+            if !operations.write().complete(token) {
+                return;
+            }
+
+            if let Some(id) = open_bytes(contents.to_vec(), name.clone(), Some(lang.clone())) {
+                let recent = recent_local_file(&recent_files.read(), &name, &lang, &handle);
+                push_recent_file(&mut recent_files.write(), recent);
+
+                file_handles.write().insert(id, handle);
+                server_doc_ids.write().remove(&id);
+            }
+        }) as Box<dyn FnMut(u32, js_sys::Uint8Array, String, String, web_sys::FileSystemFileHandle)>);
+
+        let recent_file_failed_handler = Closure::wrap(Box::new(move |token: u32, handle_key: String| {
+            release_abort_controller(token);
+
+            if !operations.write().complete(token) {
+                return;
+            }
+
+            let stale = RecentSource::Local { handle_key };
+            if let Some(recent) = recent_files.read().iter().find(|recent| recent.source == stale) {
+                let window = web_sys::window().expect("no global window exists");
+                let _ = window.alert_with_message(&format!(
+                    "{} can no longer be opened and was removed from Open Recent.", recent.name
+                ));
+            }
+            recent_files.write().retain(|recent| recent.source != stale);
+        }) as Box<dyn FnMut(u32, String)>);
+
+        let url_file_loaded_handler = Closure::wrap(Box::new(move |token: u32, contents: js_sys::Uint8Array, name: String| {
+            release_abort_controller(token);
+
+            if !operations.write().complete(token) {
+                return;
+            }
+
+            if let Some(id) = open_bytes(contents.to_vec(), name, None) {
+                file_handles.write().remove(&id);
+                server_doc_ids.write().remove(&id);
+            }
+        }) as Box<dyn FnMut(u32, js_sys::Uint8Array, String)>);
+
+        // The editor is left on its empty document
+        let url_file_failed_handler = Closure::wrap(Box::new(move |token: u32, message: String| {
+            release_abort_controller(token);
+
+            if !operations.write().complete(token) {
+                return;
+            }
+
+            url_file_error.set(Some(message));
+        }) as Box<dyn FnMut(u32, String)>);
+
+        let server_doc_missing_handler = Closure::wrap(Box::new(move |token: u32, id: String| {
+            release_abort_controller(token);
+
+            if !operations.write().complete(token) {
+                return;
+            }
+
+            let stale = RecentSource::Server { id };
+            recent_files.write().retain(|recent| recent.source != stale);
+
+            let window = web_sys::window().expect("no global window exists");
+            let _ = window.alert_with_message("That document no longer exists on the server.");
+        }) as Box<dyn FnMut(u32, String)>);
+
+        let clear_recent_handler = Closure::wrap(Box::new(move || {
+            recent_files.write().clear();
+        }) as Box<dyn FnMut()>);
+
+        // Create handler for save to server
+        let save_to_server_handler = Closure::wrap(Box::new(move || {
+            handle_save_to_server(());
+        }) as Box<dyn FnMut()>);
+
+        // Create handler for operations that finish without producing a result
+        let operation_done_handler = Closure::wrap(Box::new(move |token: u32| {
+            release_abort_controller(token);
+            operations.write().complete(token);
+        }) as Box<dyn FnMut(u32)>);
+
+        let toggle_read_only_handler = Closure::wrap(Box::new(move || {
+            let mut documents = documents.write();
+            let active = documents.active_mut();
+            active.read_only = !active.read_only;
+        }) as Box<dyn FnMut()>);
+
+        let save_all_handler = Closure::wrap(Box::new(move || {
+            handle_save_all(());
+        }) as Box<dyn FnMut()>);
+
+        // One document of a Save All was written. `handle` is the file picked
+        // for a document that had none, or null.
+        let save_all_file_saved_handler = Closure::wrap(Box::new(move |token: u32, id: u32, handle: JsValue| {
+            if !operations.read().is_current(token) {
+                return;
+            }
+
+            if let Ok(handle) = handle.dyn_into::<web_sys::FileSystemFileHandle>() {
+                let name = handle.name();
+                let language = language_from_filename(&name);
+                if let Some(document) = documents.write().get_mut(id) {
+                    document.filename = Some(name.clone());
+                    document.language = language.to_string();
+                }
+
+                let recent = recent_local_file(&recent_files.read(), &name, language, &handle);
+                push_recent_file(&mut recent_files.write(), recent);
+                file_handles.write().insert(id, handle);
+            }
+
+            document_saved(id);
+        }) as Box<dyn FnMut(u32, u32, JsValue)>);
+
+        let save_all_done_handler = Closure::wrap(Box::new(move |token: u32, saved: u32, total: u32| {
+            release_abort_controller(token);
+
+            if operations.write().complete(token) {
+                save_all_report.set(Some(save_all_summary(saved, total)));
+            }
+        }) as Box<dyn FnMut(u32, u32, u32)>);
+
+        // Create handler for a save to an existing file handle succeeding
+        let file_saved_handler = Closure::wrap(Box::new(move |token: u32| {
+            if operations.read().is_current(token) {
+                if let Some(id) = operation_document() {
+                    document_saved(id);
+                }
+            }
+        }) as Box<dyn FnMut(u32)>);
+
         // Create handler for theme change
         let theme_handler = {
-            let mut current_theme_idx = current_theme_idx.clone();
-            let themes = themes.clone();
+            let mut current_theme_idx = current_theme_idx;
             
             Closure::wrap(Box::new(move |theme_type: String| {
-                let target_substring = if theme_type == "light" { "Light" } else { "Dark" };
-                if let Some(idx) = themes.iter().position(|theme| theme.name.contains(target_substring)) {
+                if let Some(idx) = theme_index_for(&themes.read(), theme_type != "light") {
+                    settings.write().theme = Some(themes.read()[idx].name.clone());
                     current_theme_idx.set(idx);
                 }
             }) as Box<dyn FnMut(String)>)
         };
         
+        // Create handler for system light/dark preference changes
+        let system_theme_handler = Closure::wrap(Box::new(move |prefers_dark: bool| {
+            // Once the user picks a theme it no longer follows the system preference
+            if settings.read().theme.is_some() {
+                return;
+            }
+
+            if let Some(idx) = theme_index_for(&themes.read(), prefers_dark) {
+                current_theme_idx.set(idx);
+            }
+        }) as Box<dyn FnMut(bool)>);
+
+        // Create handlers for theme import and export
+        let export_theme_handler = Closure::wrap(Box::new(move || {
+            handle_export_theme(());
+        }) as Box<dyn FnMut()>);
+
+        let import_theme_handler = Closure::wrap(Box::new(move || {
+            handle_import_theme(());
+        }) as Box<dyn FnMut()>);
+
+        let theme_imported_handler = Closure::wrap(Box::new(move |json: String| {
+            handle_theme_imported(json);
+        }) as Box<dyn FnMut(String)>);
+
+        // Create handlers for the folder sidebar
+        let open_folder_handler = Closure::wrap(Box::new(move || {
+            handle_open_folder(());
+        }) as Box<dyn FnMut()>);
+
+        let folder_opened_handler = Closure::wrap(Box::new(move |handle: web_sys::FileSystemDirectoryHandle| {
+            handle_folder_opened(handle);
+        }) as Box<dyn FnMut(web_sys::FileSystemDirectoryHandle)>);
+
+        let folder_read_handler = Closure::wrap(Box::new(move |path: String, listing: String| {
+            let entries = folder_entries(&path, &listing);
+            folder_tree.write().insert(path, entries);
+        }) as Box<dyn FnMut(String, String)>);
+
+        let folder_file_opened_handler = Closure::wrap(Box::new(move |handle: web_sys::FileSystemFileHandle, contents: js_sys::Uint8Array, name: String| {
+            handle_folder_file_opened(handle, contents, name);
+        }) as Box<dyn FnMut(web_sys::FileSystemFileHandle, js_sys::Uint8Array, String)>);
+
+        let lint_done_handler = Closure::wrap(Box::new(move |generation: u32, id: u32, json: String| {
+            // The document has changed since, its next lint is on the way
+            if *lint_generation.peek() != generation {
+                return;
+            }
+            let found: Vec<Diagnostic> = serde_json::from_str(&json).unwrap_or_default();
+            diagnostics.set(Some((id, found)));
+        }) as Box<dyn FnMut(u32, u32, String)>);
+
+        let lint_unavailable_handler = Closure::wrap(Box::new(move |language: String| {
+            unlintable_languages.write().insert(language);
+        }) as Box<dyn FnMut(String)>);
+
         // Get window
         let window = web_sys::window().expect("no global window exists");
         let window_any = window.dyn_into::<js_sys::Object>().expect("window should be an object");
@@ -592,33 +2537,267 @@ let setup_js_handlers = {
         js_sys::Reflect::set(
             &actions, 
             &JsValue::from_str("newFile"), 
-            &new_file_handler.as_ref()
+            new_file_handler.as_ref()
         ).expect("Failed to set newFile handler");
         
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("newScratch"),
+            new_scratch_handler.as_ref()
+        ).expect("Failed to set newScratch handler");
+
         js_sys::Reflect::set(
             &actions, 
             &JsValue::from_str("openFile"), 
-            &open_file_handler.as_ref()
+            open_file_handler.as_ref()
         ).expect("Failed to set openFile handler");
         
         js_sys::Reflect::set(
             &actions, 
             &JsValue::from_str("saveFile"), 
-            &save_handler.as_ref()
+            save_handler.as_ref()
         ).expect("Failed to set saveFile handler");
         
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("saveAll"),
+            save_all_handler.as_ref()
+        ).expect("Failed to set saveAll handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("saveAllFileSaved"),
+            save_all_file_saved_handler.as_ref()
+        ).expect("Failed to set saveAllFileSaved handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("saveAllDone"),
+            save_all_done_handler.as_ref()
+        ).expect("Failed to set saveAllDone handler");
+
         js_sys::Reflect::set(
             &actions, 
             &JsValue::from_str("saveFileAs"), 
-            &save_as_handler.as_ref()
+            save_as_handler.as_ref()
         ).expect("Failed to set saveFileAs handler");
         
         js_sys::Reflect::set(
             &actions, 
             &JsValue::from_str("setTheme"), 
-            &theme_handler.as_ref()
+            theme_handler.as_ref()
         ).expect("Failed to set setTheme handler");
         
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("systemThemeChanged"),
+            system_theme_handler.as_ref()
+        ).expect("Failed to set systemThemeChanged handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("exportTheme"),
+            export_theme_handler.as_ref()
+        ).expect("Failed to set exportTheme handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("importTheme"),
+            import_theme_handler.as_ref()
+        ).expect("Failed to set importTheme handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("themeImported"),
+            theme_imported_handler.as_ref()
+        ).expect("Failed to set themeImported handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("operationDone"),
+            operation_done_handler.as_ref()
+        ).expect("Failed to set operationDone handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("toggleReadOnly"),
+            toggle_read_only_handler.as_ref()
+        ).expect("Failed to set toggleReadOnly handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("fileSaved"),
+            file_saved_handler.as_ref()
+        ).expect("Failed to set fileSaved handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("setDragging"),
+            dragging_handler.as_ref()
+        ).expect("Failed to set setDragging handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("fileDropped"),
+            file_dropped_handler.as_ref()
+        ).expect("Failed to set fileDropped handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("goToLine"),
+            goto_line_handler.as_ref()
+        ).expect("Failed to set goToLine handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("goToBracket"),
+            goto_bracket_handler.as_ref()
+        ).expect("Failed to set goToBracket handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("find"),
+            find_handler.as_ref()
+        ).expect("Failed to set find handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("showSettings"),
+            show_settings_handler.as_ref()
+        ).expect("Failed to set showSettings handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("showChanges"),
+            show_changes_handler.as_ref()
+        ).expect("Failed to set showChanges handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("copySelection"),
+            copy_selection_handler.as_ref()
+        ).expect("Failed to set copySelection handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("paste"),
+            paste_handler.as_ref()
+        ).expect("Failed to set paste handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("undo"),
+            undo_handler.as_ref()
+        ).expect("Failed to set undo handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("duplicateLines"),
+            duplicate_lines_handler.as_ref()
+        ).expect("Failed to set duplicateLines handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("moveLines"),
+            move_lines_handler.as_ref()
+        ).expect("Failed to set moveLines handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("openRecent"),
+            open_recent_handler.as_ref()
+        ).expect("Failed to set openRecent handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("recentFileOpened"),
+            recent_file_opened_handler.as_ref()
+        ).expect("Failed to set recentFileOpened handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("recentFileFailed"),
+            recent_file_failed_handler.as_ref()
+        ).expect("Failed to set recentFileFailed handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("urlFileLoaded"),
+            url_file_loaded_handler.as_ref()
+        ).expect("Failed to set urlFileLoaded handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("urlFileFailed"),
+            url_file_failed_handler.as_ref()
+        ).expect("Failed to set urlFileFailed handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("serverDocMissing"),
+            server_doc_missing_handler.as_ref()
+        ).expect("Failed to set serverDocMissing handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("clearRecent"),
+            clear_recent_handler.as_ref()
+        ).expect("Failed to set clearRecent handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("saveToServer"),
+            save_to_server_handler.as_ref()
+        ).expect("Failed to set saveToServer handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("serverDocSaved"),
+            server_doc_saved_handler.as_ref()
+        ).expect("Failed to set serverDocSaved handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("serverDocLoaded"),
+            server_doc_loaded_handler.as_ref()
+        ).expect("Failed to set serverDocLoaded handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("openFolder"),
+            open_folder_handler.as_ref()
+        ).expect("Failed to set openFolder handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("folderOpened"),
+            folder_opened_handler.as_ref()
+        ).expect("Failed to set folderOpened handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("folderRead"),
+            folder_read_handler.as_ref()
+        ).expect("Failed to set folderRead handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("folderFileOpened"),
+            folder_file_opened_handler.as_ref()
+        ).expect("Failed to set folderFileOpened handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("lintDone"),
+            lint_done_handler.as_ref()
+        ).expect("Failed to set lintDone handler");
+
+        js_sys::Reflect::set(
+            &actions,
+            &JsValue::from_str("lintUnavailable"),
+            lint_unavailable_handler.as_ref()
+        ).expect("Failed to set lintUnavailable handler");
+
         // Set the actions object on window
         js_sys::Reflect::set(
             &window_any,
@@ -628,49 +2807,365 @@ let setup_js_handlers = {
         
         // Prevent handlers from being dropped
         new_file_handler.forget();
+        new_scratch_handler.forget();
         open_file_handler.forget();
         save_handler.forget();
         save_as_handler.forget();
         theme_handler.forget();
+        system_theme_handler.forget();
+        export_theme_handler.forget();
+        import_theme_handler.forget();
+        theme_imported_handler.forget();
+        operation_done_handler.forget();
+        toggle_read_only_handler.forget();
+        file_saved_handler.forget();
+        save_all_handler.forget();
+        save_all_file_saved_handler.forget();
+        save_all_done_handler.forget();
+        dragging_handler.forget();
+        file_dropped_handler.forget();
+        goto_line_handler.forget();
+        goto_bracket_handler.forget();
+        find_handler.forget();
+        show_settings_handler.forget();
+        show_changes_handler.forget();
+        copy_selection_handler.forget();
+        paste_handler.forget();
+        undo_handler.forget();
+        duplicate_lines_handler.forget();
+        move_lines_handler.forget();
+        open_recent_handler.forget();
+        recent_file_opened_handler.forget();
+        recent_file_failed_handler.forget();
+        url_file_loaded_handler.forget();
+        url_file_failed_handler.forget();
+        server_doc_missing_handler.forget();
+        clear_recent_handler.forget();
+        save_to_server_handler.forget();
+        server_doc_saved_handler.forget();
+        server_doc_loaded_handler.forget();
+        open_folder_handler.forget();
+        folder_opened_handler.forget();
+        folder_read_handler.forget();
+        folder_file_opened_handler.forget();
+        lint_done_handler.forget();
+        lint_unavailable_handler.forget();
     }
 };
 
 // Call the setup function
 setup_js_handlers();
 
+// Open the server document or file from the URL on first load
+use_hook(|| {
+    if let Some(id) = doc_id_from_location() {
+        let token = operations.write().begin(OperationKind::Open);
+        register_abort_controller(token);
+        load_document(token, &id);
+    } else if let Some(url) = file_url_from_location() {
+        let token = operations.write().begin(OperationKind::Open);
+        register_abort_controller(token);
+        fetch_url_file(token, &url, &file_name_from_url(&url));
+    }
+});
+
 // Create menu handler with current state
+let active_document = documents.read().active().clone();
 let menu_handler = EditorMenuHandler::new(
-    buffer.read().is_modified(),
-    filename.read().is_some(),
-    themes[current_theme_idx()].name.contains("Light"),
+    active_document.buffer.is_modified(),
+    documents.read().has_unsaved_changes(),
+    active_document.filename.is_some(),
+    active_document.read_only,
+    current_theme.name.contains("Light"),
+    ViewToggles { reveal_hidden_chars, render_whitespace, spellcheck, split_editor, stack_panes },
+    settings,
 );
 
+let hidden_char_count = find_suspicious_chars(&active_document.buffer.text()).len();
+
+// A result for another document is stale until the new one is checked
+let active_validation = validation
+    .read()
+    .as_ref()
+    .filter(|(id, _)| *id == active_document.id)
+    .map(|(_, result)| result.clone());
+let validation_error = active_validation.clone().and_then(Result::err);
+// Diagnostics are stale the same way
+let active_diagnostics: Vec<Diagnostic> = diagnostics
+    .read()
+    .as_ref()
+    .filter(|(id, _)| *id == active_document.id)
+    .map(|(_, found)| found.clone())
+    .unwrap_or_default();
+
+// What each pane shows, until the effect catches up with a closed document
+let pane_count = if split_editor() { 2 } else { 1 };
+let panes: Vec<_> = pane_documents()[..pane_count]
+    .iter()
+    .enumerate()
+    .map(|(pane, id)| {
+        let document = documents.read().documents().iter().find(|document| document.id == *id).cloned();
+        (pane, document.unwrap_or_else(|| active_document.clone()))
+    })
+    .collect();
+let pane_border = format!(
+    "{}: 1px solid {};",
+    if stack_panes() { "border-top" } else { "border-left" },
+    current_theme.selection
+);
+
+// Counting selected text is cheap, the document totals come from `document_counts`
+let (word_count, char_count) = if selection().is_empty() {
+    document_counts()
+} else {
+    let (start, end) = selection().char_range();
+    let end = end.min(active_document.buffer.char_count());
+    (active_document.buffer.word_count_in(start, end), end - start.min(end))
+};
+
+// Keyboard shortcuts come from the keymap the menus are labelled from, so the two can't disagree
+let handle_shortcut = {
+    let keymap = keymap.clone();
+    let mut handler = menu_handler.clone();
+
+    move |event: Event<KeyboardData>| {
+        if event.key() == Key::Escape && context_menu_at().is_some() {
+            context_menu_at.set(None);
+            return;
+        }
+
+        let modifiers = event.modifiers();
+        let Some(action_id) = keymap
+            .command_for(
+                &event.key().to_string(),
+                modifiers.ctrl() || modifiers.meta(),
+                modifiers.shift(),
+                modifiers.alt(),
+            )
+            .map(str::to_string)
+        else {
+            return;
+        };
+
+        if NATIVE_SHORTCUTS.contains(&action_id.as_str()) {
+            return;
+        }
+
+        // Keep the browser's own Save/Open/Find from running as well
+        event.prevent_default();
+
+        if handler.is_item_enabled(&action_id) {
+            handler.handle_menu_action(&action_id);
+        }
+    }
+};
+
     rsx! {
         div {
-            style: "display: flex; flex-direction: column; height: 100vh; overflow: hidden;",
+            style: "display: flex; flex-direction: column; height: 100vh; overflow: hidden; outline: none;",
+            // Focusable so shortcuts still work when nothing inside has focus
+            tabindex: "-1",
+            onkeydown: handle_shortcut,
             MenuBar {
                 theme: current_theme.clone(),
                 menus: menu_items,
-                handler: menu_handler,
+                handler: menu_handler.clone(),
+                on_close: move |_| {
+                    let _ = js_sys::eval(&format!("document.getElementById('{}')?.focus()", editor_textarea_id()));
+                },
+            }
+
+            TabBar {
+                theme: current_theme.clone(),
+                documents: documents(),
+                on_select: handle_select_tab,
+                on_close: handle_close_tab,
+                on_close_others: move |index| close_tabs(index, OpenDocuments::others, OpenDocuments::close_others),
+                on_close_to_right: move |index| close_tabs(index, OpenDocuments::to_right, OpenDocuments::close_to_right),
+                on_new: move |_| handle_new_file(()),
+                on_pin: move |index: usize| {
+                    let pinned = documents.read().documents().get(index).is_some_and(|document| document.pinned);
+                    documents.write().set_pinned(index, !pinned);
+                },
+                on_move: move |(from, to)| documents.write().move_tab(from, to),
+            }
+
+            if in_collab_room {
+                PresencePanel {
+                    theme: current_theme.clone(),
+                    users: connected_users(),
+                    editing: last_edit_at
+                        .read()
+                        .iter()
+                        .filter(|(_, at)| js_sys::Date::now() - **at < EDITING_INDICATOR_MS as f64)
+                        .map(|(user_id, _)| user_id.clone())
+                        .collect::<Vec<_>>(),
+                }
+            }
+
+            if show_find_bar() {
+                FindBar {
+                    theme: current_theme.clone(),
+                    match_count: search_matches.read().len(),
+                    active_match: active_match().filter(|index| *index < search_matches.read().len()),
+                    read_only: active_document.read_only,
+                    on_search: handle_search,
+                    on_next: move |_| step_match(1),
+                    on_previous: move |_| step_match(-1),
+                    on_replace_all: handle_replace_all,
+                    on_close: handle_close_find,
+                }
             }
 
             div {
-                style: "flex: 1; overflow: hidden;",
-                EditorView {
-                    buffer: buffer(),
+                style: "flex: 1; min-height: 0; display: flex;",
+
+                if let Some(folder) = folder_handle() {
+                    FileTree {
+                        theme: current_theme.clone(),
+                        root: folder.name(),
+                        entries: folder_tree(),
+                        expanded: expanded_folders(),
+                        on_toggle: handle_toggle_folder,
+                        on_open: handle_open_folder_file,
+                        on_close: move |_| {
+                            folder_handle.set(None);
+                            folder_tree.set(HashMap::new());
+                            expanded_folders.set(BTreeSet::new());
+                        },
+                    }
+                }
+
+                div {
+                    style: format!(
+                        "flex: 1; min-width: 0; overflow: hidden; display: flex; flex-direction: {};",
+                        if stack_panes() { "column" } else { "row" }
+                    ),
+
+                    for (pane, document) in panes {
+                        div {
+                            key: "{pane}",
+                            style: format!(
+                                "flex: 1; min-width: 0; min-height: 0; overflow: hidden; {}",
+                                if pane > 0 { pane_border.as_str() } else { "" }
+                            ),
+                            EditorView {
+                                // Each document gets its own view, so cursor state doesn't leak between tabs
+                                key: "{document.id}",
+                                pane: pane,
+                                buffer: document.buffer.clone(),
+                                theme: current_theme.clone(),
+                                language: Some(document.language.clone()),
+                                reveal_hidden_chars: reveal_hidden_chars(),
+                                render_whitespace: render_whitespace(),
+                                spellcheck: spellcheck(),
+                                // Matches are only found in the active document
+                                search_term: if document.id == active_document.id { search_term() } else { String::new() },
+                                active_match: active_match()
+                                    .filter(|_| pane == active_pane())
+                                    .and_then(|index| search_matches.read().get(index).copied()),
+                                validation_error: validation_error.clone().filter(|_| document.id == active_document.id),
+                                diagnostics: if document.id == active_document.id { active_diagnostics.clone() } else { Vec::new() },
+                                settings: settings(),
+                                detected_indent: if document.id == active_document.id {
+                                    document_stats.read().detected_indent
+                                } else {
+                                    document.buffer.stats().detected_indent
+                                },
+                                read_only: document.read_only,
+                                remote_cursors: remote_cursors(),
+                                on_buffer_change: move |new_buffer| handle_buffer_change(document.id, new_buffer),
+                                // Only the active pane's caret is the editor's
+                                on_cursor_move: move |new_cursor| {
+                                    if pane == *active_pane.peek() {
+                                        handle_cursor_move.call(new_cursor);
+                                    }
+                                },
+                                on_selection_change: move |new_selection| {
+                                    if pane == *active_pane.peek() {
+                                        handle_selection_change(new_selection);
+                                    }
+                                },
+                                on_context_menu: move |at| context_menu_at.set(Some(at)),
+                                on_focus: move |_| handle_focus_pane(pane),
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Let the user cancel whatever is still in flight
+            if let Some(kind) = operations().pending() {
+                Banner {
+                    theme: current_theme.clone(),
+                    message: kind.label().to_string(),
+                    action_label: "Cancel".to_string(),
+                    on_action: handle_cancel_operation,
+                }
+            }
+
+            if let Some(message) = clipboard_error() {
+                Banner {
+                    theme: current_theme.clone(),
+                    message: message,
+                    action_label: "Dismiss".to_string(),
+                    on_action: move |_| clipboard_error.set(None),
+                }
+            }
+
+            if let Some(message) = save_all_report() {
+                Banner {
+                    theme: current_theme.clone(),
+                    message: message,
+                    action_label: "Dismiss".to_string(),
+                    on_action: move |_| save_all_report.set(None),
+                }
+            }
+
+            if let Some(message) = url_file_error() {
+                Banner {
+                    theme: current_theme.clone(),
+                    message: message,
+                    action_label: "Dismiss".to_string(),
+                    on_action: move |_| url_file_error.set(None),
+                }
+            }
+
+            // Offer another decoding for text that wasn't valid UTF-8
+            if invalid_utf8_document.read().as_ref().is_some_and(|(id, _)| *id == active_document.id) {
+                Banner {
                     theme: current_theme.clone(),
-                    on_buffer_change: handle_buffer_change,
-                    on_cursor_move: handle_cursor_move,
+                    message: format!("{} contained invalid UTF-8 bytes, which were replaced with �", active_document.title()),
+                    action_label: "Reopen as Latin-1".to_string(),
+                    on_action: handle_reopen_as_latin1,
                 }
             }
 
             StatusBar {
                 theme: current_theme.clone(),
-                filename: filename(),
-                language: language(),
+                filename: active_document.filename.clone(),
+                modified: active_document.has_unsaved_changes(),
+                language: Some(active_document.language.clone()),
+                encoding: active_document.buffer.encoding().label().to_string(),
                 cursor_line: cursor_position().line,
-                cursor_column: cursor_position().column,
-                total_lines: buffer().line_count(),
+                cursor_column: active_document.buffer.grapheme_column(cursor_position().line, cursor_position().column),
+                total_lines: active_document.buffer.line_count(),
+                word_count: word_count,
+                char_count: char_count,
+                counts_selection: !selection().is_empty(),
+                hidden_char_count: hidden_char_count,
+                indentation: if settings.read().insert_spaces {
+                    format!("Spaces: {}", settings.read().tab_width)
+                } else {
+                    format!("Tab Size: {}", settings.read().tab_width)
+                },
+                mixed_indentation: document_stats.read().detected_indent == Some(IndentStyle::Mixed),
+                zoom: settings.read().zoom,
+                validation: active_validation,
+                message: status_message(),
+                on_jump_to_hidden_char: handle_jump_to_hidden_char,
+                on_jump_to_error: handle_jump_to_error,
+                on_language_change: handle_language_change,
             }
 
             // Conditionally render the NewFileDialog when show_new_file_dialog is true/false
@@ -681,6 +3176,100 @@ let menu_handler = EditorMenuHandler::new(
                     on_cancel: handle_cancel_new_file,
                  }
             }
+
+            if let Some((x, y)) = context_menu_at() {
+                ContextMenu {
+                    theme: current_theme.clone(),
+                    items: get_editor_context_menu(),
+                    handler: menu_handler.clone(),
+                    x: x,
+                    y: y,
+                    on_close: move |_| context_menu_at.set(None),
+                }
+            }
+
+            if dragging_files() {
+                div {
+                    style: format!(
+                        "position: fixed; top: 0; left: 0; right: 0; bottom: 0; z-index: 90;
+                         display: flex; align-items: center; justify-content: center;
+                         pointer-events: none; font-size: 1.5rem;
+                         background-color: rgba(0, 0, 0, 0.5); color: {};
+                         border: 3px dashed {};",
+                        current_theme.foreground, current_theme.cursor
+                    ),
+                    "Drop files to open them"
+                }
+            }
+
+            if show_goto_line_dialog() {
+                GoToLineDialog {
+                    theme: current_theme.clone(),
+                    line_count: active_document.buffer.line_count(),
+                    on_go: handle_go_to,
+                    on_cancel: move |_| show_goto_line_dialog.set(false),
+                }
+            }
+
+            if show_settings_dialog() {
+                SettingsDialog {
+                    theme: current_theme.clone(),
+                    settings: settings(),
+                    theme_names: themes.read().iter().map(|theme| theme.name.clone()).collect::<Vec<_>>(),
+                    detected_indent: document_stats.read().detected_indent,
+                    on_change: handle_settings_change,
+                    on_keybindings: move |_| {
+                        show_settings_dialog.set(false);
+                        show_keybindings_dialog.set(true);
+                    },
+                    on_close: move |_| show_settings_dialog.set(false),
+                }
+            }
+
+            if show_keybindings_dialog() {
+                KeybindingsDialog {
+                    theme: current_theme.clone(),
+                    commands: key_commands.clone(),
+                    defaults: default_keymap.clone(),
+                    overrides: key_overrides(),
+                    fixed: NATIVE_SHORTCUTS.iter().map(|command| command.to_string()).collect::<Vec<_>>(),
+                    on_change: move |overrides| key_overrides.set(overrides),
+                    on_close: move |_| show_keybindings_dialog.set(false),
+                }
+            }
+
+            if show_diff_view() {
+                DiffView {
+                    theme: current_theme.clone(),
+                    saved_text: active_document.buffer.saved_text(),
+                    current_text: active_document.buffer.text(),
+                    on_close: move |_| show_diff_view.set(false),
+                }
+            }
+
+            if let Some(imported) = low_contrast_theme() {
+                ThemeContrastDialog {
+                    theme: current_theme.clone(),
+                    theme_name: imported.name.clone(),
+                    issues: imported.contrast_report(),
+                    on_use: move |_| {
+                        if let Some(theme) = low_contrast_theme.take() {
+                            use_imported_theme(theme);
+                        }
+                    },
+                    on_cancel: move |_| low_contrast_theme.set(None),
+                }
+            }
+
+            if let Some(autosave) = restorable_autosave() {
+                RestoreAutosaveDialog {
+                    theme: current_theme.clone(),
+                    document_count: autosave.documents.len(),
+                    saved_at: autosave.saved_at_label(),
+                    on_restore: handle_restore_autosave,
+                    on_discard: handle_discard_autosave,
+                }
+            }
         }
     }
 }
\ No newline at end of file
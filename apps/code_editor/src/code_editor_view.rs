@@ -1,130 +1,927 @@
 use dioxus::prelude::*;
 use wasm_bindgen::{prelude::*, JsCast};
 use web_sys::HtmlTextAreaElement;
-use components_lib::editor::editor_core::{Buffer, CursorPosition};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeSet, HashMap};
+use std::rc::Rc;
+use components_lib::editor::editor_core::{
+    Buffer, CursorPosition, Diagnostic, Edit, EditorSettings, FoldedText, HighlightCache, IndentStyle, Selection,
+    Severity, SyntaxHighlighter, ValidationError, WordIndex, auto_close, closing_for, dedent_lines, find_matching_bracket,
+    LanguageDefinition, fold_regions, highlight_stylesheet, indent_guides, indent_lines, is_prose, language_rules,
+    shift_folds, steps_over, toggle_comment_lines, word_before,
+};
+use components_lib::editor::editor_core::settings::MIN_TAB_WIDTH;
 use components_lib::core::Theme;
-use crate::highlighter::SyntaxHighlighter;
+use components_lib::core::collab::RemoteCursor;
+use crate::js::js_string;
+use crate::minimap::{Minimap, ScrollMetrics};
+
+/// Lines highlighted either side of the ones in view. The visible window moves
+/// in steps of this many lines, so scrolling only re-renders every so often.
+const OVERSCAN_LINES: usize = 50;
+
+/// Documents at least this many chars long keep using a word list up to
+/// `WORD_INDEX_MAX_AGE_MS` old, rather than rebuilding it on every keystroke
+const LARGE_DOCUMENT_CHARS: usize = 100_000;
+const WORD_INDEX_MAX_AGE_MS: f64 = 1000.0;
+
+/// The textarea's 0.5rem of padding in px, at the usual 16px to the rem
+const TEXT_PADDING_PX: f64 = 8.0;
+
+/// Lays the textarea's text up to the caret out in a hidden copy of it, and
+/// returns where the caret ends up as [left, bottom] px from the textarea's
+/// top left, allowing for its scroll
+const CARET_COORDINATES_JS: &str = r#"
+(function(id) {
+    const textarea = document.getElementById(id);
+    if (!textarea) return null;
+
+    const style = getComputedStyle(textarea);
+    const mirror = document.createElement('div');
+    for (const prop of ['paddingTop', 'paddingRight', 'paddingBottom', 'paddingLeft', 'fontFamily', 'fontSize',
+                        'fontWeight', 'lineHeight', 'letterSpacing', 'tabSize', 'whiteSpace', 'wordBreak', 'overflowWrap']) {
+        mirror.style[prop] = style[prop];
+    }
+    mirror.style.position = 'absolute';
+    mirror.style.visibility = 'hidden';
+    mirror.style.top = '0';
+    mirror.style.left = '0';
+    mirror.style.boxSizing = 'border-box';
+    mirror.style.width = textarea.clientWidth + 'px';
+    mirror.textContent = textarea.value.slice(0, textarea.selectionEnd);
+
+    const marker = document.createElement('span');
+    marker.textContent = '\u200b';
+    mirror.appendChild(marker);
+    document.body.appendChild(mirror);
+    const point = [
+        marker.offsetLeft - textarea.scrollLeft,
+        marker.offsetTop + marker.offsetHeight - textarea.scrollTop,
+    ];
+    mirror.remove();
+    return point;
+})
+"#;
+
+thread_local! {
+    /// The pane the editor's commands act on, the one focused last
+    static ACTIVE_PANE: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Make the editor's commands act on `pane`
+pub fn set_active_pane(pane: usize) {
+    ACTIVE_PANE.set(pane);
+}
+
+/// Id of the element `name` in `pane`. Ids in the first pane are just the name.
+pub fn pane_element_id(name: &str, pane: usize) -> String {
+    if pane == 0 {
+        name.to_string()
+    } else {
+        format!("{name}-{pane}")
+    }
+}
+
+/// Id of the active pane's textarea
+pub fn editor_textarea_id() -> String {
+    pane_element_id("editor-textarea", ACTIVE_PANE.get())
+}
+
+/// The lines worth highlighting for the textarea's scroll position, as a
+/// half-open range. `line_height` is in px.
+fn visible_lines(textarea: &HtmlTextAreaElement, line_height: f64) -> (usize, usize) {
+    let first = (textarea.scroll_top() as f64 / line_height) as usize;
+    let in_view = (textarea.client_height() as f64 / line_height).ceil() as usize;
+
+    let start = (first / OVERSCAN_LINES).saturating_sub(1) * OVERSCAN_LINES;
+    let end = ((first + in_view) / OVERSCAN_LINES + 2) * OVERSCAN_LINES;
+    (start, end)
+}
+
+/// Select the char range `start..end` of `text` in the active pane's textarea,
+/// focus it and scroll the selection towards the middle of the view
+pub fn select_in_textarea(text: &str, start: usize, end: usize) {
+    let Some(textarea_ele) = web_sys::window()
+        .and_then(|win| win.document())
+        .and_then(|doc| doc.get_element_by_id(&editor_textarea_id()))
+        .and_then(|ele| ele.dyn_into::<HtmlTextAreaElement>().ok())
+    else {
+        return;
+    };
+
+    // Folded lines aren't in the textarea, so show everything before selecting
+    if textarea_ele.value() != text {
+        let unfold_all = pane_element_id("_editorUnfoldAll", ACTIVE_PANE.get());
+        let _ = js_sys::eval(&format!("window['{unfold_all}'] && window['{unfold_all}']()"));
+        textarea_ele.set_value(text);
+    }
+
+    let _ = textarea_ele.focus();
+    let _ = textarea_ele.set_selection_range(utf16_len(text, start), utf16_len(text, end));
+
+    let total_lines = text.matches('\n').count() + 1;
+    let line = text.chars().take(start).filter(|c| *c == '\n').count();
+    let line_top = textarea_ele.scroll_height() as f64 * line as f64 / total_lines as f64;
+    let scroll_top = line_top - textarea_ele.client_height() as f64 / 2.0;
+    textarea_ele.set_scroll_top(scroll_top.max(0.0) as i32);
+}
+
+/// Keep the textarea's selection on the same text through someone else's
+/// `edit`, which replaces the textarea's value and with it the caret. Only
+/// done while the textarea has focus and nothing is folded.
+pub fn keep_selection_through(edit: &Edit, old_text: &str, new_text: String) {
+    keep_selection_mapped(old_text, new_text, |offset| edit.transform_offset(offset));
+}
+
+/// Keep the textarea's selection through a change from `old_text` to
+/// `new_text`, with `map` taking a char offset in the old text to the new one
+pub fn keep_selection_mapped(old_text: &str, new_text: String, map: impl Fn(usize) -> usize) {
+    let window = web_sys::window().expect("no global window exists");
+    let Some(textarea_ele) = window
+        .document()
+        .and_then(|doc| doc.get_element_by_id(&editor_textarea_id()))
+        .and_then(|ele| ele.dyn_into::<HtmlTextAreaElement>().ok())
+    else {
+        return;
+    };
+
+    let focused = window
+        .document()
+        .and_then(|doc| doc.active_element())
+        .is_some_and(|active| JsValue::from(active) == JsValue::from(textarea_ele.clone()));
+    if !focused || textarea_ele.value() != old_text {
+        return;
+    }
+
+    let (Ok(Some(start)), Ok(Some(end))) = (textarea_ele.selection_start(), textarea_ele.selection_end()) else {
+        return;
+    };
+    let start = utf16_len(&new_text, map(char_offset(old_text, start as usize)));
+    let end = utf16_len(&new_text, map(char_offset(old_text, end as usize)));
+
+    // The new value is only in the textarea once the editor has rendered
+    let restore = Closure::once_into_js(move || {
+        if textarea_ele.value() == new_text {
+            let _ = textarea_ele.set_selection_range(start, end);
+        }
+    });
+    let _ = window.set_timeout_with_callback(restore.unchecked_ref());
+}
+
+/// Char offset of the textarea's UTF-16 `offset` within `text`
+fn char_offset(text: &str, offset: usize) -> usize {
+    let mut utf16_len = 0;
+    text.chars()
+        .take_while(|c| {
+            utf16_len += c.len_utf16();
+            utf16_len <= offset
+        })
+        .count()
+}
+
+/// Chars a smart Backspace removes before and after the caret, or None when
+/// it should delete one char as usual. Within leading spaces it goes back to
+/// the previous indent level, and between an empty pair of brackets or quotes
+/// it removes both.
+fn smart_backspace(line_before_caret: &str, after_caret: Option<char>, tab_width: usize) -> Option<(usize, usize)> {
+    let before_len = line_before_caret.chars().count();
+    if before_len >= 2 && line_before_caret.chars().all(|c| c == ' ') {
+        let len = (before_len - 1) % tab_width.max(MIN_TAB_WIDTH) + 1;
+        return (len > 1).then_some((len, 0));
+    }
+
+    let open = line_before_caret.chars().last()?;
+    closing_for(open).is_some_and(|close| Some(close) == after_caret).then_some((1, 1))
+}
+
+/// The worst severity of the diagnostics on each line that has any, and their
+/// messages one to a line
+fn diagnostic_markers(diagnostics: &[Diagnostic]) -> HashMap<usize, (Severity, String)> {
+    let mut markers: HashMap<usize, (Severity, String)> = HashMap::new();
+    for diagnostic in diagnostics {
+        let (severity, messages) = markers.entry(diagnostic.line).or_insert((diagnostic.severity, String::new()));
+        *severity = (*severity).min(diagnostic.severity);
+        if !messages.is_empty() {
+            messages.push('\n');
+        }
+        messages.push_str(&diagnostic.message);
+    }
+    markers
+}
+
+/// Line and column of the char at `offset` within `text`
+fn line_and_column(text: &str, offset: usize) -> (usize, usize) {
+    let before: Vec<char> = text.chars().take(offset).collect();
+    let line_start = before.iter().rposition(|c| *c == '\n').map(|idx| idx + 1).unwrap_or(0);
+
+    (before[..line_start].iter().filter(|c| **c == '\n').count(), offset - line_start)
+}
+
+/// Replace the char range `start..end` of `buffer` with `inserted`, moving the
+/// folds in `folded` along with the lines they're on
+fn replace_in_buffer(
+    buffer: &Buffer,
+    folded: &BTreeSet<usize>,
+    start: usize,
+    end: usize,
+    inserted: &str,
+) -> (Buffer, BTreeSet<usize>) {
+    let folded = shift_folds(folded, &buffer.text(), start, end, inserted);
+
+    let mut new_buffer = buffer.clone();
+    let _ = new_buffer.replace_range(start, end, inserted);
+
+    (new_buffer, folded)
+}
+
+/// UTF-16 length of the first `char_idx` chars of `text`, which is how the
+/// textarea counts offsets
+fn utf16_len(text: &str, char_idx: usize) -> u32 {
+    text.chars().take(char_idx).map(|c| c.len_utf16() as u32).sum()
+}
+
+/// Where the caret of textarea `id` is drawn, as (left, bottom) px from the
+/// textarea's top left
+fn caret_coordinates(id: &str) -> Option<(f64, f64)> {
+    let id = js_string(id);
+    let point = js_sys::eval(&format!("({CARET_COORDINATES_JS})({id})")).ok()?;
+    let point = point.dyn_into::<js_sys::Array>().ok()?;
+    Some((point.get(0).as_f64()?, point.get(1).as_f64()?))
+}
+
+/// The word list a text's words are completed from, and when it was made
+struct WordCache {
+    text: String,
+    built_at: f64,
+    index: Rc<WordIndex>,
+}
+
+/// The word list for `text`, from `cache` if it was made from the same text
+/// or the text is large and it's recent enough
+fn cached_words(cache: &RefCell<Option<WordCache>>, text: &str) -> Rc<WordIndex> {
+    let now = js_sys::Date::now();
+    let mut cache = cache.borrow_mut();
+
+    if let Some(cached) = cache.as_ref() {
+        let recent = now - cached.built_at < WORD_INDEX_MAX_AGE_MS;
+        if cached.text == text || (recent && text.len() >= LARGE_DOCUMENT_CHARS) {
+            return cached.index.clone();
+        }
+    }
+
+    let index = Rc::new(WordIndex::new(text));
+    *cache = Some(WordCache { text: text.to_string(), built_at: now, index: index.clone() });
+    index
+}
+
+/// Words offered to finish the one before the caret
+#[derive(Clone, PartialEq)]
+struct Completion {
+    /// Char offsets in the displayed text of the start of the word and the caret
+    start: usize,
+    caret: usize,
+    words: Vec<String>,
+    selected: usize,
+    /// Where the popup goes, in px from the textarea's top left
+    left: f64,
+    top: f64,
+}
+
+/// The position of the char offset `offset` within `text`
+fn position_at(text: &str, offset: usize) -> CursorPosition {
+    let (line, column) = line_and_column(text, offset);
+    CursorPosition { offset, line, column }
+}
 
 #[component]
 pub fn EditorView(
+    // Which of the split panes this is, which keeps its elements' ids apart
+    pane: usize,
     buffer: Buffer,
     theme: Theme,
     on_buffer_change: EventHandler<Buffer>,
     on_cursor_move: EventHandler<CursorPosition>,
+    on_selection_change: EventHandler<Selection>,
+    on_context_menu: EventHandler<(f64, f64)>,
+    on_focus: EventHandler<()>,
     language: Option<String>,
+    reveal_hidden_chars: bool,
+    render_whitespace: bool,
+    spellcheck: bool,
+    // Marked wherever it appears, empty when nothing is being searched for
+    search_term: String,
+    // Char offset in the buffer of the match the find bar is on
+    active_match: Option<usize>,
+    // Underlined where the document stops parsing
+    validation_error: Option<ValidationError>,
+    // Underlined and marked in the gutter where the server's linters found something
+    diagnostics: Vec<Diagnostic>,
+    settings: EditorSettings,
+    // How the document is already indented, which spaces out the indent guides
+    detected_indent: Option<IndentStyle>,
+    read_only: bool,
+    remote_cursors: Vec<RemoteCursor>,
 ) -> Element {
+    let EditorSettings { tab_width, word_wrap, show_line_numbers, show_minimap, show_indent_guides, word_completion, .. } = settings;
+    let font = settings.display_font();
+    let indent_unit = settings.indent_unit();
+    let textarea_id = pane_element_id("editor-textarea", pane);
+    let layer_ids = ["highlight-layer", "bracket-layer", "guide-layer", "cursor-layer", "gutter-layer"]
+        .map(|name| pane_element_id(name, pane));
     let mut textarea = use_signal(|| None::<HtmlTextAreaElement>);
-    let mut cursor = use_signal(|| CursorPosition::default());
+    // Only the minimap reads this, so scrolling doesn't re-render the editor
+    let mut scroll_metrics = use_signal(ScrollMetrics::default);
+    // Without wrapping, only these lines are highlighted. Kept apart from the
+    // scroll position so scrolling within the window doesn't re-render.
+    let mut highlighted_window = use_signal(|| (0, 4 * OVERSCAN_LINES));
+    let mut cursor = use_signal(CursorPosition::default);
+    let mut selection = use_signal(Selection::default);
+    // Start lines of the folded regions. Folding only changes what's shown,
+    // the buffer always holds the whole text.
+    let mut folded_lines = use_signal(BTreeSet::<usize>::new);
+    // The displayed line under the mouse, to show its diagnostics for
+    let mut hovered_line = use_signal(|| None::<usize>);
+    // Not a signal, filling it in while rendering mustn't trigger another render
+    let highlight_cache = use_hook(|| Rc::new(RefCell::new(HighlightCache::default())));
+    let mut completion = use_signal(|| None::<Completion>);
+    let word_cache = use_hook(|| Rc::new(RefCell::new(None::<WordCache>)));
+
+    // The textarea and highlight layer must wrap identically or the text drifts from the caret
+    let wrap_style = if word_wrap {
+        "white-space: pre-wrap; word-break: break-word; overflow-x: hidden;"
+    } else {
+        "white-space: pre;"
+    };
+
+    // A dimmed caret hints that the text can't be changed
+    let caret_color = if read_only {
+        format!("color-mix(in srgb, {} 35%, transparent)", theme.cursor)
+    } else {
+        theme.cursor.clone()
+    };
+
+    // Shared by every layer over the text so they stay lined up
+    let font_style = font.css();
+    let line_height = font.size as f64 * 1.5;
+
+    // Zooming changes how tall lines are, so scroll to keep the same ones in view
+    let laid_out_line_height = use_hook(|| Rc::new(Cell::new(line_height)));
+    if laid_out_line_height.get() != line_height {
+        let ratio = line_height / laid_out_line_height.replace(line_height);
+        if let Some(textarea_ele) = textarea.peek().clone() {
+            let top = (textarea_ele.scroll_top() as f64 * ratio).round() as i32;
+            let left = (textarea_ele.scroll_left() as f64 * ratio).round() as i32;
+            // The text is only laid out at the new size once the editor has rendered
+            let rescroll = Closure::once_into_js(move || {
+                textarea_ele.set_scroll_top(top);
+                textarea_ele.set_scroll_left(left);
+            });
+            let window = web_sys::window().expect("no global window exists");
+            let _ = window.set_timeout_with_callback(rescroll.unchecked_ref());
+        }
+    }
 
     let style = format!(
         "position: absolute; top: 0; left: 0; right: 0; bottom: 0; padding: 0.5rem;
          resize: none; outline: none; border: none;
          background-color: transparent; color: transparent; caret-color: {};
-         font-family: 'Fira Code', monospace; font-size: 14px; line-height: 1.5;
-         white-space: pre; tab-size: 4; z-index: 2;",
-        theme.cursor
+         {} {} tab-size: {}; z-index: 2;",
+        caret_color, font_style, wrap_style, tab_width
     );
 
     // Create a syntax highlighter for the specified language
     let lang = language.clone().unwrap_or_else(|| "plain".to_string());
-    let highlighter = SyntaxHighlighter::new(lang, theme.clone());
+    // Wrapped lines don't line up with the gutter, so there's no folding while wrapping
+    let text = buffer.text();
+    let regions = if word_wrap { Vec::new() } else { fold_regions(&text, &lang) };
+    let folded = FoldedText::new(&text, &regions, &folded_lines.read());
+    let display_text = folded.text().to_string();
+
+    let active_match = active_match.map(|offset| line_and_column(&display_text, folded.to_display(offset)));
+    // Diagnostics in folded lines wait to be drawn until they're unfolded
+    let display_lines_of: HashMap<usize, usize> =
+        folded.line_numbers().iter().enumerate().map(|(display, line)| (*line, display)).collect();
+    let diagnostics_shown = diagnostics
+        .iter()
+        .filter_map(|diagnostic| {
+            let line = display_lines_of.get(&diagnostic.line)?;
+            Some((*line, diagnostic.col, diagnostic.len, diagnostic.severity))
+        })
+        .collect();
+    let markers = diagnostic_markers(&diagnostics);
+    // Wrapped lines don't sit at `line * 1.5em`, so there's no telling which one the mouse is over
+    let hovered_messages = hovered_line()
+        .filter(|_| !word_wrap)
+        .and_then(|line| folded.line_numbers().get(line))
+        .and_then(|line| markers.get(line))
+        .map(|(_, messages)| messages.clone());
+    let highlighter = SyntaxHighlighter::new(lang.clone(), theme.clone())
+        .reveal_hidden_chars(reveal_hidden_chars)
+        .render_whitespace(render_whitespace)
+        .search(&search_term, active_match)
+        .error_underline(validation_error.map(|error| {
+            let (line, column) = line_and_column(&display_text, folded.to_display(error.offset));
+            (line, column, error.len)
+        }))
+        .diagnostics(diagnostics_shown);
 
-    // Generate highlighted HTML
-    let highlighted_code = highlighter.highlight(&buffer.text());
+    let longest_line = display_text.lines().map(|line| line.chars().count()).max().unwrap_or(0);
+
+    // Wrapped lines differ in height, so while wrapping every line is
+    // highlighted. Otherwise only the window around the view is, with spacers
+    // standing in for the rest so the layer scrolls as far as the textarea.
+    let highlighted_code = if word_wrap {
+        highlighter.highlight(&display_text, 0..usize::MAX, &mut highlight_cache.borrow_mut())
+    } else {
+        let display_lines = folded.line_numbers().len();
+        let (start, end) = highlighted_window();
+        let start = start.min(display_lines);
+        let end = end.min(display_lines);
+        let visible = highlighter.highlight(&display_text, start..end, &mut highlight_cache.borrow_mut());
+
+        format!(
+            "<div style=\"height: calc({start} * 1.5em); width: {longest_line}ch;\"></div>\
+             <div>{visible}</div>\
+             <div style=\"height: calc({} * 1.5em);\"></div>",
+            display_lines - end
+        )
+    };
+
+    // The bracket pair next to the caret, as (line, column) positions. Wrapped
+    // and folded lines don't sit at `line * 1.5em`, so the overlays placed by
+    // logical line and column are left out while wrapping or folding.
+    let bracket_match = find_matching_bracket(&text, cursor().offset)
+        .filter(|_| !word_wrap && !folded.is_folded())
+        .map(|(bracket, partner)| [line_and_column(&text, bracket), line_and_column(&text, partner)]);
+
+    // Guides on the highlighted lines, as (line, column) positions in the
+    // displayed text. There are none while wrapping, for the same reason.
+    let guides = if show_indent_guides && !word_wrap {
+        let indent_width = match detected_indent {
+            Some(IndentStyle::Spaces(width)) => width,
+            _ => tab_width,
+        };
+        let (start, end) = highlighted_window();
+        indent_guides(&display_text, start..end, indent_width, tab_width)
+    } else {
+        Vec::new()
+    };
 
     // Handle keyboard events including tab
     let buffer_tab_event = buffer.clone();
+    let indent_rules = language_rules(&lang);
+    // Prose has nothing to comment out with
+    let comment_prefix = if is_prose(&lang) { None } else { LanguageDefinition::built_in(&lang).line_comment };
+    let folded_keydown = folded.clone();
+    // What the textarea shows for `text` with the folds in `folded`
+    let fold_language = lang.clone();
+    let display_for = move |text: &str, folded: &BTreeSet<usize>| {
+        let regions = if word_wrap { Vec::new() } else { fold_regions(text, &fold_language) };
+        FoldedText::new(text, &regions, folded)
+    };
+    // Replace the chars `start..end` of the buffer with `inserted`, then select
+    // from `anchor` to `head`, char offsets in the new text, and show it all in
+    // the textarea with the folds moved along
+    let buffer_edit = buffer.clone();
+    let mut apply_edit_result = move |start: usize, end: usize, inserted: &str, anchor: usize, head: usize| {
+        let (new_buffer, new_folds) = replace_in_buffer(&buffer_edit, &folded_lines.peek(), start, end, inserted);
+        let new_text = new_buffer.text();
+        on_buffer_change.call(new_buffer);
+
+        let new_selection = Selection { anchor: position_at(&new_text, anchor), head: position_at(&new_text, head) };
+        cursor.set(new_selection.head);
+        selection.set(new_selection);
+        on_selection_change.call(new_selection);
+        on_cursor_move.call(new_selection.head);
+
+        // Update the textarea right away so the caret can be placed
+        let new_display = display_for(&new_text, &new_folds);
+        folded_lines.set(new_folds);
+        if let Some(textarea_ele) = textarea() {
+            let anchor_utf16 = utf16_len(new_display.text(), new_display.to_display(anchor));
+            let head_utf16 = utf16_len(new_display.text(), new_display.to_display(head));
+            textarea_ele.set_value(new_display.text());
+            let _ = textarea_ele.set_selection_range(anchor_utf16, head_utf16);
+        }
+    };
+
+    // Finish the word being typed with the completion at `index`
+    let folded_accept = folded.clone();
+    let mut accept_edit = apply_edit_result.clone();
+    let accept_completion = use_callback(move |index: usize| {
+        let Some(popup) = completion.take() else {
+            return;
+        };
+        let (Some(textarea_ele), Some(word)) = (textarea(), popup.words.get(index)) else {
+            return;
+        };
+        let Ok((start, end)) = folded_accept.map_edit(popup.start, popup.caret) else {
+            return;
+        };
+
+        let new_offset = start + word.chars().count();
+        accept_edit(start, end, word, new_offset, new_offset);
+        let _ = textarea_ele.focus();
+    });
+
     let handle_keydown = use_callback(move |event: Event<KeyboardData>| {
-        // Check if it's the Tab key
+        if read_only {
+            return;
+        }
+
+        // While words are on offer, the arrows pick one, Enter or Tab takes
+        // it and Escape puts them away
+        if let Some(popup) = completion() {
+            match event.key() {
+                Key::ArrowDown | Key::ArrowUp => {
+                    event.prevent_default();
+                    let count = popup.words.len();
+                    let step = if event.key() == Key::ArrowDown { 1 } else { count - 1 };
+                    completion.set(Some(Completion { selected: (popup.selected + step) % count, ..popup }));
+                    return;
+                },
+                Key::Enter | Key::Tab => {
+                    event.prevent_default();
+                    accept_completion(popup.selected);
+                    return;
+                },
+                Key::Escape => {
+                    event.prevent_default();
+                    event.stop_propagation();
+                    completion.set(None);
+                    return;
+                },
+                _ => {},
+            }
+        }
+
+        // Enter keeps the current line's indentation
+        if event.key() == Key::Enter {
+            let Some(textarea_ele) = textarea() else {
+                return;
+            };
+            let (Ok(Some(start)), Ok(Some(end))) = (textarea_ele.selection_start(), textarea_ele.selection_end()) else {
+                return;
+            };
+
+            // We insert the newline ourselves, so stop the textarea adding another
+            event.prevent_default();
+
+            let text = buffer_tab_event.text();
+            let display = folded_keydown.text();
+            let edit = folded_keydown.map_edit(char_offset(display, start as usize), char_offset(display, end as usize));
+            let (start, end) = match edit {
+                Ok(range) => range,
+                Err(touched) => {
+                    // Unfold rather than replace text that can't be seen
+                    folded_lines.write().retain(|line| !touched.contains(line));
+                    return;
+                },
+            };
+
+            let before_caret: String = text.chars().take(start).collect();
+            let line_start = before_caret.rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+            let indent = indent_rules.next_line_indent(&before_caret[line_start..], &indent_unit);
+            let inserted = format!("\n{indent}");
+
+            // Typing over a selection replaces it, and the caret goes after the indent
+            let new_offset = start + inserted.chars().count();
+            apply_edit_result(start, end, &inserted, new_offset, new_offset);
+            return;
+        }
+
+        // Ctrl+/ comments out the caret's line or every line of a selection,
+        // or uncomments them if they all are already
+        let modifiers = event.modifiers();
+        if event.key() == Key::Character("/".to_string()) && (modifiers.ctrl() || modifiers.meta()) {
+            let Some(prefix) = comment_prefix.as_deref() else {
+                return;
+            };
+            let Some(textarea_ele) = textarea() else {
+                return;
+            };
+            let (Ok(Some(start)), Ok(Some(end))) = (textarea_ele.selection_start(), textarea_ele.selection_end()) else {
+                return;
+            };
+
+            event.prevent_default();
+
+            let display = folded_keydown.text();
+            let edit = folded_keydown.map_edit(char_offset(display, start as usize), char_offset(display, end as usize));
+            let (start, end) = match edit {
+                Ok(range) => range,
+                Err(touched) => {
+                    // Unfold rather than change lines that can't be seen
+                    folded_lines.write().retain(|line| !touched.contains(line));
+                    return;
+                },
+            };
+
+            let text = buffer_tab_event.text();
+            let (line_start, line_end, replacement) = toggle_comment_lines(&text, start, end, prefix);
+            let replacement_len = replacement.chars().count();
+
+            // A caret moves along with its line's text, a selection grows to
+            // cover the whole of the lines changed
+            let (anchor, head) = if start == end {
+                let caret = (start + replacement_len).saturating_sub(line_end - line_start).max(line_start);
+                (caret, caret)
+            } else {
+                (line_start, line_start + replacement_len)
+            };
+            apply_edit_result(line_start, line_end, &replacement, anchor, head);
+            return;
+        }
+
+        // A closing bracket typed at the start of a line lines up with its
+        // opener, an opening one is closed straight away and a closer typed
+        // just before the same one steps over it
+        if let Key::Character(typed) = event.key() {
+            let mut chars = typed.chars();
+            let (Some(typed), None) = (chars.next(), chars.next()) else {
+                return;
+            };
+            let Some(textarea_ele) = textarea() else {
+                return;
+            };
+            let (Ok(Some(start)), Ok(Some(end))) = (textarea_ele.selection_start(), textarea_ele.selection_end()) else {
+                return;
+            };
+            let modifiers = event.modifiers();
+            if start != end || modifiers.ctrl() || modifiers.meta() || modifiers.alt() {
+                return;
+            }
+
+            let text = buffer_tab_event.text();
+            let caret = folded_keydown.to_full(char_offset(folded_keydown.text(), start as usize), false);
+            let before = caret.checked_sub(1).and_then(|idx| text.chars().nth(idx));
+            let after = text.chars().nth(caret);
+
+            if steps_over(typed, after) {
+                event.prevent_default();
+
+                let new_position = position_at(&text, caret + 1);
+                let new_selection = Selection { anchor: new_position, head: new_position };
+
+                cursor.set(new_position);
+                selection.set(new_selection);
+                on_selection_change.call(new_selection);
+                on_cursor_move.call(new_position);
+
+                let utf16_offset = utf16_len(folded_keydown.text(), folded_keydown.to_display(caret + 1));
+                let _ = textarea_ele.set_selection_range(utf16_offset, utf16_offset);
+                return;
+            }
+
+            // What replaces the text from `edit_start` to the caret, and where the caret goes after
+            let (edit_start, inserted, new_offset) = if let Some((line_start, indent)) = indent_rules.dedent(&text, caret, typed) {
+                let inserted = format!("{indent}{typed}");
+                let new_offset = line_start + inserted.chars().count();
+                (line_start, inserted, new_offset)
+            } else if let Some(close) = auto_close(typed, before, after) {
+                (caret, format!("{typed}{close}"), caret + 1)
+            } else {
+                return;
+            };
+
+            event.prevent_default();
+            apply_edit_result(edit_start, caret, &inserted, new_offset, new_offset);
+            return;
+        }
+
+        // Backspace removes a whole indent level, or both halves of an empty pair
+        if event.key() == Key::Backspace {
+            let Some(textarea_ele) = textarea() else {
+                return;
+            };
+            let (Ok(Some(start)), Ok(Some(end))) = (textarea_ele.selection_start(), textarea_ele.selection_end()) else {
+                return;
+            };
+            if start != end {
+                return;
+            }
+
+            // Work in the displayed text, whose leading spaces are all real
+            let display = folded_keydown.text();
+            let caret = char_offset(display, start as usize);
+            let before_caret: String = display.chars().take(caret).collect();
+            let line_start = before_caret.rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+            let Some((before, after)) = smart_backspace(&before_caret[line_start..], display.chars().nth(caret), tab_width) else {
+                return;
+            };
+            let Ok((start, end)) = folded_keydown.map_edit(caret - before, caret + after) else {
+                return;
+            };
+
+            event.prevent_default();
+            apply_edit_result(start, end, "", start, start);
+            return;
+        }
+
+        // Tab indents at the caret, or every line of a selection, and
+        // Shift+Tab takes the lines back out a level
         if event.key() == Key::Tab {
-            // We can't prevent default here directly, but we'll handle it specially
-            
-            if let Some(textarea_ele) = textarea() {
-                if let Ok(Some(start)) = textarea_ele.selection_start() {
-                    let current_offset = start as usize;
-                    
-                    // Create a new buffer with the tab (4 spaces)
-                    let mut new_buffer = buffer_tab_event.clone();
-                    let _ = new_buffer.insert(current_offset, "    "); // 4 spaces for tab
-                    on_buffer_change.call(new_buffer);
+            // The textarea's own listener keeps Tab from moving focus
+            let Some(textarea_ele) = textarea() else {
+                return;
+            };
+            let (Ok(Some(start)), Ok(Some(end))) = (textarea_ele.selection_start(), textarea_ele.selection_end()) else {
+                return;
+            };
+            let dedent = event.modifiers().shift();
 
-                    // Update the cursor position
-                    let new_position = CursorPosition {
-                        offset: current_offset + 4,
-                        line: cursor.with(|c| c.line),
-                        column: cursor.with(|c| c.column) + 4,
-                    };
+            if start == end && !dedent {
+                let current_offset = folded_keydown.to_full(char_offset(folded_keydown.text(), start as usize), false);
 
-                    cursor.set(new_position);
-                    on_cursor_move.call(new_position);
+                // One indent level inserted, with the caret after it. The
+                // position is worked out from the new text, the tracked cursor
+                // can be behind the textarea's selection.
+                let new_offset = current_offset + indent_unit.chars().count();
+                apply_edit_result(current_offset, current_offset, &indent_unit, new_offset, new_offset);
+                return;
+            }
 
-                    // Need to update the textarea's selection position manually
-                    let _ = textarea_ele.set_selection_range(
-                        (current_offset + 4) as u32,
-                        (current_offset + 4) as u32,
-                    );
-                }
+            let display = folded_keydown.text();
+            let edit = folded_keydown.map_edit(char_offset(display, start as usize), char_offset(display, end as usize));
+            let (start, end) = match edit {
+                Ok(range) => range,
+                Err(touched) => {
+                    // Unfold rather than change lines that can't be seen
+                    folded_lines.write().retain(|line| !touched.contains(line));
+                    return;
+                },
+            };
+
+            let text = buffer_tab_event.text();
+            let (line_start, line_end, replacement) = if dedent {
+                dedent_lines(&text, start, end, tab_width)
+            } else {
+                indent_lines(&text, start, end, &indent_unit)
+            };
+            let replacement_len = replacement.chars().count();
+            // Nothing to indent or nothing left to take out
+            if replacement_len == line_end - line_start {
+                return;
             }
+
+            // A caret keeps its place in the line, a selection grows to cover
+            // the whole of the lines changed
+            let (anchor, head) = if start == end {
+                let removed = line_end - line_start - replacement_len;
+                let caret = start.saturating_sub(removed).max(line_start);
+                (caret, caret)
+            } else {
+                (line_start, line_start + replacement_len)
+            };
+            apply_edit_result(line_start, line_end, &replacement, anchor, head);
         }
     });
 
+    // Offer words to finish the one before the caret, or stop offering them.
+    // Both texts are as they are after the edit, `display` as the textarea holds it.
+    let completion_language = lang.clone();
+    let completion_textarea_id = textarea_id.clone();
+    let mut refresh_completion = move |display: &str, full_text: &str| {
+        let Some(caret) = textarea()
+            .filter(|_| word_completion)
+            .and_then(|textarea_ele| match (textarea_ele.selection_start(), textarea_ele.selection_end()) {
+                (Ok(Some(start)), Ok(Some(end))) if start == end => Some(char_offset(display, start as usize)),
+                _ => None,
+            })
+        else {
+            completion.set(None);
+            return;
+        };
+
+        let words = word_before(display, caret, &completion_language)
+            .map(|(start, prefix)| (start, cached_words(&word_cache, full_text).completions(&prefix)))
+            .filter(|(_, words)| !words.is_empty());
+        let popup = words.zip(caret_coordinates(&completion_textarea_id)).map(|((start, words), (left, top))| {
+            Completion { start, caret, words, selected: 0, left, top }
+        });
+        completion.set(popup);
+    };
+
     let buffer_input = buffer.clone();
-    let mut handle_input = use_callback(move |event: Event<FormData>| {
+    let folded_input = folded.clone();
+    let handle_input = use_callback(move |event: Event<FormData>| {
+        if read_only {
+            return;
+        }
+
         let new_text = event.value().clone();
-        let buffer_text = buffer_input.text();
 
-        if new_text != buffer_text {
-            let new_buffer = Buffer::from_str(&new_text, buffer_input.clone().filename().cloned());
-            on_buffer_change.call(new_buffer);
+        if !folded_input.is_folded() {
+            if new_text != buffer_input.text() {
+                let mut new_buffer = buffer_input.clone();
+                new_buffer.set_text(&new_text);
+                on_buffer_change.call(new_buffer);
+            }
+            refresh_completion(&new_text, &new_text);
+            return;
+        }
+
+        // The textarea only holds the displayed text, so work out what changed
+        // in it and make the same change to the buffer
+        let old: Vec<char> = folded_input.text().chars().collect();
+        let new: Vec<char> = new_text.chars().collect();
+        let prefix = old.iter().zip(&new).take_while(|(old, new)| old == new).count();
+        let suffix = old[prefix..].iter().rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(old, new)| old == new)
+            .count();
+        let inserted: String = new[prefix..new.len() - suffix].iter().collect();
+
+        match folded_input.map_edit(prefix, old.len() - suffix) {
+            Ok((start, end)) => {
+                let (new_buffer, new_folds) = replace_in_buffer(&buffer_input, &folded_lines.peek(), start, end, &inserted);
+                refresh_completion(&new_text, &new_buffer.text());
+                folded_lines.set(new_folds);
+                on_buffer_change.call(new_buffer);
+            },
+            // Unfold rather than change text that can't be seen. The textarea
+            // goes back to the buffer's text on the next render.
+            Err(touched) => folded_lines.write().retain(|line| !touched.contains(line)),
         }
     });
 
     // This function will do all the work for updating cursor position
     // but doesn't take any parameters - we'll call it from the event handlers
+    let buffer_cursor = buffer.clone();
+    let folded_cursor = folded.clone();
     let mut update_cursor = move || {
         if let Some(textarea_elem) = textarea() {
-            if let Ok(Some(position)) = textarea_elem.selection_start() {
-                let selection_start = position as usize;
-
-                // Calculate line and column
+            if let (Ok(Some(start)), Ok(Some(end))) = (textarea_elem.selection_start(), textarea_elem.selection_end()) {
                 let text = textarea_elem.value();
-                let line = text[..selection_start].matches('\n').count();
-                let last_newline = text[..selection_start].rfind('\n').map(|line_num| line_num + 1).unwrap_or(0);
-                let column = selection_start - last_newline;
-
-                let new_position = CursorPosition {
-                    offset: selection_start,
-                    line,
-                    column,
+
+                // Positions are reported in chars of the buffer's text, not in
+                // the textarea's UTF-16 units of the folded one
+                let to_position = |offset: u32| {
+                    let offset = char_offset(&text, offset as usize);
+                    if folded_cursor.is_folded() {
+                        position_at(&buffer_cursor.text(), folded_cursor.to_full(offset, false))
+                    } else {
+                        position_at(&text, offset)
+                    }
                 };
+                let start_position = to_position(start);
+                let end_position = to_position(end);
+
+                // The caret sits at the start of a selection made backwards
+                let backward = textarea_elem.selection_direction().ok().flatten().as_deref() == Some("backward");
+                let new_selection = if backward {
+                    Selection { anchor: end_position, head: start_position }
+                } else {
+                    Selection { anchor: start_position, head: end_position }
+                };
+
+                if selection() != new_selection {
+                    selection.set(new_selection);
+                    on_selection_change.call(new_selection);
+                }
+
+                if cursor() != new_selection.head {
+                    cursor.set(new_selection.head);
+                    on_cursor_move.call(new_selection.head);
+                }
+
+                // Moving the caret away from the word puts the completions away
+                let caret = (start == end).then(|| char_offset(&text, end as usize));
+                if completion.peek().as_ref().is_some_and(|popup| Some(popup.caret) != caret) {
+                    completion.set(None);
+                }
 
-                if cursor() != new_position {
-                    cursor.set(new_position);
-                    on_cursor_move.call(new_position);
+                // Typing can change how far the textarea scrolls
+                if show_minimap {
+                    scroll_metrics.set(ScrollMetrics::of(&textarea_elem));
                 }
             }
         }
     };
 
     // Split the callbacks to handle different event types
+    let mut update_cursor_on_select = update_cursor.clone();
     let handle_keyup = use_callback(move |_: Event<KeyboardData>| {
         update_cursor();
     });
 
     let handle_selection_change = use_callback(move |_: Event<SelectionData>| {
-        update_cursor();
+        update_cursor_on_select();
     });
 
     // Set up the textarea and event handlers
+    let setup_id = textarea_id.clone();
+    let script_layer_ids = layer_ids.clone();
     let setup_textarea = move |_| {
         // Set the textarea reference
         let element = web_sys::window()
             .and_then(|win| win.document())
-            .and_then(|doc| doc.get_element_by_id("editor-textarea"))
+            .and_then(|doc| doc.get_element_by_id(&setup_id))
             .and_then(|ele| ele.dyn_into::<HtmlTextAreaElement>().ok());
 
         if let Some(textarea_ele) = element {
             textarea.set(Some(textarea_ele.clone()));
+            highlighted_window.set(visible_lines(&textarea_ele, line_height));
             
             // Add a keydown event listener to prevent default tab behavior
             let tab_handler = Closure::wrap(Box::new(move |event: web_sys::KeyboardEvent| {
@@ -142,20 +939,35 @@ pub fn EditorView(
             let window = web_sys::window().expect("no global window");
             let document = window.document().expect("no document");
 
+            // Lets `select_in_textarea` reach text that's folded away
+            let unfold_all = Closure::wrap(Box::new(move || {
+                folded_lines.set(BTreeSet::new());
+            }) as Box<dyn FnMut()>);
+            js_sys::Reflect::set(
+                &window,
+                &JsValue::from_str(&pane_element_id("_editorUnfoldAll", pane)),
+                unfold_all.as_ref()
+            ).expect("Failed to set unfold handler");
+            unfold_all.forget();
+
             let script = document.create_element("script").expect("couldn't create script");
-            script.set_text_content(Some(r#"
-                (function() {
-                    const textarea = document.getElementById('editor-textarea');
-                    const highlightLayer = document.getElementById('highlight-layer');
-
-                    if (textarea && highlightLayer) {
-                        textarea.addEventListener('scroll', function() {
-                            highlightLayer.scrollTop = textarea.scrollTop;
-                            highlightLayer.scrollLeft = textarea.scrollLeft;
-                        });
-                    }
-                })();
-            "#));
+            script.set_text_content(Some(&format!(r#"
+                (function() {{
+                    const textarea = document.getElementById('{}');
+                    const layers = {}
+                        .map(id => document.getElementById(id))
+                        .filter(layer => layer);
+
+                    if (textarea) {{
+                        textarea.addEventListener('scroll', function() {{
+                            layers.forEach(layer => {{
+                                layer.scrollTop = textarea.scrollTop;
+                                layer.scrollLeft = textarea.scrollLeft;
+                            }});
+                        }});
+                    }}
+                }})();
+            "#, setup_id, serde_json::to_string(&script_layer_ids).unwrap_or_default())));
 
             document.body().expect("no body").append_child(&script).expect("couldn't append scroll sync script");
             
@@ -165,47 +977,434 @@ pub fn EditorView(
     };
 
     // Sync the scrolling
+    let scroll_layer_ids = layer_ids.clone();
     let sync_scroll = move |_| {
         if let Some(textarea_ele) = textarea() {
             let window = web_sys::window().expect("no window");
             let document = window.document().expect("no document");
 
-            if let Some(highlight_div) = document.get_element_by_id("highlight-layer") {
-                highlight_div.set_scroll_top(textarea_ele.scroll_top());
-                highlight_div.set_scroll_left(textarea_ele.scroll_left());
+            for layer_id in &scroll_layer_ids {
+                if let Some(layer) = document.get_element_by_id(layer_id) {
+                    layer.set_scroll_top(textarea_ele.scroll_top());
+                    layer.set_scroll_left(textarea_ele.scroll_left());
+                }
+            }
+
+            if show_minimap {
+                scroll_metrics.set(ScrollMetrics::of(&textarea_ele));
+            }
+
+            // The popup would be left behind by the text
+            if completion.peek().is_some() {
+                completion.set(None);
+            }
+
+            let window = visible_lines(&textarea_ele, line_height);
+            if window != *highlighted_window.peek() {
+                highlighted_window.set(window);
             }
         }
     };
 
+    let display_lines = folded.line_numbers().len();
+    let [highlight_layer_id, bracket_layer_id, guide_layer_id, cursor_layer_id, gutter_layer_id] = layer_ids;
+    let foldable_lines: Vec<usize> = regions.iter().map(|region| region.start_line).collect();
+
     rsx! {
         div {
-            style: "height: 100%; position: relative;",
+            style: "height: 100%; display: flex;",
+
+            if !word_wrap {
+                FoldGutter {
+                    layer_id: gutter_layer_id,
+                    theme: theme.clone(),
+                    font_style: font_style.clone(),
+                    show_line_numbers: show_line_numbers,
+                    line_numbers: folded.line_numbers().to_vec(),
+                    total_lines: buffer.line_count(),
+                    foldable_lines: foldable_lines,
+                    folded_lines: folded_lines(),
+                    markers: markers,
+                    on_toggle: move |line: usize| {
+                        let mut folded_lines = folded_lines.write();
+                        if !folded_lines.remove(&line) {
+                            folded_lines.insert(line);
+                        }
+                    },
+                }
+            }
 
-            // Add a div for the syntax highlighted text
             div {
-                id: "highlight-layer",
-                style: format!(
-                    "position: absolute; top: 0; left: 0; right: 0; bottom: 0; padding: 0.5rem;
-                     pointer-events: none; overflow: auto; white-space: pre;
-                     font-family: 'Fira Code', monospace; font-size: 14px; line-height: 1.5;
-                     tab-size: 4; z-index: 1; background-color: {}; color: {};",
-                     theme.background, theme.foreground
-                ),
-                dangerous_inner_html: format!("{highlighted_code}"),
+                style: "flex: 1; min-width: 0; position: relative;",
+
+                // Colors for the highlighted markup's classes, so a new theme
+                // doesn't mean highlighting again
+                style { {highlight_stylesheet(&theme)} }
+
+                // Add a div for the syntax highlighted text
+                div {
+                    id: highlight_layer_id,
+                    style: format!(
+                        "position: absolute; top: 0; left: 0; right: 0; bottom: 0; padding: 0.5rem;
+                         pointer-events: none; overflow: auto; {} {}
+                         tab-size: {}; z-index: 1; background-color: {}; color: {};",
+                         wrap_style, font_style, tab_width, theme.background, theme.foreground
+                    ),
+                    dangerous_inner_html: format!("{highlighted_code}"),
+                }
+
+                // Boxes behind the matching bracket pair, kept out of the highlighted markup
+                div {
+                    id: bracket_layer_id,
+                    style: "position: absolute; top: 0; left: 0; right: 0; bottom: 0;
+                            pointer-events: none; overflow: hidden; z-index: 1; {font_style}",
+
+                    div {
+                        style: "position: relative; height: calc({display_lines} * 1.5em + 1rem); width: calc({longest_line}ch + 1rem);",
+
+                        for (line, column) in bracket_match.into_iter().flatten() {
+                            div {
+                                style: format!(
+                                    "position: absolute; top: calc(0.5rem + {} * 1.5em); left: calc(0.5rem + {}ch);
+                                     width: 1ch; height: 1.5em; box-sizing: border-box;
+                                     border: 1px solid {}; background-color: {}; opacity: 0.6;",
+                                    line, column, theme.foreground, theme.selection
+                                ),
+                            }
+                        }
+                    }
+                }
+
+                IndentGuideLayer {
+                    layer_id: guide_layer_id,
+                    font_style: font_style.clone(),
+                    color: theme.get_color("comment"),
+                    guides: guides,
+                    total_lines: display_lines,
+                    longest_line: longest_line,
+                }
+
+                textarea {
+                    id: textarea_id.clone(),
+                    value: display_text,
+                    style: style,
+                    readonly: read_only,
+                    spellcheck: spellcheck,
+                    onmounted: setup_textarea,
+                    onkeydown: handle_keydown,
+                    oninput: handle_input,
+                    onselectionchange: handle_selection_change,
+                    onkeyup: handle_keyup,
+                    onscroll: sync_scroll,
+                    onblur: move |_| completion.set(None),
+                    title: hovered_messages,
+                    onmousemove: move |event: Event<MouseData>| {
+                        let Some(textarea_ele) = textarea.peek().clone() else {
+                            return;
+                        };
+                        let y = event.element_coordinates().y + textarea_ele.scroll_top() as f64 - TEXT_PADDING_PX;
+                        let line = (y >= 0.0).then(|| (y / line_height) as usize);
+                        if line != *hovered_line.peek() {
+                            hovered_line.set(line);
+                        }
+                    },
+                    onmouseleave: move |_| hovered_line.set(None),
+                    onfocus: move |_| {
+                        // Tell the editor where this pane's caret is, it may
+                        // have moved on in the other pane
+                        on_focus.call(());
+                        on_selection_change.call(selection());
+                        on_cursor_move.call(cursor());
+                    },
+                    oncontextmenu: move |event: Event<MouseData>| {
+                        // Our own menu replaces the browser's
+                        event.prevent_default();
+                        let point = event.client_coordinates();
+                        on_context_menu.call((point.x, point.y));
+                    },
+                }
+
+                RemoteCursorLayer {
+                    layer_id: cursor_layer_id,
+                    font_style: font_style.clone(),
+                    cursors: if word_wrap || folded.is_folded() { Vec::new() } else { remote_cursors },
+                    total_lines: display_lines,
+                    longest_line: longest_line,
+                }
+
+                if let Some(popup) = completion() {
+                    CompletionPopup {
+                        theme: theme.clone(),
+                        font_style: font_style.clone(),
+                        words: popup.words,
+                        selected: popup.selected,
+                        left: popup.left,
+                        top: popup.top,
+                        on_pick: accept_completion,
+                    }
+                }
             }
-            
-            textarea {
-                id: "editor-textarea",
-                value: buffer.text(),
-                style: style,
-                spellcheck: false,
-                onmounted: setup_textarea,
-                onkeydown: handle_keydown,
-                oninput: handle_input,
-                onselectionchange: handle_selection_change,
-                onkeyup: handle_keyup,
-                onscroll: sync_scroll,
+
+            if show_minimap {
+                Minimap {
+                    textarea_id: textarea_id.clone(),
+                    text: text.clone(),
+                    theme: theme.clone(),
+                    scroll: scroll_metrics,
+                }
+            }
+        }
+    }
+}
+
+/// Line numbers beside the text, with an arrow to fold or unfold each region.
+/// The gutter scrolls with the textarea the same way the highlight layer does.
+#[component]
+fn FoldGutter(
+    layer_id: String,
+    theme: Theme,
+    font_style: String,
+    // Without them only the fold arrows are shown
+    show_line_numbers: bool,
+    // The text's line number for each displayed line
+    line_numbers: Vec<usize>,
+    total_lines: usize,
+    foldable_lines: Vec<usize>,
+    folded_lines: BTreeSet<usize>,
+    // The worst severity of the diagnostics on each line that has any, and their messages
+    markers: HashMap<usize, (Severity, String)>,
+    on_toggle: EventHandler<usize>,
+) -> Element {
+    let digits = total_lines.to_string().len();
+    let shown_lines = line_numbers.len();
+    let width = if show_line_numbers { format!("calc({digits}ch + 3.25rem)") } else { "3rem".to_string() };
+
+    rsx! {
+        div {
+            id: layer_id,
+            style: format!(
+                "flex-shrink: 0; width: {}; overflow: hidden; user-select: none;
+                 {} background-color: {}; color: {}; border-right: 1px solid {};",
+                width, font_style, theme.background, theme.get_color("comment"), theme.selection
+            ),
+
+            div {
+                style: "padding: 0.5rem 0; height: calc({shown_lines} * 1.5em + 1rem); box-sizing: border-box;",
+
+                for line in line_numbers {
+                    div {
+                        key: "{line}",
+                        style: "display: flex; height: 1.5em; padding: 0 0.25rem 0 0.5rem;",
+                        span {
+                            style: format!(
+                                "width: 0.75rem; color: {};",
+                                markers.get(&line).map(|(severity, _)| theme.get_color(severity.as_str())).unwrap_or_default()
+                            ),
+                            title: markers.get(&line).map(|(_, messages)| messages.clone()),
+                            if markers.contains_key(&line) { "●" }
+                        }
+                        span {
+                            style: "flex: 1; text-align: right;",
+                            if show_line_numbers {
+                                "{line + 1}"
+                            }
+                        }
+                        span {
+                            style: "width: 1.5rem; text-align: center; cursor: pointer;",
+                            onclick: move |_| on_toggle.call(line),
+                            if foldable_lines.contains(&line) {
+                                if folded_lines.contains(&line) { "▸" } else { "▾" }
+                            }
+                        }
+                    }
+                }
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Faint lines down through each level of indentation, drawn behind the
+/// textarea. The layer scrolls with the textarea the same way the highlight
+/// layer does.
+#[component]
+fn IndentGuideLayer(
+    layer_id: String,
+    font_style: String,
+    color: String,
+    // (line, column) of the top of each line's guides
+    guides: Vec<(usize, usize)>,
+    total_lines: usize,
+    longest_line: usize,
+) -> Element {
+    rsx! {
+        div {
+            id: layer_id,
+            style: "position: absolute; top: 0; left: 0; right: 0; bottom: 0;
+                    pointer-events: none; overflow: hidden; z-index: 1; {font_style}",
+
+            // Sized like the text so the layer can scroll as far as the textarea
+            div {
+                style: "position: relative; height: calc({total_lines} * 1.5em + 1rem); width: calc({longest_line}ch + 1rem);",
+
+                for (line, column) in guides {
+                    div {
+                        style: format!(
+                            "position: absolute; top: calc(0.5rem + {} * 1.5em); left: calc(0.5rem + {}ch);
+                             width: 0; height: 1.5em; border-left: 1px solid {}; opacity: 0.3;",
+                            line, column, color
+                        ),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The words on offer to finish the one being typed, just below the caret
+#[component]
+fn CompletionPopup(
+    theme: Theme,
+    font_style: String,
+    words: Vec<String>,
+    selected: usize,
+    // px from the textarea's top left
+    left: f64,
+    top: f64,
+    on_pick: EventHandler<usize>,
+) -> Element {
+    rsx! {
+        div {
+            role: "listbox",
+            style: format!(
+                "position: absolute; left: {}px; top: {}px; z-index: 4; min-width: 10rem; padding: 0.125rem 0;
+                 background-color: {}; color: {}; border: 1px solid {};
+                 box-shadow: 0 2px 5px rgba(0, 0, 0, 0.3); {}",
+                left, top, theme.ui.toolbar_bg, theme.ui.toolbar_fg, theme.selection, font_style
+            ),
+
+            for (index, word) in words.into_iter().enumerate() {
+                div {
+                    key: "{word}",
+                    role: "option",
+                    aria_selected: "{index == selected}",
+                    style: format!(
+                        "padding: 0 0.5rem; cursor: pointer; white-space: pre; background-color: {};",
+                        if index == selected { theme.selection.clone() } else { "transparent".to_string() }
+                    ),
+                    // Picking with the mouse mustn't take focus from the textarea
+                    onmousedown: move |event| {
+                        event.prevent_default();
+                        on_pick.call(index);
+                    },
+                    "{word}"
+                }
+            }
+        }
+    }
+}
+
+/// Other users' carets, drawn over the textarea. The layer scrolls with the
+/// textarea the same way the highlight layer does.
+#[component]
+fn RemoteCursorLayer(layer_id: String, font_style: String, cursors: Vec<RemoteCursor>, total_lines: usize, longest_line: usize) -> Element {
+    rsx! {
+        div {
+            id: layer_id,
+            style: "position: absolute; top: 0; left: 0; right: 0; bottom: 0;
+                    pointer-events: none; overflow: hidden; z-index: 3; {font_style}",
+
+            // Sized like the text so the layer can scroll as far as the textarea
+            div {
+                style: "position: relative; height: calc({total_lines} * 1.5em + 1rem); width: calc({longest_line}ch + 1rem);",
+
+                for cursor in cursors {
+                    div {
+                        key: "{cursor.user.id}",
+                        style: format!(
+                            "position: absolute; top: calc(0.5rem + {} * 1.5em); left: calc(0.5rem + {}ch);
+                             width: 2px; height: 1.5em; background-color: {};",
+                            cursor.line, cursor.column, cursor.user.color()
+                        ),
+                        span {
+                            style: format!(
+                                "position: absolute; bottom: 100%; left: 0; padding: 0 0.25rem;
+                                 font-size: 10px; line-height: 1.4; white-space: nowrap;
+                                 border-radius: 2px; color: #FFFFFF; background-color: {};",
+                                cursor.user.color()
+                            ),
+                            "{cursor.user.name}"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tab_after_a_newline_puts_the_caret_on_the_new_line() {
+        let buffer = Buffer::from_str("fn main() {\n}", None);
+        for indent in ["\t", "    "] {
+            let (new_buffer, _) = replace_in_buffer(&buffer, &BTreeSet::new(), 12, 12, indent);
+            let new_offset = 12 + indent.chars().count();
+
+            let position = position_at(&new_buffer.text(), new_offset);
+            assert_eq!((position.line, position.column), (1, indent.chars().count()));
+            assert_eq!(position.offset, new_offset);
+        }
+    }
+
+    #[test]
+    fn tab_mid_line_moves_the_caret_along_it() {
+        let buffer = Buffer::from_str("a\nbc", None);
+        let (new_buffer, _) = replace_in_buffer(&buffer, &BTreeSet::new(), 3, 3, "\t");
+        assert_eq!(new_buffer.text(), "a\nb\tc");
+
+        let position = position_at(&new_buffer.text(), 4);
+        assert_eq!((position.line, position.column), (1, 2));
+    }
+
+    #[test]
+    fn textarea_offsets_become_char_columns() {
+        // é is one UTF-16 unit, 𝟘 and 😀 are two each
+        let text = "é𝟘😀x\nab";
+        let caret = char_offset(text, 5);
+        assert_eq!(caret, 3);
+
+        let position = position_at(text, caret);
+        assert_eq!((position.line, position.column), (0, 3));
+        assert_eq!(position_at(text, char_offset(text, 8)).line, 1);
+    }
+
+    #[test]
+    fn char_offsets_convert_back_to_textarea_offsets() {
+        let text = "é𝟘😀x";
+        for (chars, units) in [(0, 0), (1, 1), (2, 3), (3, 5), (4, 6)] {
+            assert_eq!(utf16_len(text, chars), units);
+            assert_eq!(char_offset(text, units as usize), chars);
+        }
+    }
+
+    #[test]
+    fn backspace_in_leading_spaces_goes_back_an_indent_level() {
+        assert_eq!(smart_backspace("      ", None, 4), Some((2, 0)));
+        assert_eq!(smart_backspace("        ", None, 4), Some((4, 0)));
+        assert_eq!(smart_backspace("     ", None, 4), None);
+        assert_eq!(smart_backspace("  x", None, 4), None);
+    }
+
+    #[test]
+    fn backspace_survives_a_zero_tab_width() {
+        assert_eq!(smart_backspace("    ", None, 0), None);
+    }
+
+    #[test]
+    fn backspace_between_a_pair_removes_both() {
+        assert_eq!(smart_backspace("f(", Some(')'), 4), Some((1, 1)));
+        assert_eq!(smart_backspace("f(", Some(']'), 4), None);
+    }
+}
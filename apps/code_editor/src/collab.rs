@@ -0,0 +1,174 @@
+use components_lib::core::{collab::{CollabMessage, OtClient}, users::User};
+use components_lib::editor::editor_core::{Buffer, Edit};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::{MessageEvent, UrlSearchParams, WebSocket};
+
+/// Close code for leaving a room whose document this client has lost track
+/// of, from the range left to applications
+const DESYNC_CLOSE_CODE: u16 = 4000;
+
+/// The shared document as this client has told the room about it
+#[derive(Default, PartialEq)]
+struct SharedDocument {
+    ot: OtClient,
+    /// The text once every edit sent or received so far is applied
+    text: String,
+    /// Whether the server has said which revision the room is at
+    synced: bool,
+}
+
+/// A connection to a collaboration room on the backend
+#[derive(Clone, PartialEq)]
+pub struct CollabSession {
+    socket: WebSocket,
+    pub user: User,
+    shared: Rc<RefCell<SharedDocument>>,
+}
+
+impl CollabSession {
+    /// Join the room named by the page's `?collab=<room>` query parameter, if
+    /// there is one. `?name=First Last` sets the display name, otherwise the
+    /// user joins as a guest.
+    ///
+    /// `current_text` reads the shared document as it is now. Edits from
+    /// others reach `on_message` already transformed to apply to it.
+    pub fn from_location(
+        current_text: impl Fn() -> String + 'static,
+        on_message: impl FnMut(CollabMessage) + 'static,
+    ) -> Option<Self> {
+        let location = web_sys::window()?.location();
+        let params = UrlSearchParams::new_with_str(&location.search().ok()?).ok()?;
+        let room = params.get("collab").filter(|room| !room.is_empty())?;
+
+        let user = match params.get("name") {
+            Some(name) if !name.trim().is_empty() => {
+                let (f_name, l_name) = name.trim().split_once(' ').unwrap_or((name.trim(), ""));
+                User::new(f_name, l_name.trim(), "")
+            }
+            _ => {
+                let mut guest = User::new("Guest", "", "");
+                guest.name = format!("Guest {}", &guest.id[..4]);
+                guest
+            }
+        };
+
+        let scheme = if location.protocol().ok()? == "https:" { "wss" } else { "ws" };
+        let url = format!(
+            "{}://{}/api/collab/{}?user_id={}",
+            scheme,
+            location.host().ok()?,
+            js_sys::encode_uri_component(&room),
+            user.id
+        );
+
+        let socket = WebSocket::new(&url).ok()?;
+        let shared = Rc::new(RefCell::new(SharedDocument {
+            text: current_text(),
+            ..Default::default()
+        }));
+        let session = Self { socket: socket.clone(), user, shared };
+
+        let mut on_message = on_message;
+        let handler_session = session.clone();
+        let message_handler = Closure::wrap(Box::new(move |event: MessageEvent| {
+            let Some(message) = event.data().as_string().and_then(|text| CollabMessage::from_json(&text)) else {
+                return;
+            };
+
+            match message {
+                CollabMessage::Sync { revision } => {
+                    let mut shared = handler_session.shared.borrow_mut();
+                    shared.ot.sync(revision);
+                    shared.synced = true;
+                    drop(shared);
+
+                    // Whatever changed while joining goes out now
+                    handler_session.share_text(&current_text());
+                },
+                CollabMessage::Ack { .. } => {
+                    let next = handler_session.shared.borrow_mut().ot.ack();
+                    if let Some((revision, edit)) = next {
+                        handler_session.send_edit(revision, edit);
+                    }
+                },
+                CollabMessage::Edit { user_id, revision, edit } => {
+                    // Local changes not sent yet have to be in the transform
+                    handler_session.share_text(&current_text());
+
+                    // Worked out on a copy, so an edit that doesn't fit the text
+                    // leaves the OT state as it was
+                    let mut shared = handler_session.shared.borrow_mut();
+                    let mut ot = shared.ot.clone();
+                    let edit = ot.remote_edit(edit);
+                    let mut buffer = Buffer::from_str(&shared.text, None);
+                    if buffer.apply(&edit).is_err() {
+                        drop(shared);
+                        handler_session.desync();
+                        return;
+                    }
+                    shared.ot = ot;
+                    shared.text = buffer.text();
+                    drop(shared);
+
+                    on_message(CollabMessage::Edit { user_id, revision, edit });
+                },
+                message => on_message(message),
+            }
+        }) as Box<dyn FnMut(MessageEvent)>);
+
+        // Announce ourselves as soon as we can
+        let open_session = session.clone();
+        let open_handler = Closure::wrap(Box::new(move || {
+            open_session.send(&CollabMessage::Join { user: open_session.user.clone() });
+        }) as Box<dyn FnMut()>);
+
+        socket.set_onmessage(Some(message_handler.as_ref().unchecked_ref()));
+        socket.set_onopen(Some(open_handler.as_ref().unchecked_ref()));
+
+        // The socket owns the handlers for the rest of the page's life
+        message_handler.forget();
+        open_handler.forget();
+
+        Some(session)
+    }
+
+    /// Tell the room about whatever changed between the text it last saw and
+    /// `text`. Held back until the socket is open and the room's revision is known.
+    pub fn share_text(&self, text: &str) {
+        let mut shared = self.shared.borrow_mut();
+        if !shared.synced || self.socket.ready_state() != WebSocket::OPEN || shared.text == text {
+            return;
+        }
+
+        let edits = Edit::diff(&shared.text, text);
+        shared.text = text.to_string();
+        let to_send: Vec<_> = edits.into_iter().filter_map(|edit| shared.ot.local_edit(edit)).collect();
+        drop(shared);
+
+        for (revision, edit) in to_send {
+            self.send_edit(revision, edit);
+        }
+    }
+
+    /// Leave the room once this client's text and the room's no longer agree.
+    /// The server only keeps edits, not the text, so there's nothing to resync
+    /// from, and staying would only send more edits against the wrong text.
+    fn desync(&self) {
+        self.shared.borrow_mut().synced = false;
+        let _ = self.socket.close_with_code_and_reason(DESYNC_CLOSE_CODE, "document out of sync");
+    }
+
+    fn send_edit(&self, revision: usize, edit: Edit) {
+        self.send(&CollabMessage::Edit { user_id: self.user.id.clone(), revision, edit });
+    }
+
+    /// Send a message to everyone else in the room. Messages sent before the
+    /// socket has opened are dropped.
+    pub fn send(&self, message: &CollabMessage) {
+        if self.socket.ready_state() == WebSocket::OPEN {
+            let _ = self.socket.send_with_str(&message.to_json());
+        }
+    }
+}
@@ -0,0 +1,16 @@
+/// Quote `value` as a JS string literal, for passing strings into scripts
+/// run with `js_sys::eval`
+pub fn js_string(value: &str) -> String {
+    serde_json::to_string(value).expect("strings always serialize")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_and_escapes_strings() {
+        assert_eq!(js_string("plain"), r#""plain""#);
+        assert_eq!(js_string("say \"hi\"\n\\"), r#""say \"hi\"\n\\""#);
+    }
+}
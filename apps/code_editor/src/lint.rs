@@ -0,0 +1,62 @@
+use wasm_bindgen::{prelude::*, JsCast};
+
+use crate::js::js_string;
+
+/// Posts a document to the backend's linters and reports back through
+/// `window._editorActions`
+const LINT_REQUEST_JS: &str = "
+    async function(generation, id, language, url, text) {
+        const actions = window._editorActions;
+
+        try {
+            const response = await fetch(url, {
+                method: 'POST',
+                body: text,
+                headers: { 'Content-Type': 'text/plain' },
+            });
+
+            // Nothing lints the language, or nothing that would can run
+            if (response.status === 404 || response.status === 503) {
+                actions && actions.lintUnavailable(language);
+                return;
+            }
+
+            // Linting too often, or the document uses something the server
+            // won't compile. The next pause in typing tries again.
+            if (response.status === 429 || response.status === 400) {
+                return;
+            }
+
+            if (!response.ok) {
+                throw new Error(`${response.status} ${response.statusText}`);
+            }
+
+            actions && actions.lintDone(generation, id, await response.text());
+        } catch (err) {
+            // Without a backend there's nothing to lint with
+            console.error('Error linting document:', err);
+            actions && actions.lintUnavailable(language);
+        }
+    }
+";
+
+/// Have the backend lint `text`, the document `id` in `language`. Reports the
+/// JSON list of diagnostics through `lintDone` with `generation`, or
+/// `lintUnavailable` if the language can't be linted there.
+pub fn lint_document(generation: u32, id: u32, language: &str, text: &str) {
+    let url = format!("/api/lint/{}", js_sys::encode_uri_component(language));
+
+    let window = web_sys::window().expect("no global window exists");
+    let window_any = window.dyn_into::<js_sys::Object>().expect("window should be an object");
+    js_sys::Reflect::set(
+        &window_any,
+        &JsValue::from_str("_docToLint"),
+        &JsValue::from_str(text)
+    ).expect("Failed to set document to lint");
+
+    let _ = js_sys::eval(&format!(
+        "({LINT_REQUEST_JS})({generation}, {id}, {}, {}, window._docToLint);",
+        js_string(language),
+        js_string(&url)
+    ));
+}
@@ -1,6 +1,17 @@
+mod autosave;
+mod clipboard;
 mod code_editor;
+mod collab;
 mod code_editor_view;
-mod highlighter;
+mod js;
+mod lint;
+mod minimap;
+mod operations;
+mod preferences;
+mod recent_files;
+mod session;
+mod server_docs;
+mod url_files;
 
 use dioxus::{prelude::*, web::{launch::launch_cfg, Config}};
 use crate::code_editor::CodeEditor;
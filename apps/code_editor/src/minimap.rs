@@ -0,0 +1,175 @@
+use components_lib::core::Theme;
+use dioxus::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, HtmlTextAreaElement};
+
+/// Upper bound on how tall one source line is drawn, long files are squeezed to fit
+const MAX_LINE_HEIGHT: f64 = 3.0;
+const CHAR_WIDTH: f64 = 1.0;
+
+/// How far the textarea is scrolled, in px
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScrollMetrics {
+    pub top: f64,
+    pub height: f64,
+    pub client_height: f64,
+}
+
+impl ScrollMetrics {
+    pub fn of(textarea: &HtmlTextAreaElement) -> Self {
+        Self {
+            top: textarea.scroll_top() as f64,
+            height: textarea.scroll_height() as f64,
+            client_height: textarea.client_height() as f64,
+        }
+    }
+}
+
+fn editor_textarea(id: &str) -> Option<HtmlTextAreaElement> {
+    web_sys::window()?
+        .document()?
+        .get_element_by_id(id)?
+        .dyn_into::<HtmlTextAreaElement>()
+        .ok()
+}
+
+fn line_height(line_count: usize, available: f64) -> f64 {
+    (available / line_count.max(1) as f64).min(MAX_LINE_HEIGHT)
+}
+
+/// One bar per line, starting at its indentation and as long as the line
+fn draw(canvas: &HtmlCanvasElement, text: &str, theme: &Theme) {
+    let (width, height) = (canvas.client_width().max(0), canvas.client_height().max(0));
+    canvas.set_width(width as u32);
+    canvas.set_height(height as u32);
+
+    let Some(context) = canvas
+        .get_context("2d").ok().flatten()
+        .and_then(|context| context.dyn_into::<CanvasRenderingContext2d>().ok())
+    else {
+        return;
+    };
+
+    let lines: Vec<&str> = text.split('\n').collect();
+    let line_height = line_height(lines.len(), height as f64);
+
+    context.clear_rect(0.0, 0.0, width as f64, height as f64);
+    context.set_fill_style_str(&theme.foreground);
+    context.set_global_alpha(0.4);
+
+    for (idx, line) in lines.iter().enumerate() {
+        let column_width = |c: char| if c == '\t' { 4 } else { 1 };
+        let indent: usize = line.chars().take_while(|c| c.is_whitespace()).map(column_width).sum();
+        let length: usize = line.trim_end().chars().map(column_width).sum();
+
+        if length > indent {
+            context.fill_rect(
+                indent as f64 * CHAR_WIDTH,
+                idx as f64 * line_height,
+                (length - indent) as f64 * CHAR_WIDTH,
+                // Leave a gap between lines while there's room for one
+                if line_height > 2.0 { line_height - 1.0 } else { line_height },
+            );
+        }
+    }
+}
+
+/// A scaled down outline of the whole document with the visible part marked.
+/// Dragging the marker scrolls the editor. The outline is only redrawn when
+/// the text changes; scrolling just moves the marker.
+#[component]
+pub fn Minimap(
+    // The textarea of the pane the minimap is beside
+    textarea_id: String,
+    text: String,
+    theme: Theme,
+    mut scroll: Signal<ScrollMetrics>,
+) -> Element {
+    let mut canvas = use_signal(|| None::<HtmlCanvasElement>);
+    let mut canvas_height = use_signal(|| 0.0);
+    let mut dragging = use_signal(|| false);
+
+    let scroll_textarea_id = textarea_id.clone();
+    use_effect(use_reactive((&text, &theme), move |(text, theme)| {
+        if let Some(canvas) = canvas() {
+            draw(&canvas, &text, &theme);
+            canvas_height.set(canvas.client_height() as f64);
+        }
+
+        // The editor only reports scrolling while the minimap is shown
+        if let Some(textarea) = editor_textarea(&textarea_id) {
+            scroll.set(ScrollMetrics::of(&textarea));
+        }
+    }));
+
+    let line_count = text.split('\n').count();
+    let content_height = line_count as f64 * line_height(line_count, canvas_height());
+
+    let metrics = scroll();
+    let (marker_top, marker_height) = if metrics.height > 0.0 {
+        (
+            metrics.top / metrics.height * content_height,
+            (metrics.client_height / metrics.height).min(1.0) * content_height,
+        )
+    } else {
+        (0.0, content_height)
+    };
+
+    // Centre the editor on the part of the document under `client_y`
+    let scroll_to = move |client_y: f64| {
+        let (Some(textarea), Some(canvas)) = (editor_textarea(&scroll_textarea_id), canvas()) else {
+            return;
+        };
+        if content_height <= 0.0 {
+            return;
+        }
+
+        let y = client_y - canvas.get_bounding_client_rect().top();
+        let metrics = ScrollMetrics::of(&textarea);
+        let fraction = (y / content_height).clamp(0.0, 1.0);
+        textarea.set_scroll_top((fraction * metrics.height - metrics.client_height / 2.0).max(0.0) as i32);
+    };
+
+    let drag_to = scroll_to.clone();
+
+    rsx! {
+        div {
+            style: format!(
+                "position: relative; width: 80px; flex-shrink: 0; cursor: pointer;
+                 border-left: 1px solid {}; background-color: {};",
+                theme.selection, theme.background
+            ),
+            onmousedown: move |event: Event<MouseData>| {
+                event.prevent_default();
+                dragging.set(true);
+                scroll_to(event.client_coordinates().y);
+            },
+
+            canvas {
+                style: "position: absolute; top: 0; left: 0; width: 100%; height: 100%;",
+                onmounted: move |event| {
+                    canvas.set(event.data().downcast::<web_sys::Element>()
+                        .and_then(|element| element.clone().dyn_into::<HtmlCanvasElement>().ok()));
+                },
+            }
+
+            div {
+                style: format!(
+                    "position: absolute; left: 0; right: 0; top: {}px; height: {}px;
+                     background-color: {}; opacity: 0.5; pointer-events: none;",
+                    marker_top, marker_height, theme.selection
+                ),
+            }
+        }
+
+        // Follow the mouse anywhere on the page until the button comes up
+        if dragging() {
+            div {
+                style: "position: fixed; top: 0; left: 0; right: 0; bottom: 0; z-index: 50; cursor: pointer;",
+                onmousemove: move |event: Event<MouseData>| drag_to(event.client_coordinates().y),
+                onmouseup: move |_| dragging.set(false),
+                onmouseleave: move |_| dragging.set(false),
+            }
+        }
+    }
+}
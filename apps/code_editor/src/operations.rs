@@ -0,0 +1,137 @@
+/// The kinds of long-running work the editor can have in flight
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationKind {
+    Open,
+    Save,
+    SaveAs,
+    SaveAll,
+}
+
+impl OperationKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            OperationKind::Open => "Opening file...",
+            OperationKind::Save => "Saving file...",
+            OperationKind::SaveAs => "Saving file as...",
+            OperationKind::SaveAll => "Saving files...",
+        }
+    }
+}
+
+/// Hands out generation tokens for editor operations. Starting a new operation
+/// invalidates every token handed out before it, so a slow completion callback
+/// can't clobber the state produced by a newer one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OperationTracker {
+    generation: u32,
+    pending: Option<OperationKind>,
+}
+
+impl OperationTracker {
+    /// Start a new operation and return its token
+    pub fn begin(&mut self, kind: OperationKind) -> u32 {
+        self.generation = self.generation.wrapping_add(1);
+        self.pending = Some(kind);
+        self.generation
+    }
+
+    /// Whether `token` belongs to the operation that is still in flight
+    pub fn is_current(&self, token: u32) -> bool {
+        self.pending.is_some() && token == self.generation
+    }
+
+    /// Finish the operation for `token`. Returns false for stale tokens, in
+    /// which case the caller should drop the result.
+    pub fn complete(&mut self, token: u32) -> bool {
+        if self.is_current(token) {
+            self.pending = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Cancel the pending operation, returning its token so any JS side work
+    /// can be aborted too
+    pub fn cancel(&mut self) -> Option<u32> {
+        self.pending.take().map(|_| {
+            let token = self.generation;
+            self.generation = self.generation.wrapping_add(1);
+            token
+        })
+    }
+
+    pub fn pending(&self) -> Option<OperationKind> {
+        self.pending
+    }
+}
+
+/// Create an `AbortController` for `token` that the JS side of an operation
+/// can read from `window._editorAbortControllers[token]`
+pub fn register_abort_controller(token: u32) {
+    let _ = js_sys::eval(&format!(
+        "window._editorAbortControllers = window._editorAbortControllers || {{}}; \
+         window._editorAbortControllers[{token}] = new AbortController();"
+    ));
+}
+
+/// Abort and forget the controller for `token`
+pub fn abort_operation(token: u32) {
+    let _ = js_sys::eval(&format!(
+        "(function() {{ \
+            const controllers = window._editorAbortControllers || {{}}; \
+            if (controllers[{token}]) {{ controllers[{token}].abort(); delete controllers[{token}]; }} \
+        }})();"
+    ));
+}
+
+/// Forget the controller for `token` once its operation has finished
+pub fn release_abort_controller(token: u32) {
+    let _ = js_sys::eval(&format!(
+        "window._editorAbortControllers && delete window._editorAbortControllers[{token}];"
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_current_token_completes() {
+        let mut tracker = OperationTracker::default();
+        let token = tracker.begin(OperationKind::Save);
+        assert!(tracker.is_current(token));
+        assert_eq!(tracker.pending(), Some(OperationKind::Save));
+
+        assert!(tracker.complete(token));
+        assert_eq!(tracker.pending(), None);
+        assert!(!tracker.complete(token));
+    }
+
+    #[test]
+    fn a_newer_operation_makes_older_tokens_stale() {
+        let mut tracker = OperationTracker::default();
+        let open = tracker.begin(OperationKind::Open);
+        let save = tracker.begin(OperationKind::SaveAs);
+
+        assert!(!tracker.complete(open));
+        assert_eq!(tracker.pending(), Some(OperationKind::SaveAs));
+        assert!(tracker.complete(save));
+    }
+
+    #[test]
+    fn cancelling_invalidates_the_outstanding_token() {
+        let mut tracker = OperationTracker::default();
+        let token = tracker.begin(OperationKind::SaveAll);
+
+        assert_eq!(tracker.cancel(), Some(token));
+        assert!(!tracker.is_current(token));
+        assert!(!tracker.complete(token));
+        assert_eq!(tracker.cancel(), None);
+
+        // A token handed out after the cancel isn't mistaken for the old one
+        let next = tracker.begin(OperationKind::Open);
+        assert_ne!(next, token);
+        assert!(tracker.complete(next));
+    }
+}
@@ -0,0 +1,119 @@
+use components_lib::core::Theme;
+use components_lib::editor::editor_core::EditorSettings;
+use components_lib::editor::editor_core::settings::{MAX_TAB_WIDTH, MIN_TAB_WIDTH};
+use components_lib::editor::panels::menus::KeyOverrides;
+
+const SETTINGS_KEY: &str = "collab_hub.settings";
+const KEYMAP_KEY: &str = "collab_hub.keymap";
+const THEMES_KEY: &str = "collab_hub.themes";
+
+// Where each setting was kept before they were saved together
+const LEGACY_THEME_KEY: &str = "collab_hub.theme";
+const LEGACY_WORD_WRAP_KEY: &str = "collab_hub.word_wrap";
+const LEGACY_MINIMAP_KEY: &str = "collab_hub.minimap";
+const LEGACY_FONT_KEY: &str = "collab_hub.font";
+
+pub fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// The settings from the separate keys older versions saved them under
+fn legacy_settings(storage: &web_sys::Storage) -> EditorSettings {
+    let item = |key: &str| storage.get_item(key).ok().flatten();
+
+    EditorSettings {
+        word_wrap: item(LEGACY_WORD_WRAP_KEY).is_some_and(|value| value == "true"),
+        show_minimap: item(LEGACY_MINIMAP_KEY).is_some_and(|value| value == "true"),
+        font: item(LEGACY_FONT_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        theme: item(LEGACY_THEME_KEY),
+        ..EditorSettings::default()
+    }
+}
+
+/// The settings saved last time, or the defaults
+pub fn saved_settings() -> EditorSettings {
+    let Some(storage) = local_storage() else {
+        return EditorSettings::default();
+    };
+
+    let mut settings: EditorSettings = match storage.get_item(SETTINGS_KEY).ok().flatten() {
+        Some(json) => serde_json::from_str(&json).unwrap_or_default(),
+        None => legacy_settings(&storage),
+    };
+    // Saved by hand or by an older version, it may be a width the settings
+    // dialog wouldn't allow, and a width of 0 can't be indented to
+    settings.tab_width = settings.tab_width.clamp(MIN_TAB_WIDTH, MAX_TAB_WIDTH);
+    settings
+}
+
+pub fn save_settings(settings: &EditorSettings) {
+    if let (Some(storage), Ok(json)) = (local_storage(), serde_json::to_string(settings)) {
+        let _ = storage.set_item(SETTINGS_KEY, &json);
+    }
+}
+
+/// The shortcuts the user rebound last time. Ones that no longer parse are
+/// dropped along with the rest, leaving the defaults.
+pub fn saved_key_overrides() -> KeyOverrides {
+    local_storage()
+        .and_then(|storage| storage.get_item(KEYMAP_KEY).ok().flatten())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_key_overrides(overrides: &KeyOverrides) {
+    if let (Some(storage), Ok(json)) = (local_storage(), serde_json::to_string(overrides)) {
+        let _ = storage.set_item(KEYMAP_KEY, &json);
+    }
+}
+
+/// The JSON of each theme the user imported, to load alongside the built-in ones
+pub fn saved_themes() -> Vec<String> {
+    local_storage()
+        .and_then(|storage| storage.get_item(THEMES_KEY).ok().flatten())
+        .and_then(|json| serde_json::from_str::<Vec<serde_json::Value>>(&json).ok())
+        .map(|themes| themes.iter().map(serde_json::Value::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Keep an imported theme for next time, in place of any saved with its name
+pub fn save_theme(theme: &Theme) {
+    let Some(storage) = local_storage() else {
+        return;
+    };
+
+    let mut themes: Vec<serde_json::Value> = saved_themes()
+        .iter()
+        .filter_map(|json| serde_json::from_str(json).ok())
+        .filter(|saved: &serde_json::Value| saved["name"].as_str() != Some(theme.name.as_str()))
+        .collect();
+    if let Ok(json) = serde_json::to_value(theme) {
+        themes.push(json);
+    }
+    if let Ok(json) = serde_json::to_string(&themes) {
+        let _ = storage.set_item(THEMES_KEY, &json);
+    }
+}
+
+pub fn system_prefers_dark() -> bool {
+    web_sys::window()
+        .and_then(|window| window.match_media("(prefers-color-scheme: dark)").ok().flatten())
+        .map(|query| query.matches())
+        .unwrap_or(true)
+}
+
+/// Index of the first light or dark theme
+pub fn theme_index_for(themes: &[Theme], dark: bool) -> Option<usize> {
+    let target_substring = if dark { "Dark" } else { "Light" };
+    themes.iter().position(|theme| theme.name.contains(target_substring))
+}
+
+/// The theme named `picked` if there is one, otherwise whichever matches the system
+pub fn picked_theme_index(themes: &[Theme], picked: Option<&str>) -> usize {
+    picked
+        .and_then(|name| themes.iter().rposition(|theme| theme.name == name))
+        .or_else(|| theme_index_for(themes, system_prefers_dark()))
+        .unwrap_or(0)
+}
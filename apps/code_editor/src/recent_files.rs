@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{prelude::*, JsCast};
+
+use crate::js::js_string;
+use crate::preferences::local_storage;
+
+/// localStorage key holding the recent files list
+const RECENT_FILES_KEY: &str = "collab_hub.recent_files";
+const MAX_RECENT_FILES: usize = 10;
+
+/// Where a recent file can be reopened from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RecentSource {
+    /// A local file whose handle is kept in IndexedDB under `handle_key`,
+    /// since handles can't be stored in localStorage
+    Local { handle_key: String },
+    Server { id: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentFile {
+    pub name: String,
+    pub language: String,
+    pub source: RecentSource,
+}
+
+/// Opens (creating on first use) the IndexedDB store that holds file handles
+const HANDLE_DB_JS: &str = "
+    function() {
+        return new Promise((resolve, reject) => {
+            const request = indexedDB.open('collab_hub', 1);
+            request.onupgradeneeded = () => request.result.createObjectStore('file_handles');
+            request.onsuccess = () => resolve(request.result);
+            request.onerror = () => reject(request.error);
+        });
+    }
+";
+
+/// Reads a stored handle back and reopens the file through `window._editorActions`
+const REOPEN_LOCAL_JS: &str = "
+    async function(openDb, token, key, name, lang) {
+        const actions = window._editorActions;
+
+        try {
+            const db = await openDb();
+            const handle = await new Promise((resolve, reject) => {
+                const request = db.transaction('file_handles').objectStore('file_handles').get(key);
+                request.onsuccess = () => resolve(request.result);
+                request.onerror = () => reject(request.error);
+            });
+
+            if (!handle) {
+                throw new Error('No stored handle');
+            }
+
+            // Permission isn't kept across page loads
+            if (await handle.requestPermission({ mode: 'readwrite' }) !== 'granted') {
+                throw new Error('Permission denied');
+            }
+
+            const file = await handle.getFile();
+            const contents = new Uint8Array(await file.arrayBuffer());
+            actions && actions.recentFileOpened(token, contents, name, lang, handle);
+        } catch (err) {
+            console.error('Error reopening recent file:', err);
+            actions && actions.recentFileFailed(token, key);
+        }
+    }
+";
+
+pub fn load_recent_files() -> Vec<RecentFile> {
+    local_storage()
+        .and_then(|storage| storage.get_item(RECENT_FILES_KEY).ok().flatten())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_recent_files(recent_files: &[RecentFile]) {
+    if let (Some(storage), Ok(json)) = (local_storage(), serde_json::to_string(recent_files)) {
+        let _ = storage.set_item(RECENT_FILES_KEY, &json);
+    }
+}
+
+/// Move `file` to the front of the list, dropping any older entry for the same file
+pub fn push_recent_file(recent_files: &mut Vec<RecentFile>, file: RecentFile) {
+    recent_files.retain(|recent| match (&recent.source, &file.source) {
+        (RecentSource::Server { id }, RecentSource::Server { id: new_id }) => id != new_id,
+        (RecentSource::Local { .. }, RecentSource::Local { .. }) => recent.name != file.name,
+        _ => true,
+    });
+
+    recent_files.insert(0, file);
+    recent_files.truncate(MAX_RECENT_FILES);
+}
+
+/// Remember a local file, storing its handle so it can be reopened later
+pub fn recent_local_file(
+    recent_files: &[RecentFile],
+    name: &str,
+    language: &str,
+    handle: &web_sys::FileSystemFileHandle,
+) -> RecentFile {
+    // Reuse the key of an older entry for the same name so its handle is replaced
+    let handle_key = recent_files
+        .iter()
+        .find_map(|recent| match &recent.source {
+            RecentSource::Local { handle_key } if recent.name == name => Some(handle_key.clone()),
+            _ => None,
+        })
+        .unwrap_or_else(|| format!("{}-{}", js_sys::Date::now() as u64, name));
+
+    let window = web_sys::window().expect("no global window exists");
+    let window_any = window.dyn_into::<js_sys::Object>().expect("window should be an object");
+    js_sys::Reflect::set(
+        &window_any,
+        &JsValue::from_str("_recentHandleToStore"),
+        handle
+    ).expect("Failed to set recent file handle");
+
+    let _ = js_sys::eval(&format!(
+        "(async function(openDb, key, handle) {{ \
+            try {{ \
+                const db = await openDb(); \
+                db.transaction('file_handles', 'readwrite').objectStore('file_handles').put(handle, key); \
+            }} catch (err) {{ \
+                console.error('Error storing recent file handle:', err); \
+            }} \
+        }})(({HANDLE_DB_JS}), {}, window._recentHandleToStore);",
+        js_string(&handle_key)
+    ));
+
+    RecentFile {
+        name: name.to_string(),
+        language: language.to_string(),
+        source: RecentSource::Local { handle_key },
+    }
+}
+
+/// Reopen a recent local file. Reports through `recentFileOpened`, or
+/// `recentFileFailed` if the file can't be opened any more.
+pub fn reopen_local_file(token: u32, handle_key: &str, name: &str, language: &str) {
+    let _ = js_sys::eval(&format!(
+        "({REOPEN_LOCAL_JS})(({HANDLE_DB_JS}), {token}, {}, {}, {});",
+        js_string(handle_key),
+        js_string(name),
+        js_string(language)
+    ));
+}
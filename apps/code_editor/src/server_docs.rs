@@ -0,0 +1,117 @@
+use components_lib::core::documents::StoredDocument;
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::UrlSearchParams;
+
+use crate::js::js_string;
+
+/// Performs one `/api/docs` request for an operation token and reports back
+/// through `window._editorActions`
+const DOCS_REQUEST_JS: &str = "
+    async function(token, kind, method, url, id, body) {
+        const controller = window._editorAbortControllers && window._editorAbortControllers[token];
+        const signal = controller ? controller.signal : undefined;
+        const actions = window._editorActions;
+
+        try {
+            const response = await fetch(url, {
+                method,
+                body,
+                headers: body ? { 'Content-Type': 'application/json' } : undefined,
+                signal,
+            });
+
+            // The document was deleted, or never existed
+            if (kind === 'load' && response.status === 404) {
+                actions && actions.serverDocMissing(token, id);
+                return;
+            }
+
+            if (!response.ok) {
+                throw new Error(`${response.status} ${response.statusText}`);
+            }
+
+            if (kind === 'load') {
+                actions && actions.serverDocLoaded(token, id, await response.text());
+            } else if (kind === 'create') {
+                const created = await response.json();
+                actions && actions.serverDocSaved(token, created.id);
+            } else {
+                actions && actions.serverDocSaved(token, id);
+            }
+        } catch (err) {
+            actions && actions.operationDone(token);
+
+            // Cancelling aborts the request, which isn't worth reporting
+            if (err.name !== 'AbortError') {
+                console.error('Error talking to the document server:', err);
+                alert('Document server request failed: ' + err.message);
+            }
+        }
+    }
+";
+
+fn docs_request(token: u32, kind: &str, id: Option<&str>, document: Option<&StoredDocument>) {
+    let (method, url) = match id {
+        Some(id) if kind == "load" => ("GET", format!("/api/docs/{}", js_sys::encode_uri_component(id))),
+        Some(id) => ("PUT", format!("/api/docs/{}", js_sys::encode_uri_component(id))),
+        None => ("POST", "/api/docs".to_string()),
+    };
+
+    let window = web_sys::window().expect("no global window exists");
+    let window_any = window.dyn_into::<js_sys::Object>().expect("window should be an object");
+
+    let body = document
+        .map(|document| JsValue::from_str(&serde_json::to_string(document).expect("documents always serialize")))
+        .unwrap_or(JsValue::UNDEFINED);
+
+    js_sys::Reflect::set(
+        &window_any,
+        &JsValue::from_str("_docToSave"),
+        &body
+    ).expect("Failed to set document body");
+
+    let _ = js_sys::eval(&format!(
+        "({DOCS_REQUEST_JS})({token}, {}, {}, {}, {}, window._docToSave);",
+        js_string(kind),
+        js_string(method),
+        js_string(&url),
+        id.map(js_string).unwrap_or_else(|| "null".to_string())
+    ));
+}
+
+/// The server document named by the page's `?doc=<id>` query parameter
+pub fn doc_id_from_location() -> Option<String> {
+    let search = web_sys::window()?.location().search().ok()?;
+    UrlSearchParams::new_with_str(&search).ok()?
+        .get("doc")
+        .filter(|id| !id.is_empty())
+}
+
+/// Put `id` in the address bar so reloading the page reopens the server copy
+pub fn set_location_doc_id(id: &str) {
+    let _ = js_sys::eval(&format!(
+        "(function() {{ \
+            const url = new URL(window.location.href); \
+            url.searchParams.set('doc', {}); \
+            window.history.replaceState(null, '', url); \
+        }})();",
+        js_string(id)
+    ));
+}
+
+/// Store `document` as a new server document. Reports the new id through
+/// `serverDocSaved`.
+pub fn create_document(token: u32, document: &StoredDocument) {
+    docs_request(token, "create", None, Some(document));
+}
+
+/// Overwrite the server document `id`. Reports through `serverDocSaved`.
+pub fn update_document(token: u32, id: &str, document: &StoredDocument) {
+    docs_request(token, "update", Some(id), Some(document));
+}
+
+/// Fetch the server document `id`. Reports its JSON through `serverDocLoaded`,
+/// or `serverDocMissing` if there's no such document.
+pub fn load_document(token: u32, id: &str) {
+    docs_request(token, "load", Some(id), None);
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use components_lib::editor::editor_core::{Buffer, OpenDocuments};
+use serde::{Deserialize, Serialize};
+
+use crate::preferences::local_storage;
+
+/// localStorage key holding the tabs that were open last time
+const SESSION_KEY: &str = "collab_hub.session";
+
+/// One tab as it was when the session was written
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionTab {
+    pub filename: Option<String>,
+    pub language: String,
+    pub content: String,
+    /// The text as last saved, for tabs with changes that weren't. It's what
+    /// the tab is restored as having been saved with.
+    #[serde(default)]
+    pub saved_content: Option<String>,
+    #[serde(default)]
+    pub read_only: bool,
+    /// The server document the tab was loaded from or saved to
+    #[serde(default)]
+    pub server_id: Option<String>,
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+/// Every tab that was open and which one was being edited. Scratch buffers
+/// are left out, they're not meant to outlive the page.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub tabs: Vec<SessionTab>,
+    pub active: usize,
+}
+
+impl Session {
+    /// The session for `documents`, linked to the server documents in `server_ids`
+    pub fn of(documents: &OpenDocuments, server_ids: &HashMap<u32, String>) -> Self {
+        let kept = documents.documents().iter().enumerate().filter(|(_, document)| !document.scratch);
+        let active = kept.clone().position(|(index, _)| index == documents.active_index()).unwrap_or(0);
+
+        let tabs = kept
+            .map(|(_, document)| SessionTab {
+                filename: document.filename.clone(),
+                language: document.language.clone(),
+                content: document.buffer.text(),
+                saved_content: document.buffer.is_modified().then(|| document.buffer.saved_text()),
+                read_only: document.read_only,
+                server_id: server_ids.get(&document.id).cloned(),
+                pinned: document.pinned,
+            })
+            .collect();
+
+        Self { tabs, active }
+    }
+
+    /// The tabs reopened, and the server document each of them is linked to
+    /// by its new id
+    pub fn restore(self) -> (OpenDocuments, HashMap<u32, String>) {
+        let mut documents = OpenDocuments::new();
+        let mut server_ids = HashMap::new();
+
+        for tab in self.tabs {
+            let buffer = match tab.saved_content {
+                Some(saved) => {
+                    let mut buffer = Buffer::from_str(&saved, tab.filename.clone());
+                    buffer.set_text(&tab.content);
+                    buffer
+                },
+                None => Buffer::from_str(&tab.content, tab.filename.clone()),
+            };
+
+            let id = documents.open(buffer, tab.filename, tab.language);
+            if let Some(document) = documents.get_mut(id) {
+                document.read_only = tab.read_only;
+                document.pinned = tab.pinned;
+            }
+            if let Some(server_id) = tab.server_id {
+                server_ids.insert(id, server_id);
+            }
+        }
+
+        documents.set_active(self.active);
+        (documents, server_ids)
+    }
+}
+
+pub fn write_session(session: &Session) {
+    if let (Some(storage), Ok(json)) = (local_storage(), serde_json::to_string(session)) {
+        let _ = storage.set_item(SESSION_KEY, &json);
+    }
+}
+
+/// The session written last time, if there's one with any tabs
+pub fn load_session() -> Option<Session> {
+    let json = local_storage()?.get_item(SESSION_KEY).ok()??;
+    serde_json::from_str::<Session>(&json).ok().filter(|session| !session.tabs.is_empty())
+}
+
+pub fn clear_session() {
+    if let Some(storage) = local_storage() {
+        let _ = storage.remove_item(SESSION_KEY);
+    }
+}
@@ -0,0 +1,73 @@
+use web_sys::UrlSearchParams;
+
+use crate::js::js_string;
+
+/// Files bigger than this aren't worth freezing the editor over
+const MAX_URL_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Downloads a file for an operation token and reports back through
+/// `window._editorActions`
+const FETCH_FILE_JS: &str = "
+    async function(token, url, name, maxBytes) {
+        const controller = window._editorAbortControllers && window._editorAbortControllers[token];
+        const signal = controller ? controller.signal : undefined;
+        const actions = window._editorActions;
+
+        try {
+            const response = await fetch(url, { signal });
+            if (!response.ok) {
+                throw new Error(`${response.status} ${response.statusText}`);
+            }
+
+            // Check the size before downloading the body
+            const length = Number(response.headers.get('Content-Length'));
+            if (length > maxBytes) {
+                controller && controller.abort();
+                actions && actions.urlFileFailed(token, `${name} is too large to open (${length} bytes)`);
+                return;
+            }
+
+            const contents = new Uint8Array(await response.arrayBuffer());
+            actions && actions.urlFileLoaded(token, contents, name);
+        } catch (err) {
+            if (err.name === 'AbortError') {
+                actions && actions.operationDone(token);
+                return;
+            }
+
+            // CORS failures surface as a bare TypeError with no status
+            console.error('Error fetching file:', err);
+            actions && actions.urlFileFailed(token, `Couldn't open ${url}: ${err.message}`);
+        }
+    }
+";
+
+/// The file named by the page's `?file=<url>` query parameter
+pub fn file_url_from_location() -> Option<String> {
+    let search = web_sys::window()?.location().search().ok()?;
+    UrlSearchParams::new_with_str(&search).ok()?
+        .get("file")
+        .filter(|url| !url.is_empty())
+}
+
+/// The file name at the end of `url`'s path, ignoring any query or fragment
+pub fn file_name_from_url(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let name = path.trim_end_matches('/').rsplit('/').next().unwrap_or_default();
+
+    js_sys::decode_uri_component(name)
+        .ok()
+        .and_then(|name| name.as_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "untitled".to_string())
+}
+
+/// Download the file at `url`. Reports its bytes through `urlFileLoaded`, or
+/// `urlFileFailed` with a message if it can't be fetched.
+pub fn fetch_url_file(token: u32, url: &str, name: &str) {
+    let _ = js_sys::eval(&format!(
+        "({FETCH_FILE_JS})({token}, {}, {}, {MAX_URL_FILE_BYTES});",
+        js_string(url),
+        js_string(name)
+    ));
+}
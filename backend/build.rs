@@ -1,6 +1,5 @@
-use std::env;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use std::process::Command;
 
 fn main() {
@@ -29,7 +28,7 @@ fn main() {
         let to = Path::new(to_str);
 
         if to.exists() {
-            fs::remove_dir_all(&to).expect("Failed to clean previous dist folder");
+            fs::remove_dir_all(to).expect("Failed to clean previous dist folder");
         }
 
         copy_dir_filtered(from, to).expect("Failed to copy built files");
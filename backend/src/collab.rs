@@ -0,0 +1,306 @@
+use axum::{
+  extract::{
+    ws::{Message, WebSocket, WebSocketUpgrade},
+    Path, Query, State,
+  },
+  response::IntoResponse,
+};
+use components_lib::core::{
+  collab::{transform_incoming, CollabMessage},
+  users::User,
+};
+use components_lib::editor::editor_core::Edit;
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use std::{
+  collections::{HashMap, VecDeque},
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+  },
+};
+use tokio::sync::broadcast;
+
+use crate::AppState;
+
+/// How many of a room's latest edits are kept to transform incoming ones
+/// against. An edit made before all of them is too old to put in order.
+const HISTORY_LIMIT: usize = 1000;
+
+/// A message relayed within a room, tagged with the connection that sent it
+#[derive(Clone)]
+struct RoomEvent {
+  from: u64,
+  /// For the sender only, rather than everyone else
+  reply: bool,
+  text: String,
+}
+
+struct Room {
+  sender: broadcast::Sender<RoomEvent>,
+  /// The latest edits made in the room, in the order they apply. An edit's
+  /// revision is its index plus `base` plus one.
+  history: VecDeque<Edit>,
+  /// How many edits were made before the oldest one kept
+  base: usize,
+  /// Who is connected, keyed by connection
+  users: HashMap<u64, User>,
+}
+
+/// Every open collaboration room, keyed by room name
+#[derive(Clone, Default)]
+pub struct Rooms {
+  rooms: Arc<Mutex<HashMap<String, Room>>>,
+  next_connection: Arc<AtomicU64>,
+}
+
+impl Rooms {
+  /// The room's sender, a receiver for everything from now on and the
+  /// revision the receiver starts after
+  fn join(&self, room: &str) -> (broadcast::Sender<RoomEvent>, broadcast::Receiver<RoomEvent>, usize) {
+    let mut rooms = self.rooms.lock().unwrap();
+    let room = rooms.entry(room.to_string()).or_insert_with(|| Room {
+      sender: broadcast::channel(256).0,
+      history: VecDeque::new(),
+      base: 0,
+      users: HashMap::new(),
+    });
+
+    (room.sender.clone(), room.sender.subscribe(), room.base + room.history.len())
+  }
+
+  /// Put an edit made against `revision` into the room's order and send it
+  /// on, acknowledging it to the connection it came from. Edits reaching past
+  /// `max_len` chars, the longest a document can be, or made against a
+  /// revision the room doesn't have are turned away with why.
+  fn apply_edit(
+    &self,
+    room: &str,
+    from: u64,
+    user_id: String,
+    revision: usize,
+    edit: Edit,
+    max_len: usize,
+  ) -> Result<(), &'static str> {
+    let end = match &edit {
+      Edit::Insert { pos, text } => pos.checked_add(text.chars().count()),
+      Edit::Delete { pos, len } => pos.checked_add(*len),
+    };
+    if end.is_none_or(|end| end > max_len) {
+      return Err("edit is out of range");
+    }
+
+    let mut rooms = self.rooms.lock().unwrap();
+    let Some(room) = rooms.get_mut(room) else {
+      return Ok(());
+    };
+    if revision > room.base + room.history.len() {
+      return Err("edit is against a revision the room hasn't reached");
+    }
+    let Some(since) = revision.checked_sub(room.base) else {
+      return Err("edit is against a revision too old to transform");
+    };
+
+    let edit = transform_incoming(room.history.make_contiguous(), since, edit);
+    room.history.push_back(edit.clone());
+    if room.history.len() > HISTORY_LIMIT {
+      room.history.pop_front();
+      room.base += 1;
+    }
+    let revision = room.base + room.history.len();
+
+    // Sent while the lock is held, so edits go out in revision order
+    let relayed = CollabMessage::Edit { user_id, revision, edit };
+    let _ = room.sender.send(RoomEvent { from, reply: false, text: relayed.to_json() });
+    let ack = CollabMessage::Ack { revision };
+    let _ = room.sender.send(RoomEvent { from, reply: true, text: ack.to_json() });
+    Ok(())
+  }
+
+  /// Tell the room `user` connected, and tell them who else is here
+  fn add_user(&self, room: &str, from: u64, user: User) {
+    let mut rooms = self.rooms.lock().unwrap();
+    let Some(room) = rooms.get_mut(room) else {
+      return;
+    };
+
+    room.users.insert(from, user.clone());
+
+    let join = CollabMessage::Join { user };
+    let _ = room.sender.send(RoomEvent { from, reply: false, text: join.to_json() });
+    let presence = CollabMessage::Presence { users: room.users.values().cloned().collect() };
+    let _ = room.sender.send(RoomEvent { from, reply: true, text: presence.to_json() });
+  }
+
+  fn leave(&self, room: &str, connection: u64) {
+    let mut rooms = self.rooms.lock().unwrap();
+    if let Some(joined) = rooms.get_mut(room) {
+      joined.users.remove(&connection);
+    }
+    if rooms.get(room).is_some_and(|room| room.sender.receiver_count() == 0) {
+      rooms.remove(room);
+    }
+  }
+}
+
+#[derive(Deserialize)]
+pub struct CollabParams {
+  user_id: String,
+}
+
+pub async fn collab_socket(
+  ws: WebSocketUpgrade,
+  Path(room): Path<String>,
+  Query(params): Query<CollabParams>,
+  State(state): State<AppState>,
+) -> impl IntoResponse {
+  // Edits are usually small, but pasting in a whole document makes one as big
+  let max_size = state.limits.max_ws_message_bytes;
+  ws.max_message_size(max_size)
+    .max_frame_size(max_size)
+    .on_upgrade(move |socket| relay(socket, room, params.user_id, state.rooms, state.limits.max_document_bytes))
+}
+
+/// Forward every message from this socket to the rest of the room and back,
+/// then tell the room this user left once the socket closes. Edits are put in
+/// order first, and a bad one ends the connection. Documents are no longer
+/// than `max_len` chars.
+async fn relay(socket: WebSocket, room: String, user_id: String, rooms: Rooms, max_len: usize) {
+  let connection = rooms.next_connection.fetch_add(1, Ordering::Relaxed);
+  let (sender, mut receiver, revision) = rooms.join(&room);
+  let (mut socket_tx, mut socket_rx) = socket.split();
+
+  let sync = CollabMessage::Sync { revision };
+  if socket_tx.send(Message::Text(sync.to_json().into())).await.is_err() {
+    drop(receiver);
+    rooms.leave(&room, connection);
+    return;
+  }
+
+  let mut outgoing = tokio::spawn(async move {
+    while let Ok(event) = receiver.recv().await {
+      let for_us = if event.reply { event.from == connection } else { event.from != connection };
+      if for_us && socket_tx.send(Message::Text(event.text.into())).await.is_err() {
+        break;
+      }
+    }
+  });
+
+  let room_sender = sender.clone();
+  let edit_rooms = rooms.clone();
+  let edit_room = room.clone();
+  let mut incoming = tokio::spawn(async move {
+    while let Some(Ok(message)) = socket_rx.next().await {
+      match message {
+        Message::Text(text) => match CollabMessage::from_json(&text) {
+          Some(CollabMessage::Edit { user_id, revision, edit }) => {
+            if let Err(why) = edit_rooms.apply_edit(&edit_room, connection, user_id, revision, edit, max_len) {
+              tracing::warn!(room = edit_room, why, "dropping collaborator that sent a bad edit");
+              break;
+            }
+          }
+          Some(CollabMessage::Join { user }) => {
+            edit_rooms.add_user(&edit_room, connection, user);
+          }
+          _ => {
+            let _ = room_sender.send(RoomEvent { from: connection, reply: false, text: text.to_string() });
+          }
+        },
+        Message::Close(_) => break,
+        _ => {}
+      }
+    }
+  });
+
+  // Whichever side finishes first ends the connection
+  tokio::select! {
+    _ = &mut outgoing => {
+      incoming.abort();
+      let _ = incoming.await;
+    }
+    _ = &mut incoming => {
+      outgoing.abort();
+      let _ = outgoing.await;
+    }
+  }
+
+  let leave = CollabMessage::Leave { user_id };
+  let _ = sender.send(RoomEvent { from: connection, reply: false, text: leave.to_json() });
+
+  drop(sender);
+  rooms.leave(&room, connection);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn insert(pos: usize, text: &str) -> Edit {
+    Edit::Insert { pos, text: text.to_string() }
+  }
+
+  #[test]
+  fn edits_are_transformed_and_relayed() {
+    let rooms = Rooms::default();
+    let (_sender, mut receiver, revision) = rooms.join("room");
+    assert_eq!(revision, 0);
+
+    rooms.apply_edit("room", 1, "a".to_string(), 0, insert(0, "abc"), 100).unwrap();
+    rooms.apply_edit("room", 2, "b".to_string(), 0, insert(0, "xy"), 100).unwrap();
+
+    let relayed: Vec<_> = std::iter::from_fn(|| receiver.try_recv().ok())
+        .filter(|event| !event.reply)
+        .filter_map(|event| CollabMessage::from_json(&event.text))
+        .collect();
+    assert_eq!(relayed[1], CollabMessage::Edit { user_id: "b".to_string(), revision: 2, edit: insert(3, "xy") });
+  }
+
+  #[test]
+  fn out_of_range_edits_are_rejected() {
+    let rooms = Rooms::default();
+    let _joined = rooms.join("room");
+
+    let overflowing = Edit::Delete { pos: 1, len: usize::MAX };
+    assert!(rooms.apply_edit("room", 1, "a".to_string(), 0, overflowing, 100).is_err());
+    assert!(rooms.apply_edit("room", 1, "a".to_string(), 0, insert(99, "xy"), 100).is_err());
+    assert!(rooms.apply_edit("room", 1, "a".to_string(), 5, insert(0, "x"), 100).is_err());
+    assert!(rooms.apply_edit("room", 1, "a".to_string(), 0, insert(0, "x"), 100).is_ok());
+  }
+
+  #[test]
+  fn history_is_capped() {
+    let rooms = Rooms::default();
+    let _joined = rooms.join("room");
+
+    for revision in 0..HISTORY_LIMIT + 10 {
+      rooms.apply_edit("room", 1, "a".to_string(), revision, insert(0, "x"), usize::MAX).unwrap();
+    }
+
+    let (_, _, revision) = rooms.join("room");
+    assert_eq!(revision, HISTORY_LIMIT + 10);
+    assert_eq!(rooms.rooms.lock().unwrap()["room"].history.len(), HISTORY_LIMIT);
+    assert!(rooms.apply_edit("room", 1, "a".to_string(), 5, insert(0, "x"), usize::MAX).is_err());
+    assert!(rooms.apply_edit("room", 1, "a".to_string(), 10, insert(0, "x"), usize::MAX).is_ok());
+  }
+
+  #[test]
+  fn stale_edits_after_trimming_pass_only_what_came_since() {
+    let rooms = Rooms::default();
+    let _joined = rooms.join("room");
+    for revision in 0..HISTORY_LIMIT + 10 {
+      rooms.apply_edit("room", 1, "a".to_string(), revision, insert(0, "x"), usize::MAX).unwrap();
+    }
+
+    // Ten edits behind, with the first ten of the history long gone
+    let (_sender, mut receiver, _) = rooms.join("room");
+    rooms.apply_edit("room", 2, "b".to_string(), HISTORY_LIMIT, insert(0, "y"), usize::MAX).unwrap();
+
+    let relayed = std::iter::from_fn(|| receiver.try_recv().ok())
+        .find(|event| !event.reply)
+        .and_then(|event| CollabMessage::from_json(&event.text));
+    assert_eq!(
+      relayed,
+      Some(CollabMessage::Edit { user_id: "b".to_string(), revision: HISTORY_LIMIT + 11, edit: insert(10, "y") })
+    );
+  }
+}
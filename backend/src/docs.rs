@@ -0,0 +1,74 @@
+use axum::{
+  extract::Path,
+  http::StatusCode,
+  response::{IntoResponse, Response},
+  Json,
+};
+use components_lib::core::documents::{CreatedDocument, StoredDocument};
+use std::path::PathBuf;
+use tokio::fs;
+use uuid::Uuid;
+
+/// Documents are stored here as one JSON file per document id
+const DATA_DIR: &str = "data";
+
+fn document_path(id: &Uuid) -> PathBuf {
+  PathBuf::from(DATA_DIR).join(format!("{id}.json"))
+}
+
+async fn write_document(id: &Uuid, document: &StoredDocument) -> std::io::Result<()> {
+  fs::create_dir_all(DATA_DIR).await?;
+  fs::write(document_path(id), serde_json::to_vec(document)?).await
+}
+
+/// Only ids we handed out can name a file, so a bad id can't reach outside `DATA_DIR`
+fn parse_id(id: &str) -> Option<Uuid> {
+  Uuid::parse_str(id).ok()
+}
+
+pub async fn create_document(Json(document): Json<StoredDocument>) -> impl IntoResponse {
+  let id = Uuid::new_v4();
+
+  match write_document(&id, &document).await {
+    Ok(()) => (StatusCode::CREATED, Json(CreatedDocument { id: id.to_string() })).into_response(),
+    Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Failed to store document").into_response(),
+  }
+}
+
+/// The document stored under `id`, or the response to send when it can't be read
+pub async fn read_document(id: &str) -> Result<StoredDocument, Response> {
+  let Some(id) = parse_id(id) else {
+    return Err((StatusCode::NOT_FOUND, "Document Not Found").into_response());
+  };
+
+  match fs::read(document_path(&id)).await {
+    Ok(contents) => serde_json::from_slice::<StoredDocument>(&contents)
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Stored document is corrupt").into_response()),
+    Err(_) => Err((StatusCode::NOT_FOUND, "Document Not Found").into_response()),
+  }
+}
+
+pub async fn get_document(Path(id): Path<String>) -> impl IntoResponse {
+  match read_document(&id).await {
+    Ok(document) => Json(document).into_response(),
+    Err(response) => response,
+  }
+}
+
+pub async fn update_document(
+  Path(id): Path<String>,
+  Json(document): Json<StoredDocument>,
+) -> impl IntoResponse {
+  let Some(id) = parse_id(&id) else {
+    return (StatusCode::NOT_FOUND, "Document Not Found").into_response();
+  };
+
+  if !fs::try_exists(document_path(&id)).await.unwrap_or(false) {
+    return (StatusCode::NOT_FOUND, "Document Not Found").into_response();
+  }
+
+  match write_document(&id, &document).await {
+    Ok(()) => StatusCode::NO_CONTENT.into_response(),
+    Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Failed to store document").into_response(),
+  }
+}
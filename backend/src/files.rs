@@ -0,0 +1,387 @@
+use axum::{
+  body::Bytes,
+  extract::{Path, Query, State},
+  http::{header, HeaderMap, StatusCode},
+  response::{IntoResponse, Response},
+  Json,
+};
+use components_lib::core::documents::WorkspaceEntry;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path as FsPath, PathBuf};
+use tokio::fs;
+
+use crate::AppState;
+
+/// The directory `WORKSPACE_ROOT` names, which files are saved to and read
+/// from. Unset, the file routes are off.
+pub fn workspace_root_from_env() -> Option<PathBuf> {
+  let root = std::env::var("WORKSPACE_ROOT").ok().filter(|root| !root.trim().is_empty())?;
+  Some(PathBuf::from(root.trim()))
+}
+
+/// Why the file routes answer 404 while `WORKSPACE_ROOT` is unset
+const FILES_OFF: &str = "Workspace files are off, set WORKSPACE_ROOT to turn them on";
+
+/// What saving a file wrote
+#[derive(Serialize)]
+struct WrittenFile {
+  bytes: usize,
+}
+
+/// Where `path` is under `root`, or None if it would lead outside it. Only
+/// plain names are allowed, no `..`, and no absolute paths.
+fn resolve(root: &FsPath, path: &str) -> Option<PathBuf> {
+  let relative = FsPath::new(path);
+  let plain = relative.components().all(|part| matches!(part, Component::Normal(_) | Component::CurDir));
+  (plain && relative.components().any(|part| matches!(part, Component::Normal(_)))).then(|| root.join(relative))
+}
+
+/// Whether `path` really is inside `root`, following any symlinks that
+/// could lead out of it. False if it doesn't exist.
+async fn is_within(root: &FsPath, path: &FsPath) -> bool {
+  match (fs::canonicalize(root).await, fs::canonicalize(path).await) {
+    (Ok(root), Ok(path)) => path.starts_with(root),
+    _ => false,
+  }
+}
+
+/// The workspace root and the file under it `path` names, or why there isn't
+/// one it may name
+fn file_path<'a>(state: &'a AppState, path: &str) -> Result<(&'a FsPath, PathBuf), (StatusCode, &'static str)> {
+  let root = state
+      .workspace_root
+      .as_deref()
+      .ok_or((StatusCode::NOT_FOUND, FILES_OFF))?;
+  let file_path = resolve(root, path).ok_or((StatusCode::BAD_REQUEST, "Path leads outside the workspace"))?;
+  Ok((root, file_path))
+}
+
+/// The only type saved files may be posted as. Browsers won't send it from
+/// another site's page without asking first, the way they would a form's.
+const FILE_CONTENT_TYPE: &str = "application/octet-stream";
+
+/// Whether a write with these headers came from a page that may save files:
+/// one from the backend itself or an allowed origin, or no page at all. The
+/// body has to be [`FILE_CONTENT_TYPE`], so other sites can't save files
+/// with a request that skips the CORS preflight.
+fn check_write_request(state: &AppState, headers: &HeaderMap) -> Result<(), (StatusCode, &'static str)> {
+  let content_type = headers
+      .get(header::CONTENT_TYPE)
+      .and_then(|value| value.to_str().ok())
+      .and_then(|value| value.split(';').next())
+      .map(str::trim);
+  if !content_type.is_some_and(|content_type| content_type.eq_ignore_ascii_case(FILE_CONTENT_TYPE)) {
+    return Err((StatusCode::UNSUPPORTED_MEDIA_TYPE, "Files must be saved as application/octet-stream"));
+  }
+
+  let Some(origin) = headers.get(header::ORIGIN) else {
+    return Ok(());
+  };
+  let same_origin = headers.get(header::HOST).and_then(|host| host.to_str().ok()).is_some_and(|host| {
+    origin
+        .to_str()
+        .ok()
+        .and_then(|origin| origin.split_once("://"))
+        .is_some_and(|(_, origin_host)| origin_host == host)
+  });
+  if same_origin || state.allowed_origins.contains(origin) {
+    Ok(())
+  } else {
+    Err((StatusCode::FORBIDDEN, "Files can't be saved from this origin"))
+  }
+}
+
+/// Write the body to the file `path` names under the workspace root, making
+/// any directories it's in. Answers with how many bytes were written.
+pub async fn write_file(
+  State(state): State<AppState>,
+  Path(path): Path<String>,
+  headers: HeaderMap,
+  contents: Bytes,
+) -> Response {
+  if let Err(rejection) = check_write_request(&state, &headers) {
+    return rejection.into_response();
+  }
+  let (root, file_path) = match file_path(&state, &path) {
+    Ok(found) => found,
+    Err(rejection) => return rejection.into_response(),
+  };
+  let dir = file_path.parent().unwrap_or(root);
+
+  // A symlink on the way, or the file itself being one, mustn't lead the
+  // write out. The directories still to make go in the nearest one there is.
+  let mut existing = file_path.as_path();
+  while !fs::try_exists(existing).await.unwrap_or(false) {
+    let Some(parent) = existing.parent() else {
+      break;
+    };
+    existing = parent;
+  }
+  if !is_within(root, existing).await {
+    return (StatusCode::BAD_REQUEST, "Path leads outside the workspace").into_response();
+  }
+
+  if let Err(err) = fs::create_dir_all(dir).await {
+    tracing::warn!(path = %file_path.display(), %err, "couldn't make the file's directory");
+    return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to save file").into_response();
+  }
+
+  match fs::write(&file_path, &contents).await {
+    Ok(()) => Json(WrittenFile { bytes: contents.len() }).into_response(),
+    Err(err) => {
+      tracing::warn!(path = %file_path.display(), %err, "couldn't save file");
+      (StatusCode::INTERNAL_SERVER_ERROR, "Failed to save file").into_response()
+    }
+  }
+}
+
+/// The contents of the file `path` names under the workspace root
+pub async fn read_file(State(state): State<AppState>, Path(path): Path<String>) -> Response {
+  let (root, file_path) = match file_path(&state, &path) {
+    Ok(found) => found,
+    Err(rejection) => return rejection.into_response(),
+  };
+  if !is_within(root, &file_path).await {
+    return (StatusCode::NOT_FOUND, "File Not Found").into_response();
+  }
+
+  match fs::read(&file_path).await {
+    Ok(contents) => contents.into_response(),
+    Err(_) => (StatusCode::NOT_FOUND, "File Not Found").into_response(),
+  }
+}
+
+#[derive(Deserialize)]
+pub struct TreeParams {
+  /// Whether to list dotfiles too
+  #[serde(default)]
+  hidden: bool,
+}
+
+/// What's in the directory at `path` under the workspace root, directories
+/// first and then files, each alphabetically. Dotfiles are left out unless
+/// `hidden` is set.
+async fn list_directory(state: &AppState, path: Option<&str>, hidden: bool) -> Response {
+  let (root, dir) = match path {
+    Some(path) => match file_path(state, path) {
+      Ok(found) => found,
+      Err(rejection) => return rejection.into_response(),
+    },
+    None => match state.workspace_root.as_deref() {
+      Some(root) => (root, root.to_path_buf()),
+      None => return (StatusCode::NOT_FOUND, FILES_OFF).into_response(),
+    },
+  };
+  if !is_within(root, &dir).await {
+    return (StatusCode::NOT_FOUND, "Directory Not Found").into_response();
+  }
+
+  let mut read_dir = match fs::read_dir(&dir).await {
+    Ok(read_dir) => read_dir,
+    Err(_) => return (StatusCode::NOT_FOUND, "Directory Not Found").into_response(),
+  };
+
+  let mut entries = Vec::new();
+  loop {
+    let entry = match read_dir.next_entry().await {
+      Ok(Some(entry)) => entry,
+      Ok(None) => break,
+      Err(err) => {
+        tracing::warn!(path = %dir.display(), %err, "couldn't list directory");
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to list directory").into_response();
+      }
+    };
+    let name = entry.file_name().to_string_lossy().into_owned();
+    if name.starts_with('.') && !hidden {
+      continue;
+    }
+    // Whatever a symlink points at, or nothing if it's broken
+    let Ok(metadata) = fs::metadata(entry.path()).await else {
+      continue;
+    };
+    let is_dir = metadata.is_dir();
+    entries.push(WorkspaceEntry { name, is_dir, size: if is_dir { 0 } else { metadata.len() } });
+  }
+
+  entries.sort_by(|a, b| {
+    b.is_dir
+        .cmp(&a.is_dir)
+        .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        .then_with(|| a.name.cmp(&b.name))
+  });
+  Json(entries).into_response()
+}
+
+/// What's at the top of the workspace
+pub async fn workspace_tree(State(state): State<AppState>, Query(params): Query<TreeParams>) -> Response {
+  list_directory(&state, None, params.hidden).await
+}
+
+/// What's in the directory `path` names under the workspace root
+pub async fn directory_tree(
+  State(state): State<AppState>,
+  Path(path): Path<String>,
+  Query(params): Query<TreeParams>,
+) -> Response {
+  list_directory(&state, Some(&path), params.hidden).await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use axum::{body::to_bytes, http::HeaderValue};
+  use uuid::Uuid;
+
+  /// A fresh, empty workspace, removed when dropped
+  struct Workspace(PathBuf);
+
+  impl Workspace {
+    fn new() -> Self {
+      let root = std::env::temp_dir().join(format!("collab_hub-files-{}", Uuid::new_v4()));
+      std::fs::create_dir_all(&root).unwrap();
+      Self(root)
+    }
+
+    fn state(&self) -> AppState {
+      AppState {
+        workspace_root: Some(self.0.clone()),
+        allowed_origins: vec![HeaderValue::from_static("http://localhost:8081")].into(),
+        ..Default::default()
+      }
+    }
+  }
+
+  impl Drop for Workspace {
+    fn drop(&mut self) {
+      let _ = std::fs::remove_dir_all(&self.0);
+    }
+  }
+
+  fn octet_stream() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(FILE_CONTENT_TYPE));
+    headers
+  }
+
+  async fn write(state: &AppState, path: &str, headers: HeaderMap, contents: &'static str) -> Response {
+    write_file(State(state.clone()), Path(path.to_string()), headers, Bytes::from_static(contents.as_bytes())).await
+  }
+
+  async fn body(response: Response) -> String {
+    String::from_utf8(to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec()).unwrap()
+  }
+
+  #[tokio::test]
+  async fn written_files_read_back() {
+    let workspace = Workspace::new();
+    let state = workspace.state();
+
+    let response = write(&state, "src/main.rs", octet_stream(), "fn main() {}\n").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body(response).await, r#"{"bytes":13}"#);
+
+    let response = read_file(State(state), Path("src/main.rs".to_string())).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body(response).await, "fn main() {}\n");
+  }
+
+  #[tokio::test]
+  async fn paths_out_of_the_workspace_are_rejected() {
+    let workspace = Workspace::new();
+    let state = workspace.state();
+
+    for path in ["../escaped.txt", "src/../../escaped.txt", "/etc/hostname"] {
+      let response = write(&state, path, octet_stream(), "nope").await;
+      assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{path}");
+
+      let response = read_file(State(state.clone()), Path(path.to_string())).await;
+      assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{path}");
+    }
+    assert!(!workspace.0.parent().unwrap().join("escaped.txt").exists());
+  }
+
+  #[tokio::test]
+  async fn writes_need_the_file_content_type() {
+    let workspace = Workspace::new();
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+
+    let response = write(&workspace.state(), "notes.txt", headers, "hi").await;
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    let response = write(&workspace.state(), "notes.txt", HeaderMap::new(), "hi").await;
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert!(!workspace.0.join("notes.txt").exists());
+  }
+
+  #[tokio::test]
+  async fn writes_from_other_sites_are_forbidden() {
+    let workspace = Workspace::new();
+    let state = workspace.state();
+    let with_origin = |origin: &'static str| {
+      let mut headers = octet_stream();
+      headers.insert(header::HOST, HeaderValue::from_static("127.0.0.1:8080"));
+      headers.insert(header::ORIGIN, HeaderValue::from_static(origin));
+      headers
+    };
+
+    let response = write(&state, "notes.txt", with_origin("https://evil.example"), "hi").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert!(!workspace.0.join("notes.txt").exists());
+
+    for origin in ["http://127.0.0.1:8080", "http://localhost:8081"] {
+      let response = write(&state, "notes.txt", with_origin(origin), "hi").await;
+      assert_eq!(response.status(), StatusCode::OK, "{origin}");
+    }
+  }
+
+  #[tokio::test]
+  async fn directories_are_listed_first_then_alphabetically() {
+    let workspace = Workspace::new();
+    let root = &workspace.0;
+    std::fs::create_dir_all(root.join("src/bin")).unwrap();
+    std::fs::create_dir(root.join("Docs")).unwrap();
+    std::fs::create_dir(root.join(".git")).unwrap();
+    std::fs::write(root.join("README.md"), "# Hi\n").unwrap();
+    std::fs::write(root.join("build.rs"), "fn main() {}").unwrap();
+    std::fs::write(root.join(".env"), "SECRET=1").unwrap();
+    std::fs::write(root.join("src/lib.rs"), "").unwrap();
+
+    let response = workspace_tree(State(workspace.state()), Query(TreeParams { hidden: false })).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let listed: serde_json::Value = serde_json::from_str(&body(response).await).unwrap();
+    assert_eq!(
+      listed,
+      serde_json::json!([
+        { "name": "Docs", "is_dir": true, "size": 0 },
+        { "name": "src", "is_dir": true, "size": 0 },
+        { "name": "build.rs", "is_dir": false, "size": 12 },
+        { "name": "README.md", "is_dir": false, "size": 5 },
+      ])
+    );
+
+    let response = directory_tree(State(workspace.state()), Path("src".to_string()), Query(TreeParams { hidden: false })).await;
+    let listed: Vec<WorkspaceEntry> = serde_json::from_str(&body(response).await).unwrap();
+    let names: Vec<_> = listed.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, ["bin", "lib.rs"]);
+  }
+
+  #[tokio::test]
+  async fn dotfiles_are_listed_when_asked_for() {
+    let workspace = Workspace::new();
+    std::fs::create_dir(workspace.0.join(".git")).unwrap();
+    std::fs::write(workspace.0.join(".env"), "").unwrap();
+    std::fs::write(workspace.0.join("main.rs"), "").unwrap();
+
+    let response = workspace_tree(State(workspace.state()), Query(TreeParams { hidden: true })).await;
+    let listed: Vec<WorkspaceEntry> = serde_json::from_str(&body(response).await).unwrap();
+    let names: Vec<_> = listed.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, [".git", ".env", "main.rs"]);
+  }
+
+  #[tokio::test]
+  async fn directories_out_of_the_workspace_are_rejected() {
+    let workspace = Workspace::new();
+    let response = directory_tree(State(workspace.state()), Path("..".to_string()), Query(TreeParams { hidden: false })).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+  }
+}
@@ -0,0 +1,18 @@
+use axum::{http::StatusCode, response::IntoResponse, Json};
+use serde_json::json;
+use tokio::fs;
+
+use crate::PUBLIC_DIR;
+
+/// The process is up and answering requests
+pub async fn healthz() -> impl IntoResponse {
+  Json(json!({ "status": "ok" }))
+}
+
+/// The built app can be served, which needs its public directory to be readable
+pub async fn readyz() -> impl IntoResponse {
+  match fs::read_dir(PUBLIC_DIR).await {
+    Ok(_) => (StatusCode::OK, Json(json!({ "status": "ok" }))),
+    Err(_) => (StatusCode::SERVICE_UNAVAILABLE, Json(json!({ "status": "unavailable" }))),
+  }
+}
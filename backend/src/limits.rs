@@ -0,0 +1,128 @@
+use axum::{
+  extract::{ConnectInfo, Request, State},
+  http::{header, HeaderValue, StatusCode},
+  middleware::Next,
+  response::{IntoResponse, Response},
+};
+use std::{
+  collections::HashMap,
+  net::{IpAddr, SocketAddr},
+  str::FromStr,
+  sync::{Arc, Mutex},
+  time::Instant,
+};
+
+const MIB: usize = 1024 * 1024;
+
+/// How big requests may get and how often they may come. Each can be set
+/// with the environment variable of the same name, in bytes or per minute.
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+  /// `MAX_DOCUMENT_BYTES`, the largest body `POST /api/docs`,
+  /// `PUT /api/docs/{id}`, `POST /code_editor/files/{*path}` and
+  /// `POST /api/lint/{language}` accept. Bigger ones get a 413. Edits to a
+  /// collaboration room's document can't reach past this many chars either.
+  pub max_document_bytes: usize,
+  /// `MAX_WS_MESSAGE_BYTES`, the largest collaboration message relayed.
+  /// Bigger ones close the socket.
+  pub max_ws_message_bytes: usize,
+  /// `DOC_WRITES_PER_MINUTE`, how many documents and files one IP may create or save
+  /// a minute. More get a 429.
+  pub doc_writes_per_minute: u32,
+  /// `LINTS_PER_MINUTE`, how many documents one IP may have linted a minute.
+  /// More get a 429.
+  pub lints_per_minute: u32,
+}
+
+impl Default for Limits {
+  fn default() -> Self {
+    Self {
+      max_document_bytes: 5 * MIB,
+      max_ws_message_bytes: 5 * MIB,
+      doc_writes_per_minute: 60,
+      lints_per_minute: 60,
+    }
+  }
+}
+
+impl Limits {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    Self {
+      max_document_bytes: env_or("MAX_DOCUMENT_BYTES", defaults.max_document_bytes),
+      max_ws_message_bytes: env_or("MAX_WS_MESSAGE_BYTES", defaults.max_ws_message_bytes),
+      doc_writes_per_minute: env_or("DOC_WRITES_PER_MINUTE", defaults.doc_writes_per_minute),
+      lints_per_minute: env_or("LINTS_PER_MINUTE", defaults.lints_per_minute),
+    }
+  }
+}
+
+/// The environment variable `name` parsed, or `default` if it's unset or invalid
+fn env_or<T: FromStr>(name: &str, default: T) -> T {
+  let Ok(value) = std::env::var(name) else {
+    return default;
+  };
+
+  value.trim().parse().unwrap_or_else(|_| {
+    tracing::warn!(name, value, "ignoring invalid setting");
+    default
+  })
+}
+
+/// A token bucket per IP. Each request takes a token, and the bucket fills
+/// back up at the per-minute rate to at most that many.
+#[derive(Clone)]
+pub struct RateLimiter {
+  per_minute: u32,
+  // The tokens left in each IP's bucket, as of when it was last used
+  buckets: Arc<Mutex<HashMap<IpAddr, (f64, Instant)>>>,
+}
+
+impl RateLimiter {
+  pub fn new(per_minute: u32) -> Self {
+    Self { per_minute, buckets: Arc::default() }
+  }
+
+  /// Take a token from `ip`'s bucket. If it's empty, the seconds until it
+  /// has one again.
+  fn take(&self, ip: IpAddr) -> Result<(), u64> {
+    let capacity = self.per_minute as f64;
+    let per_second = capacity / 60.0;
+    let now = Instant::now();
+
+    let mut buckets = self.buckets.lock().unwrap();
+    // Full buckets are no different from missing ones, so they're dropped
+    // to keep the map from growing with every IP ever seen
+    buckets.retain(|_, (tokens, at)| *tokens + now.duration_since(*at).as_secs_f64() * per_second < capacity);
+
+    let (tokens, at) = buckets.entry(ip).or_insert((capacity, now));
+    *tokens = (*tokens + now.duration_since(*at).as_secs_f64() * per_second).min(capacity);
+    *at = now;
+
+    if *tokens >= 1.0 {
+      *tokens -= 1.0;
+      Ok(())
+    } else if per_second > 0.0 {
+      Err(((1.0 - *tokens) / per_second).ceil() as u64)
+    } else {
+      Err(60)
+    }
+  }
+}
+
+/// Turn away requests from an IP that has used up its tokens, with a 429
+/// saying when to try again
+pub async fn rate_limit(
+  State(limiter): State<RateLimiter>,
+  ConnectInfo(addr): ConnectInfo<SocketAddr>,
+  request: Request,
+  next: Next,
+) -> Response {
+  match limiter.take(addr.ip()) {
+    Ok(()) => next.run(request).await,
+    Err(retry_after) => {
+      let retry_after = HeaderValue::from(retry_after);
+      (StatusCode::TOO_MANY_REQUESTS, [(header::RETRY_AFTER, retry_after)], "Too many requests").into_response()
+    }
+  }
+}
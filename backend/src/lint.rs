@@ -0,0 +1,460 @@
+use axum::{
+  extract::{Path, State},
+  http::StatusCode,
+  response::{IntoResponse, Response},
+  Json,
+};
+use components_lib::editor::editor_core::{chars::is_word_char, validator_for, Diagnostic, Severity};
+use futures_util::future::BoxFuture;
+use serde::Deserialize;
+use std::{
+  io::ErrorKind,
+  path::{Path as FsPath, PathBuf},
+  process::Stdio,
+  time::Duration,
+};
+use tokio::{fs, process::Command, sync::OnceCell};
+use uuid::Uuid;
+
+use crate::AppState;
+
+/// Whether `SERVER_LINTING=1` is set. Linting compiles what anyone posts, so
+/// it's off unless asked for.
+pub fn server_linting_from_env() -> bool {
+  std::env::var("SERVER_LINTING").is_ok_and(|value| value.trim() == "1")
+}
+
+/// Why the lint route answers 404 while `SERVER_LINTING` is unset
+const LINTING_OFF: &str = "Server linting is off, set SERVER_LINTING=1 to turn it on";
+
+/// How long a linter may run before it's given up on
+const LINT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Why a linter couldn't check a document
+pub enum LintError {
+  /// What it runs isn't installed on the server
+  Unavailable(String),
+  /// It won't check this document
+  Refused(String),
+  Failed(String),
+}
+
+/// Checks documents in some language. Linting is a future so linters that
+/// run a tool don't hold up the server while it works.
+pub trait Linter: Send + Sync {
+  /// Reported as the source of what it finds
+  fn name(&self) -> &'static str;
+  fn lint<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Vec<Diagnostic>, LintError>>;
+}
+
+/// The editor's own check that a document parses, for languages it has one for
+struct ParseLinter {
+  language: &'static str,
+}
+
+impl Linter for ParseLinter {
+  fn name(&self) -> &'static str {
+    self.language
+  }
+
+  fn lint<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Vec<Diagnostic>, LintError>> {
+    Box::pin(async move {
+      let Some(validator) = validator_for(self.language) else {
+        return Ok(Vec::new());
+      };
+      Ok(validator.validate(text).err().map(Diagnostic::from).into_iter().collect())
+    })
+  }
+}
+
+/// Type checks a Rust file as a library crate of its own, so it can't use
+/// other crates. Unused items aren't reported, a file on its own rarely uses
+/// everything it declares.
+struct RustcLinter;
+
+/// Macros that read the server's files or environment while compiling, and
+/// whose output would come back in diagnostics
+const FORBIDDEN_MACROS: [&str; 5] = ["include", "include_str", "include_bytes", "env", "option_env"];
+
+/// Where the string whose opening quote comes before `idx` ends, just past
+/// its closing quote. A raw string is closed by a quote and `hashes` hashes.
+fn string_end(chars: &[char], mut idx: usize, hashes: Option<usize>) -> usize {
+  while idx < chars.len() {
+    match (chars[idx], hashes) {
+      ('\\', None) => idx += 2,
+      ('"', None) => return idx + 1,
+      ('"', Some(hashes)) if chars[idx + 1..].iter().take(hashes).filter(|c| **c == '#').count() == hashes => {
+        return idx + 1 + hashes;
+      }
+      _ => idx += 1,
+    }
+  }
+  chars.len()
+}
+
+/// `text` with its comments as spaces and its string and char literals
+/// emptied, leaving only what rustc reads as code. Block comments nest, as
+/// they do in Rust.
+fn code_only(text: &str) -> String {
+  let chars: Vec<char> = text.chars().collect();
+  let mut code = String::with_capacity(text.len());
+  let mut idx = 0;
+
+  while idx < chars.len() {
+    let c = chars[idx];
+    let next = chars.get(idx + 1).copied();
+
+    if c == '/' && next == Some('/') {
+      while idx < chars.len() && chars[idx] != '\n' {
+        idx += 1;
+      }
+      code.push(' ');
+      continue;
+    }
+    if c == '/' && next == Some('*') {
+      let mut depth = 0;
+      while idx < chars.len() {
+        match (chars[idx], chars.get(idx + 1)) {
+          ('/', Some('*')) => {
+            depth += 1;
+            idx += 2;
+          }
+          ('*', Some('/')) => {
+            depth -= 1;
+            idx += 2;
+            if depth == 0 {
+              break;
+            }
+          }
+          _ => idx += 1,
+        }
+      }
+      code.push(' ');
+      continue;
+    }
+    if c == '"' {
+      idx = string_end(&chars, idx + 1, None);
+      code.push_str("\"\"");
+      continue;
+    }
+    // r"..", r#".."#, br".." and cr".."
+    if matches!(c, 'r' | 'b' | 'c') && !idx.checked_sub(1).is_some_and(|before| is_word_char(chars[before])) {
+      let r = if c != 'r' && next == Some('r') { idx + 1 } else { idx };
+      let hashes = chars[r + 1..].iter().take_while(|c| **c == '#').count();
+      if chars[r] == 'r' && chars.get(r + 1 + hashes) == Some(&'"') {
+        code.extend(&chars[idx..r]);
+        idx = string_end(&chars, r + 2 + hashes, Some(hashes));
+        code.push_str("\"\"");
+        continue;
+      }
+    }
+    // A char literal, not the quote starting a lifetime
+    if c == '\'' {
+      if next == Some('\\') {
+        idx += 3;
+        while idx < chars.len() && chars[idx] != '\'' {
+          idx += 1;
+        }
+        idx += 1;
+        code.push_str("' '");
+        continue;
+      }
+      if chars.get(idx + 2) == Some(&'\'') {
+        idx += 3;
+        code.push_str("' '");
+        continue;
+      }
+    }
+
+    code.push(c);
+    idx += 1;
+  }
+
+  code
+}
+
+/// Why rustc mustn't be run on `text`, if it mustn't. Any use of one of the
+/// [`FORBIDDEN_MACROS`] is refused, even passed to another macro to call. So
+/// is an out-of-line `mod name;`, whose `#[path]` could name any file. `env`
+/// is still allowed as the path to `std::env`. Comments and literals are
+/// taken out first, so neither can hide what follows a name from the check.
+fn forbidden_source(text: &str) -> Option<String> {
+  let text = &code_only(text);
+  let mut rest = text.char_indices().peekable();
+
+  while let Some((start, c)) = rest.next() {
+    if !is_word_char(c) {
+      continue;
+    }
+    let mut end = start + c.len_utf8();
+    while let Some((at, c)) = rest.next_if(|(_, c)| is_word_char(*c)) {
+      end = at + c.len_utf8();
+    }
+    let word = &text[start..end];
+    let before = text[..start].trim_end();
+    let after = text[end..].trim_start();
+
+    if FORBIDDEN_MACROS.contains(&word) {
+      let module_path = word == "env" && !after.starts_with('!') && (before.ends_with("::") || after.starts_with("::"));
+      if !module_path {
+        return Some(format!("Server linting doesn't check code that uses `{word}!`"));
+      }
+    }
+
+    if word == "mod" && after.find([';', '{']).is_some_and(|at| after[at..].starts_with(';')) {
+      return Some("Server linting doesn't check code with out-of-line modules".to_string());
+    }
+  }
+
+  None
+}
+
+impl Linter for RustcLinter {
+  fn name(&self) -> &'static str {
+    "rustc"
+  }
+
+  fn lint<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Vec<Diagnostic>, LintError>> {
+    Box::pin(async move {
+      if let Some(why) = forbidden_source(text) {
+        return Err(LintError::Refused(why));
+      }
+      let dir = std::env::temp_dir().join(format!("collab_hub-lint-{}", Uuid::new_v4()));
+      fs::create_dir_all(&dir).await.map_err(|err| LintError::Failed(err.to_string()))?;
+      let result = run_rustc(&dir, text).await;
+      let _ = fs::remove_dir_all(&dir).await;
+      result
+    })
+  }
+}
+
+/// The rustc binary itself, found once through whichever `rustc` is on the
+/// path. A toolchain manager's `rustc` needs the environment to pick the
+/// toolchain, and the real one doesn't, so linting can run it without any.
+static RUSTC: OnceCell<Option<PathBuf>> = OnceCell::const_new();
+
+async fn rustc_path() -> Option<&'static PathBuf> {
+  RUSTC
+      .get_or_init(|| async {
+        let output = Command::new("rustc").args(["--print", "sysroot"]).stdin(Stdio::null()).output().await.ok()?;
+        let sysroot = String::from_utf8(output.stdout).ok().filter(|_| output.status.success())?;
+        let rustc = PathBuf::from(sysroot.trim()).join("bin").join(format!("rustc{}", std::env::consts::EXE_SUFFIX));
+        rustc.is_file().then_some(rustc)
+      })
+      .await
+      .as_ref()
+}
+
+/// Runs rustc in `dir` with an empty environment, so no `env!` that gets
+/// past [`forbidden_source`] has anything of the server's to read, and
+/// relative paths only reach the lint's own files
+async fn run_rustc(dir: &FsPath, text: &str) -> Result<Vec<Diagnostic>, LintError> {
+  let Some(rustc) = rustc_path().await else {
+    return Err(LintError::Unavailable("rustc isn't installed on the server".to_string()));
+  };
+  fs::write(dir.join("lint.rs"), text).await.map_err(|err| LintError::Failed(err.to_string()))?;
+
+  let output = Command::new(rustc)
+      .args(["--edition", "2021", "--crate-type", "lib", "--emit=metadata", "--error-format=json"])
+      .args(["-A", "dead_code"])
+      .args(["-o", "lint.rmeta", "lint.rs"])
+      .env_clear()
+      .current_dir(dir)
+      .stdin(Stdio::null())
+      .kill_on_drop(true)
+      .output();
+
+  let output = match tokio::time::timeout(LINT_TIMEOUT, output).await {
+    Ok(Ok(output)) => output,
+    Ok(Err(err)) if err.kind() == ErrorKind::NotFound => {
+      return Err(LintError::Unavailable("rustc isn't installed on the server".to_string()));
+    }
+    Ok(Err(err)) => return Err(LintError::Failed(err.to_string())),
+    Err(_) => return Err(LintError::Failed("rustc took too long".to_string())),
+  };
+
+  Ok(String::from_utf8_lossy(&output.stderr).lines().filter_map(|line| rustc_diagnostic(line, text)).collect())
+}
+
+/// One message of rustc's `--error-format=json` output
+#[derive(Deserialize)]
+struct RustcMessage {
+  message: String,
+  level: String,
+  spans: Vec<RustcSpan>,
+}
+
+/// Where a message points, in 1-based lines and char columns
+#[derive(Deserialize)]
+struct RustcSpan {
+  line_start: usize,
+  column_start: usize,
+  line_end: usize,
+  column_end: usize,
+  is_primary: bool,
+}
+
+/// The diagnostic for a line of rustc's output about `text`. Summaries such
+/// as "aborting due to 2 previous errors" point nowhere and are left out.
+fn rustc_diagnostic(line: &str, text: &str) -> Option<Diagnostic> {
+  let message: RustcMessage = serde_json::from_str(line).ok()?;
+  let span = message.spans.iter().find(|span| span.is_primary)?;
+
+  let line = span.line_start.saturating_sub(1);
+  let col = span.column_start.saturating_sub(1);
+  // Spans running onto later lines are underlined to the end of the first
+  let len = if span.line_end == span.line_start {
+    span.column_end.saturating_sub(span.column_start)
+  } else {
+    text.lines().nth(line).map(|line| line.chars().count().saturating_sub(col)).unwrap_or(1)
+  };
+
+  let severity = match message.level.as_str() {
+    "warning" => Severity::Warning,
+    "note" | "help" => Severity::Info,
+    _ => Severity::Error,
+  };
+
+  Some(Diagnostic { line, col, len: len.max(1), severity, message: message.message, source: None })
+}
+
+/// Every linter for documents in `language`
+fn linters_for(language: &str) -> &'static [&'static dyn Linter] {
+  match language {
+    "rust" => &[&RustcLinter],
+    "json" => &[&ParseLinter { language: "json" }],
+    "toml" => &[&ParseLinter { language: "toml" }],
+    _ => &[],
+  }
+}
+
+/// What the linters for `language` find in the document posted as the body,
+/// as a JSON list of diagnostics. A 404 means nothing lints the language and
+/// a 503 that nothing which would could run. A 400 says what in the document
+/// linters won't check.
+pub async fn lint_document(State(state): State<AppState>, Path(language): Path<String>, text: String) -> Response {
+  if !state.server_linting {
+    return (StatusCode::NOT_FOUND, LINTING_OFF).into_response();
+  }
+
+  let linters = linters_for(&language);
+  if linters.is_empty() {
+    return (StatusCode::NOT_FOUND, format!("No linter for {language}")).into_response();
+  }
+
+  let mut diagnostics = Vec::new();
+  let mut ran = false;
+  let mut unavailable = None;
+  let mut refused = None;
+
+  for linter in linters {
+    match linter.lint(&text).await {
+      Ok(found) => {
+        ran = true;
+        diagnostics.extend(found.into_iter().map(|diagnostic| Diagnostic {
+          source: diagnostic.source.or_else(|| Some(linter.name().to_string())),
+          ..diagnostic
+        }));
+      }
+      Err(LintError::Unavailable(why)) => {
+        tracing::warn!(linter = linter.name(), why, "linter unavailable");
+        unavailable = Some(why);
+      }
+      Err(LintError::Refused(why)) => refused = Some(why),
+      Err(LintError::Failed(why)) => tracing::warn!(linter = linter.name(), why, "linter failed"),
+    }
+  }
+
+  if !ran {
+    return match (refused, unavailable) {
+      (Some(why), _) => (StatusCode::BAD_REQUEST, why).into_response(),
+      (None, Some(why)) => (StatusCode::SERVICE_UNAVAILABLE, why).into_response(),
+      (None, None) => (StatusCode::INTERNAL_SERVER_ERROR, "Linting failed").into_response(),
+    };
+  }
+
+  diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.col));
+  Json(diagnostics).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn plain_code_is_checked() {
+    let text = "use std::env;\n\nmod inner {\n  pub fn home() -> Option<String> {\n    std::env::var(\"HOME\").ok()\n  }\n}\n";
+    assert_eq!(forbidden_source(text), None);
+    assert_eq!(forbidden_source("fn f() { env::args(); }"), None);
+  }
+
+  #[test]
+  fn macros_reading_the_server_are_refused() {
+    for text in [
+      r#"const S: &str = include_str!("/etc/hostname");"#,
+      r#"const B: &[u8] = include_bytes! ("/etc/hostname");"#,
+      r#"include!("/etc/hostname");"#,
+      r#"const E: &str = env!("HOME");"#,
+      r#"const E: &str = core::env!("HOME");"#,
+      r#"const E: Option<&str> = option_env!("HOME");"#,
+    ] {
+      assert!(forbidden_source(text).is_some(), "{text}");
+    }
+  }
+
+  #[test]
+  fn macros_passed_to_other_macros_are_refused() {
+    let text = "macro_rules! call { ($m:ident) => { $m!(\"HOME\") } }\nconst E: &str = call!(env);";
+    assert!(forbidden_source(text).is_some());
+  }
+
+  #[test]
+  fn out_of_line_modules_are_refused() {
+    assert!(forbidden_source("#[path = \"/etc/hostname\"]\nmod secret;").is_some());
+    assert!(forbidden_source("macro_rules! m { ($i:ident) => { mod $i; } }").is_some());
+  }
+
+  #[test]
+  fn comments_cannot_hide_a_macro_call() {
+    let text = r#"compile_error!(concat!(core::env/**/!("HOME")));"#;
+    assert!(forbidden_source(text).is_some());
+    assert!(forbidden_source("const E: &str = env // !\n!(\"HOME\");").is_some());
+  }
+
+  #[test]
+  fn comments_cannot_hide_an_out_of_line_module() {
+    assert!(forbidden_source(r#"#[path="/etc/hostname"] mod secret /* { */ ;"#).is_some());
+    assert!(forbidden_source("mod secret // {\n;").is_some());
+    assert!(forbidden_source("mod secret /* /* { */ */ ;").is_some());
+  }
+
+  #[test]
+  fn literals_and_comments_are_not_code() {
+    assert_eq!(forbidden_source(r#"const S: &str = "env!(\"HOME\") mod x;";"#), None);
+    assert_eq!(forbidden_source(r##"const S: &str = r#"include!("x") " mod y;"#;"##), None);
+    assert_eq!(forbidden_source("// include!(\"/etc/hostname\")\n/* mod x; */ fn f<'a>(c: char) -> bool { c == '\"' }"), None);
+    assert_eq!(forbidden_source("const Q: char = '\\''; const B: &[u8] = b\"mod x;\";"), None);
+  }
+
+  #[test]
+  fn code_only_keeps_lifetimes_and_empties_literals() {
+    assert_eq!(code_only(r#"fn f<'a>(s: &'a str) -> char { 'x' } // done"#), "fn f<'a>(s: &'a str) -> char { ' ' }  ");
+    assert_eq!(code_only(r##"let s = br#"a"b"#; let t = "\"";"##), r#"let s = b""; let t = "";"#);
+  }
+
+  #[tokio::test]
+  async fn rustc_runs_without_the_servers_environment() {
+    if rustc_path().await.is_none() {
+      return;
+    }
+    let dir = std::env::temp_dir().join(format!("collab_hub-lint-test-{}", Uuid::new_v4()));
+    fs::create_dir_all(&dir).await.unwrap();
+    // Straight to rustc, past forbidden_source, to see what environment it gets
+    let result = run_rustc(&dir, "const _: () = { let _ = core::env!(\"PATH\"); };").await;
+    let _ = fs::remove_dir_all(&dir).await;
+
+    let Ok(diagnostics) = result else { panic!("rustc didn't run") };
+    assert!(diagnostics.iter().any(|diagnostic| diagnostic.message.contains("not defined at compile time")));
+  }
+}
+
@@ -1,55 +1,345 @@
+mod collab;
+mod docs;
+mod files;
+mod health;
+mod limits;
+mod lint;
+mod request_log;
+mod snippets;
+
 use axum::{
-  extract::Path,
-  http::{HeaderMap, StatusCode},
-  response::{Html, IntoResponse},
-  routing::get,
+  extract::{DefaultBodyLimit, Path},
+  http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+  middleware,
+  response::{Html, IntoResponse, Response},
+  routing::{get, post, put},
   Router,
 };
-use std::{net::SocketAddr, path::PathBuf};
+use std::{
+  net::SocketAddr,
+  path::{Path as FsPath, PathBuf},
+  sync::{Arc, OnceLock},
+};
 use tokio::fs;
-use tower_http::trace::TraceLayer;
+use limits::{Limits, RateLimiter};
+use tower_http::{
+  compression::CompressionLayer,
+  cors::{AllowOrigin, CorsLayer},
+  request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+  trace::TraceLayer,
+};
+
+/// Where `dx` puts the built app
+const PUBLIC_DIR: &str = "../target/dx/code_editor/release/web/public";
+
+/// State shared by every route
+#[derive(Clone, Default)]
+pub struct AppState {
+  rooms: collab::Rooms,
+  limits: Limits,
+  /// Where files are saved, if saving them is on
+  workspace_root: Option<PathBuf>,
+  /// Whether documents may be linted here
+  server_linting: bool,
+  /// Origins other than the backend's own whose pages may use it
+  allowed_origins: Arc<[HeaderValue]>,
+}
 
 #[tokio::main]
 async fn main() {
+  tracing_subscriber::fmt().with_target(false).init();
+
+  let limits = Limits::from_env();
+  let allowed_origins = allowed_origins_from_env();
+  let doc_writes = RateLimiter::new(limits.doc_writes_per_minute);
+  // Creating and saving documents is capped in size and rate
+  let write_limits = |route: axum::routing::MethodRouter<AppState>| {
+    route
+        .layer(DefaultBodyLimit::max(limits.max_document_bytes))
+        .layer(middleware::from_fn_with_state(doc_writes.clone(), limits::rate_limit))
+  };
+  // Linting can run a compiler, so it's capped the same way on its own budget
+  let lints = RateLimiter::new(limits.lints_per_minute);
+  let lint_limits = |route: axum::routing::MethodRouter<AppState>| {
+    route
+        .layer(DefaultBodyLimit::max(limits.max_document_bytes))
+        .layer(middleware::from_fn_with_state(lints.clone(), limits::rate_limit))
+  };
+
   let app = Router::new()
       // Serve static files (JS/WASM/...) from /code_editor/assets/*path
       .route("/code_editor/assets/{*path}", get(serve_asset))
       // Serve index.html for any /code_editor route (SPA fallback)
       .route("/code_editor", get(serve_index))
       .route("/code_editor/{*path}", get(serve_index))
-      .layer(TraceLayer::new_for_http());
+      // Read and save files under WORKSPACE_ROOT, when it's set
+      .route(
+        "/code_editor/files/{*path}",
+        get(files::read_file).merge(write_limits(post(files::write_file))),
+      )
+      // What's in the workspace's directories, for browsing it
+      .route("/code_editor/tree", get(files::workspace_tree))
+      .route("/code_editor/tree/{*path}", get(files::directory_tree))
+      // Relay collaboration messages between everyone in a room
+      .route("/api/collab/{room}", get(collab::collab_socket))
+      // Store documents server-side
+      .route("/api/docs", write_limits(post(docs::create_document)))
+      .route("/api/docs/{id}", get(docs::get_document).merge(write_limits(put(docs::update_document))))
+      // Diagnostics for a document from the linters for its language
+      .route("/api/lint/{language}", lint_limits(post(lint::lint_document)))
+      // A stored document as a highlighted page, for sharing without the app
+      .route("/snippet/{id}", get(snippets::snippet_page))
+      // Brotli or gzip, whichever the client accepts. The WASM bundle shrinks a lot.
+      .layer(CompressionLayer::new())
+      .layer(cors_layer(&allowed_origins))
+      .layer(
+        TraceLayer::new_for_http()
+            .make_span_with(request_log::make_span)
+            .on_request(())
+            .on_response(request_log::on_response),
+      )
+      // Outside the trace layer so its span has the id to show
+      .layer(PropagateRequestIdLayer::x_request_id())
+      .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+      // Probed constantly, so added after the trace layer to keep them out of the logs
+      .route("/healthz", get(health::healthz))
+      .route("/readyz", get(health::readyz))
+      .with_state(AppState {
+        limits,
+        workspace_root: files::workspace_root_from_env(),
+        server_linting: lint::server_linting_from_env(),
+        allowed_origins: allowed_origins.into(),
+        ..Default::default()
+      });
 
   let addr = SocketAddr::from(([127, 0, 0, 1], 8080));
   println!("🚀 Serving: http://{}/code_editor", addr);
 
   axum::serve(
       tokio::net::TcpListener::bind(addr).await.unwrap(),
-      app.into_make_service(),
+      // The rate limiter tells clients apart by address
+      app.into_make_service_with_connect_info::<SocketAddr>(),
   )
   .await
   .unwrap();
 }
 
-async fn serve_asset(Path(path): Path<String>) -> impl IntoResponse {
-  let base = PathBuf::from("../target/dx/code_editor/release/web/public/assets");
-  let file_path = base.join(&path);
+/// Whether to send the COOP/COEP headers that make the page cross-origin
+/// isolated, which WASM threads need. Enabled by setting
+/// `CROSS_ORIGIN_ISOLATION=1`.
+fn cross_origin_isolation() -> bool {
+  static ENABLED: OnceLock<bool> = OnceLock::new();
+  *ENABLED.get_or_init(|| std::env::var("CROSS_ORIGIN_ISOLATION").is_ok_and(|value| value == "1"))
+}
+
+/// The origins `CORS_ALLOWED_ORIGINS` lists, for when the editor is served
+/// separately from the backend (e.g. by `dx serve` on another port). It's a
+/// comma-separated list of origins such as `http://localhost:8081`. Unset,
+/// only same-origin requests are allowed.
+fn allowed_origins_from_env() -> Vec<HeaderValue> {
+  std::env::var("CORS_ALLOWED_ORIGINS")
+      .unwrap_or_default()
+      .split(',')
+      .map(str::trim)
+      .filter(|origin| !origin.is_empty())
+      .filter_map(|origin| match origin.parse() {
+        Ok(origin) => Some(origin),
+        Err(_) => {
+          tracing::warn!(origin, "ignoring invalid CORS origin");
+          None
+        }
+      })
+      .collect()
+}
+
+/// Lets pages from the allowed `origins` use the document API.
+///
+/// Cross-origin requests may use `GET`, `POST` and `PUT` with a
+/// `Content-Type` header, which covers loading, creating and saving
+/// documents. Preflight `OPTIONS` requests are answered by the layer itself.
+fn cors_layer(origins: &[HeaderValue]) -> CorsLayer {
+  CorsLayer::new()
+      .allow_origin(AllowOrigin::list(origins.iter().cloned()))
+      .allow_methods([Method::GET, Method::POST, Method::PUT])
+      .allow_headers([header::CONTENT_TYPE])
+}
+
+fn insert_cross_origin_headers(headers: &mut HeaderMap) {
+  if cross_origin_isolation() {
+    headers.insert("Cross-Origin-Opener-Policy", HeaderValue::from_static("same-origin"));
+    headers.insert("Cross-Origin-Embedder-Policy", HeaderValue::from_static("require-corp"));
+  }
+}
+
+/// Streaming WASM compilation needs exactly `application/wasm`, so the types
+/// that matter are pinned rather than left to `mime_guess`
+fn content_type(file_path: &FsPath) -> String {
+  match file_path.extension().and_then(|ext| ext.to_str()) {
+    Some("wasm") => "application/wasm".to_string(),
+    Some("js") | Some("mjs") => "text/javascript".to_string(),
+    _ => mime_guess::from_path(file_path).first_or_octet_stream().to_string(),
+  }
+}
+
+/// Whether the file name carries a content hash (`name-<hash>.ext`), so the
+/// file never changes and can be cached forever
+fn is_hashed_asset(file_path: &FsPath) -> bool {
+  let Some(stem) = file_path.file_stem().and_then(|stem| stem.to_str()) else {
+    return false;
+  };
+
+  stem.rsplit_once('-').is_some_and(|(_, hash)| {
+    let hash = hash.strip_prefix("dxh").unwrap_or(hash);
+    hash.len() >= 8 && hash.chars().all(|c| c.is_ascii_hexdigit())
+  })
+}
+
+/// A tag for `contents` that changes whenever they do, for `ETag`. It's weak
+/// because the compression layer sends the same contents encoded different
+/// ways, which a strong tag would have to tell apart.
+fn etag_for(contents: &[u8]) -> HeaderValue {
+  // FNV-1a, the same on every build so tags outlast restarts
+  let hash = contents
+      .iter()
+      .fold(0xCBF2_9CE4_8422_2325u64, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01B3));
+  HeaderValue::from_str(&format!("W/\"{hash:016x}\"")).expect("hex digits are a valid header value")
+}
+
+/// Whether the request's `If-None-Match` lists `etag`, so the copy the
+/// browser has is still good
+fn not_modified(request_headers: &HeaderMap, etag: &HeaderValue) -> bool {
+  let Some(etag) = etag.to_str().ok() else {
+    return false;
+  };
+  let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+
+  request_headers
+      .get_all(header::IF_NONE_MATCH)
+      .iter()
+      .filter_map(|value| value.to_str().ok())
+      .flat_map(|value| value.split(','))
+      .any(|tag| tag.trim() == "*" || opaque(tag) == opaque(etag))
+}
 
-  match fs::read(&file_path).await {
+async fn serve_asset(Path(path): Path<String>, request_headers: HeaderMap) -> impl IntoResponse {
+  let base = PathBuf::from(PUBLIC_DIR).join("assets");
+  asset_response(&base.join(&path), &request_headers).await
+}
+
+/// The asset at `file_path`, or a 304 if the browser's copy of it is current
+async fn asset_response(file_path: &FsPath, request_headers: &HeaderMap) -> Response {
+  match fs::read(file_path).await {
       Ok(contents) => {
-          let mime = mime_guess::from_path(&file_path).first_or_octet_stream();
           let mut headers = HeaderMap::new();
-          headers.insert("Content-Type", mime.to_string().parse().unwrap());
+          let cache_control = if is_hashed_asset(file_path) {
+            "public, max-age=31536000, immutable"
+          } else {
+            "no-cache"
+          };
+          headers.insert(header::CACHE_CONTROL, HeaderValue::from_static(cache_control));
+          let etag = etag_for(&contents);
+          let unchanged = not_modified(request_headers, &etag);
+          headers.insert(header::ETAG, etag);
+          if unchanged {
+            return (StatusCode::NOT_MODIFIED, headers).into_response();
+          }
+          headers.insert(header::CONTENT_TYPE, content_type(file_path).parse().unwrap());
+          insert_cross_origin_headers(&mut headers);
+
           (headers, contents).into_response()
       }
-      Err(_) => (StatusCode::NOT_FOUND, "Asset Not Found").into_response(),
+      Err(err) => {
+          tracing::warn!(path = %file_path.display(), %err, "couldn't read asset");
+          (StatusCode::NOT_FOUND, "Asset Not Found").into_response()
+      }
   }
 }
 
-async fn serve_index() -> impl IntoResponse {
-  let index_path = "../target/dx/code_editor/release/web/public/index.html";
+async fn serve_index(request_headers: HeaderMap) -> impl IntoResponse {
+  let index_path = PathBuf::from(PUBLIC_DIR).join("index.html");
+
+  match fs::read_to_string(&index_path).await {
+      Ok(contents) => {
+          // The index names the current hashed assets, so it must always be
+          // revalidated. An unchanged one comes back as a 304.
+          let mut headers = HeaderMap::new();
+          headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+          let etag = etag_for(contents.as_bytes());
+          let unchanged = not_modified(&request_headers, &etag);
+          headers.insert(header::ETAG, etag);
+          if unchanged {
+            return (StatusCode::NOT_MODIFIED, headers).into_response();
+          }
+          insert_cross_origin_headers(&mut headers);
 
-  match fs::read_to_string(index_path).await {
-      Ok(contents) => Html(contents).into_response(),
+          (headers, Html(contents)).into_response()
+      }
       Err(_) => (StatusCode::NOT_FOUND, "index.html not found").into_response(),
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use uuid::Uuid;
+
+  /// A file named `name` holding `contents`, in a directory of its own
+  fn asset(name: &str, contents: &[u8]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("collab_hub-assets-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, contents).unwrap();
+    path
+  }
+
+  fn if_none_match(tag: &HeaderValue) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(header::IF_NONE_MATCH, tag.clone());
+    headers
+  }
+
+  #[tokio::test]
+  async fn wasm_is_served_as_wasm_and_cached_forever() {
+    let path = asset("code_editor_bg-dxh1a2b3c4d5e6f.wasm", b"\0asm");
+    let response = asset_response(&path, &HeaderMap::new()).await;
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/wasm");
+    assert_eq!(response.headers()[header::CACHE_CONTROL], "public, max-age=31536000, immutable");
+    assert_eq!(response.headers()[header::ETAG], etag_for(b"\0asm"));
+  }
+
+  #[tokio::test]
+  async fn unhashed_assets_are_revalidated() {
+    let path = asset("style.css", b"body {}");
+    let response = asset_response(&path, &HeaderMap::new()).await;
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+
+    assert_eq!(response.headers()[header::CACHE_CONTROL], "no-cache");
+  }
+
+  #[tokio::test]
+  async fn a_current_tag_gets_a_304() {
+    let path = asset("code_editor-dxh1a2b3c4d5e6f.js", b"export {}");
+    let current = etag_for(b"export {}");
+    let response = asset_response(&path, &if_none_match(&current)).await;
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(response.headers()[header::ETAG], current);
+    assert!(response.headers().get(header::CONTENT_TYPE).is_none());
+  }
+
+  #[tokio::test]
+  async fn a_stale_tag_gets_the_asset() {
+    let path = asset("code_editor-dxh1a2b3c4d5e6f.js", b"export {}");
+    let stale = etag_for(b"export { old }");
+    let response = asset_response(&path, &if_none_match(&stale)).await;
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "text/javascript");
+    assert_eq!(response.headers()[header::ETAG], etag_for(b"export {}"));
+  }
+}
@@ -0,0 +1,30 @@
+use axum::http::{Request, Response};
+use std::time::Duration;
+use tower_http::request_id::RequestId;
+use tracing::Span;
+
+/// The span every request is handled in, carrying the id that
+/// `SetRequestIdLayer` gave it so everything logged for it can be told
+/// apart from other requests'. The same id goes back in `x-request-id`.
+pub fn make_span<B>(request: &Request<B>) -> Span {
+  let id = request
+      .extensions()
+      .get::<RequestId>()
+      .and_then(|id| id.header_value().to_str().ok())
+      .unwrap_or("-");
+
+  tracing::info_span!("request", id, method = %request.method(), path = request.uri().path())
+}
+
+/// One line per response with its status and how long it took. Client errors
+/// such as missing assets are warnings, so they stand out from the rest.
+pub fn on_response<B>(response: &Response<B>, latency: Duration, _span: &Span) {
+  let status = response.status().as_u16();
+  let latency_ms = latency.as_millis() as u64;
+
+  if response.status().is_client_error() {
+    tracing::warn!(status, latency_ms, "finished");
+  } else {
+    tracing::info!(status, latency_ms, "finished");
+  }
+}
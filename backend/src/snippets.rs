@@ -0,0 +1,37 @@
+use axum::{
+  extract::Path,
+  response::{Html, IntoResponse, Response},
+};
+use components_lib::{
+  core::Theme,
+  editor::editor_core::{escape_html, highlight_to_html},
+};
+
+use crate::docs;
+
+/// A stored document highlighted as a static page, which loads without the WASM app
+pub async fn snippet_page(Path(id): Path<String>) -> Response {
+  let document = match docs::read_document(&id).await {
+    Ok(document) => document,
+    Err(response) => return response,
+  };
+
+  let theme = Theme::default();
+  let title = escape_html(document.filename.as_deref().unwrap_or("Untitled"));
+  let code = highlight_to_html(&document.content, &document.language, &theme);
+
+  Html(format!(
+    "<!DOCTYPE html>\n\
+     <html>\n\
+     <head>\n\
+     <meta charset=\"utf-8\">\n\
+     <title>{title}</title>\n\
+     </head>\n\
+     <body style=\"margin: 0; background-color: {};\">\n\
+     {code}\n\
+     </body>\n\
+     </html>\n",
+    theme.background
+  ))
+  .into_response()
+}
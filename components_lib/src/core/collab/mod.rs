@@ -0,0 +1,64 @@
+pub mod ot;
+
+use serde::{Deserialize, Serialize};
+use crate::core::users::User;
+use crate::editor::editor_core::Edit;
+
+pub use ot::{OtClient, transform_incoming};
+
+/// Messages exchanged over a collaboration room's WebSocket
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CollabMessage {
+    /// A user's caret moved
+    Cursor {
+        user: User,
+        line: usize,
+        column: usize,
+    },
+    /// A user connected, sent by their client once its socket opens
+    Join {
+        user: User,
+    },
+    /// Everyone in the room, sent by the server in reply to a join
+    Presence {
+        users: Vec<User>,
+    },
+    /// A user's socket disconnected, sent by the server
+    Leave {
+        user_id: String,
+    },
+    /// A change to the shared document. Clients send it with the revision it
+    /// was made against, the server relays it with the revision it became.
+    Edit {
+        user_id: String,
+        revision: usize,
+        edit: Edit,
+    },
+    /// The server applied the client's outstanding edit, sent to that client only
+    Ack {
+        revision: usize,
+    },
+    /// The room's current revision, sent to a client as it joins
+    Sync {
+        revision: usize,
+    },
+}
+
+impl CollabMessage {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("collab messages always serialize")
+    }
+
+    pub fn from_json(json: &str) -> Option<Self> {
+        serde_json::from_str(json).ok()
+    }
+}
+
+/// Where another user's caret currently is
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteCursor {
+    pub user: User,
+    pub line: usize,
+    pub column: usize,
+}
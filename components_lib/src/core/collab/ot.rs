@@ -0,0 +1,165 @@
+use std::collections::VecDeque;
+use crate::editor::editor_core::Edit;
+
+/// A client's side of keeping a shared document consistent. The server puts
+/// every edit in one order, numbering them with revisions. A client sends one
+/// edit at a time, tagged with the last revision it has seen, and holds the
+/// rest until the server acknowledges it. Edits from others arrive already
+/// transformed against everything the server had before them, so they only
+/// have to be transformed against the client's own unacknowledged edits.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OtClient {
+    /// How many of the server's edits this client has seen
+    revision: usize,
+    /// Sent, waiting on the server's acknowledgement
+    outstanding: Option<Edit>,
+    /// Made while waiting, sent one by one once the outstanding edit is acknowledged
+    buffered: VecDeque<Edit>,
+}
+
+impl OtClient {
+    /// Start from the server's current revision, on joining a room
+    pub fn sync(&mut self, revision: usize) {
+        self.revision = revision;
+    }
+
+    /// Record an edit made locally. Returns the revision and edit to send, if
+    /// nothing else is waiting on the server.
+    pub fn local_edit(&mut self, edit: Edit) -> Option<(usize, Edit)> {
+        if self.outstanding.is_some() {
+            self.buffered.push_back(edit);
+            return None;
+        }
+
+        self.outstanding = Some(edit.clone());
+        Some((self.revision, edit))
+    }
+
+    /// The server applied the outstanding edit. Returns the next edit to send.
+    pub fn ack(&mut self) -> Option<(usize, Edit)> {
+        self.outstanding.take()?;
+        self.revision += 1;
+
+        let next = self.buffered.pop_front()?;
+        self.local_edit(next)
+    }
+
+    /// Take in an edit from someone else. Returns it transformed to apply to
+    /// the local text, which already has this client's unacknowledged edits.
+    pub fn remote_edit(&mut self, edit: Edit) -> Edit {
+        let mut remote = edit;
+        for local in self.outstanding.iter_mut().chain(self.buffered.iter_mut()) {
+            let transformed = remote.transform(local);
+            *local = local.transform(&remote);
+            remote = transformed;
+        }
+
+        self.revision += 1;
+        remote
+    }
+}
+
+/// An edit a client made against `revision`, transformed to apply after every
+/// edit the server has since put in `history`. Returns the edit as it goes
+/// into the history.
+pub fn transform_incoming(history: &[Edit], revision: usize, edit: Edit) -> Edit {
+    history
+        .get(revision..)
+        .unwrap_or_default()
+        .iter()
+        .fold(edit, |edit, applied| edit.transform(applied))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::editor_core::Buffer;
+
+    fn insert(pos: usize, text: &str) -> Edit {
+        Edit::Insert { pos, text: text.to_string() }
+    }
+
+    fn delete(pos: usize, len: usize) -> Edit {
+        Edit::Delete { pos, len }
+    }
+
+    fn applied(text: &str, edit: &Edit) -> String {
+        let mut buffer = Buffer::from_str(text, None);
+        buffer.apply(edit).unwrap();
+        buffer.text()
+    }
+
+    #[test]
+    fn edits_wait_for_the_outstanding_one() {
+        let mut client = OtClient::default();
+        client.sync(3);
+        assert_eq!(client.local_edit(insert(0, "a")), Some((3, insert(0, "a"))));
+        assert_eq!(client.local_edit(insert(1, "b")), None);
+        assert_eq!(client.local_edit(insert(2, "c")), None);
+
+        // Each ack sends the next buffered edit, against the revision it made
+        assert_eq!(client.ack(), Some((4, insert(1, "b"))));
+        assert_eq!(client.outstanding, Some(insert(1, "b")));
+        assert_eq!(client.ack(), Some((5, insert(2, "c"))));
+        assert_eq!(client.ack(), None);
+        assert_eq!(client.revision, 6);
+        assert_eq!(client.ack(), None);
+        assert_eq!(client.revision, 6);
+    }
+
+    #[test]
+    fn remote_edits_pass_unacknowledged_ones_and_converge() {
+        let start = "abc";
+        let mut client = OtClient::default();
+        let mut server: Vec<Edit> = Vec::new();
+        let mut server_text = start.to_string();
+
+        // The client types at both ends, the second edit held back
+        let (sent_at, first) = client.local_edit(insert(0, "X")).unwrap();
+        assert_eq!(client.local_edit(insert(4, "Y")), None);
+        let mut client_text = applied(&applied(start, &insert(0, "X")), &insert(4, "Y"));
+
+        // Meanwhile someone else's delete of the "b" reaches the server first
+        let other = transform_incoming(&server, 0, delete(1, 1));
+        server_text = applied(&server_text, &other);
+        server.push(other.clone());
+
+        let remote = client.remote_edit(other);
+        assert_eq!(remote, delete(2, 1));
+        client_text = applied(&client_text, &remote);
+        assert_eq!(client.buffered, [insert(3, "Y")]);
+
+        // Then the client's edits go through one at a time
+        let first = transform_incoming(&server, sent_at, first);
+        server_text = applied(&server_text, &first);
+        server.push(first);
+        let (sent_at, second) = client.ack().unwrap();
+        assert_eq!(sent_at, 2);
+        let second = transform_incoming(&server, sent_at, second);
+        server_text = applied(&server_text, &second);
+        server.push(second);
+        assert_eq!(client.ack(), None);
+
+        assert_eq!(client_text, "XacY");
+        assert_eq!(server_text, client_text);
+        assert_eq!(client.revision, server.len());
+    }
+
+    #[test]
+    fn stale_edits_are_transformed_past_everything_since() {
+        let history = [insert(0, "ab"), delete(5, 2), insert(0, "!")];
+        assert_eq!(transform_incoming(&history, 0, insert(3, "x")), insert(6, "x"));
+        assert_eq!(transform_incoming(&history, 2, insert(3, "x")), insert(4, "x"));
+        assert_eq!(transform_incoming(&history, 3, insert(3, "x")), insert(3, "x"));
+    }
+
+    #[test]
+    fn trimmed_history_is_indexed_from_what_is_kept() {
+        // The server keeps a window of its history and passes the revision
+        // relative to the window's start, as after trimming
+        let kept = [insert(0, "a"), insert(0, "b")];
+        assert_eq!(transform_incoming(&kept, 1, delete(0, 1)), delete(1, 1));
+        // Past the end there's nothing to transform against
+        assert_eq!(transform_incoming(&kept, 5, delete(0, 1)), delete(0, 1));
+    }
+}
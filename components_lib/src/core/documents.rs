@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+/// A document as persisted by the backend's `/api/docs` routes
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StoredDocument {
+    pub filename: Option<String>,
+    pub language: String,
+    pub content: String,
+}
+
+/// Response body for a newly created document
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CreatedDocument {
+    pub id: String,
+}
+/// A file or directory in the backend's workspace, as `/code_editor/tree`
+/// lists them
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkspaceEntry {
+    pub name: String,
+    pub is_dir: bool,
+    /// In bytes, 0 for a directory
+    pub size: u64,
+}
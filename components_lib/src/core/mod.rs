@@ -1,5 +1,8 @@
+pub mod collab;
+pub mod documents;
 pub mod themes;
 pub mod users;
 pub mod security;
+mod vscode_theme;
 
-pub use themes::{Theme, available_themes};
\ No newline at end of file
+pub use themes::{ContrastIssue, Theme, ThemeError, available_themes, available_themes_with};
\ No newline at end of file
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Theme {
@@ -10,9 +11,17 @@ pub struct Theme {
     pub cursor: String,
     pub line_highlight: String,
     pub syntax_colors: HashMap<String, String>,
+    /// Brackets cycle through these by nesting depth. Optional in imported
+    /// themes, which get the default palette.
+    #[serde(default = "default_bracket_colors")]
+    pub bracket_colors: Vec<String>,
     pub ui: UiColors,
 }
 
+fn default_bracket_colors() -> Vec<String> {
+    ["#E5C07B", "#C678DD", "#56B6C2"].map(String::from).to_vec()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct UiColors {
     pub toolbar_bg: String,
@@ -32,6 +41,7 @@ impl Default for Theme {
         syntax_colors.insert("comment".to_string(), "#7F848E".to_string());
         syntax_colors.insert("function".to_string(), "#61AFEF".to_string());
         syntax_colors.insert("type".to_string(), "#E5C07B".to_string());
+        syntax_colors.insert("attribute".to_string(), "#56B6C2".to_string());
 
         Self {
             name: "Default Dark".to_string(),
@@ -41,6 +51,7 @@ impl Default for Theme {
             cursor: "#528BFF".to_string(),
             line_highlight: "#2C313A".to_string(),
             syntax_colors,
+            bracket_colors: default_bracket_colors(),
             ui: UiColors {
                 toolbar_bg: "#21252B".to_string(),
                 toolbar_fg: "#ABB2BF".to_string(),
@@ -55,40 +66,251 @@ impl Default for Theme {
 }
 
 pub fn light_theme() -> Theme {
-    let mut light_theme = Theme::default();
-    light_theme.name = "Light".to_string();
-    light_theme.background = "#FFFFFF".to_string();
-    light_theme.foreground = "#383A42".to_string();
-    light_theme.selection = "#E5E5E6".to_string();
-    light_theme.cursor = "#526FFF".to_string();
-    light_theme.line_highlight = "#F2F2F2".to_string();
-    
     let mut syntax_colors = HashMap::new();
     syntax_colors.insert("keyword".to_string(), "#A626A4".to_string());
     syntax_colors.insert("string".to_string(), "#50A14F".to_string());
     syntax_colors.insert("comment".to_string(), "#A0A1A7".to_string());
     syntax_colors.insert("function".to_string(), "#4078F2".to_string());
     syntax_colors.insert("type".to_string(), "#C18401".to_string());
-    light_theme.syntax_colors = syntax_colors;
-    
-    light_theme.ui = UiColors {
-        toolbar_bg: "#E5E5E6".to_string(),
-        toolbar_fg: "#383A42".to_string(),
-        statusbar_bg: "#E5E5E6".to_string(),
-        statusbar_fg: "#696C77".to_string(),
-        button: "#D4D4D4".to_string(),
-        button_hover: "#CACACA".to_string(),
-        button_active: "#4078F2".to_string(),
+    syntax_colors.insert("attribute".to_string(), "#0184BC".to_string());
+
+    Theme {
+        name: "Light".to_string(),
+        background: "#FFFFFF".to_string(),
+        foreground: "#383A42".to_string(),
+        selection: "#E5E5E6".to_string(),
+        cursor: "#526FFF".to_string(),
+        line_highlight: "#F2F2F2".to_string(),
+        syntax_colors,
+        bracket_colors: ["#C18401", "#A626A4", "#0184BC"].map(String::from).to_vec(),
+        ui: UiColors {
+            toolbar_bg: "#E5E5E6".to_string(),
+            toolbar_fg: "#383A42".to_string(),
+            statusbar_bg: "#E5E5E6".to_string(),
+            statusbar_fg: "#696C77".to_string(),
+            button: "#D4D4D4".to_string(),
+            button_hover: "#CACACA".to_string(),
+            button_active: "#4078F2".to_string(),
+        },
+    }
+}
+
+/// Why a theme couldn't be imported
+#[derive(Debug, Clone, PartialEq)]
+pub enum ThemeError {
+    InvalidJson(String),
+    MissingKey(String),
+    /// Every key whose color isn't hex
+    InvalidColors(Vec<String>),
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeError::InvalidJson(err) => write!(f, "Theme is not valid JSON: {err}"),
+            ThemeError::MissingKey(key) => write!(f, "Theme is missing \"{key}\""),
+            ThemeError::InvalidColors(keys) => {
+                let quoted: Vec<String> = keys.iter().map(|key| format!("\"{key}\"")).collect();
+                match keys.len() {
+                    1 => write!(f, "{} is not a hex color", quoted.join(", ")),
+                    _ => write!(f, "{} are not hex colors", quoted.join(", ")),
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {}
+
+/// The least contrast WCAG AA allows for normal sized text
+pub const MIN_CONTRAST_RATIO: f64 = 4.5;
+
+/// A pair of theme colors too close to read one on the other
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContrastIssue {
+    pub foreground_key: String,
+    pub background_key: String,
+    /// From 1 for identical colors up to 21 for black on white
+    pub ratio: f64,
+}
+
+impl fmt::Display for ContrastIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "\"{}\" on \"{}\" has a contrast of {:.1}:1, below {MIN_CONTRAST_RATIO}:1",
+            self.foreground_key, self.background_key, self.ratio
+        )
+    }
+}
+
+const THEME_KEYS: [&str; 8] = [
+    "name", "background", "foreground", "selection", "cursor", "line_highlight", "syntax_colors", "ui",
+];
+
+const UI_KEYS: [&str; 7] = [
+    "toolbar_bg", "toolbar_fg", "statusbar_bg", "statusbar_fg", "button", "button_hover", "button_active",
+];
+
+/// Syntax colors every theme has to give, the ones no highlighted file goes without
+const SYNTAX_KEYS: [&str; 3] = ["keyword", "string", "comment"];
+
+/// `#RGB` or `#RRGGBB`
+pub(crate) fn is_hex_color(value: &str) -> bool {
+    value.strip_prefix('#').is_some_and(|digits| {
+        matches!(digits.len(), 3 | 6) && digits.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+/// The red, green and blue of a hex color, each 0 to 255
+fn parse_hex_color(value: &str) -> Option<[u8; 3]> {
+    if !is_hex_color(value) {
+        return None;
+    }
+
+    let digits = &value[1..];
+    let channel = |idx: usize| match digits.len() {
+        3 => u8::from_str_radix(&digits[idx..=idx], 16).ok().map(|digit| digit * 17),
+        _ => u8::from_str_radix(&digits[idx * 2..idx * 2 + 2], 16).ok(),
     };
+    Some([channel(0)?, channel(1)?, channel(2)?])
+}
 
-    light_theme
+/// How bright a color looks, from 0 for black to 1 for white, by the WCAG
+/// definition of relative luminance
+fn relative_luminance([r, g, b]: [u8; 3]) -> f64 {
+    let linear = |channel: u8| {
+        let channel = f64::from(channel) / 255.0;
+        if channel <= 0.04045 {
+            channel / 12.92
+        } else {
+            ((channel + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+/// The WCAG contrast ratio between two hex colors, whichever is lighter.
+/// None if either isn't a hex color.
+pub fn contrast_ratio(first: &str, second: &str) -> Option<f64> {
+    let first = relative_luminance(parse_hex_color(first)?);
+    let second = relative_luminance(parse_hex_color(second)?);
+    Some((first.max(second) + 0.05) / (first.min(second) + 0.05))
 }
 
 pub fn available_themes() -> Vec<Theme> {
     vec![Theme::default(), light_theme()]
 }
 
+/// The built-in themes followed by the user's own, each given as the JSON it
+/// was imported from. One that no longer loads is left out rather than
+/// keeping the rest from loading.
+pub fn available_themes_with(user_themes: &[String]) -> Vec<Theme> {
+    let mut themes = available_themes();
+    themes.extend(user_themes.iter().filter_map(|json| Theme::from_json(json).ok()));
+    themes
+}
+
 impl Theme {
+    /// Import a theme, checking every key is present and every color is hex
+    pub fn from_json(json: &str) -> Result<Theme, ThemeError> {
+        let value: serde_json::Value = serde_json::from_str(json)
+            .map_err(|err| ThemeError::InvalidJson(err.to_string()))?;
+
+        for key in THEME_KEYS {
+            if value.get(key).is_none() {
+                return Err(ThemeError::MissingKey(key.to_string()));
+            }
+        }
+        for key in UI_KEYS {
+            if value["ui"].get(key).is_none() {
+                return Err(ThemeError::MissingKey(format!("ui.{key}")));
+            }
+        }
+        for key in SYNTAX_KEYS {
+            if value["syntax_colors"].get(key).is_none() {
+                return Err(ThemeError::MissingKey(format!("syntax_colors.{key}")));
+            }
+        }
+
+        let theme: Theme = serde_json::from_value(value)
+            .map_err(|err| ThemeError::InvalidJson(err.to_string()))?;
+
+        theme.validate().map_err(ThemeError::InvalidColors)?;
+        Ok(theme)
+    }
+
+    /// Check every color is hex, a typo in one would leave it out of the
+    /// CSS. Lists each key that isn't, syntax colors in order of name.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut syntax_colors: Vec<(&String, &String)> = self.syntax_colors.iter().collect();
+        syntax_colors.sort();
+
+        let mut colors = vec![
+            ("background".to_string(), &self.background),
+            ("foreground".to_string(), &self.foreground),
+            ("selection".to_string(), &self.selection),
+            ("cursor".to_string(), &self.cursor),
+            ("line_highlight".to_string(), &self.line_highlight),
+            ("ui.toolbar_bg".to_string(), &self.ui.toolbar_bg),
+            ("ui.toolbar_fg".to_string(), &self.ui.toolbar_fg),
+            ("ui.statusbar_bg".to_string(), &self.ui.statusbar_bg),
+            ("ui.statusbar_fg".to_string(), &self.ui.statusbar_fg),
+            ("ui.button".to_string(), &self.ui.button),
+            ("ui.button_hover".to_string(), &self.ui.button_hover),
+            ("ui.button_active".to_string(), &self.ui.button_active),
+        ];
+        colors.extend(syntax_colors.into_iter().map(|(key, color)| (format!("syntax_colors.{key}"), color)));
+        colors.extend(self.bracket_colors.iter().enumerate().map(|(idx, color)| (format!("bracket_colors[{idx}]"), color)));
+
+        let invalid: Vec<String> = colors
+            .into_iter()
+            .filter(|(_, color)| !is_hex_color(color))
+            .map(|(key, _)| key)
+            .collect();
+        if invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(invalid)
+        }
+    }
+
+    /// Every pair of colors text is drawn in and on with less contrast than
+    /// `MIN_CONTRAST_RATIO`: the editor's text and syntax colors on its
+    /// background, and the toolbar's and status bar's text on theirs
+    pub fn contrast_report(&self) -> Vec<ContrastIssue> {
+        let mut syntax_colors: Vec<(&String, &String)> = self.syntax_colors.iter().collect();
+        syntax_colors.sort();
+
+        let mut pairs = vec![
+            ("foreground".to_string(), &self.foreground, "background", &self.background),
+            ("foreground".to_string(), &self.foreground, "selection", &self.selection),
+            ("foreground".to_string(), &self.foreground, "line_highlight", &self.line_highlight),
+        ];
+        pairs.extend(
+            syntax_colors.into_iter().map(|(key, color)| (format!("syntax_colors.{key}"), color, "background", &self.background)),
+        );
+        pairs.push(("ui.toolbar_fg".to_string(), &self.ui.toolbar_fg, "ui.toolbar_bg", &self.ui.toolbar_bg));
+        pairs.push(("ui.statusbar_fg".to_string(), &self.ui.statusbar_fg, "ui.statusbar_bg", &self.ui.statusbar_bg));
+
+        pairs
+            .into_iter()
+            .filter_map(|(foreground_key, foreground, background_key, background)| {
+                let ratio = contrast_ratio(foreground, background)?;
+                (ratio < MIN_CONTRAST_RATIO).then(|| ContrastIssue {
+                    foreground_key,
+                    background_key: background_key.to_string(),
+                    ratio,
+                })
+            })
+            .collect()
+    }
+
+    /// Export the theme as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("themes always serialize")
+    }
+
     pub fn get_color(&self, token_type: &str) -> String {
         match token_type {
             "keyword" => self.syntax_colors.get("keyword").cloned().unwrap_or_else(|| "#C678DD".to_string()),
@@ -96,8 +318,165 @@ impl Theme {
             "comment" => self.syntax_colors.get("comment").cloned().unwrap_or_else(|| "#7F848E".to_string()),
             "function" => self.syntax_colors.get("function").cloned().unwrap_or_else(|| "#61AFEF".to_string()),
             "type" => self.syntax_colors.get("type").cloned().unwrap_or_else(|| "#E5C07B".to_string()),
+            "attribute" => self.syntax_colors.get("attribute").cloned().unwrap_or_else(|| "#56B6C2".to_string()),
             "number" => self.syntax_colors.get("number").cloned().unwrap_or_else(|| "#D19A66".to_string()),
+            "invisible" => self.syntax_colors.get("invisible").cloned().unwrap_or_else(|| "#E06C75".to_string()),
+            "error" => self.syntax_colors.get("error").cloned().unwrap_or_else(|| "#E06C75".to_string()),
+            "warning" => self.syntax_colors.get("warning").cloned().unwrap_or_else(|| "#E5C07B".to_string()),
+            "info" => self.syntax_colors.get("info").cloned().unwrap_or_else(|| "#61AFEF".to_string()),
             _ => self.foreground.clone(),
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contrast_ratios_match_wcag() {
+        let black_on_white = contrast_ratio("#000000", "#FFFFFF").unwrap();
+        assert!((black_on_white - 21.0).abs() < 0.01, "{black_on_white}");
+        assert_eq!(contrast_ratio("#FFF", "#000"), Some(black_on_white));
+        assert!((contrast_ratio("#ABCDEF", "#abcdef").unwrap() - 1.0).abs() < f64::EPSILON);
+        assert_eq!(contrast_ratio("red", "#FFFFFF"), None);
+    }
+
+    #[test]
+    fn contrast_threshold_sits_between_known_pairs() {
+        // The lightest grays that do and don't pass AA on white
+        assert!(contrast_ratio("#767676", "#FFFFFF").unwrap() >= MIN_CONTRAST_RATIO);
+        assert!(contrast_ratio("#777777", "#FFFFFF").unwrap() < MIN_CONTRAST_RATIO);
+    }
+
+    #[test]
+    fn reports_low_contrast_pairs() {
+        let mut theme = light_theme();
+        theme.foreground = "#000000".to_string();
+        theme.syntax_colors = HashMap::from([("comment".to_string(), "#EEEEEE".to_string())]);
+        theme.ui.statusbar_fg = theme.ui.statusbar_bg.clone();
+
+        let flagged: Vec<(String, String)> = theme
+            .contrast_report()
+            .into_iter()
+            .map(|issue| (issue.foreground_key, issue.background_key))
+            .collect();
+        assert_eq!(
+            flagged,
+            [
+                ("syntax_colors.comment".to_string(), "background".to_string()),
+                ("ui.statusbar_fg".to_string(), "ui.statusbar_bg".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn high_contrast_themes_have_nothing_to_report() {
+        let mut theme = Theme {
+            foreground: "#FFFFFF".to_string(),
+            background: "#000000".to_string(),
+            selection: "#000000".to_string(),
+            line_highlight: "#000000".to_string(),
+            ..Theme::default()
+        };
+        theme.syntax_colors = HashMap::from([("keyword".to_string(), "#FFFF00".to_string())]);
+        theme.ui.toolbar_fg = "#FFFFFF".to_string();
+        theme.ui.statusbar_fg = "#FFFFFF".to_string();
+        assert_eq!(theme.contrast_report(), []);
+    }
+
+    /// `theme` as JSON with `key` taken out of the object at `path`
+    fn json_without(theme: &Theme, path: &[&str], key: &str) -> String {
+        let mut value = serde_json::to_value(theme).unwrap();
+        let object = path.iter().fold(&mut value, |value, key| &mut value[key]);
+        object.as_object_mut().unwrap().remove(key);
+        value.to_string()
+    }
+
+    #[test]
+    fn themes_round_trip_through_json() {
+        for theme in available_themes() {
+            assert_eq!(Theme::from_json(&theme.to_json()), Ok(theme));
+        }
+    }
+
+    #[test]
+    fn bracket_colors_are_optional() {
+        let theme = Theme::default();
+        let loaded = Theme::from_json(&json_without(&theme, &[], "bracket_colors")).unwrap();
+        assert_eq!(loaded.bracket_colors, default_bracket_colors());
+    }
+
+    #[test]
+    fn names_the_missing_key() {
+        let theme = Theme::default();
+        assert_eq!(
+            Theme::from_json(&json_without(&theme, &[], "cursor")),
+            Err(ThemeError::MissingKey("cursor".to_string()))
+        );
+        assert_eq!(
+            Theme::from_json(&json_without(&theme, &["ui"], "button_hover")),
+            Err(ThemeError::MissingKey("ui.button_hover".to_string()))
+        );
+
+        let error = Theme::from_json(&json_without(&theme, &["syntax_colors"], "keyword")).unwrap_err();
+        assert_eq!(error, ThemeError::MissingKey("syntax_colors.keyword".to_string()));
+        assert_eq!(error.to_string(), "Theme is missing \"syntax_colors.keyword\"");
+    }
+
+    #[test]
+    fn malformed_user_themes_are_left_out() {
+        let mut custom = light_theme();
+        custom.name = "Custom".to_string();
+        let user_themes = ["{ not json".to_string(), custom.to_json(), "[]".to_string()];
+
+        let names: Vec<String> = available_themes_with(&user_themes).into_iter().map(|theme| theme.name).collect();
+        assert_eq!(names, ["Default Dark", "Light", "Custom"]);
+        assert!(matches!(Theme::from_json("{ not json"), Err(ThemeError::InvalidJson(_))));
+    }
+
+    #[test]
+    fn hex_colors_have_three_or_six_digits() {
+        for color in ["#abc", "#ABCDEF", "#012345"] {
+            assert!(is_hex_color(color), "{color}");
+        }
+        for color in ["#12345", "#abcd", "#ABCDEF80", "ABCDEF", "#GGGGGG", "#", "red"] {
+            assert!(!is_hex_color(color), "{color}");
+        }
+    }
+
+    #[test]
+    fn validate_lists_every_invalid_color() {
+        let mut theme = Theme::default();
+        assert_eq!(theme.validate(), Ok(()));
+
+        theme.foreground = "#12345".to_string();
+        theme.cursor = "#52".to_string();
+        theme.selection = "#3E4451".to_string();
+        theme.syntax_colors.insert("string".to_string(), "green".to_string());
+        theme.syntax_colors.insert("comment".to_string(), "#7F848E80".to_string());
+        theme.ui.button_hover = "#4B526".to_string();
+        theme.bracket_colors[1] = "#abcd".to_string();
+
+        assert_eq!(
+            theme.validate(),
+            Err(vec![
+                "foreground".to_string(),
+                "cursor".to_string(),
+                "ui.button_hover".to_string(),
+                "syntax_colors.comment".to_string(),
+                "syntax_colors.string".to_string(),
+                "bracket_colors[1]".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn from_json_rejects_invalid_colors() {
+        let mut theme = light_theme();
+        theme.background = "#FFFFF".to_string();
+        let error = Theme::from_json(&theme.to_json()).unwrap_err();
+        assert_eq!(error, ThemeError::InvalidColors(vec!["background".to_string()]));
+        assert_eq!(error.to_string(), "\"background\" is not a hex color");
+    }
+}
@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct User {
     pub id: String,
     pub name: String,
@@ -10,7 +11,78 @@ pub struct User {
     pub email: String,
 }
 
+/// The colors users are told apart by, for their cursors and in the list of
+/// who's connected. Each is dark enough for white text on it and bright enough
+/// to see on a dark theme.
+pub const USER_COLORS: [&str; 8] = [
+    "#D7263D", "#E46C0A", "#AD8A00", "#2E7D32", "#1B998B", "#2E86DE", "#8E44AD", "#C2185B",
+];
+
+/// Why a user's details were rejected
+#[derive(Debug, Clone, PartialEq)]
+pub enum UserError {
+    EmptyFirstName,
+    EmptyLastName,
+    InvalidEmail(String),
+}
+
+impl fmt::Display for UserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UserError::EmptyFirstName => write!(f, "First name can't be empty"),
+            UserError::EmptyLastName => write!(f, "Last name can't be empty"),
+            UserError::InvalidEmail(email) => write!(f, "\"{email}\" is not a valid email address"),
+        }
+    }
+}
+
+impl std::error::Error for UserError {}
+
+/// `local@domain.tld`: one `@`, no whitespace, and a domain made of non-empty
+/// labels with at least one dot
+fn is_valid_email(email: &str) -> bool {
+    let Some((local, domain)) = email.split_once('@') else {
+        return false;
+    };
+
+    !local.is_empty()
+        && !domain.contains('@')
+        && !email.chars().any(char::is_whitespace)
+        && domain.contains('.')
+        && domain.split('.').all(|label| !label.is_empty())
+}
+
 impl User {
+    /// Build a user from details someone entered, rejecting empty names and
+    /// malformed emails
+    pub fn try_new(f_name: &str, l_name: &str, email: &str) -> Result<Self, UserError> {
+        if f_name.trim().is_empty() {
+            return Err(UserError::EmptyFirstName);
+        }
+        if l_name.trim().is_empty() {
+            return Err(UserError::EmptyLastName);
+        }
+        if !is_valid_email(email) {
+            return Err(UserError::InvalidEmail(email.to_string()));
+        }
+
+        Ok(Self::new(f_name, l_name, email))
+    }
+
+    /// The user's color from `USER_COLORS`, picked by a hash of their id so it's
+    /// the same every session and on everyone's screen
+    pub fn color(&self) -> String {
+        // FNV-1a, which unlike std's hasher is the same on every build
+        let hash = self
+            .id
+            .bytes()
+            .fold(0x811C_9DC5u32, |hash, byte| (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193));
+        USER_COLORS[hash as usize % USER_COLORS.len()].to_string()
+    }
+
+    /// Build a user without checking its details. Collaboration guests have no
+    /// email and may have no last name; anything a person typed in should go
+    /// through `try_new`.
     pub fn new(f_name: &str, l_name: &str, email: &str) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
@@ -20,4 +92,43 @@ impl User {
             email: email.into(),
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_valid_emails() {
+        for email in ["ada@example.com", "first.last@mail.example.org", "dev+tag@sub.domain.io"] {
+            let user = User::try_new("Ada", "Lovelace", email).unwrap();
+            assert_eq!(user.email, email);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_emails() {
+        for email in ["", "ada", "ada@", "@example.com", "ada@example", "ada@example.", "ada@@example.com", "a da@example.com"] {
+            assert_eq!(User::try_new("Ada", "Lovelace", email), Err(UserError::InvalidEmail(email.to_string())));
+        }
+    }
+
+    #[test]
+    fn rejects_empty_names() {
+        assert_eq!(User::try_new(" ", "Lovelace", "ada@example.com"), Err(UserError::EmptyFirstName));
+        assert_eq!(User::try_new("Ada", "", "ada@example.com"), Err(UserError::EmptyLastName));
+    }
+
+    fn user_with_id(id: &str) -> User {
+        User { id: id.to_string(), ..User::new("Ada", "Lovelace", "ada@example.com") }
+    }
+
+    #[test]
+    fn colors_follow_the_id() {
+        let user = user_with_id("3f2b7c1e-alice");
+        assert_eq!(user.color(), user.color());
+        assert_eq!(user.color(), user_with_id("3f2b7c1e-alice").color());
+        assert!(USER_COLORS.contains(&user.color().as_str()));
+        assert_ne!(user.color(), user_with_id("9a41d0e5-bob").color());
+    }
+}
@@ -0,0 +1,267 @@
+use serde_json::Value;
+use super::themes::{Theme, ThemeError, is_hex_color, light_theme};
+
+/// The TextMate scope each syntax color is taken from. A theme's rule for a
+/// scope also covers the scopes under it, so `entity.name` colors functions
+/// unless something nearer sets `entity.name.function`.
+const TOKEN_SCOPES: [(&str, &str); 7] = [
+    ("keyword", "keyword"),
+    ("string", "string"),
+    ("comment", "comment"),
+    ("function", "entity.name.function"),
+    ("type", "entity.name.type"),
+    ("attribute", "entity.other.attribute-name"),
+    ("number", "constant.numeric"),
+];
+
+/// VS Code theme files are JSON with comments, and often with a comma after
+/// the last entry too. Both are dropped so serde_json can read what's left.
+fn strip_jsonc(json: &str) -> String {
+    let chars: Vec<char> = json.chars().collect();
+    let mut stripped = String::with_capacity(json.len());
+    let mut idx = 0;
+    let mut in_string = false;
+    // A comma is only kept once something other than a closing bracket follows it
+    let mut pending_comma = false;
+
+    while idx < chars.len() {
+        let c = chars[idx];
+        if in_string {
+            stripped.push(c);
+            if c == '\\' && let Some(&escaped) = chars.get(idx + 1) {
+                stripped.push(escaped);
+                idx += 1;
+            } else if c == '"' {
+                in_string = false;
+            }
+            idx += 1;
+            continue;
+        }
+
+        match (c, chars.get(idx + 1)) {
+            ('/', Some('/')) => {
+                while idx < chars.len() && chars[idx] != '\n' {
+                    idx += 1;
+                }
+            }
+            ('/', Some('*')) => {
+                idx += 2;
+                while idx < chars.len() && !(chars[idx] == '*' && chars.get(idx + 1) == Some(&'/')) {
+                    idx += 1;
+                }
+                idx += 2;
+            }
+            (',', _) => {
+                pending_comma = true;
+                idx += 1;
+            }
+            _ => {
+                if pending_comma && !c.is_whitespace() {
+                    if !matches!(c, '}' | ']') {
+                        stripped.push(',');
+                    }
+                    pending_comma = false;
+                }
+                in_string = c == '"';
+                stripped.push(c);
+                idx += 1;
+            }
+        }
+    }
+
+    stripped
+}
+
+/// How closely a rule's selector matches `scope`, the number of its parts.
+/// None if it doesn't cover the scope, or picks it out by what it's nested
+/// in, which a single color per token can't follow.
+fn selector_match(selector: &str, scope: &str) -> Option<usize> {
+    let selector = selector.trim();
+    if selector.is_empty() || selector.contains(' ') {
+        return None;
+    }
+    let covers = scope == selector || scope.strip_prefix(selector).is_some_and(|rest| rest.starts_with('.'));
+    covers.then(|| selector.split('.').count())
+}
+
+/// The selectors a `tokenColors` rule applies to, given as a list or as a
+/// comma separated string
+fn rule_selectors(rule: &Value) -> Vec<&str> {
+    match &rule["scope"] {
+        Value::String(scopes) => scopes.split(',').collect(),
+        Value::Array(scopes) => scopes.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// `value` if it's a hex color. VS Code colors may have an alpha, which is
+/// dropped since a theme's colors are all opaque.
+fn opaque_color(value: &str) -> Option<String> {
+    let digits = value.strip_prefix('#').filter(|digits| digits.chars().all(|c| c.is_ascii_hexdigit()))?;
+    let digits = match digits.len() {
+        4 | 8 => &digits[..digits.len() / 4 * 3],
+        _ => digits,
+    };
+    let color = format!("#{digits}");
+    is_hex_color(&color).then_some(color)
+}
+
+/// The foreground of the nearest rule covering `scope`. Among rules as near
+/// as each other the last wins, as it does in VS Code.
+fn scope_color(rules: &[Value], scope: &str) -> Option<String> {
+    let mut found: Option<(usize, String)> = None;
+    for rule in rules {
+        let Some(color) = rule["settings"]["foreground"].as_str().and_then(opaque_color) else {
+            continue;
+        };
+        let nearest = rule_selectors(rule).into_iter().filter_map(|selector| selector_match(selector, scope)).max();
+        if let Some(nearest) = nearest
+            && found.as_ref().is_none_or(|(best, _)| nearest >= *best)
+        {
+            found = Some((nearest, color));
+        }
+    }
+    found.map(|(_, color)| color)
+}
+
+impl Theme {
+    /// Import a VS Code color theme. The editor's colors come from `colors`
+    /// and the syntax colors from the `tokenColors` rules. Scopes the editor
+    /// doesn't highlight are ignored, and whatever the theme leaves out is
+    /// filled in from the built-in light or dark theme, following its `type`.
+    pub fn from_vscode(json: &str) -> Result<Theme, ThemeError> {
+        let value: Value = serde_json::from_str(&strip_jsonc(json))
+            .map_err(|err| ThemeError::InvalidJson(err.to_string()))?;
+        if value.get("colors").is_none() && value.get("tokenColors").is_none() {
+            return Err(ThemeError::MissingKey("colors".to_string()));
+        }
+
+        let mut theme = match value["type"].as_str() {
+            Some("light" | "hc-light") => light_theme(),
+            _ => Theme::default(),
+        };
+        theme.name = value["name"].as_str().unwrap_or("VS Code Theme").to_string();
+
+        let colors = &value["colors"];
+        let set = |field: &mut String, key: &str| {
+            if let Some(color) = colors[key].as_str().and_then(opaque_color) {
+                *field = color;
+            }
+        };
+        set(&mut theme.background, "editor.background");
+        set(&mut theme.foreground, "editor.foreground");
+        set(&mut theme.selection, "editor.selectionBackground");
+        set(&mut theme.cursor, "editorCursor.foreground");
+        set(&mut theme.line_highlight, "editor.lineHighlightBackground");
+        set(&mut theme.ui.toolbar_bg, "titleBar.activeBackground");
+        set(&mut theme.ui.toolbar_fg, "titleBar.activeForeground");
+        set(&mut theme.ui.statusbar_bg, "statusBar.background");
+        set(&mut theme.ui.statusbar_fg, "statusBar.foreground");
+        set(&mut theme.ui.button, "button.secondaryBackground");
+        set(&mut theme.ui.button_hover, "button.secondaryHoverBackground");
+        set(&mut theme.ui.button_active, "button.background");
+
+        let bracket_colors: Vec<String> = (1..=6)
+            .map_while(|idx| colors[format!("editorBracketHighlight.foreground{idx}")].as_str())
+            .filter_map(opaque_color)
+            .collect();
+        if !bracket_colors.is_empty() {
+            theme.bracket_colors = bracket_colors;
+        }
+
+        let rules = value["tokenColors"].as_array().map(Vec::as_slice).unwrap_or_default();
+        // Older themes give the editor's colors in a rule without a scope
+        if let Some(global) = rules.iter().find(|rule| rule.get("scope").is_none()) {
+            let settings = &global["settings"];
+            for (field, key, color_key) in [
+                (&mut theme.background, "background", "editor.background"),
+                (&mut theme.foreground, "foreground", "editor.foreground"),
+            ] {
+                if colors.get(color_key).is_none()
+                    && let Some(color) = settings[key].as_str().and_then(opaque_color)
+                {
+                    *field = color;
+                }
+            }
+        }
+
+        for (token, scope) in TOKEN_SCOPES {
+            if let Some(color) = scope_color(rules, scope) {
+                theme.syntax_colors.insert(token.to_string(), color);
+            }
+        }
+
+        Ok(theme)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_lose_their_alpha() {
+        assert_eq!(opaque_color("#264F7880").as_deref(), Some("#264F78"));
+        assert_eq!(opaque_color("#fff8").as_deref(), Some("#fff"));
+        assert_eq!(opaque_color("#1E1E1E").as_deref(), Some("#1E1E1E"));
+        assert_eq!(opaque_color("#12345"), None);
+        assert_eq!(opaque_color("#é1234"), None);
+        assert_eq!(opaque_color("red"), None);
+    }
+
+    const FRAGMENT: &str = r##"{
+        // A cut down Dark+
+        "name": "Dark Plus",
+        "type": "dark",
+        "colors": {
+            "editor.background": "#1E1E1E",
+            "editor.foreground": "#D4D4D4",
+            "editor.selectionBackground": "#264F7880",
+            "editorCursor.foreground": "#AEAFAD",
+        },
+        "tokenColors": [
+            { "scope": ["keyword", "storage.type"], "settings": { "foreground": "#569CD6" } },
+            { "scope": "keyword.control", "settings": { "foreground": "#C586C0" } },
+            { "scope": "string", "settings": { "foreground": "#CE9178" } },
+            { "scope": "entity.name", "settings": { "foreground": "#DCDCAA" } },
+            { "scope": "meta.embedded", "settings": { "foreground": "#FFFFFF" } },
+            { "scope": "source.rust keyword", "settings": { "foreground": "#FF0000" } },
+        ],
+    }"##;
+
+    #[test]
+    fn maps_editor_and_token_colors() {
+        let theme = Theme::from_vscode(FRAGMENT).unwrap();
+        assert_eq!(theme.name, "Dark Plus");
+        assert_eq!(theme.background, "#1E1E1E");
+        assert_eq!(theme.foreground, "#D4D4D4");
+        assert_eq!(theme.selection, "#264F78");
+        assert_eq!(theme.cursor, "#AEAFAD");
+        assert_eq!(theme.syntax_colors["keyword"], "#569CD6");
+        assert_eq!(theme.syntax_colors["string"], "#CE9178");
+        // `entity.name` covers functions and types alike
+        assert_eq!(theme.syntax_colors["function"], "#DCDCAA");
+        assert_eq!(theme.syntax_colors["type"], "#DCDCAA");
+        assert_eq!(theme.validate(), Ok(()));
+    }
+
+    #[test]
+    fn missing_colors_come_from_the_built_in_theme() {
+        let theme = Theme::from_vscode(FRAGMENT).unwrap();
+        let default = Theme::default();
+        assert_eq!(theme.line_highlight, default.line_highlight);
+        assert_eq!(theme.ui, default.ui);
+        assert_eq!(theme.syntax_colors["comment"], default.syntax_colors["comment"]);
+
+        let light = Theme::from_vscode(r#"{ "type": "light", "colors": {} }"#).unwrap();
+        assert_eq!(light.background, light_theme().background);
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_themes() {
+        assert!(matches!(Theme::from_vscode("{ nope"), Err(ThemeError::InvalidJson(_))));
+        let error = Theme::from_vscode(r#"{ "name": "Empty" }"#).unwrap_err();
+        assert_eq!(error, ThemeError::MissingKey("colors".to_string()));
+        assert_eq!(error.to_string(), "Theme is missing \"colors\"");
+    }
+}
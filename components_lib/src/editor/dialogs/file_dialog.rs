@@ -1,21 +1,53 @@
 use dioxus::prelude::*;
 use crate::core::Theme;
+use crate::editor::editor_core::{LANGUAGES, extension_for, language_for_extension, template_for};
+
+/// The language `filename`'s extension is for, if it has one the editor knows.
+/// A dot at the very start begins a hidden file's name, not an extension.
+fn typed_language(filename: &str) -> Option<&'static str> {
+    filename
+        .rsplit_once('.')
+        .filter(|(base, _)| !base.is_empty())
+        .and_then(|(_, extension)| language_for_extension(extension))
+}
+
+/// `filename` without its extension or any trailing dots. Only an extension
+/// the editor knows is dropped, so other dots in the name stay.
+fn base_name(filename: &str) -> &str {
+    let trimmed = filename.trim_end_matches('.');
+    match trimmed.rsplit_once('.') {
+        Some((base, _)) if typed_language(trimmed).is_some() => base,
+        _ => trimmed,
+    }
+}
+
+/// `filename` with the extension for `language` in place of any it had
+fn with_extension(filename: &str, language: &str) -> String {
+    let base = match base_name(filename.trim()) {
+        "" => "untitled",
+        base => base,
+    };
+    format!("{}.{}", base, extension_for(language))
+}
 
 #[component]
 pub fn NewFileDialog(
     theme: Theme,
-    on_create: EventHandler<(String, String)>, // (filename, language)
+    on_create: EventHandler<(String, String, bool)>, // (filename, language, start from its template)
     on_cancel: EventHandler<()>, // No parameters for cancel
 ) -> Element {
     let mut filename = use_signal(|| String::from("untitled.rs"));
     let mut language = use_signal(|| String::from("rust"));
+    // Set once the user types an extension for some other language than the
+    // selected one, which picking a language then leaves alone
+    let mut extension_typed = use_signal(|| false);
+    // Whether to start from the language's template rather than a blank file
+    let mut use_template = use_signal(|| true);
 
-    let dialog_style = format!(
-        "position: fixed; top: 0; left: 0; right: 0; bottom: 0;
+    let dialog_style = "position: fixed; top: 0; left: 0; right: 0; bottom: 0;
          background-color: rgba(0, 0, 0, 0.7);
          display: flex; align-items: center; justify-content: center;
-         z-index: 100;",
-    );
+         z-index: 100;";
 
     let panel_style = format!(
         "background-color: {}; color: {}; padding: 1.5rem;
@@ -37,10 +69,8 @@ pub fn NewFileDialog(
          theme.background, theme.foreground
     );
 
-    let button_style = format!(
-        "padding: 0.5rem 1rem; margin-left: 0.5rem;
-         border: none; border-radius: 3px; cursor: pointer;"
-    );
+    let button_style = "padding: 0.5rem 1rem; margin-left: 0.5rem;
+         border: none; border-radius: 3px; cursor: pointer;";
 
     let primary_button_style = format!(
         "{} background-color: #0078d7; color: white;",
@@ -53,41 +83,35 @@ pub fn NewFileDialog(
     );
 
     let handle_submit = move |_| {
-        on_create.call((filename(), language()));
+        on_create.call((filename(), language(), use_template()));
     };
 
     let handle_cancel = move |_| {
         on_cancel.call(());
     };
 
-    // Auto-update the file extension based on the language selection
+    // Keep the file extension in step with the language selection
     let mut update_extension = move |selected_lang: String| {
-        language.set(selected_lang.clone());
-
-        // Extract the base name without extension
-        let filename_value = filename();
-        let base_name = if let Some(dot_pos) = filename_value.rfind('.') {
-            &filename_value[0..dot_pos]
-        } else {
-            &filename_value
-        };
-
-        // Set new extension based on language
-        let extension = match selected_lang.as_str() {
-            "rust" => "rs",
-            "javascript" => "js",
-            "html" => "html",
-            "css" => "css",
-            "markdown" => "md",
-            "json" => "json",
-            "toml" => "toml",
-            "yaml" => "yaml",
-            _ => "txt", // default to plain text
-        };
-
-        filename.set(format!("{}.{}", base_name, extension));
+        if !extension_typed() {
+            filename.set(with_extension(&filename(), &selected_lang));
+        }
+        language.set(selected_lang);
+    };
+
+    let mut handle_filename_input = move |value: String| {
+        let typed = typed_language(&value);
+        extension_typed.set(typed.is_some_and(|typed| typed != language()));
+        filename.set(value);
     };
 
+    // Offer to match the language to the extension the user typed
+    let suggested_language = typed_language(&filename())
+        .filter(|typed| *typed != language())
+        .and_then(|typed| LANGUAGES.iter().find(|(id, _)| *id == typed).copied());
+
+    let language_name = LANGUAGES.iter().find(|(id, _)| *id == language()).map(|(_, name)| *name);
+    let template_name = language_name.filter(|_| template_for(&language()).is_some());
+
     rsx! {
         div {
             style: dialog_style,
@@ -105,7 +129,23 @@ pub fn NewFileDialog(
                         id: "filename-input",
                         style: input_style,
                         value: filename(),
-                        oninput: move |e| filename.set(e.value().clone()),
+                        oninput: move |e| handle_filename_input(e.value()),
+                    }
+                    if let Some((id, name)) = suggested_language {
+                        div {
+                            style: "font-size: 12px;",
+                            "That's a {name} file name. "
+                            a {
+                                href: "#",
+                                style: "color: #0078d7;",
+                                onclick: move |event| {
+                                    event.prevent_default();
+                                    language.set(id.to_string());
+                                    extension_typed.set(false);
+                                },
+                                "Switch to {name}"
+                            }
+                        }
                     }
                 }
 
@@ -121,15 +161,27 @@ pub fn NewFileDialog(
                         value: language(),
                         onchange: move |e| update_extension(e.value().clone()),
 
-                        option { value: "rust", "Rust" }
-                        option { value: "javascript", "JavaScript" }
-                        option { value: "html", "HTML" }
-                        option { value: "css", "CSS" }
-                        option { value: "markdown", "Markdown" }
-                        option { value: "json", "JSON" }
-                        option { value: "toml", "TOML" }
-                        option { value: "yaml", "YAML" }
-                        option { value: "plain", "Plain Text" }
+                        for (id, name) in LANGUAGES {
+                            option { value: *id, "{name}" }
+                        }
+                    }
+                }
+
+                if let Some(name) = template_name {
+                    div {
+                        style: "margin-bottom: 1.5rem;",
+                        label {
+                            r#for: "template-select",
+                            "Start with:"
+                        }
+                        select {
+                            id: "template-select",
+                            style: select_style.clone(),
+                            onchange: move |e| use_template.set(e.value() == "template"),
+
+                            option { value: "template", selected: use_template(), "{name} template" }
+                            option { value: "blank", selected: !use_template(), "Blank file" }
+                        }
                     }
                 }
 
@@ -149,4 +201,49 @@ pub fn NewFileDialog(
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swaps_a_known_extension() {
+        assert_eq!(with_extension("main.rs", "python"), "main.py");
+        assert_eq!(with_extension("main", "rust"), "main.rs");
+    }
+
+    #[test]
+    fn keeps_dots_inside_the_name() {
+        assert_eq!(with_extension("my.module.rs", "javascript"), "my.module.js");
+        assert_eq!(with_extension("v1.2", "rust"), "v1.2.rs");
+    }
+
+    #[test]
+    fn trailing_dots_do_not_double_up() {
+        assert_eq!(with_extension("foo.", "rust"), "foo.rs");
+        assert_eq!(with_extension("foo..", "rust"), "foo.rs");
+    }
+
+    #[test]
+    fn switching_language_repeatedly_does_not_pile_up_extensions() {
+        let mut filename = "notes".to_string();
+        for language in ["rust", "python", "markdown", "rust"] {
+            filename = with_extension(&filename, language);
+        }
+        assert_eq!(filename, "notes.rs");
+    }
+
+    #[test]
+    fn empty_names_become_untitled() {
+        assert_eq!(with_extension("  ", "rust"), "untitled.rs");
+    }
+
+    #[test]
+    fn typed_extensions_name_their_language() {
+        assert_eq!(typed_language("script.py"), Some("python"));
+        assert_eq!(typed_language("README.MD"), Some("markdown"));
+        assert_eq!(typed_language("archive.tar"), None);
+        assert_eq!(typed_language(".bashrc"), None);
+    }
+}
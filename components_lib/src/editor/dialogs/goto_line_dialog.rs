@@ -0,0 +1,122 @@
+use dioxus::prelude::*;
+use crate::core::Theme;
+
+/// Parse the 1-based line number typed into the dialog, or explain what's wrong with it
+fn parse_line_number(input: &str, line_count: usize) -> Result<usize, String> {
+    let line = input
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| "Enter a line number".to_string())?;
+
+    if line == 0 || line > line_count {
+        return Err(format!("Line must be between 1 and {line_count}"));
+    }
+
+    Ok(line)
+}
+
+#[component]
+pub fn GoToLineDialog(
+    theme: Theme,
+    line_count: usize,
+    on_go: EventHandler<usize>, // 1-based line number
+    on_cancel: EventHandler<()>,
+) -> Element {
+    let mut line_input = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let dialog_style = "position: fixed; top: 0; left: 0; right: 0; bottom: 0;
+         background-color: rgba(0, 0, 0, 0.7);
+         display: flex; align-items: center; justify-content: center;
+         z-index: 100;";
+
+    let panel_style = format!(
+        "background-color: {}; color: {}; padding: 1.5rem;
+         border-radius: 4px; width: 300px;",
+         theme.background, theme.foreground
+    );
+
+    let input_style = format!(
+        "width: 100%; padding: 0.5rem; margin: 0.5rem 0;
+         background-color: {}; color: {}; border: 1px solid {};
+         border-radius: 3px;",
+         theme.background, theme.foreground,
+         if error.read().is_some() { "#E06C75" } else { "#555" }
+    );
+
+    let button_style = "padding: 0.5rem 1rem; margin-left: 0.5rem;
+         border: none; border-radius: 3px; cursor: pointer;";
+
+    let primary_button_style = format!(
+        "{} background-color: #0078d7; color: white;",
+        button_style
+    );
+
+    let secondary_button_style = format!(
+        "{} background-color: #333; color: white;",
+        button_style
+    );
+
+    let mut submit = move || match parse_line_number(&line_input(), line_count) {
+        Ok(line) => on_go.call(line),
+        Err(message) => error.set(Some(message)),
+    };
+
+    let handle_keydown = move |event: Event<KeyboardData>| match event.key() {
+        Key::Enter => submit(),
+        Key::Escape => on_cancel.call(()),
+        _ => {}
+    };
+
+    rsx! {
+        div {
+            style: dialog_style,
+            div {
+                style: panel_style,
+                h3 { "Go to Line" }
+
+                div {
+                    style: "margin-bottom: 1.5rem;",
+                    label {
+                        r#for: "goto-line-input",
+                        "Line number (1-{line_count}):"
+                    }
+                    input {
+                        id: "goto-line-input",
+                        style: input_style,
+                        inputmode: "numeric",
+                        value: line_input(),
+                        onmounted: move |event| async move {
+                            let _ = event.set_focus(true).await;
+                        },
+                        oninput: move |e| {
+                            line_input.set(e.value().clone());
+                            error.set(None);
+                        },
+                        onkeydown: handle_keydown,
+                    }
+                    if let Some(message) = error() {
+                        div {
+                            style: "font-size: 12px; color: #E06C75;",
+                            "{message}"
+                        }
+                    }
+                }
+
+                div {
+                    style: "display: flex; justify-content: flex-end;",
+                    button {
+                        style: secondary_button_style,
+                        onclick: move |_| on_cancel.call(()),
+                        "Cancel"
+                    }
+                    button {
+                        style: primary_button_style,
+                        onclick: move |_| submit(),
+                        "Go"
+                    }
+                }
+            }
+        }
+    }
+}
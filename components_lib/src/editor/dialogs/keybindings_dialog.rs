@@ -0,0 +1,282 @@
+use dioxus::prelude::*;
+use crate::core::Theme;
+use crate::editor::panels::menus::{KeyCombo, KeyOverrides, Keymap};
+
+/// A key combination that's already taken, found while rebinding a command
+#[derive(Clone, PartialEq)]
+struct Conflict {
+    command: String,
+    combo: KeyCombo,
+    bound_to: String,
+}
+
+/// The overrides with `command` bound to `combo`, leaving `unbound` without
+/// a shortcut. Binding a command back to its default drops its override.
+fn rebound(defaults: &Keymap, overrides: &KeyOverrides, command: &str, combo: KeyCombo, unbound: Option<&str>) -> KeyOverrides {
+    let mut rebound = overrides.clone();
+    if defaults.combo_for(command) == Some(&combo) {
+        rebound.remove(command);
+    } else {
+        rebound.insert(command.to_string(), Some(combo));
+    }
+    if let Some(unbound) = unbound {
+        rebound.insert(unbound.to_string(), None);
+    }
+    rebound
+}
+
+/// Every command's keyboard shortcut, to look through and rebind. Clicking a
+/// shortcut records the next key combination pressed. Each change applies
+/// straight away.
+#[component]
+pub fn KeybindingsDialog(
+    theme: Theme,
+    // Each command by id, with where it is in the menus
+    commands: Vec<(String, String)>,
+    // The shortcuts the menus come with
+    defaults: Keymap,
+    overrides: KeyOverrides,
+    // Commands whose shortcuts the browser handles, shown but not rebindable
+    fixed: Vec<String>,
+    on_change: EventHandler<KeyOverrides>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let mut recording = use_signal(|| None::<String>);
+    let mut conflict = use_signal(|| None::<Conflict>);
+    let mut error = use_signal(|| None::<String>);
+
+    let keymap = defaults.clone().with_overrides(&overrides);
+    let label_of = {
+        let commands = commands.clone();
+        move |command: &str| {
+            commands
+                .iter()
+                .find(|(id, _)| id == command)
+                .map(|(_, label)| label.clone())
+                .unwrap_or_else(|| command.to_string())
+        }
+    };
+
+    let dialog_style = "position: fixed; top: 0; left: 0; right: 0; bottom: 0;
+         background-color: rgba(0, 0, 0, 0.7);
+         display: flex; align-items: center; justify-content: center;
+         z-index: 100;";
+
+    let panel_style = format!(
+        "background-color: {}; color: {}; padding: 1.5rem;
+         border-radius: 4px; width: 480px; max-height: 80vh;
+         display: flex; flex-direction: column;",
+         theme.background, theme.foreground
+    );
+
+    let row_style = "display: flex; align-items: center; justify-content: space-between; margin: 0.25rem 0; gap: 0.5rem;";
+
+    let combo_style = format!(
+        "min-width: 8rem; padding: 0.25rem; background-color: {}; color: {};
+         border: 1px solid #555; border-radius: 3px; cursor: pointer; font-family: monospace;",
+         theme.background, theme.foreground
+    );
+
+    let link_style = "background: none; border: none; padding: 0 0.25rem; color: #0078d7; cursor: pointer; font-size: 12px;";
+
+    let button_style = "padding: 0.5rem 1rem; margin-left: 0.5rem;
+         border: none; border-radius: 3px; cursor: pointer;";
+
+    let primary_button_style = format!(
+        "{} background-color: #0078d7; color: white;",
+        button_style
+    );
+
+    let secondary_button_style = format!(
+        "{} background-color: #333; color: white;",
+        button_style
+    );
+
+    // Record the key combination pressed for the command being rebound
+    let handle_record = {
+        let keymap = keymap.clone();
+        let defaults = defaults.clone();
+        let overrides = overrides.clone();
+        move |event: Event<KeyboardData>| {
+            let Some(command) = recording() else {
+                return;
+            };
+            // Keep the editor from running the shortcut as well
+            event.prevent_default();
+            event.stop_propagation();
+
+            if event.key() == Key::Escape {
+                recording.set(None);
+                return;
+            }
+
+            let modifiers = event.modifiers();
+            let Some(combo) = KeyCombo::from_key_press(
+                &event.key().to_string(),
+                modifiers.ctrl() || modifiers.meta(),
+                modifiers.shift(),
+                modifiers.alt(),
+            ) else {
+                return;
+            };
+
+            recording.set(None);
+            if !combo.is_bindable() {
+                error.set(Some(format!("{combo} would get in the way of typing, use Ctrl or Alt with it")));
+                return;
+            }
+
+            match keymap.command_bound_to(&combo, &command) {
+                Some(bound_to) => conflict.set(Some(Conflict { command, combo, bound_to: bound_to.to_string() })),
+                None => on_change.call(rebound(&defaults, &overrides, &command, combo, None)),
+            }
+        }
+    };
+
+    let replace = {
+        let defaults = defaults.clone();
+        let overrides = overrides.clone();
+        move |_| {
+            if let Some(Conflict { command, combo, bound_to }) = conflict.take() {
+                on_change.call(rebound(&defaults, &overrides, &command, combo, Some(&bound_to)));
+            }
+        }
+    };
+
+    let conflict_message = conflict().map(|found| {
+        format!(
+            "{} is already the shortcut for {}. Use it for {} instead?",
+            found.combo,
+            label_of(&found.bound_to),
+            label_of(&found.command)
+        )
+    });
+
+    rsx! {
+        div {
+            style: dialog_style,
+            tabindex: "-1",
+            onmounted: move |event| async move {
+                let _ = event.set_focus(true).await;
+            },
+            onkeydown: move |event: Event<KeyboardData>| {
+                if event.key() == Key::Escape {
+                    on_close.call(());
+                }
+            },
+            div {
+                style: panel_style,
+                h3 { "Keyboard Shortcuts" }
+
+                if let Some(message) = conflict_message {
+                    div {
+                        style: "font-size: 12px; margin-bottom: 0.5rem;",
+                        role: "alert",
+                        "{message} "
+                        button { style: link_style, onclick: replace, "Replace" }
+                        button {
+                            style: link_style,
+                            onclick: move |_| conflict.set(None),
+                            "Cancel"
+                        }
+                    }
+                }
+
+                if let Some(message) = error() {
+                    div {
+                        style: "font-size: 12px; color: #E06C75; margin-bottom: 0.5rem;",
+                        role: "alert",
+                        "{message}"
+                    }
+                }
+
+                div {
+                    style: "overflow-y: auto; flex: 1;",
+                    for (command, label) in commands.clone() {
+                        div {
+                            key: "{command}",
+                            style: row_style,
+                            span { "{label}" }
+                            if fixed.contains(&command) {
+                                span {
+                                    style: "font-family: monospace; opacity: 0.7;",
+                                    title: "Handled by the browser",
+                                    {keymap.combo_for(&command).map(KeyCombo::to_string).unwrap_or_default()}
+                                }
+                            } else {
+                                div {
+                                    style: "display: flex; align-items: center; flex-shrink: 0;",
+                                    if overrides.contains_key(&command) {
+                                        button {
+                                            style: link_style,
+                                            title: "Use the default shortcut",
+                                            onclick: {
+                                                let command = command.clone();
+                                                let overrides = overrides.clone();
+                                                move |_| {
+                                                    let mut reset = overrides.clone();
+                                                    reset.remove(&command);
+                                                    on_change.call(reset);
+                                                }
+                                            },
+                                            "Reset"
+                                        }
+                                    }
+                                    if keymap.combo_for(&command).is_some() {
+                                        button {
+                                            style: link_style,
+                                            title: "Leave without a shortcut",
+                                            onclick: {
+                                                let command = command.clone();
+                                                let overrides = overrides.clone();
+                                                move |_| {
+                                                    let mut removed = overrides.clone();
+                                                    removed.insert(command.clone(), None);
+                                                    on_change.call(removed);
+                                                }
+                                            },
+                                            "Remove"
+                                        }
+                                    }
+                                    button {
+                                        style: combo_style.clone(),
+                                        aria_label: "Shortcut for {label}",
+                                        onclick: {
+                                            let command = command.clone();
+                                            move |_| {
+                                                conflict.set(None);
+                                                error.set(None);
+                                                recording.set(Some(command.clone()));
+                                            }
+                                        },
+                                        onkeydown: handle_record.clone(),
+                                        onblur: move |_| recording.set(None),
+                                        if recording().as_ref() == Some(&command) {
+                                            "Press keys…"
+                                        } else {
+                                            {keymap.combo_for(&command).map(KeyCombo::to_string).unwrap_or_else(|| "—".to_string())}
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                div {
+                    style: "display: flex; justify-content: flex-end; margin-top: 1.5rem;",
+                    button {
+                        style: secondary_button_style,
+                        onclick: move |_| on_change.call(KeyOverrides::new()),
+                        "Reset all"
+                    }
+                    button {
+                        style: primary_button_style,
+                        onclick: move |_| on_close.call(()),
+                        "Close"
+                    }
+                }
+            }
+        }
+    }
+}
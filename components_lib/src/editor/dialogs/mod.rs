@@ -1,3 +1,13 @@
 pub mod file_dialog;
+pub mod goto_line_dialog;
+pub mod keybindings_dialog;
+pub mod restore_autosave_dialog;
+pub mod settings_dialog;
+pub mod theme_contrast_dialog;
 
-pub use file_dialog::NewFileDialog;
\ No newline at end of file
+pub use file_dialog::NewFileDialog;
+pub use goto_line_dialog::GoToLineDialog;
+pub use keybindings_dialog::KeybindingsDialog;
+pub use restore_autosave_dialog::RestoreAutosaveDialog;
+pub use settings_dialog::SettingsDialog;
+pub use theme_contrast_dialog::ThemeContrastDialog;
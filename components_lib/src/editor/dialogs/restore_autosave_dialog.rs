@@ -0,0 +1,73 @@
+use dioxus::prelude::*;
+use crate::core::Theme;
+
+#[component]
+pub fn RestoreAutosaveDialog(
+    theme: Theme,
+    document_count: usize,
+    saved_at: String,
+    on_restore: EventHandler<()>,
+    on_discard: EventHandler<()>,
+) -> Element {
+    let dialog_style = "position: fixed; top: 0; left: 0; right: 0; bottom: 0;
+         background-color: rgba(0, 0, 0, 0.7);
+         display: flex; align-items: center; justify-content: center;
+         z-index: 100;";
+
+    let panel_style = format!(
+        "background-color: {}; color: {}; padding: 1.5rem;
+         border-radius: 4px; width: 360px;",
+         theme.background, theme.foreground
+    );
+
+    let button_style = "padding: 0.5rem 1rem; margin-left: 0.5rem;
+         border: none; border-radius: 3px; cursor: pointer;";
+
+    let primary_button_style = format!(
+        "{} background-color: #0078d7; color: white;",
+        button_style
+    );
+
+    let secondary_button_style = format!(
+        "{} background-color: #333; color: white;",
+        button_style
+    );
+
+    let documents = if document_count == 1 {
+        "1 document".to_string()
+    } else {
+        format!("{document_count} documents")
+    };
+
+    rsx! {
+        div {
+            style: dialog_style,
+            div {
+                style: panel_style,
+                h3 { "Restore Unsaved Work?" }
+
+                p {
+                    style: "margin-bottom: 1.5rem;",
+                    "{documents} had unsaved changes when the editor was last closed. They were autosaved at {saved_at}."
+                }
+
+                div {
+                    style: "display: flex; justify-content: flex-end;",
+                    button {
+                        style: secondary_button_style,
+                        onclick: move |_| on_discard.call(()),
+                        "Discard"
+                    }
+                    button {
+                        style: primary_button_style,
+                        onmounted: move |event| async move {
+                            let _ = event.set_focus(true).await;
+                        },
+                        onclick: move |_| on_restore.call(()),
+                        "Restore"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,367 @@
+use dioxus::prelude::*;
+use crate::core::Theme;
+use crate::editor::editor_core::{EditorSettings, IndentStyle, MONOSPACE_FONTS};
+use crate::editor::editor_core::font::{MAX_FONT_SIZE, MIN_FONT_SIZE};
+use crate::editor::editor_core::settings::{MAX_TAB_WIDTH, MIN_TAB_WIDTH};
+
+/// Every editor setting in one place. Each change applies straight away.
+#[component]
+pub fn SettingsDialog(
+    theme: Theme,
+    settings: EditorSettings,
+    // Names of the themes to pick from
+    theme_names: Vec<String>,
+    // How the active document is already indented, if it is
+    detected_indent: Option<IndentStyle>,
+    on_change: EventHandler<EditorSettings>,
+    on_keybindings: EventHandler<()>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let dialog_style = "position: fixed; top: 0; left: 0; right: 0; bottom: 0;
+         background-color: rgba(0, 0, 0, 0.7);
+         display: flex; align-items: center; justify-content: center;
+         z-index: 100;";
+
+    let panel_style = format!(
+        "background-color: {}; color: {}; padding: 1.5rem;
+         border-radius: 4px; width: 360px;",
+         theme.background, theme.foreground
+    );
+
+    let row_style = "display: flex; align-items: center; justify-content: space-between; margin: 0.5rem 0;";
+
+    let input_style = format!(
+        "width: 10rem; padding: 0.25rem; background-color: {}; color: {};
+         border: 1px solid #555; border-radius: 3px;",
+         theme.background, theme.foreground
+    );
+
+    let button_style = "padding: 0.5rem 1rem; margin-left: 0.5rem;
+         border: none; border-radius: 3px; cursor: pointer;";
+
+    let primary_button_style = format!(
+        "{} background-color: #0078d7; color: white;",
+        button_style
+    );
+
+    let secondary_button_style = format!(
+        "{} background-color: #333; color: white;",
+        button_style
+    );
+
+    // Settings with one field changed by `update`
+    let changed = {
+        let settings = settings.clone();
+        move |update: &dyn Fn(&mut EditorSettings)| {
+            let mut changed = settings.clone();
+            update(&mut changed);
+            on_change.call(changed);
+        }
+    };
+
+    let on_tab_width = changed.clone();
+    let on_insert_spaces = changed.clone();
+    let on_word_wrap = changed.clone();
+    let on_line_numbers = changed.clone();
+    let on_minimap = changed.clone();
+    let on_indent_guides = changed.clone();
+    let on_word_completion = changed.clone();
+    let on_font_family = changed.clone();
+    let on_font_size = changed.clone();
+    let on_theme = changed.clone();
+    let on_trim = changed.clone();
+    let on_final_newline = changed.clone();
+    let on_restore_session = changed.clone();
+    let on_server_linting = changed.clone();
+    let on_match_indent = changed;
+
+    // Settings that indent the way the document already does
+    let matching_indent = match detected_indent {
+        Some(IndentStyle::Tabs) => Some((false, settings.tab_width)),
+        Some(IndentStyle::Spaces(width)) => Some((true, width.clamp(MIN_TAB_WIDTH, MAX_TAB_WIDTH))),
+        _ => None,
+    }
+    .filter(|indent| *indent != (settings.insert_spaces, settings.tab_width));
+
+    rsx! {
+        div {
+            style: dialog_style,
+            tabindex: "-1",
+            onmounted: move |event| async move {
+                let _ = event.set_focus(true).await;
+            },
+            onkeydown: move |event: Event<KeyboardData>| {
+                if event.key() == Key::Escape {
+                    on_close.call(());
+                }
+            },
+            div {
+                style: panel_style,
+                h3 { "Settings" }
+
+                div {
+                    style: row_style,
+                    label { r#for: "settings-tab-width", "Tab width" }
+                    input {
+                        id: "settings-tab-width",
+                        style: input_style.clone(),
+                        r#type: "number",
+                        min: "{MIN_TAB_WIDTH}",
+                        max: "{MAX_TAB_WIDTH}",
+                        value: "{settings.tab_width}",
+                        oninput: move |e| {
+                            if let Ok(width) = e.value().parse::<usize>() {
+                                on_tab_width(&|settings| settings.tab_width = width.clamp(MIN_TAB_WIDTH, MAX_TAB_WIDTH));
+                            }
+                        },
+                    }
+                }
+
+                div {
+                    style: row_style,
+                    label { r#for: "settings-insert-spaces", "Indent with" }
+                    select {
+                        id: "settings-insert-spaces",
+                        style: input_style.clone(),
+                        onchange: move |e| {
+                            let insert_spaces = e.value() == "spaces";
+                            on_insert_spaces(&|settings| settings.insert_spaces = insert_spaces);
+                        },
+                        option { value: "spaces", selected: settings.insert_spaces, "Spaces" }
+                        option { value: "tabs", selected: !settings.insert_spaces, "Tabs" }
+                    }
+                }
+
+                if let Some(style) = detected_indent {
+                    div {
+                        style: "font-size: 12px; opacity: 0.8;",
+                        "This document is indented with {style.label().to_lowercase()}. "
+                        if let Some((insert_spaces, tab_width)) = matching_indent {
+                            a {
+                                href: "#",
+                                style: "color: #0078d7;",
+                                onclick: move |event| {
+                                    event.prevent_default();
+                                    on_match_indent(&|settings| {
+                                        settings.insert_spaces = insert_spaces;
+                                        settings.tab_width = tab_width;
+                                    });
+                                },
+                                "Match it"
+                            }
+                        }
+                    }
+                }
+
+                div {
+                    style: row_style,
+                    label { r#for: "settings-font-family", "Font" }
+                    select {
+                        id: "settings-font-family",
+                        style: input_style.clone(),
+                        onchange: move |e| {
+                            let family = e.value();
+                            on_font_family(&|settings| settings.font.family = family.clone());
+                        },
+                        for (name, family) in MONOSPACE_FONTS {
+                            option {
+                                value: *family,
+                                selected: settings.font.family == *family,
+                                "{name}"
+                            }
+                        }
+                    }
+                }
+
+                div {
+                    style: row_style,
+                    label { r#for: "settings-font-size", "Font size" }
+                    input {
+                        id: "settings-font-size",
+                        style: input_style.clone(),
+                        r#type: "number",
+                        min: "{MIN_FONT_SIZE}",
+                        max: "{MAX_FONT_SIZE}",
+                        value: "{settings.font.size}",
+                        oninput: move |e| {
+                            if let Ok(size) = e.value().parse::<u32>() {
+                                on_font_size(&|settings| settings.font.size = size.clamp(MIN_FONT_SIZE, MAX_FONT_SIZE));
+                            }
+                        },
+                    }
+                }
+
+                div {
+                    style: row_style,
+                    label { r#for: "settings-theme", "Theme" }
+                    select {
+                        id: "settings-theme",
+                        style: input_style.clone(),
+                        onchange: move |e| {
+                            let name = Some(e.value()).filter(|name| !name.is_empty());
+                            on_theme(&|settings| settings.theme = name.clone());
+                        },
+                        option { value: "", selected: settings.theme.is_none(), "Match system" }
+                        for name in theme_names {
+                            option {
+                                value: "{name}",
+                                selected: settings.theme.as_ref() == Some(&name),
+                                "{name}"
+                            }
+                        }
+                    }
+                }
+
+                div {
+                    style: row_style,
+                    label { r#for: "settings-word-wrap", "Word wrap" }
+                    input {
+                        id: "settings-word-wrap",
+                        r#type: "checkbox",
+                        checked: settings.word_wrap,
+                        onchange: move |e| {
+                            let word_wrap = e.checked();
+                            on_word_wrap(&|settings| settings.word_wrap = word_wrap);
+                        },
+                    }
+                }
+
+                div {
+                    style: row_style,
+                    label { r#for: "settings-line-numbers", "Line numbers" }
+                    input {
+                        id: "settings-line-numbers",
+                        r#type: "checkbox",
+                        checked: settings.show_line_numbers,
+                        onchange: move |e| {
+                            let show = e.checked();
+                            on_line_numbers(&|settings| settings.show_line_numbers = show);
+                        },
+                    }
+                }
+
+                div {
+                    style: row_style,
+                    label { r#for: "settings-minimap", "Minimap" }
+                    input {
+                        id: "settings-minimap",
+                        r#type: "checkbox",
+                        checked: settings.show_minimap,
+                        onchange: move |e| {
+                            let show = e.checked();
+                            on_minimap(&|settings| settings.show_minimap = show);
+                        },
+                    }
+                }
+
+                div {
+                    style: row_style,
+                    label { r#for: "settings-indent-guides", "Indentation guides" }
+                    input {
+                        id: "settings-indent-guides",
+                        r#type: "checkbox",
+                        checked: settings.show_indent_guides,
+                        onchange: move |e| {
+                            let show = e.checked();
+                            on_indent_guides(&|settings| settings.show_indent_guides = show);
+                        },
+                    }
+                }
+
+                div {
+                    style: row_style,
+                    label { r#for: "settings-word-completion", "Suggest words as you type" }
+                    input {
+                        id: "settings-word-completion",
+                        r#type: "checkbox",
+                        checked: settings.word_completion,
+                        onchange: move |e| {
+                            let complete = e.checked();
+                            on_word_completion(&|settings| settings.word_completion = complete);
+                        },
+                    }
+                }
+
+                div {
+                    style: row_style,
+                    label { r#for: "settings-trim-whitespace", "Trim trailing whitespace on save" }
+                    input {
+                        id: "settings-trim-whitespace",
+                        r#type: "checkbox",
+                        checked: settings.trim_trailing_whitespace,
+                        onchange: move |e| {
+                            let trim = e.checked();
+                            on_trim(&|settings| settings.trim_trailing_whitespace = trim);
+                        },
+                    }
+                }
+
+                div {
+                    style: row_style,
+                    label { r#for: "settings-final-newline", "End with one newline on save" }
+                    input {
+                        id: "settings-final-newline",
+                        r#type: "checkbox",
+                        checked: settings.insert_final_newline,
+                        onchange: move |e| {
+                            let final_newline = e.checked();
+                            on_final_newline(&|settings| settings.insert_final_newline = final_newline);
+                        },
+                    }
+                }
+
+                div {
+                    style: row_style,
+                    label { r#for: "settings-restore-session", "Reopen tabs from last time" }
+                    input {
+                        id: "settings-restore-session",
+                        r#type: "checkbox",
+                        checked: settings.restore_session,
+                        onchange: move |e| {
+                            let restore = e.checked();
+                            on_restore_session(&|settings| settings.restore_session = restore);
+                        },
+                    }
+                }
+
+                div {
+                    style: row_style,
+                    label { r#for: "settings-server-linting", "Check code on the server" }
+                    input {
+                        id: "settings-server-linting",
+                        r#type: "checkbox",
+                        checked: settings.server_linting,
+                        onchange: move |e| {
+                            let server_linting = e.checked();
+                            on_server_linting(&|settings| settings.server_linting = server_linting);
+                        },
+                    }
+                }
+
+                div {
+                    style: row_style,
+                    span { "Keyboard shortcuts" }
+                    button {
+                        style: input_style.clone(),
+                        onclick: move |_| on_keybindings.call(()),
+                        "Change…"
+                    }
+                }
+
+                div {
+                    style: "display: flex; justify-content: flex-end; margin-top: 1.5rem;",
+                    button {
+                        style: secondary_button_style,
+                        onclick: move |_| on_change.call(EditorSettings::default()),
+                        "Reset to defaults"
+                    }
+                    button {
+                        style: primary_button_style,
+                        onclick: move |_| on_close.call(()),
+                        "Close"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use dioxus::prelude::*;
+use crate::core::{ContrastIssue, Theme};
+
+/// Lists the colors in an imported theme that are hard to read against each
+/// other, before the theme is switched to
+#[component]
+pub fn ThemeContrastDialog(
+    theme: Theme,
+    // Name of the theme that was imported
+    theme_name: String,
+    issues: Vec<ContrastIssue>,
+    on_use: EventHandler<()>,
+    on_cancel: EventHandler<()>,
+) -> Element {
+    let dialog_style = "position: fixed; top: 0; left: 0; right: 0; bottom: 0;
+         background-color: rgba(0, 0, 0, 0.7);
+         display: flex; align-items: center; justify-content: center;
+         z-index: 100;";
+
+    let panel_style = format!(
+        "background-color: {}; color: {}; padding: 1.5rem;
+         border-radius: 4px; width: 420px;",
+         theme.background, theme.foreground
+    );
+
+    let button_style = "padding: 0.5rem 1rem; margin-left: 0.5rem;
+         border: none; border-radius: 3px; cursor: pointer;";
+
+    let primary_button_style = format!(
+        "{} background-color: #0078d7; color: white;",
+        button_style
+    );
+
+    let secondary_button_style = format!(
+        "{} background-color: #333; color: white;",
+        button_style
+    );
+
+    rsx! {
+        div {
+            style: dialog_style,
+            tabindex: "-1",
+            onkeydown: move |event: Event<KeyboardData>| {
+                if event.key() == Key::Escape {
+                    on_cancel.call(());
+                }
+            },
+            div {
+                style: panel_style,
+                role: "alertdialog",
+                h3 { "Low Contrast Colors" }
+
+                p { "Some text in \"{theme_name}\" may be hard to read:" }
+
+                ul {
+                    style: "max-height: 12rem; overflow-y: auto; font-size: 13px;",
+                    for issue in issues {
+                        li { "{issue}" }
+                    }
+                }
+
+                div {
+                    style: "display: flex; justify-content: flex-end; margin-top: 1.5rem;",
+                    button {
+                        style: secondary_button_style,
+                        onclick: move |_| on_cancel.call(()),
+                        "Don't Import"
+                    }
+                    button {
+                        style: primary_button_style,
+                        onmounted: move |event| async move {
+                            let _ = event.set_focus(true).await;
+                        },
+                        onclick: move |_| on_use.call(()),
+                        "Use Anyway"
+                    }
+                }
+            }
+        }
+    }
+}
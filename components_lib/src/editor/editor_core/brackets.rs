@@ -0,0 +1,153 @@
+use super::chars::is_word_char;
+
+const BRACKET_PAIRS: [(char, char); 3] = [('(', ')'), ('{', '}'), ('[', ']')];
+
+/// Pairs whose opener, typed, gets its closer typed after it too
+pub const AUTO_CLOSE_PAIRS: [(char, char); 4] = [('(', ')'), ('{', '}'), ('[', ']'), ('"', '"')];
+
+/// The char closing the auto-closed pair `c` opens
+pub fn closing_for(c: char) -> Option<char> {
+    AUTO_CLOSE_PAIRS.iter().find(|(open, _)| *open == c).map(|(_, close)| *close)
+}
+
+/// The closer to type along with `typed` between the chars `before` and
+/// `after` the caret, if any. Nothing is closed in front of a word, and a
+/// quote right after a word is more likely to be closing a string.
+pub fn auto_close(typed: char, before: Option<char>, after: Option<char>) -> Option<char> {
+    let close = closing_for(typed)?;
+    if after.is_some_and(is_word_char) || (close == typed && before.is_some_and(|c| is_word_char(c) || c == '\\')) {
+        return None;
+    }
+    Some(close)
+}
+
+/// Whether typing `typed` before `after` should step over it rather than
+/// type a second closer
+pub fn steps_over(typed: char, after: Option<char>) -> bool {
+    after == Some(typed) && AUTO_CLOSE_PAIRS.iter().any(|(_, close)| *close == typed)
+}
+
+/// Char offset of the partner of the bracket at `offset`, respecting nesting.
+/// Unbalanced brackets have no partner.
+fn partner_of(chars: &[char], offset: usize) -> Option<usize> {
+    let c = *chars.get(offset)?;
+
+    if let Some(&(open, close)) = BRACKET_PAIRS.iter().find(|(open, _)| *open == c) {
+        let mut depth = 0;
+        for (idx, &ch) in chars.iter().enumerate().skip(offset) {
+            if ch == open {
+                depth += 1;
+            } else if ch == close {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(idx);
+                }
+            }
+        }
+    } else if let Some(&(open, close)) = BRACKET_PAIRS.iter().find(|(_, close)| *close == c) {
+        let mut depth = 0;
+        for idx in (0..=offset).rev() {
+            if chars[idx] == close {
+                depth += 1;
+            } else if chars[idx] == open {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(idx);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Char offsets of the bracket touching the caret at `offset` and its partner.
+/// The bracket after the caret wins over the one before it.
+pub fn find_matching_bracket(text: &str, offset: usize) -> Option<(usize, usize)> {
+    let chars: Vec<char> = text.chars().collect();
+
+    [Some(offset), offset.checked_sub(1)]
+        .into_iter()
+        .flatten()
+        .find_map(|bracket| partner_of(&chars, bracket).map(|partner| (bracket, partner)))
+}
+
+/// Char offset of the `{` opening the innermost block around `offset`
+fn enclosing_block_start(chars: &[char], offset: usize) -> Option<usize> {
+    let mut depth = 0;
+    chars[..offset.min(chars.len())].iter().rposition(|&c| {
+        match c {
+            '}' => depth += 1,
+            '{' if depth == 0 => return true,
+            '{' => depth -= 1,
+            _ => {},
+        }
+        false
+    })
+}
+
+/// Where Go to Bracket takes the caret at `offset`: to the partner of the
+/// bracket it touches, or if it touches none, to the `{` of the block it's in.
+/// None if the bracket has no partner or there's no block around the caret.
+pub fn bracket_jump(text: &str, offset: usize) -> Option<usize> {
+    let chars: Vec<char> = text.chars().collect();
+    let is_bracket = |idx: usize| {
+        chars.get(idx).is_some_and(|c| BRACKET_PAIRS.iter().any(|(open, close)| c == open || c == close))
+    };
+
+    if is_bracket(offset) || offset.checked_sub(1).is_some_and(is_bracket) {
+        return find_matching_bracket(text, offset).map(|(_, partner)| partner);
+    }
+    enclosing_block_start(&chars, offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn openers_close_with_their_partner() {
+        assert_eq!(closing_for('('), Some(')'));
+        assert_eq!(closing_for('{'), Some('}'));
+        assert_eq!(closing_for('['), Some(']'));
+        assert_eq!(closing_for('"'), Some('"'));
+        assert_eq!(closing_for(')'), None);
+        assert_eq!(closing_for('a'), None);
+    }
+
+    #[test]
+    fn auto_closes_before_spaces_and_closers() {
+        assert_eq!(auto_close('(', None, None), Some(')'));
+        assert_eq!(auto_close('[', Some('x'), Some(' ')), Some(']'));
+        assert_eq!(auto_close('{', Some(' '), Some(')')), Some('}'));
+        assert_eq!(auto_close('"', Some('('), Some(')')), Some('"'));
+    }
+
+    #[test]
+    fn does_not_auto_close_next_to_words() {
+        assert_eq!(auto_close('(', None, Some('x')), None);
+        // A quote after a word or an escape likely ends a string
+        assert_eq!(auto_close('"', Some('o'), None), None);
+        assert_eq!(auto_close('"', Some('\\'), None), None);
+        // Brackets still close after a word, as in a call
+        assert_eq!(auto_close('(', Some('f'), None), Some(')'));
+    }
+
+    #[test]
+    fn steps_over_a_matching_closer() {
+        assert!(steps_over(')', Some(')')));
+        assert!(steps_over('"', Some('"')));
+        assert!(!steps_over(')', Some(']')));
+        assert!(!steps_over(')', None));
+        assert!(!steps_over('(', Some('(')));
+    }
+
+    #[test]
+    fn matches_nested_brackets_from_either_side() {
+        let text = "f(a[0], {b})";
+        assert_eq!(find_matching_bracket(text, 1), Some((1, 11)));
+        assert_eq!(find_matching_bracket(text, 12), Some((11, 1)));
+        assert_eq!(find_matching_bracket(text, 8), Some((8, 10)));
+        assert_eq!(find_matching_bracket("(()", 0), None);
+    }
+}
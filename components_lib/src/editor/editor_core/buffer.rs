@@ -1,36 +1,192 @@
-use ropey::Rope;
+use ropey::{Rope, RopeSlice};
+use std::collections::VecDeque;
 use std::sync::Arc;
+use unicode_segmentation::UnicodeSegmentation;
+use super::{BufferEncoding, BufferStats, Edit, search, stats};
+
+/// How many changes a buffer can undo unless it's given another limit
+pub const DEFAULT_HISTORY_LIMIT: usize = 1000;
+
+/// The chars a rope ends lines at, other than `\r\n` taken together
+const LINE_BREAKS: [char; 7] = ['\n', '\r', '\u{000B}', '\u{000C}', '\u{0085}', '\u{2028}', '\u{2029}'];
+
+/// `line` without the line break it ends with, if it has one
+fn without_line_break(line: RopeSlice<'_>) -> RopeSlice<'_> {
+    let len = line.len_chars();
+    let break_len = match (len.checked_sub(2).map(|idx| line.char(idx)), len.checked_sub(1).map(|idx| line.char(idx))) {
+        (Some('\r'), Some('\n')) => 2,
+        (_, Some(last)) if LINE_BREAKS.contains(&last) => 1,
+        _ => 0,
+    };
+    line.slice(..len - break_len)
+}
+
+/// Count the runs of non-whitespace in `chars`
+fn count_words(chars: impl Iterator<Item = char>) -> usize {
+    let mut count = 0;
+    let mut in_word = false;
+
+    for c in chars {
+        if c.is_whitespace() {
+            in_word = false;
+        } else if !in_word {
+            in_word = true;
+            count += 1;
+        }
+    }
+
+    count
+}
+
+/// The char `text` is, if it's a single one
+fn single_char(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+    chars.next().filter(|_| chars.next().is_none())
+}
+
+/// The text before each change that can be undone, and before each undone
+/// one that can be redone. Ropes share what they have in common, so keeping
+/// whole texts costs little more than keeping the edits.
+#[derive(Clone, PartialEq)]
+struct History {
+    undo: VecDeque<Arc<Rope>>,
+    redo: Vec<Arc<Rope>>,
+    /// How many changes `undo` keeps, dropping the oldest past that
+    limit: usize,
+    /// Where the last char was typed and which it was, while typing just
+    /// after it still adds to the same change
+    typing: Option<(usize, char)>,
+}
+
+impl History {
+    fn new(limit: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            limit,
+            typing: None,
+        }
+    }
+
+    /// Whether typing `c` at `pos` carries on the change typed before it.
+    /// A word starts a change of its own, along with the space before it.
+    fn continues_typing(&self, pos: usize, c: char) -> bool {
+        self.typing
+            .is_some_and(|(last_pos, last)| pos == last_pos + 1 && (!c.is_whitespace() || last.is_whitespace()))
+    }
+}
 
 #[derive(Clone, PartialEq)]
 pub struct Buffer {
     rope: Arc<Rope>,
+    /// The text as it was when loaded or last saved
+    saved: Arc<Rope>,
     modified: bool,
     filename: Option<String>,
+    encoding: BufferEncoding,
+    history: History,
+}
+
+impl Default for Buffer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Buffer {
     pub fn new() -> Self {
         Self {
             rope: Arc::new(Rope::new()),
+            saved: Arc::new(Rope::new()),
             modified: false,
             filename: None,
+            encoding: BufferEncoding::Utf8,
+            history: History::new(DEFAULT_HISTORY_LIMIT),
         }
     }
 
     pub fn from_str(content: &str, filename: Option<String>) -> Self {
+        let rope = Arc::new(Rope::from_str(content));
         Self {
-            rope: Arc::new(Rope::from_str(content)),
+            rope: rope.clone(),
+            saved: rope,
             modified: false,
             filename,
+            encoding: BufferEncoding::Utf8,
+            history: History::new(DEFAULT_HISTORY_LIMIT),
         }
     }
 
+    /// The same buffer, recorded as having been decoded with `encoding`
+    pub fn with_encoding(mut self, encoding: BufferEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// The same buffer, undoing at most `limit` changes back
+    pub fn with_history_limit(mut self, limit: usize) -> Self {
+        self.history.limit = limit;
+        while self.history.undo.len() > limit {
+            self.history.undo.pop_front();
+        }
+        self
+    }
+
+    /// Replace the text with `rope`, remembering the text before so the
+    /// change can be undone. `typed` is where a single char was inserted and
+    /// which, so typing can be undone a word at a time.
+    fn change(&mut self, rope: Rope, typed: Option<(usize, char)>) {
+        let history = &mut self.history;
+        let continues = typed.is_some_and(|(pos, c)| history.continues_typing(pos, c));
+        if !continues {
+            history.undo.push_back(self.rope.clone());
+            if history.undo.len() > history.limit {
+                history.undo.pop_front();
+            }
+        }
+        history.redo.clear();
+        history.typing = typed;
+
+        self.rope = Arc::new(rope);
+        self.modified = true;
+    }
+
+    /// Go back to `rope` from the history, which is modified if it isn't the saved text
+    fn restore(&mut self, rope: Arc<Rope>) {
+        self.modified = !Arc::ptr_eq(&rope, &self.saved) && *rope != *self.saved;
+        self.rope = rope;
+        self.history.typing = None;
+    }
+
+    /// Take back the last change, or the last word typed
+    pub fn undo(&mut self) -> Result<(), String> {
+        let rope = self.history.undo.pop_back().ok_or("Nothing to undo")?;
+        self.history.redo.push(self.rope.clone());
+        self.restore(rope);
+        Ok(())
+    }
+
+    /// Make the last undone change again
+    pub fn redo(&mut self) -> Result<(), String> {
+        let rope = self.history.redo.pop().ok_or("Nothing to redo")?;
+        self.history.undo.push_back(self.rope.clone());
+        self.restore(rope);
+        Ok(())
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.history.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.history.redo.is_empty()
+    }
+
     pub fn insert(&mut self, char_idx: usize, text: &str) -> Result<(), String> {
         if char_idx <= self.rope.len_chars() {
             let mut new_rope = (*self.rope).clone();
             new_rope.insert(char_idx, text);
-            self.rope = Arc::new(new_rope);
-            self.modified = true;
+            self.change(new_rope, single_char(text).map(|c| (char_idx, c)));
             Ok(())
         } else {
             Err("Character index out of bounds".to_string())
@@ -38,21 +194,243 @@ impl Buffer {
     }
 
     pub fn delete(&mut self, char_idx: usize, len: usize) -> Result<(), String> {
-        if char_idx + len <= self.rope.len_chars() {
+        let end = char_idx.checked_add(len).filter(|end| *end <= self.rope.len_chars());
+        if let Some(end) = end {
             let mut new_rope = (*self.rope).clone();
-            new_rope.remove(char_idx..(char_idx + len));
-            self.rope = Arc::new(new_rope);
-            self.modified = true;
+            new_rope.remove(char_idx..end);
+            self.change(new_rope, None);
             Ok(())
         } else {
             Err("Delete range out of bounds".to_string())
         }
     }
 
+    /// Make `edit`, such as one from a collaborator. An edit that changes
+    /// nothing, like an insert `Edit::transform` emptied because the text
+    /// around it was deleted, leaves the buffer and its history as they are.
+    pub fn apply(&mut self, edit: &Edit) -> Result<(), String> {
+        match edit {
+            Edit::Insert { pos, text } if text.is_empty() && *pos <= self.rope.len_chars() => Ok(()),
+            Edit::Delete { pos, len: 0 } if *pos <= self.rope.len_chars() => Ok(()),
+            Edit::Insert { pos, text } => self.insert(*pos, text),
+            Edit::Delete { pos, len } => self.delete(*pos, *len),
+        }
+    }
+
+    /// Replace the chars `start..end` with `text` as a single edit
+    pub fn replace_range(&mut self, start: usize, end: usize, text: &str) -> Result<(), String> {
+        if start <= end && end <= self.rope.len_chars() {
+            let mut new_rope = (*self.rope).clone();
+            new_rope.remove(start..end);
+            new_rope.insert(start, text);
+            self.change(new_rope, None);
+            Ok(())
+        } else {
+            Err("Replace range out of bounds".to_string())
+        }
+    }
+
+    /// The line break the text uses, going by its first line
+    fn line_break(&self) -> &'static str {
+        if self.rope.line(0).to_string().ends_with("\r\n") { "\r\n" } else { "\n" }
+    }
+
+    /// Char offset just past the end of line `idx`, before its line break
+    fn line_end(&self, idx: usize) -> usize {
+        self.rope.line_to_char(idx) + without_line_break(self.rope.line(idx)).len_chars()
+    }
+
+    /// Remove the spaces and tabs at the end of every line. Returns how many
+    /// lines changed.
+    pub fn trim_trailing_whitespace(&mut self) -> usize {
+        let mut new_rope = (*self.rope).clone();
+        let mut changed = 0;
+
+        // Going from the last line up keeps the earlier lines' offsets valid
+        for idx in (0..self.rope.len_lines()).rev() {
+            let line_start = self.rope.line_to_char(idx);
+            let end = self.line_end(idx);
+
+            let mut start = end;
+            while start > line_start && matches!(self.rope.char(start - 1), ' ' | '\t') {
+                start -= 1;
+            }
+
+            if start < end {
+                new_rope.remove(start..end);
+                changed += 1;
+            }
+        }
+
+        if changed > 0 {
+            self.change(new_rope, None);
+        }
+        changed
+    }
+
+    /// End the text with exactly one line break, dropping any blank lines
+    /// after the last one. Empty text is left empty. Returns whether anything changed.
+    pub fn ensure_final_newline(&mut self) -> bool {
+        let len = self.rope.len_chars();
+        let mut content_end = len;
+        while content_end > 0 && matches!(self.rope.char(content_end - 1), '\n' | '\r') {
+            content_end -= 1;
+        }
+        if content_end == 0 {
+            return false;
+        }
+
+        // Stick to the line breaks the text already uses
+        let line_break = match content_end < len {
+            true if self.rope.char(content_end) == '\r' => "\r\n",
+            true => "\n",
+            false => self.line_break(),
+        };
+        if self.rope.slice(content_end..) == line_break {
+            return false;
+        }
+
+        self.replace_range(content_end, len, line_break).is_ok()
+    }
+
+    /// Copy lines `start..=end` to just below `end`
+    pub fn duplicate_lines(&mut self, start: usize, end: usize) -> Result<(), String> {
+        if start > end || end >= self.rope.len_lines() {
+            return Err("Line range out of bounds".to_string());
+        }
+
+        // Inserting the break before the copy works for the last line too,
+        // which has no break of its own
+        let line_end = self.line_end(end);
+        let copy = format!("{}{}", self.line_break(), self.rope.slice(self.rope.line_to_char(start)..line_end));
+        self.insert(line_end, &copy)
+    }
+
+    /// Swap lines `start..=end` with the line above them, or below if `!up`.
+    /// Lines already at the top or bottom stay where they are.
+    pub fn move_lines(&mut self, start: usize, end: usize, up: bool) -> Result<(), String> {
+        if start > end || end >= self.rope.len_lines() {
+            return Err("Line range out of bounds".to_string());
+        }
+
+        let (first, last) = match up {
+            true if start == 0 => return Ok(()),
+            true => (start - 1, end),
+            false if end + 1 == self.rope.len_lines() => return Ok(()),
+            false => (start, end + 1),
+        };
+
+        let region_start = self.rope.line_to_char(first);
+        let region_end = self.line_end(last);
+
+        // The last line of the text has no break of its own, so the lines
+        // are joined back with the one the text uses
+        let mut lines: Vec<String> = (first..=last)
+            .map(|idx| without_line_break(self.rope.line(idx)).to_string())
+            .collect();
+        if up {
+            lines.rotate_left(1);
+        } else {
+            lines.rotate_right(1);
+        }
+
+        self.replace_range(region_start, region_end, &lines.join(self.line_break()))
+    }
+
+    /// Insert `text` at a 0-based line and column
+    pub fn insert_at_line_col(&mut self, line: usize, column: usize, text: &str) -> Result<(), String> {
+        let char_idx = self.char_idx_of(line, column).ok_or("Line or column out of bounds")?;
+        self.insert(char_idx, text)
+    }
+
+    /// Delete `len` chars starting at a 0-based line and column
+    pub fn delete_at_line_col(&mut self, line: usize, column: usize, len: usize) -> Result<(), String> {
+        let char_idx = self.char_idx_of(line, column).ok_or("Line or column out of bounds")?;
+        self.delete(char_idx, len)
+    }
+
+    /// Replace all the text, as the textarea has it after the user typed.
+    /// A single char typed adds to the word it's part of.
+    pub fn set_text(&mut self, text: &str) {
+        let typed = match Edit::diff(&self.text(), text).as_slice() {
+            [Edit::Insert { pos, text }] => single_char(text).map(|c| (*pos, c)),
+            _ => None,
+        };
+        self.change(Rope::from_str(text), typed);
+    }
+
     pub fn text(&self) -> String {
         self.rope.to_string()
     }
 
+    /// The text as it was when loaded or last saved
+    pub fn saved_text(&self) -> String {
+        self.saved.to_string()
+    }
+
+    /// Char offsets of every match of `needle`, without overlaps
+    pub fn find_all(&self, needle: &str) -> Vec<usize> {
+        search::find_all(&self.text(), needle)
+    }
+
+    /// Char offsets of every match of `needle` ignoring case, without overlaps
+    pub fn find_all_ci(&self, needle: &str) -> Vec<usize> {
+        search::find_all_ci(&self.text(), needle)
+    }
+
+    /// Replace every match of `needle` with `replacement` as a single change.
+    /// Returns how many were replaced.
+    pub fn replace_all(&mut self, needle: &str, replacement: &str) -> usize {
+        let matches = self.find_all(needle);
+        self.replace_matches(&matches, needle.chars().count(), replacement)
+    }
+
+    /// Like `replace_all`, but matching `needle` ignoring case
+    pub fn replace_all_ci(&mut self, needle: &str, replacement: &str) -> usize {
+        let matches = self.find_all_ci(needle);
+        self.replace_matches(&matches, needle.chars().count(), replacement)
+    }
+
+    /// Replace the `len` chars at each offset in `matches`, which mustn't overlap
+    fn replace_matches(&mut self, matches: &[usize], len: usize, replacement: &str) -> usize {
+        if matches.is_empty() {
+            return 0;
+        }
+
+        let mut new_rope = (*self.rope).clone();
+        // Going from the last match back keeps the earlier ones' offsets valid
+        for &pos in matches.iter().rev() {
+            new_rope.remove(pos..pos + len);
+            new_rope.insert(pos, replacement);
+        }
+        self.change(new_rope, None);
+        matches.len()
+    }
+
+    /// The longest line, how the lines are indented and the like
+    pub fn stats(&self) -> BufferStats {
+        BufferStats {
+            total_chars: self.char_count(),
+            ..stats::buffer_stats(self.lines())
+        }
+    }
+
+    pub fn char_count(&self) -> usize {
+        self.rope.len_chars()
+    }
+
+    /// Words are separated by Unicode whitespace
+    pub fn word_count(&self) -> usize {
+        count_words(self.rope.chars())
+    }
+
+    /// Words within the char range `start..end`, clamped to the buffer
+    pub fn word_count_in(&self, start: usize, end: usize) -> usize {
+        let end = end.min(self.rope.len_chars());
+        let start = start.min(end);
+        count_words(self.rope.slice(start..end).chars())
+    }
+
     pub fn line_count(&self) -> usize {
         self.rope.len_lines()
     }
@@ -65,11 +443,366 @@ impl Buffer {
         }
     }
 
+    /// Every line without its line break, borrowed from the buffer. Text
+    /// ending in a line break ends with an empty line, as `line_count` counts.
+    pub fn lines(&self) -> impl Iterator<Item = RopeSlice<'_>> {
+        self.rope.lines().map(without_line_break)
+    }
+
+    /// The text of lines `start..end`, each with its line break, clamped to the buffer
+    pub fn line_text_range(&self, start: usize, end: usize) -> String {
+        let end = end.min(self.rope.len_lines());
+        let start = start.min(end);
+        self.rope.slice(self.rope.line_to_char(start)..self.rope.line_to_char(end)).to_string()
+    }
+
+    /// How many chars line `idx` has before its line break
+    pub fn line_len_chars(&self, idx: usize) -> Option<usize> {
+        (idx < self.rope.len_lines()).then(|| without_line_break(self.rope.line(idx)).len_chars())
+    }
+
+    /// How many characters as the reader sees them, grapheme clusters such as
+    /// an emoji with its skin tone, come before char `column` of line `line`
+    pub fn grapheme_column(&self, line: usize, column: usize) -> usize {
+        let Some(line_len) = self.line_len_chars(line) else {
+            return column;
+        };
+        let start = self.rope.line_to_char(line);
+        self.rope.slice(start..start + column.min(line_len)).to_string().graphemes(true).count()
+    }
+
+    /// Char offset where line `idx` starts, clamped to the last line
+    pub fn line_start(&self, idx: usize) -> usize {
+        self.rope.line_to_char(idx.min(self.rope.len_lines().saturating_sub(1)))
+    }
+
+    /// The 0-based line and column of the char offset `char_idx`, clamped to the buffer
+    pub fn position_of(&self, char_idx: usize) -> (usize, usize) {
+        let char_idx = char_idx.min(self.rope.len_chars());
+        let line = self.rope.char_to_line(char_idx);
+        (line, char_idx - self.rope.line_to_char(line))
+    }
+
+    /// Char offset of a 0-based line and column. The column may be one past
+    /// the line's last char, but not beyond it or into the line break.
+    pub fn char_idx_of(&self, line: usize, column: usize) -> Option<usize> {
+        let line_len = self.line_len_chars(line)?;
+        (column <= line_len).then(|| self.rope.line_to_char(line) + column)
+    }
+
     pub fn filename(&self) -> Option<&String> {
         self.filename.as_ref()
     }
 
+    pub fn encoding(&self) -> BufferEncoding {
+        self.encoding
+    }
+
     pub fn is_modified(&self) -> bool {
         self.modified
     }
-}
\ No newline at end of file
+
+    /// Record that the buffer's text has been written out
+    pub fn mark_saved(&mut self) {
+        self.saved = self.rope.clone();
+        self.modified = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `text` typed into `buffer` a char at a time from `pos`
+    fn type_text(buffer: &mut Buffer, pos: usize, text: &str) {
+        for (offset, c) in text.chars().enumerate() {
+            buffer.insert(pos + offset, &c.to_string()).unwrap();
+        }
+    }
+
+    #[test]
+    fn undo_and_redo_edits() {
+        let mut buffer = Buffer::from_str("hello world", None);
+        buffer.insert(5, ",").unwrap();
+        buffer.delete(0, 1).unwrap();
+        assert_eq!(buffer.text(), "ello, world");
+
+        buffer.undo().unwrap();
+        assert_eq!(buffer.text(), "hello, world");
+        buffer.undo().unwrap();
+        assert_eq!(buffer.text(), "hello world");
+        assert!(buffer.undo().is_err());
+
+        buffer.redo().unwrap();
+        assert_eq!(buffer.text(), "hello, world");
+        buffer.redo().unwrap();
+        assert_eq!(buffer.text(), "ello, world");
+        assert!(buffer.redo().is_err());
+    }
+
+    #[test]
+    fn typing_a_word_undoes_at_once() {
+        let mut buffer = Buffer::from_str("", None);
+        type_text(&mut buffer, 0, "let value");
+        assert_eq!(buffer.text(), "let value");
+
+        buffer.undo().unwrap();
+        assert_eq!(buffer.text(), "let");
+        buffer.undo().unwrap();
+        assert_eq!(buffer.text(), "");
+        assert!(!buffer.can_undo());
+    }
+
+    #[test]
+    fn typing_somewhere_else_starts_a_new_change() {
+        let mut buffer = Buffer::from_str("ab", None);
+        type_text(&mut buffer, 2, "cd");
+        type_text(&mut buffer, 0, "xy");
+        assert_eq!(buffer.text(), "xyabcd");
+
+        buffer.undo().unwrap();
+        assert_eq!(buffer.text(), "abcd");
+    }
+
+    #[test]
+    fn undoing_back_to_the_saved_text_is_unmodified() {
+        let mut buffer = Buffer::from_str("fn main() {}", None);
+        buffer.insert(12, "\n").unwrap();
+        assert!(buffer.is_modified());
+
+        buffer.undo().unwrap();
+        assert_eq!(buffer.text(), "fn main() {}");
+        assert!(!buffer.is_modified());
+        buffer.redo().unwrap();
+        assert!(buffer.is_modified());
+    }
+
+    #[test]
+    fn a_new_edit_clears_redo() {
+        let mut buffer = Buffer::from_str("abc", None);
+        buffer.delete(2, 1).unwrap();
+        buffer.undo().unwrap();
+        buffer.insert(0, "z").unwrap();
+        assert!(!buffer.can_redo());
+    }
+
+    #[test]
+    fn history_is_capped() {
+        let mut buffer = Buffer::from_str("", None).with_history_limit(2);
+        for text in ["a ", "b ", "c "] {
+            let end = buffer.char_count();
+            buffer.insert(end, text).unwrap();
+        }
+
+        buffer.undo().unwrap();
+        buffer.undo().unwrap();
+        assert_eq!(buffer.text(), "a ");
+        assert!(buffer.undo().is_err());
+    }
+
+    #[test]
+    fn replaces_matches_at_the_start_middle_and_end() {
+        let mut buffer = Buffer::from_str("foo = foo + foo", None);
+        assert_eq!(buffer.replace_all("foo", "count"), 3);
+        assert_eq!(buffer.text(), "count = count + count");
+
+        assert_eq!(buffer.replace_all("count", "n"), 3);
+        assert_eq!(buffer.text(), "n = n + n");
+    }
+
+    #[test]
+    fn replacing_nothing_leaves_the_buffer_alone() {
+        let mut buffer = Buffer::from_str("hello", None);
+        assert_eq!(buffer.replace_all("bye", "hi"), 0);
+        assert_eq!(buffer.text(), "hello");
+        assert!(!buffer.is_modified());
+        assert!(!buffer.can_undo());
+    }
+
+    #[test]
+    fn replacing_with_nothing_removes_matches() {
+        let mut buffer = Buffer::from_str("a, b, c", None);
+        assert_eq!(buffer.replace_all(", ", ""), 2);
+        assert_eq!(buffer.text(), "abc");
+    }
+
+    #[test]
+    fn overlapping_matches_are_replaced_once() {
+        let mut buffer = Buffer::from_str("aaa", None);
+        assert_eq!(buffer.replace_all("aa", "b"), 1);
+        assert_eq!(buffer.text(), "ba");
+    }
+
+    #[test]
+    fn replaces_ignoring_case_in_one_undo() {
+        let mut buffer = Buffer::from_str("Foo foo FOO", None);
+        assert_eq!(buffer.find_all_ci("foo"), [0, 4, 8]);
+        assert_eq!(buffer.replace_all_ci("foo", "bar"), 3);
+        assert_eq!(buffer.text(), "bar bar bar");
+
+        buffer.undo().unwrap();
+        assert_eq!(buffer.text(), "Foo foo FOO");
+    }
+
+    #[test]
+    fn editing_marks_the_buffer_modified_until_saved() {
+        let mut buffer = Buffer::from_str("draft", None);
+        assert!(!buffer.is_modified());
+
+        buffer.set_text("draft 2");
+        assert!(buffer.is_modified());
+
+        buffer.mark_saved();
+        assert!(!buffer.is_modified());
+        assert_eq!(buffer.saved_text(), "draft 2");
+    }
+
+    #[test]
+    fn converts_lines_and_columns_to_offsets() {
+        let buffer = Buffer::from_str("ab\ncde\n", None);
+        assert_eq!(buffer.char_idx_of(0, 0), Some(0));
+        assert_eq!(buffer.char_idx_of(0, 2), Some(2));
+        assert_eq!(buffer.char_idx_of(1, 3), Some(6));
+        assert_eq!(buffer.char_idx_of(2, 0), Some(7));
+    }
+
+    #[test]
+    fn columns_past_the_end_of_a_line_are_out_of_bounds() {
+        let mut buffer = Buffer::from_str("ab\r\ncde", None);
+        assert_eq!(buffer.char_idx_of(0, 3), None);
+        assert_eq!(buffer.char_idx_of(1, 4), None);
+        assert!(buffer.insert_at_line_col(0, 3, "x").is_err());
+        assert!(buffer.delete_at_line_col(1, 4, 1).is_err());
+        assert_eq!(buffer.text(), "ab\r\ncde");
+    }
+
+    #[test]
+    fn lines_past_the_end_of_the_file_are_out_of_bounds() {
+        let mut buffer = Buffer::from_str("ab\ncd", None);
+        assert_eq!(buffer.char_idx_of(2, 0), None);
+        assert!(buffer.insert_at_line_col(5, 0, "x").is_err());
+        assert!(buffer.delete_at_line_col(2, 0, 1).is_err());
+    }
+
+    #[test]
+    fn edits_at_lines_and_columns() {
+        let mut buffer = Buffer::from_str("fn main() {\n}", None);
+        buffer.insert_at_line_col(1, 0, "    body();\n").unwrap();
+        assert_eq!(buffer.text(), "fn main() {\n    body();\n}");
+
+        buffer.delete_at_line_col(1, 0, 4).unwrap();
+        assert_eq!(buffer.text(), "fn main() {\nbody();\n}");
+        assert!(buffer.delete_at_line_col(2, 0, 2).is_err());
+    }
+
+    #[test]
+    fn replaces_ranges_at_the_start_middle_and_end() {
+        let mut buffer = Buffer::from_str("let x = 1;", None);
+        buffer.replace_range(0, 3, "const").unwrap();
+        assert_eq!(buffer.text(), "const x = 1;");
+
+        buffer.replace_range(6, 7, "LIMIT").unwrap();
+        assert_eq!(buffer.text(), "const LIMIT = 1;");
+
+        buffer.replace_range(14, 16, "10;").unwrap();
+        assert_eq!(buffer.text(), "const LIMIT = 10;");
+        assert!(buffer.is_modified());
+    }
+
+    #[test]
+    fn a_replacement_undoes_in_one_step() {
+        let mut buffer = Buffer::from_str("hello world", None);
+        buffer.replace_range(6, 11, "there").unwrap();
+        buffer.undo().unwrap();
+        assert_eq!(buffer.text(), "hello world");
+        assert!(!buffer.can_undo());
+    }
+
+    #[test]
+    fn out_of_bounds_ranges_are_not_replaced() {
+        let mut buffer = Buffer::from_str("abc", None);
+        assert!(buffer.replace_range(2, 4, "x").is_err());
+        assert!(buffer.replace_range(2, 1, "x").is_err());
+        assert_eq!(buffer.text(), "abc");
+        assert!(!buffer.is_modified());
+    }
+
+    #[test]
+    fn lines_drop_their_line_breaks() {
+        let buffer = Buffer::from_str("one\r\ntwo\nthree", None);
+        let lines: Vec<String> = buffer.lines().map(String::from).collect();
+        assert_eq!(lines, ["one", "two", "three"]);
+        assert_eq!(buffer.line_count(), 3);
+    }
+
+    #[test]
+    fn trailing_newline_ends_with_an_empty_line() {
+        let buffer = Buffer::from_str("one\ntwo\n", None);
+        let lines: Vec<String> = buffer.lines().map(String::from).collect();
+        assert_eq!(lines, ["one", "two", ""]);
+        assert_eq!(buffer.line_count(), 3);
+        assert_eq!(buffer.line_len_chars(2), Some(0));
+    }
+
+    #[test]
+    fn line_lengths_leave_out_the_line_break() {
+        let buffer = Buffer::from_str("héllo\r\nab", None);
+        assert_eq!(buffer.line_len_chars(0), Some(5));
+        assert_eq!(buffer.line_len_chars(1), Some(2));
+        assert_eq!(buffer.line_len_chars(2), None);
+    }
+
+    #[test]
+    fn line_ranges_keep_line_breaks_and_clamp() {
+        let buffer = Buffer::from_str("a\nb\nc", None);
+        assert_eq!(buffer.line_text_range(0, 2), "a\nb\n");
+        assert_eq!(buffer.line_text_range(1, 10), "b\nc");
+        assert_eq!(buffer.line_text_range(2, 1), "");
+        assert_eq!(buffer.line_text_range(5, 9), "");
+
+        let buffer = Buffer::from_str("a\nb\n", None);
+        assert_eq!(buffer.line_text_range(0, 3), "a\nb\n");
+    }
+
+    #[test]
+    fn grapheme_columns_count_what_the_reader_sees() {
+        // 👍 is two UTF-16 units and the toned one two chars
+        let buffer = Buffer::from_str("👍x\n👍🏽é", None);
+        assert_eq!(buffer.grapheme_column(0, 1), 1);
+        assert_eq!(buffer.grapheme_column(0, 2), 2);
+        assert_eq!(buffer.grapheme_column(1, 2), 1);
+        assert_eq!(buffer.grapheme_column(1, 3), 2);
+    }
+
+    #[test]
+    fn grapheme_columns_clamp_to_the_line() {
+        let buffer = Buffer::from_str("ab\ncd", None);
+        assert_eq!(buffer.grapheme_column(0, 9), 2);
+        assert_eq!(buffer.grapheme_column(7, 4), 4);
+    }
+
+    #[test]
+    fn moving_lines_swaps_them_with_their_neighbour() {
+        let mut buffer = Buffer::from_str("a\nb\nc\n", None);
+        buffer.move_lines(0, 1, false).unwrap();
+        assert_eq!(buffer.text(), "c\na\nb\n");
+        buffer.move_lines(0, 0, true).unwrap();
+        assert_eq!(buffer.text(), "c\na\nb\n");
+    }
+
+    #[test]
+    fn moving_the_last_crlf_line_up_keeps_crlf() {
+        let mut buffer = Buffer::from_str("a\r\nb\r\nc", None);
+        buffer.move_lines(2, 2, true).unwrap();
+        assert_eq!(buffer.text(), "a\r\nc\r\nb");
+        buffer.move_lines(1, 1, false).unwrap();
+        assert_eq!(buffer.text(), "a\r\nb\r\nc");
+    }
+
+    #[test]
+    fn duplicating_the_last_crlf_line_keeps_crlf() {
+        let mut buffer = Buffer::from_str("a\r\nb", None);
+        buffer.duplicate_lines(1, 1).unwrap();
+        assert_eq!(buffer.text(), "a\r\nb\r\nb");
+    }
+}
@@ -0,0 +1,4 @@
+/// Whether `c` can be part of a word or identifier
+pub fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
@@ -0,0 +1,81 @@
+use super::indent_rules::selected_lines;
+
+/// `lines` commented out with `prefix`, or uncommented if every one already
+/// is. The prefix goes after each line's indentation with a space after it,
+/// and comes out again along with that space. Blank lines are left as they
+/// are and don't count towards whether the lines are commented.
+pub fn toggle_comment(lines: &[String], prefix: &str) -> Vec<String> {
+    let is_blank = |line: &String| line.trim().is_empty();
+    let commented = lines
+        .iter()
+        .filter(|line| !is_blank(line))
+        .all(|line| line.trim_start().starts_with(prefix));
+
+    lines
+        .iter()
+        .map(|line| {
+            if is_blank(line) {
+                return line.clone();
+            }
+            let (indent, rest) = line.split_at(line.len() - line.trim_start().len());
+            if commented {
+                let uncommented = &rest[prefix.len()..];
+                format!("{indent}{}", uncommented.strip_prefix(' ').unwrap_or(uncommented))
+            } else {
+                format!("{indent}{prefix} {rest}")
+            }
+        })
+        .collect()
+}
+
+/// Every line the selection from `start` to `end` touches commented or
+/// uncommented with `toggle_comment`, as the char range of `text` the lines
+/// cover and what replaces it
+pub fn toggle_comment_lines(text: &str, start: usize, end: usize, prefix: &str) -> (usize, usize, String) {
+    let chars: Vec<char> = text.chars().collect();
+    let (first, last) = selected_lines(&chars, start, end);
+    let lines: Vec<String> = chars[first..last].iter().collect::<String>().split('\n').map(str::to_string).collect();
+    (first, last, toggle_comment(&lines, prefix).join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.split('\n').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn comments_after_the_indentation() {
+        let toggled = toggle_comment(&lines("fn f() {\n    a();\n}"), "//");
+        assert_eq!(toggled, ["// fn f() {", "    // a();", "// }"]);
+    }
+
+    #[test]
+    fn uncomments_when_every_line_is_commented() {
+        let toggled = toggle_comment(&lines("# a\n    #b\n#  c"), "#");
+        assert_eq!(toggled, ["a", "    b", " c"]);
+    }
+
+    #[test]
+    fn mixed_lines_are_all_commented() {
+        let toggled = toggle_comment(&lines("// done\ntodo()"), "//");
+        assert_eq!(toggled, ["// // done", "// todo()"]);
+        assert_eq!(toggle_comment(&toggled, "//"), ["// done", "todo()"]);
+    }
+
+    #[test]
+    fn blank_lines_are_left_alone() {
+        let toggled = toggle_comment(&lines("a\n   \nb"), "--");
+        assert_eq!(toggled, ["-- a", "   ", "-- b"]);
+        assert_eq!(toggle_comment(&toggled, "--"), ["a", "   ", "b"]);
+    }
+
+    #[test]
+    fn toggles_the_lines_a_selection_touches() {
+        let text = "one\ntwo\nthree";
+        assert_eq!(toggle_comment_lines(text, 5, 9, "//"), (4, 13, "// two\n// three".to_string()));
+        assert_eq!(toggle_comment_lines(text, 1, 1, "//"), (0, 3, "// one".to_string()));
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use super::chars::is_word_char;
+
+/// Words shorter than this aren't worth offering
+const MIN_WORD_LEN: usize = 3;
+
+/// How much of a word has to be typed before it's completed
+const MIN_PREFIX_LEN: usize = 2;
+
+/// The most suggestions offered at once
+pub const MAX_COMPLETIONS: usize = 8;
+
+/// Every distinct word in a text, most used first, to complete what's being
+/// typed from
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WordIndex {
+    words: Vec<String>,
+}
+
+impl WordIndex {
+    pub fn new(text: &str) -> Self {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for word in text.split(|c: char| !is_word_char(c)) {
+            if word.chars().count() >= MIN_WORD_LEN && !word.starts_with(|c: char| c.is_ascii_digit()) {
+                *counts.entry(word).or_default() += 1;
+            }
+        }
+
+        let mut words: Vec<(&str, usize)> = counts.into_iter().collect();
+        words.sort_by(|(word, count), (other, other_count)| other_count.cmp(count).then(word.cmp(other)));
+
+        Self {
+            words: words.into_iter().map(|(word, _)| word.to_string()).collect(),
+        }
+    }
+
+    /// Up to `MAX_COMPLETIONS` words longer than `prefix` that start with it,
+    /// ignoring case. Words matching its case come first.
+    pub fn completions(&self, prefix: &str) -> Vec<String> {
+        let prefix_len = prefix.chars().count();
+        let lowercase = prefix.to_lowercase();
+        let (same_case, other_case): (Vec<&String>, Vec<&String>) = self
+            .words
+            .iter()
+            .filter(|word| word.chars().count() > prefix_len && word.to_lowercase().starts_with(&lowercase))
+            .partition(|word| word.starts_with(prefix));
+
+        same_case.into_iter().chain(other_case).take(MAX_COMPLETIONS).cloned().collect()
+    }
+}
+
+/// The characters that open strings in `language`
+fn string_quotes(language: &str) -> &'static [char] {
+    match language {
+        "javascript" | "go" => &['"', '\'', '`'],
+        "python" | "shell" | "c" | "cpp" | "toml" | "yaml" => &['"', '\''],
+        "markdown" | "plain" => &[],
+        // Rust's single quotes are just as often lifetimes
+        _ => &['"'],
+    }
+}
+
+/// What starts a comment running to the end of the line in `language`, if it has them
+fn line_comment(language: &str) -> Option<&'static str> {
+    match language {
+        "python" | "shell" | "toml" | "yaml" => Some("#"),
+        "json" | "markdown" | "plain" | "html" | "css" => None,
+        _ => Some("//"),
+    }
+}
+
+/// Whether the end of `line`, the start of a line up to some point, is in a
+/// string or comment. Strings and comments begun on earlier lines aren't seen.
+fn in_string_or_comment(line: &[char], language: &str) -> bool {
+    let quotes = string_quotes(language);
+    let comment: Option<Vec<char>> = line_comment(language).map(|prefix| prefix.chars().collect());
+
+    let mut quote = None;
+    let mut idx = 0;
+    while idx < line.len() {
+        let c = line[idx];
+        match quote {
+            // Skip whatever's escaped, which may be the quote
+            Some(_) if c == '\\' => idx += 1,
+            Some(open) if c == open => quote = None,
+            Some(_) => {},
+            None if quotes.contains(&c) => quote = Some(c),
+            None if comment.as_ref().is_some_and(|prefix| line[idx..].starts_with(prefix)) => return true,
+            None => {},
+        }
+        idx += 1;
+    }
+    quote.is_some()
+}
+
+/// The word being typed at the char offset `caret` in `text`, as the offset
+/// it starts at and what's been typed of it. None unless the caret is at the
+/// end of at least `MIN_PREFIX_LEN` word characters, outside strings and comments.
+pub fn word_before(text: &str, caret: usize, language: &str) -> Option<(usize, String)> {
+    let caret_byte = text.char_indices().nth(caret).map(|(idx, _)| idx).unwrap_or(text.len());
+    let (before, after) = text.split_at(caret_byte);
+
+    // Completing in the middle of a word would split it
+    if after.starts_with(is_word_char) {
+        return None;
+    }
+
+    let line: Vec<char> = before[before.rfind('\n').map(|idx| idx + 1).unwrap_or(0)..].chars().collect();
+    let word_len = line.iter().rev().take_while(|c| is_word_char(**c)).count();
+    let word_start = line.len() - word_len;
+    if word_len < MIN_PREFIX_LEN || line[word_start].is_ascii_digit() || in_string_or_comment(&line[..word_start], language) {
+        return None;
+    }
+
+    Some((caret - word_len, line[word_start..].iter().collect()))
+}
@@ -1,6 +1,50 @@
+/// A place in the text. Everything is counted in chars, like the buffer
+/// counts them, never in the UTF-16 units the textarea uses.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct CursorPosition {
     pub offset: usize,
+    /// 0-based
     pub line: usize,
+    /// Chars from the start of the line
     pub column: usize,
+}
+
+/// A selected range of text. The anchor is where the selection started and
+/// the head is where the caret is, so the head may come before the anchor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Selection {
+    pub anchor: CursorPosition,
+    pub head: CursorPosition,
+}
+
+impl Selection {
+    /// An empty selection sitting at `position`
+    pub fn caret(position: CursorPosition) -> Self {
+        Self {
+            anchor: position,
+            head: position,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.anchor.offset == self.head.offset
+    }
+
+    /// The same selection with the anchor guaranteed to come first
+    pub fn normalized(&self) -> Self {
+        if self.anchor.offset <= self.head.offset {
+            *self
+        } else {
+            Self {
+                anchor: self.head,
+                head: self.anchor,
+            }
+        }
+    }
+
+    /// The selected `(start, end)` char offsets, start inclusive and end exclusive
+    pub fn char_range(&self) -> (usize, usize) {
+        let normalized = self.normalized();
+        (normalized.anchor.offset, normalized.head.offset)
+    }
 }
\ No newline at end of file
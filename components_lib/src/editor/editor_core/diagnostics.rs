@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+use super::ValidationError;
+
+/// How serious a diagnostic is. Errors sort first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+impl Severity {
+    /// The name it goes by in diagnostics' JSON and the highlighter's classes
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        }
+    }
+}
+
+fn one() -> usize {
+    1
+}
+
+/// Something a linter found in a document, as the backend's
+/// `/api/lint/{language}` route reports it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// 0-based line and column, in chars, of the first char at fault
+    pub line: usize,
+    pub col: usize,
+    /// How many chars from there on the same line are at fault
+    #[serde(default = "one")]
+    pub len: usize,
+    pub severity: Severity,
+    pub message: String,
+    /// The linter that found it, such as `rustc`
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+impl From<ValidationError> for Diagnostic {
+    fn from(error: ValidationError) -> Self {
+        Self {
+            line: error.line,
+            col: error.column,
+            len: error.len,
+            severity: Severity::Error,
+            message: error.message,
+            source: None,
+        }
+    }
+}
@@ -0,0 +1,129 @@
+/// Past this many changed lines the diff stops looking for the shortest edit
+/// and reports the rest as replaced, so a rewritten file can't stall the editor
+const MAX_EDIT_DISTANCE: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    Unchanged,
+    Added,
+    Removed,
+}
+
+/// One line of a diff, without its line break
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+fn split_lines(text: &str) -> Vec<&str> {
+    if text.is_empty() {
+        Vec::new()
+    } else {
+        text.split('\n').collect()
+    }
+}
+
+/// The lines of `old` and `new` interleaved so that dropping the added lines
+/// gives `old` and dropping the removed ones gives `new`, keeping as many
+/// lines unchanged as possible
+pub fn line_diff(old: &str, new: &str) -> Vec<DiffLine> {
+    let old = split_lines(old);
+    let new = split_lines(new);
+
+    // Edits are usually small, so only the lines between the common prefix
+    // and suffix need comparing
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let line = |kind, text: &&str| DiffLine { kind, text: text.to_string() };
+    let mut diff: Vec<DiffLine> = old[..prefix].iter().map(|text| line(DiffKind::Unchanged, text)).collect();
+
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+    match shortest_edit(old_middle, new_middle) {
+        Some(middle) => diff.extend(middle.into_iter().map(|(kind, text)| line(kind, &text))),
+        None => {
+            diff.extend(old_middle.iter().map(|text| line(DiffKind::Removed, text)));
+            diff.extend(new_middle.iter().map(|text| line(DiffKind::Added, text)));
+        },
+    }
+
+    diff.extend(old[old.len() - suffix..].iter().map(|text| line(DiffKind::Unchanged, text)));
+    diff
+}
+
+/// Myers' diff: the longest common subsequence of `a` and `b`, found by
+/// trying ever longer edit scripts. `None` once more than
+/// `MAX_EDIT_DISTANCE` lines would have to change.
+fn shortest_edit<'a>(a: &[&'a str], b: &[&'a str]) -> Option<Vec<(DiffKind, &'a str)>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (n + m) as usize;
+    let offset = max as isize + 1;
+
+    // `v[offset + k]` is the furthest x reached on diagonal k = x - y. Before
+    // each round the diagonals it can read are saved for the walk back.
+    let mut v = vec![0isize; 2 * max + 3];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    'search: for d in 0..=max as isize {
+        if d as usize > MAX_EDIT_DISTANCE {
+            return None;
+        }
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+
+        for k in (-d..=d).step_by(2) {
+            let down = k == -d || (k != d && v[(offset + k - 1) as usize] < v[(offset + k + 1) as usize]);
+            let mut x = if down { v[(offset + k + 1) as usize] } else { v[(offset + k - 1) as usize] + 1 };
+            let mut y = x - k;
+
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[(offset + k) as usize] = x;
+
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    // Walk back from the end, one edit per round
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, saved) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let at = |k: isize| saved[(k + d + 1) as usize];
+        let k = x - y;
+
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) { k + 1 } else { k - 1 };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            edits.push((DiffKind::Unchanged, a[x as usize]));
+        }
+
+        if d > 0 {
+            if x == prev_x {
+                edits.push((DiffKind::Added, b[prev_y as usize]));
+            } else {
+                edits.push((DiffKind::Removed, a[prev_x as usize]));
+            }
+        }
+
+        x = prev_x;
+        y = prev_y;
+    }
+
+    edits.reverse();
+    Some(edits)
+}
@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+
+/// One change to a document's text, with positions in chars
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Edit {
+    Insert { pos: usize, text: String },
+    Delete { pos: usize, len: usize },
+}
+
+impl Edit {
+    /// The edits turning `old` into `new`: the changed middle deleted, then
+    /// its replacement inserted. Empty if the two are the same.
+    pub fn diff(old: &str, new: &str) -> Vec<Edit> {
+        let old: Vec<char> = old.chars().collect();
+        let new: Vec<char> = new.chars().collect();
+
+        let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        let mut edits = Vec::new();
+        if old.len() - suffix > prefix {
+            edits.push(Edit::Delete { pos: prefix, len: old.len() - suffix - prefix });
+        }
+        if new.len() - suffix > prefix {
+            edits.push(Edit::Insert { pos: prefix, text: new[prefix..new.len() - suffix].iter().collect() });
+        }
+        edits
+    }
+
+    /// This edit rewritten to apply after `other`, where both were made to the
+    /// same text. Applying either edit and then the other transformed gives the
+    /// same text. When both insert at the same place, the text that sorts first
+    /// goes first, so every client puts them in the same order without knowing
+    /// which edit the server took first. Text inserted inside a range the other edit deletes is
+    /// deleted along with it. Positions saturate rather than overflow, so an
+    /// edit too far out to apply stays out of range instead of wrapping around.
+    pub fn transform(&self, other: &Edit) -> Edit {
+        match (self, other) {
+            (Edit::Insert { pos, text }, Edit::Insert { pos: other_pos, text: other_text }) => {
+                let before = (pos, text) < (other_pos, other_text);
+                let pos = if before { *pos } else { pos.saturating_add(other_text.chars().count()) };
+                Edit::Insert { pos, text: text.clone() }
+            },
+            (Edit::Insert { pos, text }, Edit::Delete { pos: del_pos, len }) => {
+                if pos <= del_pos {
+                    self.clone()
+                } else if *pos >= del_pos.saturating_add(*len) {
+                    Edit::Insert { pos: pos - len, text: text.clone() }
+                } else {
+                    Edit::Insert { pos: *del_pos, text: String::new() }
+                }
+            },
+            (Edit::Delete { pos, len }, Edit::Insert { pos: ins_pos, text }) => {
+                let inserted = text.chars().count();
+                if ins_pos <= pos {
+                    Edit::Delete { pos: pos.saturating_add(inserted), len: *len }
+                } else if *ins_pos >= pos.saturating_add(*len) {
+                    self.clone()
+                } else {
+                    Edit::Delete { pos: *pos, len: len.saturating_add(inserted) }
+                }
+            },
+            (Edit::Delete { pos, len }, Edit::Delete { pos: other_pos, len: other_len }) => {
+                let (end, other_end) = (pos.saturating_add(*len), other_pos.saturating_add(*other_len));
+                if end <= *other_pos {
+                    self.clone()
+                } else if *pos >= other_end {
+                    Edit::Delete { pos: pos - other_len, len: *len }
+                } else {
+                    // Whatever the other edit already deleted is gone
+                    let overlap = end.min(other_end) - pos.max(other_pos);
+                    Edit::Delete { pos: *pos.min(other_pos), len: len - overlap }
+                }
+            },
+        }
+    }
+
+    /// Where the char offset `offset` ends up after this edit. A caret right
+    /// where text is inserted stays in front of it, and one inside deleted
+    /// text moves to where it was.
+    pub fn transform_offset(&self, offset: usize) -> usize {
+        match self {
+            Edit::Insert { pos, text } if offset > *pos => offset + text.chars().count(),
+            Edit::Delete { pos, len } if offset >= pos.saturating_add(*len) => offset - len,
+            Edit::Delete { pos, .. } if offset > *pos => *pos,
+            _ => offset,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::editor_core::Buffer;
+
+    fn insert(pos: usize, text: &str) -> Edit {
+        Edit::Insert { pos, text: text.to_string() }
+    }
+
+    fn delete(pos: usize, len: usize) -> Edit {
+        Edit::Delete { pos, len }
+    }
+
+    fn applied(text: &str, edits: &[&Edit]) -> String {
+        let mut buffer = Buffer::from_str(text, None);
+        for edit in edits {
+            buffer.apply(edit).unwrap();
+        }
+        buffer.text()
+    }
+
+    /// `a` and `b` made to `text` at once end up as `expected` whichever is
+    /// applied first
+    fn assert_converges(text: &str, a: &Edit, b: &Edit, expected: &str) {
+        let a_then_b = applied(text, &[a, &b.transform(a)]);
+        let b_then_a = applied(text, &[b, &a.transform(b)]);
+        assert_eq!(a_then_b, expected, "{a:?} then {b:?}");
+        assert_eq!(b_then_a, expected, "{b:?} then {a:?}");
+    }
+
+    #[test]
+    fn concurrent_inserts_converge() {
+        assert_converges("hello world", &insert(0, ">> "), &insert(11, "!"), ">> hello world!");
+        assert_converges("hello world", &insert(5, ","), &insert(6, "big "), "hello, big world");
+    }
+
+    #[test]
+    fn inserts_at_the_same_place_go_in_text_order() {
+        assert_converges("ab", &insert(1, "X"), &insert(1, "Y"), "aXYb");
+        assert_converges("ab", &insert(1, "Y"), &insert(1, "X"), "aXYb");
+        assert_converges("ab", &insert(1, "X"), &insert(1, "X"), "aXXb");
+    }
+
+    #[test]
+    fn non_overlapping_edits_converge() {
+        assert_converges("one two three", &insert(0, "zero "), &delete(7, 6), "zero one two");
+        assert_converges("one two three", &delete(0, 4), &delete(7, 6), "two");
+        assert_converges("one two three", &insert(3, ","), &insert(7, ","), "one, two, three");
+    }
+
+    #[test]
+    fn concurrent_insert_and_delete_converge() {
+        assert_converges("hello world", &insert(0, "oh "), &delete(5, 6), "oh hello");
+        assert_converges("hello world", &insert(11, "!"), &delete(0, 6), "world!");
+        assert_converges("hello world", &delete(0, 6), &insert(6, "big "), "big world");
+        // Text typed inside a deleted range goes with it
+        assert_converges("hello world", &insert(3, "XYZ"), &delete(1, 6), "horld");
+        assert_converges("hello world", &delete(1, 6), &insert(3, "XYZ"), "horld");
+    }
+
+    #[test]
+    fn concurrent_deletes_converge() {
+        assert_converges("hello world", &delete(0, 6), &delete(10, 1), "worl");
+        assert_converges("hello world", &delete(2, 5), &delete(4, 5), "held");
+        assert_converges("hello world", &delete(0, 11), &delete(3, 2), "");
+        assert_converges("hello world", &delete(3, 2), &delete(3, 2), "hel world");
+    }
+
+    #[test]
+    fn carets_move_with_edits_before_them() {
+        assert_eq!(insert(2, "abc").transform_offset(5), 8);
+        assert_eq!(insert(5, "abc").transform_offset(5), 5);
+        assert_eq!(delete(2, 3).transform_offset(8), 5);
+        assert_eq!(delete(2, 3).transform_offset(5), 2);
+        assert_eq!(delete(2, 3).transform_offset(1), 1);
+    }
+
+    #[test]
+    fn carets_inside_deleted_text_move_to_its_start() {
+        assert_eq!(delete(0, 10).transform_offset(3), 0);
+        assert_eq!(delete(4, 10).transform_offset(6), 4);
+        assert_eq!(delete(4, 10).transform_offset(4), 4);
+    }
+
+    #[test]
+    fn huge_edits_transform_without_overflowing() {
+        assert_eq!(delete(1, usize::MAX).transform_offset(5), 1);
+        assert_eq!(delete(5, 1).transform(&delete(1, usize::MAX)), delete(1, 0));
+        assert!(matches!(insert(3, "x").transform(&delete(1, usize::MAX)), Edit::Insert { pos: 1, .. }));
+    }
+}
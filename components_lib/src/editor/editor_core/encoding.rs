@@ -0,0 +1,38 @@
+/// How a buffer's text was decoded when it was opened
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BufferEncoding {
+    #[default]
+    Utf8,
+    /// UTF-8 with the invalid bytes replaced by U+FFFD
+    Utf8Lossy,
+    Latin1,
+}
+
+impl BufferEncoding {
+    pub fn label(&self) -> &'static str {
+        match self {
+            BufferEncoding::Utf8 => "UTF-8",
+            BufferEncoding::Utf8Lossy => "UTF-8 (invalid bytes replaced)",
+            BufferEncoding::Latin1 => "Latin-1",
+        }
+    }
+}
+
+/// Whether `bytes` look like a binary file rather than text. Like git, any NUL
+/// byte near the start of the file counts.
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes.iter().take(8000).any(|byte| *byte == 0)
+}
+
+/// Decode `bytes` as UTF-8, replacing anything invalid
+pub fn decode_utf8(bytes: &[u8]) -> (String, BufferEncoding) {
+    match String::from_utf8(bytes.to_vec()) {
+        Ok(text) => (text, BufferEncoding::Utf8),
+        Err(_) => (String::from_utf8_lossy(bytes).into_owned(), BufferEncoding::Utf8Lossy),
+    }
+}
+
+/// Decode `bytes` as Latin-1, which maps every byte straight to a char
+pub fn decode_latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| char::from(*byte)).collect()
+}
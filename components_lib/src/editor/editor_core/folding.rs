@@ -0,0 +1,267 @@
+use std::collections::BTreeSet;
+
+/// What stands in for the hidden part of a folded region
+pub const FOLD_PLACEHOLDER: &str = " … ";
+
+/// Languages whose blocks are marked by indentation rather than braces
+const INDENT_LANGUAGES: &[&str] = &["python", "yaml"];
+
+/// A block spanning several lines that can be folded away
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FoldRegion {
+    pub start_line: usize,
+    pub end_line: usize,
+    /// The char range hidden while the region is folded
+    pub hidden_start: usize,
+    pub hidden_end: usize,
+}
+
+/// Every foldable region of `text`, ordered by start line. Only the outermost
+/// region starting on a line is kept, since folds are keyed by start line.
+pub fn fold_regions(text: &str, language: &str) -> Vec<FoldRegion> {
+    let mut regions = if INDENT_LANGUAGES.contains(&language) {
+        indent_regions(text, language)
+    } else {
+        brace_regions(text)
+    };
+
+    regions.sort_by_key(|region| (region.start_line, std::cmp::Reverse(region.end_line)));
+    regions.dedup_by_key(|region| region.start_line);
+    regions
+}
+
+/// `{ … }` blocks whose braces are on different lines. Everything between the
+/// braces is hidden.
+fn brace_regions(text: &str) -> Vec<FoldRegion> {
+    let mut regions = Vec::new();
+    let mut open = Vec::new();
+    let mut line = 0;
+
+    for (idx, c) in text.chars().enumerate() {
+        match c {
+            '\n' => line += 1,
+            '{' => open.push((idx, line)),
+            '}' => {
+                if let Some((open_idx, open_line)) = open.pop()
+                    && line > open_line
+                {
+                    regions.push(FoldRegion {
+                        start_line: open_line,
+                        end_line: line,
+                        hidden_start: open_idx + 1,
+                        hidden_end: idx,
+                    });
+                }
+            },
+            _ => {},
+        }
+    }
+
+    regions
+}
+
+/// Lines followed by more deeply indented ones. In Python only lines ending
+/// with `:` open a block. The indented lines are hidden.
+fn indent_regions(text: &str, language: &str) -> Vec<FoldRegion> {
+    let indent_of = |line: &str| line.chars().take_while(|c| *c == ' ' || *c == '\t').count();
+
+    // (line, indent, char offset of the line's end) for every non-blank line
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for (idx, line) in text.split('\n').enumerate() {
+        let len = line.chars().count();
+        if !line.trim().is_empty() {
+            lines.push((idx, indent_of(line), offset + len, line.trim_end()));
+        }
+        offset += len + 1;
+    }
+
+    let mut regions = Vec::new();
+    for (pos, &(start_line, indent, line_end, content)) in lines.iter().enumerate() {
+        if language == "python" && !content.ends_with(':') {
+            continue;
+        }
+
+        let block = lines[pos + 1..].iter().take_while(|(_, child_indent, _, _)| *child_indent > indent);
+        if let Some(&(end_line, _, block_end, _)) = block.last() {
+            regions.push(FoldRegion {
+                start_line,
+                end_line,
+                hidden_start: line_end,
+                hidden_end: block_end,
+            });
+        }
+    }
+
+    regions
+}
+
+/// One folded region as it sits in the displayed text
+#[derive(Debug, Clone, PartialEq)]
+struct Fold {
+    start_line: usize,
+    display_start: usize,
+    hidden_start: usize,
+    hidden_end: usize,
+}
+
+impl Fold {
+    fn display_end(&self) -> usize {
+        self.display_start + FOLD_PLACEHOLDER.chars().count()
+    }
+}
+
+/// Text with its folded regions replaced by a placeholder, along with how to
+/// map offsets between the two. The text itself is never changed by folding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FoldedText {
+    display: String,
+    folds: Vec<Fold>,
+    /// The text's line number for each displayed line
+    line_numbers: Vec<usize>,
+}
+
+impl FoldedText {
+    /// Fold the regions of `text` whose start line is in `folded`. A region
+    /// inside another folded region is hidden along with it.
+    pub fn new(text: &str, regions: &[FoldRegion], folded: &BTreeSet<usize>) -> Self {
+        let chars: Vec<char> = text.chars().collect();
+        let mut display = String::with_capacity(text.len());
+        let mut display_len = 0;
+        let mut folds = Vec::new();
+        let mut line_numbers = vec![0];
+        let mut line = 0;
+        let mut pos = 0;
+
+        let mut show = |range: &[char], display: &mut String, display_len: &mut usize, line: &mut usize| {
+            for &c in range {
+                display.push(c);
+                *display_len += 1;
+                if c == '\n' {
+                    *line += 1;
+                    line_numbers.push(*line);
+                }
+            }
+        };
+
+        for region in regions.iter().filter(|region| folded.contains(&region.start_line)) {
+            // Nested in a region that's already hidden
+            if region.hidden_start < pos {
+                continue;
+            }
+
+            show(&chars[pos..region.hidden_start], &mut display, &mut display_len, &mut line);
+
+            folds.push(Fold {
+                start_line: region.start_line,
+                display_start: display_len,
+                hidden_start: region.hidden_start,
+                hidden_end: region.hidden_end,
+            });
+            display.push_str(FOLD_PLACEHOLDER);
+            display_len += FOLD_PLACEHOLDER.chars().count();
+
+            line += chars[region.hidden_start..region.hidden_end].iter().filter(|c| **c == '\n').count();
+            pos = region.hidden_end;
+        }
+
+        show(&chars[pos..], &mut display, &mut display_len, &mut line);
+
+        Self { display, folds, line_numbers }
+    }
+
+    /// The text as displayed
+    pub fn text(&self) -> &str {
+        &self.display
+    }
+
+    pub fn is_folded(&self) -> bool {
+        !self.folds.is_empty()
+    }
+
+    /// The text's line number for each displayed line
+    pub fn line_numbers(&self) -> &[usize] {
+        &self.line_numbers
+    }
+
+    /// Char offset in the text of the displayed char offset `offset`. Offsets
+    /// inside a placeholder map to the start of what it hides, or the end if
+    /// `prefer_end`.
+    pub fn to_full(&self, offset: usize, prefer_end: bool) -> usize {
+        let mut shift = 0isize;
+
+        for fold in &self.folds {
+            if offset <= fold.display_start {
+                break;
+            }
+            if offset < fold.display_end() {
+                return if prefer_end { fold.hidden_end } else { fold.hidden_start };
+            }
+            shift = fold.hidden_end as isize - fold.display_end() as isize;
+        }
+
+        (offset as isize + shift) as usize
+    }
+
+    /// Displayed char offset of the text's char offset `offset`. Hidden
+    /// offsets map to the start of the placeholder hiding them.
+    pub fn to_display(&self, offset: usize) -> usize {
+        let mut shift = 0isize;
+
+        for fold in &self.folds {
+            if offset <= fold.hidden_start {
+                break;
+            }
+            if offset < fold.hidden_end {
+                return fold.display_start;
+            }
+            shift = fold.display_end() as isize - fold.hidden_end as isize;
+        }
+
+        (offset as isize + shift) as usize
+    }
+
+    /// The text's char range for a change to the displayed range `start..end`.
+    /// A change cutting into a placeholder without covering it can't be mapped,
+    /// so the start lines of the folds it touches are returned instead.
+    pub fn map_edit(&self, start: usize, end: usize) -> Result<(usize, usize), Vec<usize>> {
+        let touched: Vec<usize> = self
+            .folds
+            .iter()
+            .filter(|fold| {
+                let overlaps = start < fold.display_end() && end > fold.display_start
+                    || start == end && start > fold.display_start && start < fold.display_end();
+                let covers = start <= fold.display_start && end >= fold.display_end();
+                overlaps && !covers
+            })
+            .map(|fold| fold.start_line)
+            .collect();
+
+        if touched.is_empty() {
+            Ok((self.to_full(start, false), self.to_full(end, true)))
+        } else {
+            Err(touched)
+        }
+    }
+}
+
+/// Move the folds in `folded` to follow an edit replacing the char range
+/// `start..end` of `text` with `inserted`. Folds starting on deleted lines go.
+pub fn shift_folds(folded: &BTreeSet<usize>, text: &str, start: usize, end: usize, inserted: &str) -> BTreeSet<usize> {
+    let edit_line = text.chars().take(start).filter(|c| *c == '\n').count();
+    let removed = text.chars().skip(start).take(end - start).filter(|c| *c == '\n').count();
+    let added = inserted.matches('\n').count();
+
+    folded
+        .iter()
+        .filter_map(|&line| {
+            if line <= edit_line {
+                Some(line)
+            } else if line <= edit_line + removed {
+                None
+            } else {
+                Some(line - removed + added)
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+/// Monospace fonts to pick from, as (name, CSS font-family)
+pub const MONOSPACE_FONTS: &[(&str, &str)] = &[
+    ("Fira Code", "'Fira Code', monospace"),
+    ("JetBrains Mono", "'JetBrains Mono', monospace"),
+    ("Source Code Pro", "'Source Code Pro', monospace"),
+    ("Consolas", "Consolas, monospace"),
+    ("System Monospace", "monospace"),
+];
+
+pub const MIN_FONT_SIZE: u32 = 8;
+pub const MAX_FONT_SIZE: u32 = 32;
+
+/// The percentages zooming in and out steps through
+pub const ZOOM_LEVELS: &[u32] = &[50, 67, 75, 80, 90, 100, 110, 125, 150, 175, 200];
+pub const DEFAULT_ZOOM: u32 = 100;
+
+/// The zoom level after `zoom`, zooming in or out
+pub fn next_zoom(zoom: u32, zoom_in: bool) -> u32 {
+    let (min, max) = (ZOOM_LEVELS[0], ZOOM_LEVELS[ZOOM_LEVELS.len() - 1]);
+    if zoom_in {
+        ZOOM_LEVELS.iter().copied().find(|level| *level > zoom).unwrap_or(max)
+    } else {
+        ZOOM_LEVELS.iter().copied().rfind(|level| *level < zoom).unwrap_or(min)
+    }
+}
+
+/// The font the editor's text is drawn in. Every layer over the text has to
+/// use the same one, or the caret drifts away from the highlighted text.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EditorFont {
+    /// A CSS font-family, one of `MONOSPACE_FONTS`
+    pub family: String,
+    /// In pixels
+    pub size: u32,
+}
+
+impl Default for EditorFont {
+    fn default() -> Self {
+        Self {
+            family: MONOSPACE_FONTS[0].1.to_string(),
+            size: 14,
+        }
+    }
+}
+
+impl EditorFont {
+    /// The same font `delta` pixels bigger, kept within the allowed sizes
+    pub fn resized(&self, delta: i32) -> Self {
+        Self {
+            family: self.family.clone(),
+            size: self.size.saturating_add_signed(delta).clamp(MIN_FONT_SIZE, MAX_FONT_SIZE),
+        }
+    }
+
+    /// The same font at `zoom` percent of its size. It's rounded to whole
+    /// pixels, which every layer over the text lays out the same.
+    pub fn zoomed(&self, zoom: u32) -> Self {
+        let zoom = zoom.clamp(ZOOM_LEVELS[0], ZOOM_LEVELS[ZOOM_LEVELS.len() - 1]);
+        Self {
+            family: self.family.clone(),
+            size: ((self.size * zoom) as f64 / 100.0).round() as u32,
+        }
+    }
+
+    /// CSS declarations for an element showing the editor's text
+    pub fn css(&self) -> String {
+        format!("font-family: {}; font-size: {}px; line-height: 1.5;", self.family, self.size)
+    }
+}
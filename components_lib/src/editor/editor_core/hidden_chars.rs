@@ -0,0 +1,73 @@
+/// Characters that don't render (or render like ordinary whitespace) but
+/// change what the text means: zero-width characters, the BOM, bidi controls,
+/// non-breaking spaces and control characters. Newlines, carriage returns and
+/// tabs are ordinary whitespace and never flagged.
+pub fn is_suspicious_char(c: char) -> bool {
+    match c {
+        '\n' | '\r' | '\t' => false,
+        '\u{00A0}' | '\u{202F}' | '\u{2007}' => true,
+        '\u{200B}'..='\u{200F}' | '\u{2060}' | '\u{FEFF}' => true,
+        '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}' => true,
+        _ => c.is_control(),
+    }
+}
+
+/// Whether a suspicious character takes up no width in the textarea
+pub fn is_zero_width(c: char) -> bool {
+    matches!(
+        c,
+        '\u{200B}'..='\u{200F}' | '\u{2060}' | '\u{FEFF}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}'
+    )
+}
+
+/// The visible glyph used in place of a suspicious character
+pub fn reveal_glyph(c: char) -> char {
+    match c {
+        '\u{00A0}' | '\u{202F}' | '\u{2007}' => '⍽',
+        '\u{7F}' => '␡',
+        '\u{00}'..='\u{1F}' => char::from_u32(0x2400 + c as u32).unwrap_or('�'),
+        _ if is_zero_width(c) => '┆',
+        _ => '�',
+    }
+}
+
+/// Char offsets of every suspicious character in `text`
+pub fn find_suspicious_chars(text: &str) -> Vec<(usize, char)> {
+    text.chars()
+        .enumerate()
+        .filter(|(_, c)| is_suspicious_char(*c))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_a_zero_width_space() {
+        assert_eq!(find_suspicious_chars("let a\u{200B} = 1;"), [(5, '\u{200B}')]);
+    }
+
+    #[test]
+    fn finds_a_non_breaking_space() {
+        assert_eq!(find_suspicious_chars("fn\u{00A0}main()"), [(2, '\u{00A0}')]);
+        assert_eq!(reveal_glyph('\u{00A0}'), '⍽');
+    }
+
+    #[test]
+    fn finds_a_bom() {
+        assert_eq!(find_suspicious_chars("\u{FEFF}{}"), [(0, '\u{FEFF}')]);
+        assert!(is_zero_width('\u{FEFF}'));
+    }
+
+    #[test]
+    fn finds_control_chars() {
+        assert_eq!(find_suspicious_chars("a\u{0}b\u{7F}"), [(1, '\u{0}'), (3, '\u{7F}')]);
+        assert_eq!(reveal_glyph('\u{0}'), '␀');
+    }
+
+    #[test]
+    fn ordinary_whitespace_is_not_flagged() {
+        assert_eq!(find_suspicious_chars("fn main() {\r\n\tlet x = 1;\n}\n"), []);
+    }
+}
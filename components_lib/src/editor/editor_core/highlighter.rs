@@ -0,0 +1,1299 @@
+use crate::core::themes::Theme;
+use super::{EditorFont, LanguageDefinition, Severity, find_all};
+use super::hidden_chars::{is_suspicious_char, is_zero_width, reveal_glyph};
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Behind every search match, and the one the find bar is on
+const MATCH_COLOR: &str = "rgba(229, 192, 123, 0.35)";
+const ACTIVE_MATCH_COLOR: &str = "rgba(229, 192, 123, 0.8)";
+
+/// Token types whose class the stylesheet colors from the theme. Brackets
+/// mostly take one of the theme's bracket colors instead.
+const TOKEN_TYPES: [&str; 10] = [
+    "keyword", "string", "comment", "function", "type", "attribute", "number", "invisible", "error", "bracket",
+];
+
+/// Escape the characters that would otherwise be read as markup, quotes
+/// included so the text is just as safe inside an attribute
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+const INTEGER_SUFFIXES: [&str; 12] = [
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+];
+const FLOAT_SUFFIXES: [&str; 2] = ["f32", "f64"];
+
+/// Whether `word` is a number literal: decimal with an optional fraction and
+/// exponent, or `0x`/`0o`/`0b` prefixed, with `_` separators and Rust type
+/// suffixes allowed. Identifiers never start with a digit, so requiring one
+/// up front keeps them out.
+fn is_number_literal(word: &str) -> bool {
+    if !word.starts_with(|c: char| c.is_ascii_digit()) {
+        return false;
+    }
+
+    let prefixed = [("0x", 16), ("0o", 8), ("0b", 2)]
+        .into_iter()
+        .find_map(|(prefix, radix)| word.strip_prefix(prefix).map(|digits| (digits, radix)));
+
+    if let Some((digits, radix)) = prefixed {
+        let digits = INTEGER_SUFFIXES
+            .iter()
+            .find_map(|suffix| digits.strip_suffix(suffix))
+            .unwrap_or(digits);
+
+        return digits.chars().any(|c| c.is_digit(radix))
+            && digits.chars().all(|c| c.is_digit(radix) || c == '_');
+    }
+
+    let digits = INTEGER_SUFFIXES
+        .iter()
+        .chain(FLOAT_SUFFIXES.iter())
+        .find_map(|suffix| word.strip_suffix(suffix))
+        .unwrap_or(word);
+
+    // A separator can't end the number, "1_" is a number followed by nothing valid
+    !digits.ends_with('_') && digits.replace('_', "").parse::<f64>().is_ok()
+}
+
+/// Whether `c` carries on the decimal number in `word` rather than ending it:
+/// the point in `2.5` or the exponent sign in `1e-9`
+fn continues_number(word: &str, c: char, next: Option<&char>) -> bool {
+    let is_decimal = word.starts_with(|c: char| c.is_ascii_digit())
+        && !word.starts_with("0x") && !word.starts_with("0o") && !word.starts_with("0b");
+    let next_is_digit = next.is_some_and(|next| next.is_ascii_digit());
+
+    if !is_decimal || !next_is_digit {
+        return false;
+    }
+
+    match c {
+        '.' => !word.contains(['.', 'e', 'E']),
+        '+' | '-' => word.ends_with(['e', 'E']),
+        _ => false,
+    }
+}
+
+/// Length in chars of the Rust char literal opening at `start`, such as `'x'`
+/// or `'\''`, or None for a lifetime like `'a`
+fn char_literal_len(chars: &[char], start: usize) -> Option<usize> {
+    match chars.get(start + 1)? {
+        // The longest escape is `\u{10FFFF}`
+        '\\' => (start + 3..chars.len().min(start + 12))
+            .find(|&end| chars[end] == '\'')
+            .map(|end| end + 1 - start),
+        _ => (chars.get(start + 2) == Some(&'\'')).then_some(3),
+    }
+}
+
+/// Index just past the `]` closing the Rust attribute whose `#` is at
+/// `start`, or the end of the line if it carries on to the next one
+fn attribute_end(chars: &[char], start: usize) -> usize {
+    let mut depth = 0;
+    let mut i = start + 1;
+
+    while i < chars.len() {
+        match chars[i] {
+            '"' => {
+                i += quoted_len(chars, i);
+                continue;
+            },
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + 1;
+                }
+            },
+            _ => {},
+        }
+        i += 1;
+    }
+
+    chars.len()
+}
+
+/// Languages whose `#` lines are preprocessor directives
+const PREPROCESSOR_LANGUAGES: &[&str] = &["c", "cpp"];
+
+/// Length in chars of the string literal opening at `start`, closing quote
+/// included. An unterminated string runs to the end of the line.
+fn quoted_len(chars: &[char], start: usize) -> usize {
+    let quote = chars[start];
+    let mut i = start + 1;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            c if c == quote => return i + 1 - start,
+            _ => i += 1,
+        }
+    }
+
+    chars.len() - start
+}
+
+/// Where `pattern` next appears in `chars`, at or after `start`
+fn find_from(chars: &[char], start: usize, pattern: &[char]) -> Option<usize> {
+    (start..chars.len()).find(|&at| chars[at..].starts_with(pattern))
+}
+
+/// Length in chars of the run starting at `start` whose chars all satisfy `pred`
+fn run_len(chars: &[char], start: usize, pred: impl Fn(char) -> bool) -> usize {
+    chars[start..].iter().take_while(|&&c| pred(c)).count()
+}
+
+/// Wrap the (start column, length) ranges of the line marked up as `html`
+/// in the tag `open` gives for each start column, closed by `close`. The tag
+/// is split around any tag a range crosses so the markup stays nested.
+fn wrap_columns(html: &str, ranges: &[(usize, usize)], open: impl Fn(usize) -> String, close: &str) -> String {
+    let mut result = String::with_capacity(html.len());
+    let mut column = 0;
+    let mut next_range = ranges.iter().peekable();
+    // The range being wrapped
+    let mut current = None::<(usize, usize)>;
+    let mut in_tag = false;
+    let mut in_entity = false;
+
+    for c in html.chars() {
+        if in_tag {
+            result.push(c);
+            if c == '>' {
+                in_tag = false;
+                if let Some((start, _)) = current {
+                    result.push_str(&open(start));
+                }
+            }
+            continue;
+        }
+        if !in_entity && current.is_some_and(|(start, len)| column == start + len) {
+            result.push_str(close);
+            current = None;
+        }
+        if c == '<' {
+            if current.is_some() {
+                result.push_str(close);
+            }
+            in_tag = true;
+            result.push(c);
+            continue;
+        }
+
+        // Each char of the line is a char of the markup or an entity
+        if !in_entity {
+            if let Some(&&(start, len)) = next_range.peek().filter(|(start, _)| *start == column) {
+                next_range.next();
+                current = Some((start, len));
+                result.push_str(&open(start));
+            }
+            column += 1;
+        }
+
+        match c {
+            '&' => in_entity = true,
+            ';' => in_entity = false,
+            _ => {},
+        }
+        result.push(c);
+    }
+
+    if current.is_some() {
+        result.push_str(close);
+    }
+    result
+}
+
+/// How a language's lines are split into tokens. Only programming languages
+/// fit the keyword list model, for the rest what matters is where a token
+/// sits: a JSON string is a key or a value depending on what follows it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Strategy {
+    /// Keywords, strings, numbers and line comments
+    Code,
+    Html,
+    Css,
+    Json,
+    Toml,
+    Yaml,
+    Markdown,
+}
+
+impl Strategy {
+    /// Whether a line's highlighting can depend on the lines before it
+    fn carries_state(&self) -> bool {
+        !matches!(self, Strategy::Markdown)
+    }
+
+    fn for_language(language: &str) -> Self {
+        match language {
+            "html" => Strategy::Html,
+            "css" => Strategy::Css,
+            "json" => Strategy::Json,
+            "toml" => Strategy::Toml,
+            "yaml" => Strategy::Yaml,
+            "markdown" => Strategy::Markdown,
+            _ => Strategy::Code,
+        }
+    }
+}
+
+/// What carries over from one line to the next
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+struct LineState {
+    /// Inside an HTML tag whose attributes continue on the next line
+    in_tag: bool,
+    /// How many CSS blocks deep the line starts. Declarations are inside a
+    /// block, selectors outside.
+    depth: usize,
+    /// The brackets still open, innermost last, for coloring by depth
+    open_brackets: Vec<char>,
+    /// Inside a block comment, `/* */` in most languages
+    in_block_comment: bool,
+    /// Inside a Go raw string, which runs to the next backtick
+    in_raw_string: bool,
+}
+
+/// Highlighted lines kept between passes. A line is reused while its text and
+/// the state it starts in are unchanged, so an edit only re-highlights the
+/// edited line and whichever lines after it start in a different state.
+/// Only the syntax colors are kept; hidden chars, whitespace and search
+/// matches are marked afresh on the lines being shown.
+#[derive(Default)]
+pub struct HighlightCache {
+    /// The language the lines were highlighted in, and how many bracket
+    /// colors there were to cycle through. The rest of the theme is left to
+    /// the stylesheet.
+    settings: Option<(LanguageDefinition, usize)>,
+    /// (starting state, line) to (markup, state the next line starts in)
+    lines: HashMap<(LineState, String), (String, LineState)>,
+}
+
+pub struct SyntaxHighlighter {
+    definition: LanguageDefinition,
+    strategy: Strategy,
+    theme: Theme,
+    reveal_hidden_chars: bool,
+    render_whitespace: bool,
+    /// The search term, and the (line, column) of the match the find bar is on
+    search: Option<(String, Option<(usize, usize)>)>,
+    /// The (line, column, length in chars) of a parse error to underline
+    error: Option<(usize, usize, usize)>,
+    /// The (line, column, length in chars) and severity of each linter diagnostic to underline
+    diagnostics: Vec<(usize, usize, usize, Severity)>,
+}
+
+impl SyntaxHighlighter {
+    /// A highlighter for one of the languages the editor comes with
+    pub fn new(language: String, theme: Theme) -> Self {
+        Self::with_language(LanguageDefinition::built_in(&language), theme)
+    }
+
+    /// A highlighter for the language `definition` describes. Where its name is
+    /// one of HTML, CSS and the data formats, those are highlighted by where
+    /// tokens sit as usual and only the keywords come from the definition.
+    pub fn with_language(definition: LanguageDefinition, theme: Theme) -> Self {
+        Self {
+            strategy: Strategy::for_language(&definition.name),
+            definition,
+            theme,
+            reveal_hidden_chars: false,
+            render_whitespace: false,
+            search: None,
+            error: None,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Render zero-width, non-breaking and control characters as visible glyphs
+    pub fn reveal_hidden_chars(mut self, reveal: bool) -> Self {
+        self.reveal_hidden_chars = reveal;
+        self
+    }
+
+    /// Mark spaces with a middot and tabs with an arrow
+    pub fn render_whitespace(mut self, render: bool) -> Self {
+        self.render_whitespace = render;
+        self
+    }
+
+    /// Mark every match of `term`, the one at the (line, column) `active` in
+    /// its own color. An empty term marks nothing.
+    pub fn search(mut self, term: &str, active: Option<(usize, usize)>) -> Self {
+        self.search = (!term.is_empty()).then(|| (term.to_string(), active));
+        self
+    }
+
+    /// Draw a wavy underline under `len` chars from the (line, column) of a
+    /// parse error
+    pub fn error_underline(mut self, error: Option<(usize, usize, usize)>) -> Self {
+        self.error = error;
+        self
+    }
+
+    /// Draw a wavy underline in its severity's color under the (line, column,
+    /// length in chars) of each diagnostic. Where they overlap only the first
+    /// on the line is drawn.
+    pub fn diagnostics(mut self, diagnostics: Vec<(usize, usize, usize, Severity)>) -> Self {
+        self.diagnostics = diagnostics;
+        self
+    }
+
+    fn is_keyword(&self, word: &str) -> bool {
+        self.definition.is_keyword(word)
+    }
+
+    /// `text` escaped and wrapped in the class for `token_type`
+    fn colored(&self, token_type: &str, text: &str) -> String {
+        format!("<span class=\"tok-{token_type}\">{}</span>", escape_html(text))
+    }
+
+    /// Highlight the lines of `text` in `visible`, reusing the lines in
+    /// `cache` that haven't changed. Earlier lines are only highlighted when
+    /// the language carries state from line to line. Only the lines of this
+    /// pass are kept afterwards.
+    pub fn highlight(&self, text: &str, visible: Range<usize>, cache: &mut HighlightCache) -> String {
+        let settings = (self.definition.clone(), self.theme.bracket_colors.len());
+        if cache.settings.as_ref() != Some(&settings) {
+            cache.settings = Some(settings);
+            cache.lines.clear();
+        }
+
+        let mut result = String::new();
+        let mut lines = HashMap::with_capacity(cache.lines.len());
+        let mut state = LineState::default();
+
+        for (idx, line) in text.split('\n').enumerate() {
+            if idx >= visible.end {
+                break;
+            }
+            if idx < visible.start && !self.strategy.carries_state() {
+                continue;
+            }
+
+            let key = (state, line.to_string());
+            // Repeated lines, like closing braces, can already be in this pass
+            let cached = lines.get(&key).cloned().or_else(|| cache.lines.remove(&key));
+            let (highlighted_line, next_state) = match cached {
+                Some(cached) => cached,
+                None => {
+                    let mut next_state = key.0.clone();
+                    let highlighted_line = self.highlight_line(line, &mut next_state);
+                    (highlighted_line, next_state)
+                }
+            };
+
+            if idx >= visible.start {
+                result.push_str(&self.decorate(idx, line, &highlighted_line));
+                result.push('\n');
+            }
+            state = next_state.clone();
+            lines.insert(key, (highlighted_line, next_state));
+        }
+
+        cache.lines = lines;
+        result
+    }
+
+    /// The highlighted `line` number `idx` with search matches, parse errors,
+    /// diagnostics, hidden chars and whitespace marked as asked. Matches, errors
+    /// and diagnostics go first, while every char of the markup's text is
+    /// still one of the line's.
+    fn decorate(&self, idx: usize, line: &str, highlighted_line: &str) -> String {
+        let mut html = highlighted_line.to_string();
+        if let Some((term, active)) = &self.search {
+            let active_column = active.filter(|(line, _)| *line == idx).map(|(_, column)| column);
+            html = self.mark_matches(&html, line, term, active_column);
+        }
+        if let Some((_, column, len)) = self.error.filter(|(line, _, _)| *line == idx) {
+            html = wrap_columns(&html, &[(column, len)], |_| "<span class=\"tok-error-underline\">".to_string(), "</span>");
+        }
+        let mut diagnostics: Vec<(usize, usize, Severity)> = self
+            .diagnostics
+            .iter()
+            .filter(|(line, ..)| *line == idx)
+            .map(|(_, column, len, severity)| (*column, (*len).max(1), *severity))
+            .collect();
+        if !diagnostics.is_empty() {
+            diagnostics.sort_by_key(|(column, ..)| *column);
+            let mut end = 0;
+            diagnostics.retain(|(column, len, _)| {
+                let keep = *column >= end;
+                if keep {
+                    end = column + len;
+                }
+                keep
+            });
+
+            let ranges: Vec<(usize, usize)> = diagnostics.iter().map(|(column, len, _)| (*column, *len)).collect();
+            let class_at = |start: usize| {
+                let severity = diagnostics.iter().find(|(column, ..)| *column == start).map(|(.., severity)| *severity);
+                format!("<span class=\"tok-diagnostic-{}\">", severity.unwrap_or(Severity::Error).as_str())
+            };
+            html = wrap_columns(&html, &ranges, class_at, "</span>");
+        }
+        if self.reveal_hidden_chars {
+            html = self.reveal_hidden(&html);
+        }
+        if self.render_whitespace {
+            html = self.mark_whitespace(&html);
+        }
+        html
+    }
+
+    /// Wrap each match of `term` in `line` in a `<mark>`
+    fn mark_matches(&self, html: &str, line: &str, term: &str, active_column: Option<usize>) -> String {
+        let term_len = term.chars().count();
+        let matches: Vec<(usize, usize)> = find_all(line, term).into_iter().map(|column| (column, term_len)).collect();
+        if matches.is_empty() {
+            return html.to_string();
+        }
+
+        let mark = |column: usize| {
+            let class = if Some(column) == active_column { "tok-match-active" } else { "tok-match" };
+            format!("<mark class=\"{class}\">")
+        };
+        wrap_columns(html, &matches, mark, "</mark>")
+    }
+
+    // The generated markup is plain ASCII, so any suspicious character in it came from the source text
+    fn reveal_hidden(&self, html: &str) -> String {
+        let mut result = String::with_capacity(html.len());
+
+        for c in html.chars() {
+            if !is_suspicious_char(c) {
+                result.push(c);
+                continue;
+            }
+
+            // Keep the glyph as wide as the character is in the textarea so the caret stays aligned
+            let width = if is_zero_width(c) { "0" } else { "1ch" };
+            result.push_str(&format!(
+                "<span class=\"tok-invisible\" style=\"display: inline-block; width: {}; overflow: visible;\" title=\"U+{:04X}\">{}</span>",
+                width, c as u32, reveal_glyph(c)
+            ));
+        }
+
+        result
+    }
+
+    // Markup never has text inside a tag, so only spaces and tabs outside one came from the source
+    fn mark_whitespace(&self, html: &str) -> String {
+        let mut result = String::with_capacity(html.len());
+        let mut in_tag = false;
+
+        for c in html.chars() {
+            match c {
+                '<' => in_tag = true,
+                '>' => in_tag = false,
+                _ => {},
+            }
+
+            match c {
+                ' ' if !in_tag => result.push_str("<span class=\"tok-invisible\">·</span>"),
+                // The tab stays to keep its width, with the arrow laid over its start
+                '\t' if !in_tag => result.push_str(
+                    "<span style=\"position: relative;\">\t<span class=\"tok-invisible\" style=\"position: absolute; left: 0;\">→</span></span>"
+                ),
+                _ => result.push(c),
+            }
+        }
+
+        result
+    }
+
+    fn highlight_line(&self, line: &str, state: &mut LineState) -> String {
+        let chars: Vec<char> = line.chars().collect();
+
+        match self.strategy {
+            Strategy::Code => self.highlight_code_line(line, state),
+            Strategy::Html => self.highlight_html_line(&chars, &mut state.in_tag),
+            Strategy::Css => self.highlight_css_line(&chars, &mut state.depth, &mut state.in_block_comment),
+            Strategy::Json => self.highlight_data(&chars, None, &mut state.open_brackets),
+            Strategy::Toml => self.highlight_toml_line(&chars, &mut state.open_brackets),
+            Strategy::Yaml => self.highlight_yaml_line(&chars, &mut state.open_brackets),
+            Strategy::Markdown => self.highlight_markdown_line(&chars),
+        }
+    }
+
+    /// A bracket colored by how deeply it's nested. A closing bracket that
+    /// doesn't close the innermost open one gets the error color.
+    fn bracket(&self, c: char, open_brackets: &mut Vec<char>) -> String {
+        let depth = match c {
+            '(' | '[' | '{' => {
+                open_brackets.push(c);
+                Some(open_brackets.len() - 1)
+            },
+            _ => {
+                let opener = match c {
+                    ')' => '(',
+                    ']' => '[',
+                    _ => '{',
+                };
+                (open_brackets.last() == Some(&opener)).then(|| {
+                    open_brackets.pop();
+                    open_brackets.len()
+                })
+            },
+        };
+
+        let palette_len = self.theme.bracket_colors.len();
+        let class = match depth {
+            Some(depth) if palette_len > 0 => format!("tok-bracket-{}", depth % palette_len),
+            Some(_) => "tok-bracket".to_string(),
+            None => "tok-error".to_string(),
+        };
+        format!("<span class=\"{class}\">{c}</span>")
+    }
+
+    fn highlight_code_line(&self, line: &str, state: &mut LineState) -> String {
+        // Simple syntax highlighting by word
+        let mut result = String::new();
+        // The quote that opened the string we're in, if any
+        let mut string_quote = None::<char>;
+        // Whether the previous char was a backslash that escapes this one
+        let mut escaped = false;
+        let mut current_word = String::new();
+        let quotes = &self.definition.string_delimiters;
+        let (block_open, block_close): (Vec<char>, Vec<char>) = match &self.definition.block_comment {
+            Some((open, close)) => (open.chars().collect(), close.chars().collect()),
+            None => (Vec::new(), Vec::new()),
+        };
+
+        let comment_prefix = self.definition.line_comment.as_deref().filter(|prefix| !prefix.is_empty());
+        let comment_chars: Vec<char> = comment_prefix.unwrap_or_default().chars().collect();
+        let chars: Vec<char> = line.chars().collect();
+
+        // Finish a comment or raw string left open by the line before
+        let mut i = 0;
+        if state.in_block_comment || state.in_raw_string {
+            let (token_type, end) = if state.in_block_comment {
+                ("comment", find_from(&chars, 0, &block_close).map(|at| at + block_close.len()))
+            } else {
+                ("string", find_from(&chars, 0, &['`']).map(|at| at + 1))
+            };
+
+            let Some(end) = end else {
+                return self.colored(token_type, line);
+            };
+            state.in_block_comment = false;
+            state.in_raw_string = false;
+            result.push_str(&self.colored(token_type, &chars[..end].iter().collect::<String>()));
+            i = end;
+        }
+
+        // Check for comments first (simplest case), unless a block comment
+        // starts the same way, like Lua's `--[[` does
+        let opens_block_comment = self.definition.block_comment.as_ref().is_some_and(|(open, _)| line.trim().starts_with(open.as_str()));
+        if i == 0 && !opens_block_comment && comment_prefix.is_some_and(|prefix| line.trim().starts_with(prefix)) {
+            return format!("<span class=\"tok-comment\">{}</span>", escape_html(line));
+        }
+
+        // A directive such as `#include <stdio.h>`, with the rest of the line highlighted as usual
+        if i == 0 && PREPROCESSOR_LANGUAGES.contains(&self.definition.name.as_str()) && line.trim_start().starts_with('#') {
+            let hash = run_len(&chars, 0, char::is_whitespace);
+            let spaces = run_len(&chars, hash + 1, |c| c == ' ' || c == '\t');
+            let name = run_len(&chars, hash + 1 + spaces, |c| c.is_alphanumeric() || c == '_');
+            i = hash + 1 + spaces + name;
+
+            let directive: String = chars[hash..i].iter().collect();
+            result.push_str(&chars[..hash].iter().collect::<String>());
+            result.push_str(&self.colored("keyword", &directive));
+
+            let gap = run_len(&chars, i, char::is_whitespace);
+            if directive.ends_with("include") && chars.get(i + gap) == Some(&'<') {
+                let end = find_from(&chars, i + gap, &['>']).map(|at| at + 1).unwrap_or(chars.len());
+                result.push_str(&escape_html(&chars[i..i + gap].iter().collect::<String>()));
+                result.push_str(&self.colored("string", &chars[i + gap..end].iter().collect::<String>()));
+                i = end;
+            }
+        }
+
+
+        while i < chars.len() {
+            let c = chars[i];
+
+            // Inside a string everything up to the closing quote belongs to it
+            if let Some(quote) = string_quote {
+                current_word.push(c);
+
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == quote {
+                    result.push_str(&format!("<span class=\"tok-string\">{}</span>", escape_html(&current_word)));
+                    current_word = String::new();
+                    string_quote = None;
+                }
+            }
+            // Rust char literals, told apart from lifetimes
+            else if c == '\'' && self.definition.name == "rust" {
+                if !current_word.is_empty() {
+                    self.add_highlighted_word(&mut result, &current_word);
+                    current_word = String::new();
+                }
+
+                match char_literal_len(&chars, i) {
+                    Some(len) => {
+                        let literal: String = chars[i..i + len].iter().collect();
+                        result.push_str(&format!("<span class=\"tok-string\">{}</span>", escape_html(&literal)));
+                        i += len;
+                        continue;
+                    },
+                    // A lifetime such as `'a` or `'static`
+                    None => match run_len(&chars, i + 1, |c| c.is_alphanumeric() || c == '_') {
+                        0 => result.push(c),
+                        len => {
+                            result.push_str(&self.colored("type", &chars[i..i + 1 + len].iter().collect::<String>()));
+                            i += 1 + len;
+                            continue;
+                        },
+                    },
+                }
+            }
+            // Rust attributes, `#[derive(Debug)]` or `#![allow(unused)]`
+            else if c == '#' && self.definition.name == "rust" && (chars[i + 1..].starts_with(&['[']) || chars[i + 1..].starts_with(&['!', '['])) {
+                if !current_word.is_empty() {
+                    self.add_highlighted_word(&mut result, &current_word);
+                    current_word = String::new();
+                }
+
+                let end = attribute_end(&chars, i);
+                result.push_str(&self.colored("attribute", &chars[i..end].iter().collect::<String>()));
+                i = end;
+                continue;
+            }
+            // Rust macro calls such as `println!`, told apart from `!=`
+            else if c == '!' && self.definition.name == "rust" && !current_word.is_empty()
+                && !is_number_literal(&current_word) && chars.get(i + 1) != Some(&'=') {
+                current_word.push(c);
+                result.push_str(&self.colored("function", &current_word));
+                current_word = String::new();
+            }
+            // Handle strings
+            else if quotes.contains(&c) {
+                if !current_word.is_empty() {
+                    self.add_highlighted_word(&mut result, &current_word);
+                    current_word = String::new();
+                }
+                current_word.push(c);
+                string_quote = Some(c);
+            }
+            // Block comments, which may carry on over the next lines
+            else if !block_open.is_empty() && chars[i..].starts_with(&block_open) {
+                if !current_word.is_empty() {
+                    self.add_highlighted_word(&mut result, &current_word);
+                    current_word = String::new();
+                }
+
+                let end = find_from(&chars, i + block_open.len(), &block_close).map(|at| at + block_close.len());
+                state.in_block_comment = end.is_none();
+                let end = end.unwrap_or(chars.len());
+                result.push_str(&self.colored("comment", &chars[i..end].iter().collect::<String>()));
+                i = end;
+                continue;
+            }
+            // Go raw strings have no escapes and may carry on over the next lines
+            else if c == '`' && self.definition.name == "go" {
+                if !current_word.is_empty() {
+                    self.add_highlighted_word(&mut result, &current_word);
+                    current_word = String::new();
+                }
+
+                let end = find_from(&chars, i + 1, &['`']).map(|at| at + 1);
+                state.in_raw_string = end.is_none();
+                let end = end.unwrap_or(chars.len());
+                result.push_str(&self.colored("string", &chars[i..end].iter().collect::<String>()));
+                i = end;
+                continue;
+            }
+            // Handle comments
+            else if !comment_chars.is_empty() && chars[i..].starts_with(&comment_chars) {
+                if !current_word.is_empty() {
+                    self.add_highlighted_word(&mut result, &current_word);
+                    current_word = String::new();
+                }
+                // Add the rest of the line as a comment
+                let comment: String = chars[i..].iter().collect();
+                result.push_str(&format!("<span class=\"tok-comment\">{}</span>", escape_html(&comment)));
+                break;
+            }
+            // Handle word boundaries
+            else if c.is_alphanumeric() || c == '_' || continues_number(&current_word, c, chars.get(i + 1)) {
+                current_word.push(c);
+            }
+            else {
+                if !current_word.is_empty() {
+                    self.add_highlighted_word(&mut result, &current_word);
+                    current_word = String::new();
+                }
+                // Special handling for parentheses and brackets
+                if c == '(' || c == ')' || c == '{' || c == '}' || c == '[' || c == ']' {
+                    result.push_str(&self.bracket(c, &mut state.open_brackets));
+                } else {
+                    result.push_str(&escape_html(&c.to_string()));
+                }
+            }
+
+            i += 1;
+        }
+
+        // Handle any remaining word
+        if !current_word.is_empty() {
+            self.add_highlighted_word(&mut result, &current_word);
+        }
+
+        result
+    }
+
+    /// Tags and their attributes, with the text between them left plain
+    fn highlight_html_line(&self, chars: &[char], in_tag: &mut bool) -> String {
+        let mut result = String::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+
+            if *in_tag {
+                if c == '>' {
+                    result.push_str("&gt;");
+                    *in_tag = false;
+                    i += 1;
+                } else if c == '"' || c == '\'' {
+                    let len = quoted_len(chars, i);
+                    let value: String = chars[i..i + len].iter().collect();
+                    result.push_str(&self.colored("string", &value));
+                    i += len;
+                } else if c.is_alphanumeric() {
+                    let len = run_len(chars, i, |c| c.is_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'));
+                    let name: String = chars[i..i + len].iter().collect();
+                    result.push_str(&self.colored("function", &name));
+                    i += len;
+                } else {
+                    result.push_str(&escape_html(&c.to_string()));
+                    i += 1;
+                }
+            } else if chars[i..].starts_with(&['<', '!', '-', '-']) {
+                // Comments spanning lines are only colored on their first line
+                let close = (i + 4..chars.len())
+                    .find(|&end| chars[end..].starts_with(&['-', '-', '>']))
+                    .map(|end| end + 3)
+                    .unwrap_or(chars.len());
+                let comment: String = chars[i..close].iter().collect();
+                result.push_str(&self.colored("comment", &comment));
+                i = close;
+            } else if c == '<' && chars.get(i + 1).is_some_and(|&next| next.is_alphabetic() || next == '/' || next == '!') {
+                result.push_str("&lt;");
+                i += 1;
+                if matches!(chars[i], '/' | '!') {
+                    result.push(chars[i]);
+                    i += 1;
+                }
+
+                let len = run_len(chars, i, |c| c.is_alphanumeric() || c == '-');
+                let name: String = chars[i..i + len].iter().collect();
+                result.push_str(&self.colored("keyword", &name));
+                i += len;
+                *in_tag = true;
+            } else {
+                result.push_str(&escape_html(&c.to_string()));
+                i += 1;
+            }
+        }
+
+        result
+    }
+
+    /// Selectors outside blocks, `property: value` declarations inside them.
+    /// A comment left open runs on into the next line, which starts `in_comment`.
+    fn highlight_css_line(&self, chars: &[char], depth: &mut usize, in_comment: &mut bool) -> String {
+        let mut result = String::new();
+        let mut in_value = false;
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+            // Rules nest inside at-rules, so a `{` coming up marks a selector at any depth
+            let in_selector = *depth == 0 || chars[i..].iter().take_while(|&&c| c != ';').any(|&c| c == '{');
+
+            if *in_comment || chars[i..].starts_with(&['/', '*']) {
+                // Carrying on from the line before, there's no `/*` to skip
+                let search_from = if *in_comment { i } else { i + 2 };
+                let close = (search_from..chars.len())
+                    .find(|&end| chars[end..].starts_with(&['*', '/']))
+                    .map(|end| end + 2);
+                *in_comment = close.is_none();
+                let close = close.unwrap_or(chars.len());
+                let comment: String = chars[i..close].iter().collect();
+                result.push_str(&self.colored("comment", &comment));
+                i = close;
+                continue;
+            }
+
+            match c {
+                '{' | '}' => {
+                    if c == '{' {
+                        *depth += 1;
+                    } else {
+                        *depth = depth.saturating_sub(1);
+                    }
+                    in_value = false;
+                    result.push_str(&self.colored("bracket", &c.to_string()));
+                    i += 1;
+                },
+                '"' | '\'' => {
+                    let len = quoted_len(chars, i);
+                    let text: String = chars[i..i + len].iter().collect();
+                    result.push_str(&self.colored("string", &text));
+                    i += len;
+                },
+                ':' if !in_selector && !in_value => {
+                    in_value = true;
+                    result.push(c);
+                    i += 1;
+                },
+                ';' => {
+                    in_value = false;
+                    result.push(c);
+                    i += 1;
+                },
+                _ if in_selector && !c.is_whitespace() && c != ',' => {
+                    let len = run_len(chars, i, |c| !c.is_whitespace() && !matches!(c, ',' | '{' | '"' | '\''));
+                    let selector: String = chars[i..i + len].iter().collect();
+                    let token_type = if selector.starts_with('@') { "keyword" } else { "type" };
+                    result.push_str(&self.colored(token_type, &selector));
+                    i += len;
+                },
+                _ if c.is_alphanumeric() || matches!(c, '-' | '#' | '.') => {
+                    let len = run_len(chars, i, |c| c.is_alphanumeric() || matches!(c, '-' | '_' | '#' | '.' | '%'));
+                    let word: String = chars[i..i + len].iter().collect();
+                    let token_type = if !in_value {
+                        "function"
+                    } else if word.starts_with(|c: char| c.is_ascii_digit() || c == '#' || c == '.') {
+                        "number"
+                    } else {
+                        "string"
+                    };
+                    result.push_str(&self.colored(token_type, &word));
+                    i += len;
+                },
+                _ => {
+                    result.push_str(&escape_html(&c.to_string()));
+                    i += 1;
+                },
+            }
+        }
+
+        result
+    }
+
+    /// `[section]` headers, then `key = value` pairs
+    fn highlight_toml_line(&self, chars: &[char], open_brackets: &mut Vec<char>) -> String {
+        let indent = run_len(chars, 0, char::is_whitespace);
+        let leading: String = chars[..indent].iter().collect();
+        let rest = &chars[indent..];
+
+        if rest.first() == Some(&'[') {
+            let close = rest.iter().rposition(|&c| c == ']').map(|end| end + 1).unwrap_or(rest.len());
+            let header: String = rest[..close].iter().collect();
+            return format!("{}{}{}", leading, self.colored("type", &header), self.highlight_data(&rest[close..], Some('#'), open_brackets));
+        }
+
+        // A `=` after a comment or inside a string doesn't start a value
+        let key_end = rest.iter().position(|&c| matches!(c, '=' | '#' | '"' | '\''));
+        match key_end {
+            Some(equals) if rest[equals] == '=' => {
+                let key: String = rest[..equals].iter().collect();
+                let key_len = key.trim_end().len();
+                format!("{}{}{}={}",
+                    leading,
+                    self.colored("function", &key[..key_len]),
+                    &key[key_len..],
+                    self.highlight_data(&rest[equals + 1..], Some('#'), open_brackets))
+            },
+            _ => format!("{}{}", leading, self.highlight_data(rest, Some('#'), open_brackets)),
+        }
+    }
+
+    /// `key: value` mappings, optionally behind `- ` sequence markers
+    fn highlight_yaml_line(&self, chars: &[char], open_brackets: &mut Vec<char>) -> String {
+        let mut indent = run_len(chars, 0, char::is_whitespace);
+        while chars[indent..].starts_with(&['-', ' ']) {
+            indent += 2 + run_len(chars, indent + 2, char::is_whitespace);
+        }
+        let leading: String = chars[..indent].iter().collect();
+        let rest = &chars[indent..];
+        let line: String = rest.iter().collect();
+
+        if line == "---" || line == "..." {
+            return format!("{}{}", leading, self.colored("keyword", &line));
+        }
+
+        // A plain key runs up to the first `: `, or a `:` ending the line
+        let key_end = (0..rest.len())
+            .take_while(|&i| match rest[i] {
+                '#' | '"' | '{' | '[' => false,
+                '\'' => i > 0 && rest[i - 1].is_alphanumeric(),
+                _ => true,
+            })
+            .find(|&i| rest[i] == ':' && rest.get(i + 1).is_none_or(|next| next.is_whitespace()));
+
+        match key_end {
+            Some(colon) if colon > 0 => {
+                let key: String = rest[..colon].iter().collect();
+                format!("{}{}:{}", leading, self.colored("function", &key), self.highlight_data(&rest[colon + 1..], Some('#'), open_brackets))
+            },
+            _ => format!("{}{}", leading, self.highlight_data(rest, Some('#'), open_brackets)),
+        }
+    }
+
+    /// Strings, numbers and literals in a JSON, TOML or YAML value. A string
+    /// followed by `:` is a key.
+    fn highlight_data(&self, chars: &[char], comment: Option<char>, open_brackets: &mut Vec<char>) -> String {
+        let mut result = String::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+            let follows_space = i == 0 || chars[i - 1].is_whitespace();
+            let is_word_char = |c: char| c.is_alphanumeric() || matches!(c, '_' | '-' | '+' | '.');
+
+            if Some(c) == comment && follows_space {
+                let rest: String = chars[i..].iter().collect();
+                result.push_str(&self.colored("comment", &rest));
+                break;
+            }
+
+            // An apostrophe inside a word isn't a quote
+            if c == '"' || (c == '\'' && (i == 0 || !chars[i - 1].is_alphanumeric())) {
+                let len = quoted_len(chars, i);
+                let text: String = chars[i..i + len].iter().collect();
+                let is_key = chars[i + len..].iter().find(|c| !c.is_whitespace()) == Some(&':');
+                result.push_str(&self.colored(if is_key { "function" } else { "string" }, &text));
+                i += len;
+            } else if is_word_char(c) {
+                let len = run_len(chars, i, is_word_char);
+                let word: String = chars[i..i + len].iter().collect();
+                if self.is_keyword(&word) {
+                    result.push_str(&self.colored("keyword", &word));
+                } else if is_number_literal(word.trim_start_matches(['-', '+'])) {
+                    result.push_str(&self.colored("number", &word));
+                } else {
+                    result.push_str(&escape_html(&word));
+                }
+                i += len;
+            } else if matches!(c, '[' | ']' | '{' | '}') {
+                result.push_str(&self.bracket(c, open_brackets));
+                i += 1;
+            } else {
+                result.push_str(&escape_html(&c.to_string()));
+                i += 1;
+            }
+        }
+
+        result
+    }
+
+    /// Headings, bold text, inline code and links. Bold text is colored
+    /// rather than set in bold, which would widen it past the caret.
+    fn highlight_markdown_line(&self, chars: &[char]) -> String {
+        let line: String = chars.iter().collect();
+        let hashes = run_len(chars, 0, |c| c == '#');
+
+        if (1..=6).contains(&hashes) && chars.get(hashes).is_none_or(|&c| c == ' ') {
+            return self.colored("keyword", &line);
+        }
+
+        let mut result = String::new();
+        let mut i = 0;
+
+        // The index just past the first `pattern` at or after `from`
+        let find_after = |pattern: &[char], from: usize| {
+            (from..chars.len()).find(|&end| chars[end..].starts_with(pattern)).map(|end| end + pattern.len())
+        };
+
+        while i < chars.len() {
+            let c = chars[i];
+
+            if chars[i..].starts_with(&['*', '*']) || chars[i..].starts_with(&['_', '_']) {
+                if let Some(close) = find_after(&[c, c], i + 2).filter(|&close| close > i + 4) {
+                    let bold: String = chars[i..close].iter().collect();
+                    result.push_str(&self.colored("type", &bold));
+                    i = close;
+                    continue;
+                }
+            } else if c == '`' {
+                if let Some(close) = find_after(&['`'], i + 1) {
+                    let code: String = chars[i..close].iter().collect();
+                    result.push_str(&self.colored("string", &code));
+                    i = close;
+                    continue;
+                }
+            } else if c == '[' {
+                let text_end = find_after(&[']', '('], i + 1);
+                let url_end = text_end.and_then(|text_end| find_after(&[')'], text_end));
+
+                if let (Some(text_end), Some(url_end)) = (text_end, url_end) {
+                    let text: String = chars[i..text_end - 1].iter().collect();
+                    let url: String = chars[text_end - 1..url_end].iter().collect();
+                    result.push_str(&self.colored("function", &text));
+                    result.push_str(&self.colored("string", &url));
+                    i = url_end;
+                    continue;
+                }
+            }
+
+            result.push_str(&escape_html(&c.to_string()));
+            i += 1;
+        }
+
+        result
+    }
+
+    fn add_highlighted_word(&self, result: &mut String, word: &str) {
+        // Check if word is a keyword for the current language
+        if self.is_keyword(word) {
+            result.push_str(&format!("<span class=\"tok-keyword\">{}</span>", word));
+            return;
+        }
+
+        // Check if word is a number
+        if is_number_literal(word) {
+            result.push_str(&format!("<span class=\"tok-number\">{}</span>", word));
+            return;
+        }
+
+        // Regular word, which may be an unterminated string
+        result.push_str(&escape_html(word));
+    }
+}
+/// The stylesheet coloring highlighted markup in `theme`. Switching theme only
+/// takes a new stylesheet, the markup stays the same.
+pub fn highlight_stylesheet(theme: &Theme) -> String {
+    let mut css = String::new();
+    for token_type in TOKEN_TYPES {
+        css.push_str(&format!(".tok-{token_type} {{ color: {}; }}\n", theme.get_color(token_type)));
+    }
+    for (idx, color) in theme.bracket_colors.iter().enumerate() {
+        css.push_str(&format!(".tok-bracket-{idx} {{ color: {color}; }}\n"));
+    }
+    css.push_str(&format!(
+        ".tok-error-underline {{ text-decoration: underline wavy {}; text-decoration-skip-ink: none; }}\n",
+        theme.get_color("error")
+    ));
+    for severity in [Severity::Error, Severity::Warning, Severity::Info] {
+        css.push_str(&format!(
+            ".tok-diagnostic-{} {{ text-decoration: underline wavy {}; text-decoration-skip-ink: none; }}\n",
+            severity.as_str(),
+            theme.get_color(severity.as_str())
+        ));
+    }
+    css.push_str(&format!(".tok-match {{ background-color: {MATCH_COLOR}; color: inherit; }}\n"));
+    css.push_str(&format!(".tok-match-active {{ background-color: {ACTIVE_MATCH_COLOR}; color: inherit; }}\n"));
+    css
+}
+
+/// All of `code` highlighted as a standalone `<pre>` block in `theme`'s
+/// colors, with the stylesheet for them, for pages with no editor to render it
+pub fn highlight_to_html(code: &str, language: &str, theme: &Theme) -> String {
+    let highlighted = SyntaxHighlighter::new(language.to_string(), theme.clone())
+        .highlight(code, 0..usize::MAX, &mut HighlightCache::default());
+
+    format!(
+        "<style>\n{}</style>\n<pre style=\"margin: 0; padding: 1rem; overflow-x: auto; background-color: {}; color: {}; {}\"><code>{}</code></pre>",
+        highlight_stylesheet(theme),
+        theme.background,
+        theme.foreground,
+        EditorFont::default().css(),
+        // Every line ends with a line break, which would leave a blank one at the bottom
+        highlighted.strip_suffix('\n').unwrap_or(&highlighted)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn highlight(language: &str, code: &str) -> String {
+        SyntaxHighlighter::new(language.to_string(), Theme::default()).highlight(code, 0..usize::MAX, &mut HighlightCache::default())
+    }
+
+    /// The markup with its span tags taken out, leaving only the text
+    fn without_spans(html: &str) -> String {
+        let mut text = String::new();
+        let mut rest = html;
+        while let Some(start) = [rest.find("<span"), rest.find("</span>")].into_iter().flatten().min() {
+            let end = start + rest[start..].find('>').unwrap() + 1;
+            text.push_str(&rest[..start]);
+            rest = &rest[end..];
+        }
+        text.push_str(rest);
+        text
+    }
+
+    #[test]
+    fn escapes_markup_in_code() {
+        let html = highlight("rust", "let x: Vec<String> = foo & bar;");
+        let text = without_spans(&html);
+        assert!(!text.contains('<') && !text.contains('>'), "{html}");
+        assert!(text.contains("Vec&lt;String&gt;"), "{html}");
+        assert!(text.contains("foo &amp; bar"), "{html}");
+    }
+
+    #[test]
+    fn recognizes_number_literals() {
+        for number in ["0xDEAD_BEEF", "1_000_000", "2.5e10", "1e-9", "3.14f32", "0b1010", "0o17", "42u8", "7"] {
+            assert!(is_number_literal(number), "{number}");
+        }
+        for word in ["x1", "u8", "1_", "0x", "0xZZ", "0b102", "1abc", "f32"] {
+            assert!(!is_number_literal(word), "{word}");
+        }
+    }
+
+    #[test]
+    fn colors_whole_number_literals() {
+        let html = highlight("rust", "let n = 0xDEAD_BEEF + 1_000_000 + 2.5e10;");
+        for number in ["0xDEAD_BEEF", "1_000_000", "2.5e10"] {
+            assert!(html.contains(&format!("<span class=\"tok-number\">{number}</span>")), "{html}");
+        }
+    }
+
+    #[test]
+    fn escaped_quotes_stay_in_the_string() {
+        let html = highlight("rust", r#"let s = "he said \"hi\""; let t = 1;"#);
+        assert!(html.contains(r#"<span class="tok-string">&quot;he said \&quot;hi\&quot;&quot;</span>"#), "{html}");
+        assert!(html.contains(r#"<span class="tok-keyword">let</span> t"#), "{html}");
+    }
+
+    #[test]
+    fn char_literals_are_strings_and_lifetimes_are_not() {
+        let html = highlight("rust", r"let q = '\''; let c = 'x'; fn f<'a>() {}");
+        assert!(html.contains(r#"<span class="tok-string">'\''</span>"#), "{html}");
+        assert!(html.contains(r#"<span class="tok-string">'x'</span>"#), "{html}");
+        assert!(html.contains(r#"<span class="tok-type">'a</span>"#), "{html}");
+    }
+
+    /// The class of each bracket in the highlighted `html`, in order
+    fn bracket_classes(html: &str) -> Vec<String> {
+        html.split("<span class=\"")
+            .skip(1)
+            .filter_map(|rest| rest.split_once("\">"))
+            .filter(|(_, text)| text.starts_with(['(', ')', '[', ']', '{', '}']))
+            .map(|(class, _)| class.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn colors_nested_brackets_by_depth() {
+        let html = highlight("rust", "{[()]}");
+        assert_eq!(
+            bracket_classes(&html),
+            ["tok-bracket-0", "tok-bracket-1", "tok-bracket-2", "tok-bracket-2", "tok-bracket-1", "tok-bracket-0"]
+        );
+    }
+
+    #[test]
+    fn bracket_depth_cycles_through_the_palette_and_carries_across_lines() {
+        let html = highlight("rust", "{[(\n{\n}\n)]}");
+        assert_eq!(bracket_classes(&html)[3], "tok-bracket-0");
+        assert_eq!(bracket_classes(&html).last().unwrap(), "tok-bracket-0");
+    }
+
+    #[test]
+    fn mismatched_brackets_get_the_error_color() {
+        let html = highlight("rust", "(]");
+        assert_eq!(bracket_classes(&html), ["tok-bracket-0", "tok-error"]);
+    }
+
+    #[test]
+    fn colors_rust_attributes() {
+        let html = highlight("rust", "#[derive(Debug)]\n#![allow(unused)]");
+        assert!(html.contains(r#"<span class="tok-attribute">#[derive(Debug)]</span>"#), "{html}");
+        assert!(html.contains(r#"<span class="tok-attribute">#![allow(unused)]</span>"#), "{html}");
+    }
+
+    #[test]
+    fn colors_rust_macro_calls_but_not_inequality() {
+        let html = highlight("rust", r#"println!("{}", x); if a != b {}"#);
+        assert!(html.contains(r#"<span class="tok-function">println!</span>"#), "{html}");
+        assert!(!html.contains(r#"<span class="tok-function">a!</span>"#), "{html}");
+    }
+
+    #[test]
+    fn colors_rust_lifetimes() {
+        let html = highlight("rust", "fn name<'a>(s: &'a str) -> &'static str {}");
+        assert!(html.contains(r#"&amp;<span class="tok-type">'a</span>"#), "{html}");
+        assert!(html.contains(r#"<span class="tok-type">'static</span>"#), "{html}");
+    }
+
+    #[test]
+    fn rust_only_rules_stay_out_of_other_languages() {
+        let html = highlight("python", "#[derive(Debug)]");
+        assert!(!html.contains("tok-attribute"), "{html}");
+    }
+
+    #[test]
+    fn markup_inside_strings_is_escaped() {
+        let html = highlight("rust", r#"let x = "<div>";"#);
+        assert!(html.contains(r#"<span class="tok-string">&quot;&lt;div&gt;&quot;</span>"#), "{html}");
+    }
+
+    #[test]
+    fn block_comments_do_not_nest() {
+        let html = highlight("rust", "/* /* */ let x = 1;");
+        assert!(html.contains(r#"<span class="tok-comment">/* /* */</span>"#), "{html}");
+        assert!(html.contains(r#"<span class="tok-keyword">let</span>"#), "{html}");
+    }
+
+    #[test]
+    fn block_comments_start_after_code_and_span_lines() {
+        let html = highlight("rust", "let x = 1; /* note\nfn f() {}\n*/ let y = 2;");
+        assert!(html.contains(r#"<span class="tok-keyword">let</span> x"#), "{html}");
+        assert!(html.contains(r#"<span class="tok-comment">/* note</span>"#), "{html}");
+        assert!(html.contains(r#"<span class="tok-comment">fn f() {}</span>"#), "{html}");
+        assert!(html.contains(r#"<span class="tok-comment">*/</span>"#), "{html}");
+        assert!(html.contains(r#"<span class="tok-keyword">let</span> y"#), "{html}");
+    }
+
+    #[test]
+    fn unclosed_block_comments_run_to_the_end() {
+        let html = highlight("rust", "/* open\nlet x = 1;\nfn main() {}");
+        assert!(!html.contains("tok-keyword"), "{html}");
+        assert!(html.contains(r#"<span class="tok-comment">fn main() {}</span>"#), "{html}");
+    }
+
+    #[test]
+    fn custom_language_definitions_are_highlighted() {
+        let definition = LanguageDefinition::new("pseudo")
+            .keywords(&["unless", "repeat"])
+            .line_comment(Some(";"))
+            .string_delimiters(&['`']);
+        let html = SyntaxHighlighter::with_language(definition, Theme::default())
+            .highlight("unless x repeat `let` ; if", 0..usize::MAX, &mut HighlightCache::default());
+        assert!(html.contains(r#"<span class="tok-keyword">unless</span>"#), "{html}");
+        assert!(html.contains(r#"<span class="tok-keyword">repeat</span>"#), "{html}");
+        assert!(html.contains(r#"<span class="tok-string">`let`</span>"#), "{html}");
+        assert!(html.contains(r#"<span class="tok-comment">; if</span>"#), "{html}");
+    }
+
+    #[test]
+    fn built_in_definitions_cover_python_and_go() {
+        assert!(highlight("python", "def f(): pass").contains(r#"<span class="tok-keyword">def</span>"#));
+        assert!(highlight("python", "x = 1 # note").contains(r#"<span class="tok-comment"># note</span>"#));
+        assert!(highlight("go", "func main() {}").contains(r#"<span class="tok-keyword">func</span>"#));
+    }
+}
@@ -0,0 +1,60 @@
+use std::ops::Range;
+
+/// How many columns `line`'s leading whitespace takes up, with tabs reaching
+/// the next multiple of `tab_width`. None for a blank line, which has no
+/// indentation of its own.
+fn indent_columns(line: &str, tab_width: usize) -> Option<usize> {
+    if line.trim().is_empty() {
+        return None;
+    }
+
+    let mut columns = 0;
+    for c in line.chars() {
+        match c {
+            ' ' => columns += 1,
+            '\t' => columns += tab_width - columns % tab_width,
+            _ => break,
+        }
+    }
+    Some(columns)
+}
+
+/// Where to draw indentation guides on `lines` of `text`, as (line, column)
+/// positions: one every `indent_width` columns up to each line's indentation.
+/// A blank line carries the guides of the less indented of the lines around
+/// it, so they run unbroken through gaps in a block.
+pub fn indent_guides(text: &str, lines: Range<usize>, indent_width: usize, tab_width: usize) -> Vec<(usize, usize)> {
+    let all_lines: Vec<&str> = text.split('\n').collect();
+    let start = lines.start.min(all_lines.len());
+    let end = lines.end.min(all_lines.len());
+    let (indent_width, tab_width) = (indent_width.max(1), tab_width.max(1));
+
+    let mut indents: Vec<Option<usize>> = all_lines[start..end].iter().map(|line| indent_columns(line, tab_width)).collect();
+
+    // Fill in blank lines from the nearest lines with text either side,
+    // which may be outside `lines`
+    let mut above = all_lines[..start].iter().rev().find_map(|line| indent_columns(line, tab_width));
+    let mut above_blank = vec![None; indents.len()];
+    for (idx, indent) in indents.iter().enumerate() {
+        match indent {
+            Some(_) => above = *indent,
+            None => above_blank[idx] = above,
+        }
+    }
+
+    let mut below = all_lines[end..].iter().find_map(|line| indent_columns(line, tab_width));
+    for (idx, indent) in indents.iter_mut().enumerate().rev() {
+        match indent {
+            Some(_) => below = *indent,
+            None => *indent = Some(above_blank[idx].unwrap_or(0).min(below.unwrap_or(0))),
+        }
+    }
+
+    indents
+        .into_iter()
+        .enumerate()
+        .flat_map(|(idx, indent)| {
+            (0..indent.unwrap_or(0)).step_by(indent_width).map(move |column| (start + idx, column))
+        })
+        .collect()
+}
@@ -0,0 +1,271 @@
+use super::chars::is_word_char;
+
+/// How a language's lines open and close blocks, for indenting as you type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LanguageRules {
+    /// What a line ends with to open a block, putting the next line one level in
+    pub indent_after: &'static [&'static str],
+    /// Chars that close a block. Typed at the start of a line they take it
+    /// back out to the indentation of the line the block was opened on.
+    pub dedent_on: &'static [char],
+}
+
+/// Rules for languages that don't indent blocks, or that we don't know
+const NO_RULES: LanguageRules = LanguageRules { indent_after: &[], dedent_on: &[] };
+
+const BRACE_RULES: LanguageRules = LanguageRules {
+    indent_after: &["{", "[", "("],
+    dedent_on: &['}', ']', ')'],
+};
+
+/// Blocks opened by a trailing colon and closed by indenting less
+const COLON_RULES: LanguageRules = LanguageRules { indent_after: &[":"], dedent_on: &[] };
+
+/// The indentation rules of each language that has them
+const LANGUAGE_RULES: &[(&str, LanguageRules)] = &[
+    ("rust", BRACE_RULES),
+    ("javascript", BRACE_RULES),
+    ("typescript", BRACE_RULES),
+    ("java", BRACE_RULES),
+    ("go", BRACE_RULES),
+    ("c", BRACE_RULES),
+    ("cpp", BRACE_RULES),
+    ("css", BRACE_RULES),
+    ("json", BRACE_RULES),
+    ("python", COLON_RULES),
+    ("yaml", COLON_RULES),
+    ("shell", LanguageRules { indent_after: &["then", "do", "{"], dedent_on: &['}'] }),
+];
+
+/// The rules for `language`, which are empty for one without any
+pub fn language_rules(language: &str) -> LanguageRules {
+    LANGUAGE_RULES
+        .iter()
+        .find(|(id, _)| *id == language)
+        .map(|(_, rules)| *rules)
+        .unwrap_or(NO_RULES)
+}
+
+/// The opening bracket `close` pairs with
+fn opener_of(close: char) -> Option<char> {
+    match close {
+        ')' => Some('('),
+        ']' => Some('['),
+        '}' => Some('{'),
+        _ => None,
+    }
+}
+
+fn leading_whitespace(line: &str) -> &str {
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+impl LanguageRules {
+    /// Whether `line` ends by opening a block. Words such as `do` only count
+    /// as a whole word, not the end of one like `undo`.
+    pub fn opens_block(&self, line: &str) -> bool {
+        let trimmed = line.trim_end();
+        self.indent_after.iter().any(|token| {
+            trimmed.strip_suffix(token).is_some_and(|before| {
+                !token.starts_with(is_word_char) || !before.ends_with(is_word_char)
+            })
+        })
+    }
+
+    /// The indentation a new line after `line_before_caret` should start with:
+    /// the same leading whitespace, plus `indent_unit` if the line opens a block
+    pub fn next_line_indent(&self, line_before_caret: &str, indent_unit: &str) -> String {
+        let mut indent = leading_whitespace(line_before_caret).to_string();
+        if self.opens_block(line_before_caret) {
+            indent.push_str(indent_unit);
+        }
+        indent
+    }
+
+    /// Where typing `typed` at the char offset `caret` in `text` should
+    /// re-indent its line, as the char offset the line starts at and the
+    /// indentation to replace everything up to the caret with. None unless
+    /// `typed` closes a block and only whitespace comes before it on the line,
+    /// or when the line is already indented to match the opener.
+    pub fn dedent(&self, text: &str, caret: usize, typed: char) -> Option<(usize, String)> {
+        if !self.dedent_on.contains(&typed) {
+            return None;
+        }
+
+        let before: Vec<char> = text.chars().take(caret).collect();
+        let line_start = before.iter().rposition(|c| *c == '\n').map(|idx| idx + 1).unwrap_or(0);
+        if !before[line_start..].iter().all(|c| *c == ' ' || *c == '\t') {
+            return None;
+        }
+
+        // The bracket left open that `typed` closes, skipping pairs closed since
+        let open = opener_of(typed)?;
+        let mut depth = 0;
+        let opener = before[..line_start].iter().rposition(|c| {
+            if *c == typed {
+                depth += 1;
+            } else if *c == open {
+                if depth == 0 {
+                    return true;
+                }
+                depth -= 1;
+            }
+            false
+        })?;
+
+        let opener_line_start = before[..opener].iter().rposition(|c| *c == '\n').map(|idx| idx + 1).unwrap_or(0);
+        let opener_line: String = before[opener_line_start..opener].iter().collect();
+        let indent = leading_whitespace(&opener_line).to_string();
+
+        let current: String = before[line_start..].iter().collect();
+        (current != indent).then_some((line_start, indent))
+    }
+}
+
+/// The char offsets the first and last lines a selection from `start` to
+/// `end` touches start and end at. A selection ending at the very start of a
+/// line doesn't take that line in.
+pub(super) fn selected_lines(chars: &[char], start: usize, end: usize) -> (usize, usize) {
+    let end = if end > start && chars[end - 1] == '\n' { end - 1 } else { end };
+    let first = chars[..start].iter().rposition(|c| *c == '\n').map(|idx| idx + 1).unwrap_or(0);
+    let last = chars[end..].iter().position(|c| *c == '\n').map(|idx| end + idx).unwrap_or(chars.len());
+    (first, last)
+}
+
+/// Every line the selection from `start` to `end` touches indented by one
+/// more `indent_unit`, as the char range of `text` the lines cover and what
+/// replaces it. Blank lines are left blank.
+pub fn indent_lines(text: &str, start: usize, end: usize, indent_unit: &str) -> (usize, usize, String) {
+    let chars: Vec<char> = text.chars().collect();
+    let (first, last) = selected_lines(&chars, start, end);
+    let lines: String = chars[first..last].iter().collect();
+
+    let indented: Vec<String> = lines
+        .split('\n')
+        .map(|line| if line.trim().is_empty() { line.to_string() } else { format!("{indent_unit}{line}") })
+        .collect();
+    (first, last, indented.join("\n"))
+}
+
+/// Every line the selection from `start` to `end` touches taken out a level,
+/// by a leading tab or up to `tab_width` leading spaces, as the char range of
+/// `text` the lines cover and what replaces it
+pub fn dedent_lines(text: &str, start: usize, end: usize, tab_width: usize) -> (usize, usize, String) {
+    let chars: Vec<char> = text.chars().collect();
+    let (first, last) = selected_lines(&chars, start, end);
+    let lines: String = chars[first..last].iter().collect();
+
+    let dedented: Vec<&str> = lines
+        .split('\n')
+        .map(|line| match line.strip_prefix('\t') {
+            Some(rest) => rest,
+            None => {
+                let spaces = line.len() - line.trim_start_matches(' ').len();
+                &line[spaces.min(tab_width)..]
+            }
+        })
+        .collect();
+    (first, last, dedented.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn python_indents_after_a_colon() {
+        let rules = language_rules("python");
+        assert_eq!(rules.next_line_indent("    def main():", "    "), "        ");
+        assert_eq!(rules.next_line_indent("    return x", "    "), "    ");
+        assert_eq!(rules.dedent("if x:\n    ", 10, '}'), None);
+    }
+
+    #[test]
+    fn brace_languages_indent_after_an_opener() {
+        let rules = language_rules("rust");
+        assert_eq!(rules.next_line_indent("fn main() {  ", "    "), "    ");
+        assert_eq!(rules.next_line_indent("\tlet v = vec![", "\t"), "\t\t");
+        assert_eq!(rules.next_line_indent("    x + 1", "    "), "    ");
+    }
+
+    #[test]
+    fn shell_keywords_only_count_as_whole_words() {
+        let rules = language_rules("shell");
+        assert!(rules.opens_block("for f in *; do"));
+        assert!(rules.opens_block("if [ -f x ]; then"));
+        assert!(!rules.opens_block("git undo"));
+    }
+
+    #[test]
+    fn unknown_languages_keep_the_indentation() {
+        let rules = language_rules("plaintext");
+        assert_eq!(rules, NO_RULES);
+        assert_eq!(rules.next_line_indent("  note: {", "    "), "  ");
+    }
+
+    #[test]
+    fn closing_brace_dedents_to_its_opener() {
+        let rules = language_rules("rust");
+        let text = "    if x {\n        y();\n        ";
+        let caret = text.chars().count();
+        assert_eq!(rules.dedent(text, caret, '}'), Some((24, "    ".to_string())));
+    }
+
+    #[test]
+    fn closing_brace_skips_pairs_closed_since() {
+        let rules = language_rules("rust");
+        let text = "fn f() {\n    let s = S { a: 1 };\n    ";
+        let caret = text.chars().count();
+        assert_eq!(rules.dedent(text, caret, '}'), Some((33, String::new())));
+    }
+
+    #[test]
+    fn closing_brace_leaves_other_lines_alone() {
+        let rules = language_rules("rust");
+        // Code before the brace on its line
+        let text = "if x {\n    y ";
+        assert_eq!(rules.dedent(text, text.chars().count(), '}'), None);
+        // Already lined up with the opener
+        let text = "if x {\n    y();\n";
+        assert_eq!(rules.dedent(text, text.chars().count(), '}'), None);
+        // Nothing open to close
+        let text = "x();\n    ";
+        assert_eq!(rules.dedent(text, text.chars().count(), '}'), None);
+    }
+
+    #[test]
+    fn indents_every_selected_line() {
+        let text = "fn f() {\na();\nb();\nc();\n}";
+        // From inside a(); to inside c();
+        let (first, last, indented) = indent_lines(text, 10, 21, "    ");
+        assert_eq!((first, last), (9, 23));
+        assert_eq!(indented, "    a();\n    b();\n    c();");
+    }
+
+    #[test]
+    fn indenting_leaves_blank_lines_blank() {
+        let (_, _, indented) = indent_lines("a\n\nb", 0, 4, "\t");
+        assert_eq!(indented, "\ta\n\n\tb");
+    }
+
+    #[test]
+    fn selection_ending_at_a_line_start_leaves_that_line_out() {
+        let (first, last, indented) = indent_lines("a\nb\nc", 0, 4, "  ");
+        assert_eq!((first, last), (0, 3));
+        assert_eq!(indented, "  a\n  b");
+    }
+
+    #[test]
+    fn dedents_by_a_tab_or_up_to_tab_width_spaces() {
+        let text = "      six\n  two\n\tone tab\nnone";
+        let (first, last, dedented) = dedent_lines(text, 0, text.chars().count(), 4);
+        assert_eq!((first, last), (0, text.chars().count()));
+        assert_eq!(dedented, "  six\ntwo\none tab\nnone");
+    }
+
+    #[test]
+    fn dedents_only_one_tab() {
+        let (_, _, dedented) = dedent_lines("\t\tdeep", 0, 0, 4);
+        assert_eq!(dedented, "\tdeep");
+    }
+}
@@ -0,0 +1,190 @@
+/// Every language the highlighter knows, as (id, display name)
+pub const LANGUAGES: &[(&str, &str)] = &[
+    ("rust", "Rust"),
+    ("python", "Python"),
+    ("javascript", "JavaScript"),
+    ("go", "Go"),
+    ("c", "C"),
+    ("cpp", "C++"),
+    ("html", "HTML"),
+    ("css", "CSS"),
+    ("markdown", "Markdown"),
+    ("json", "JSON"),
+    ("toml", "TOML"),
+    ("yaml", "YAML"),
+    ("shell", "Shell"),
+    ("plain", "Plain Text"),
+];
+
+/// Whether documents in `language` are mostly prose, worth spell checking
+pub fn is_prose(language: &str) -> bool {
+    matches!(language, "markdown" | "plain")
+}
+
+/// The highlighter language of files ending in `.extension`, if the editor knows it
+pub fn language_for_extension(extension: &str) -> Option<&'static str> {
+    match extension.to_lowercase().as_str() {
+        "rs" => Some("rust"),
+        "py" => Some("python"),
+        "js" => Some("javascript"),
+        "go" => Some("go"),
+        "c" | "h" => Some("c"),
+        "cpp" | "hpp" | "cc" => Some("cpp"),
+        "html" => Some("html"),
+        "css" => Some("css"),
+        "md" => Some("markdown"),
+        "json" => Some("json"),
+        "toml" => Some("toml"),
+        "yaml" | "yml" => Some("yaml"),
+        "sh" | "bash" | "zsh" => Some("shell"),
+        "txt" => Some("plain"),
+        _ => None,
+    }
+}
+
+/// Extensions of text files the highlighter has no language for
+const OTHER_TEXT_EXTENSIONS: &[&str] = &[
+    "log", "csv", "tsv", "ini", "cfg", "conf", "env", "xml", "svg", "lock",
+    "ts", "tsx", "jsx", "java", "kt", "rb", "php", "sql", "scss", "lua",
+];
+
+/// Whether a file called `name` is likely text the editor can open. Names
+/// without an extension, like `Makefile` or `.gitignore`, usually are.
+pub fn is_text_file_name(name: &str) -> bool {
+    match name.rsplit_once('.') {
+        None | Some(("", _)) => true,
+        Some((_, extension)) => {
+            language_for_extension(extension).is_some()
+                || OTHER_TEXT_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+        },
+    }
+}
+
+/// The extension a new file in `language` is given
+pub fn extension_for(language: &str) -> &'static str {
+    match language {
+        "rust" => "rs",
+        "python" => "py",
+        "javascript" => "js",
+        "go" => "go",
+        "c" => "c",
+        "cpp" => "cpp",
+        "html" => "html",
+        "css" => "css",
+        "markdown" => "md",
+        "json" => "json",
+        "toml" => "toml",
+        "yaml" => "yaml",
+        "shell" => "sh",
+        _ => "txt",
+    }
+}
+
+/// The highlighter language for a file, going by its extension
+pub fn language_from_filename(name: &str) -> &'static str {
+    name.rsplit_once('.')
+        .and_then(|(_, extension)| language_for_extension(extension))
+        .unwrap_or("plain")
+}
+
+/// The language of a script run by `interpreter`, the program named on its
+/// `#!` line
+fn language_for_interpreter(interpreter: &str) -> Option<&'static str> {
+    // Versions like python3 or python3.12 run the same language
+    let name = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    match name {
+        "python" => Some("python"),
+        "node" | "nodejs" | "deno" | "bun" => Some("javascript"),
+        "sh" | "bash" | "zsh" | "ksh" | "dash" => Some("shell"),
+        _ => None,
+    }
+}
+
+/// The language a `#!` first line says a file is in
+fn language_from_shebang(first_line: &str) -> Option<&'static str> {
+    let mut words = first_line.strip_prefix("#!")?.split_whitespace();
+    let program = words.next()?.rsplit('/').next()?;
+
+    // `#!/usr/bin/env [-S] python3` names the interpreter after env
+    let interpreter = if program == "env" {
+        words.find(|word| !word.starts_with('-'))?
+    } else {
+        program
+    };
+    language_for_interpreter(interpreter)
+}
+
+/// The language a file looks to be in from how it starts, for files whose
+/// name gives nothing away
+fn language_from_content(content: &str) -> Option<&'static str> {
+    let content = content.trim_start_matches('\u{feff}');
+    if let Some(language) = content.lines().next().and_then(language_from_shebang) {
+        return Some(language);
+    }
+
+    let start = content.trim_start();
+    let first_line = start.lines().next().unwrap_or_default().trim_end();
+    let lowercase: String = start.chars().take(16).collect::<String>().to_lowercase();
+
+    if start.starts_with('{') {
+        Some("json")
+    } else if start.starts_with('[') {
+        // A line like `[package]` opens a TOML table rather than a JSON array
+        let is_table = first_line
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+            .is_some_and(|name| !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || "_-.[]".contains(c)));
+        Some(if is_table { "toml" } else { "json" })
+    } else if lowercase.starts_with("<!doctype html") || lowercase.starts_with("<html") {
+        Some("html")
+    } else {
+        None
+    }
+}
+
+/// The highlighter language for a file, going by its extension where the
+/// editor knows it and otherwise by its contents. Plain text files are
+/// looked into too, since plenty of them are really something else.
+pub fn detect_language(filename: Option<&str>, content: &str) -> String {
+    let from_name = filename.map(language_from_filename).filter(|language| *language != "plain");
+
+    from_name
+        .or_else(|| language_from_content(content))
+        .unwrap_or("plain")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_extension_wins() {
+        assert_eq!(detect_language(Some("main.rs"), "#!/bin/bash\n"), "rust");
+        assert_eq!(detect_language(Some("data.JSON"), ""), "json");
+    }
+
+    #[test]
+    fn detects_shebangs() {
+        assert_eq!(detect_language(Some("deploy"), "#!/bin/bash\nset -e\n"), "shell");
+        assert_eq!(detect_language(Some("tool"), "#!/usr/bin/env python3\nprint()\n"), "python");
+        assert_eq!(detect_language(None, "#!/usr/bin/env -S node --no-warnings\n"), "javascript");
+        assert_eq!(detect_language(None, "\u{feff}#!/usr/bin/python3.12\n"), "python");
+        assert_eq!(detect_language(None, "#!/usr/bin/perl\n"), "plain");
+    }
+
+    #[test]
+    fn sniffs_content_of_plain_and_unnamed_files() {
+        assert_eq!(detect_language(Some("notes.txt"), "  {\"a\": 1}"), "json");
+        assert_eq!(detect_language(None, "[1, 2, 3]"), "json");
+        assert_eq!(detect_language(None, "[package]\nname = \"x\"\n"), "toml");
+        assert_eq!(detect_language(Some("page"), "<!DOCTYPE html>\n<html>"), "html");
+        assert_eq!(detect_language(None, "<html lang=\"en\">"), "html");
+    }
+
+    #[test]
+    fn anything_else_is_plain() {
+        assert_eq!(detect_language(Some("README"), "Just some words\n"), "plain");
+        assert_eq!(detect_language(None, ""), "plain");
+    }
+}
@@ -0,0 +1,133 @@
+/// What the highlighter needs to know to split a language's code into
+/// tokens. Rules only one language has, like Rust's lifetimes or Go's raw
+/// strings, stay with the highlighter and go by the definition's name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanguageDefinition {
+    /// The language's id, such as `rust`
+    pub name: String,
+    pub keywords: Vec<String>,
+    /// What starts a comment that runs to the end of the line
+    pub line_comment: Option<String>,
+    /// What opens and closes a comment that can run over several lines.
+    /// They don't nest, the first close ends the comment.
+    pub block_comment: Option<(String, String)>,
+    /// The quotes strings open and close with
+    pub string_delimiters: Vec<char>,
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "fn", "let", "mut", "pub", "impl", "struct", "enum", "trait", "use", "mod",
+    "match", "if", "else", "for", "while", "loop", "return", "self", "super", "where",
+];
+
+const JAVASCRIPT_KEYWORDS: &[&str] = &[
+    "function", "var", "let", "const", "class", "import", "export", "from", "return",
+    "if", "else", "for", "while", "switch", "case", "default", "break", "continue",
+];
+
+const GO_KEYWORDS: &[&str] = &[
+    "break", "case", "chan", "const", "continue", "default", "defer", "else",
+    "fallthrough", "for", "func", "go", "goto", "if", "import", "interface", "map",
+    "package", "range", "return", "select", "struct", "switch", "type", "var",
+    "nil", "true", "false",
+];
+
+const C_KEYWORDS: &[&str] = &[
+    "auto", "break", "case", "char", "const", "continue", "default", "do", "double",
+    "else", "enum", "extern", "float", "for", "goto", "if", "inline", "int", "long",
+    "register", "return", "short", "signed", "sizeof", "static", "struct", "switch",
+    "typedef", "union", "unsigned", "void", "volatile", "while", "NULL",
+];
+
+/// C++ has all of C's keywords as well as these
+const CPP_KEYWORDS: &[&str] = &[
+    "bool", "catch", "class", "constexpr", "const_cast", "delete", "dynamic_cast",
+    "explicit", "false", "friend", "namespace", "new", "noexcept", "nullptr",
+    "operator", "override", "private", "protected", "public", "reinterpret_cast",
+    "static_cast", "template", "this", "throw", "true", "try", "typename", "using",
+    "virtual",
+];
+
+const PYTHON_KEYWORDS: &[&str] = &[
+    "def", "class", "import", "from", "return", "if", "elif", "else", "for", "while",
+    "with", "as", "lambda", "yield", "try", "except", "finally", "raise", "pass",
+    "break", "continue", "in", "is", "not", "and", "or", "global", "nonlocal",
+    "assert", "del", "async", "await", "None", "True", "False",
+];
+
+/// Shell keywords and the builtins scripts lean on most
+const SHELL_KEYWORDS: &[&str] = &[
+    "if", "then", "else", "elif", "fi", "case", "esac", "for", "while", "until",
+    "do", "done", "in", "function", "select", "return", "break", "continue",
+    "local", "export", "readonly", "declare", "unset", "shift", "source", "exit",
+];
+
+// Literals in data files
+const JSON_KEYWORDS: &[&str] = &["true", "false", "null"];
+const TOML_KEYWORDS: &[&str] = &["true", "false", "inf", "nan"];
+const YAML_KEYWORDS: &[&str] = &["true", "false", "null", "yes", "no", "on", "off", "True", "False", "Null"];
+
+impl LanguageDefinition {
+    /// A language without keywords, with `//` line comments and `"` strings
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            keywords: Vec::new(),
+            line_comment: Some("//".to_string()),
+            block_comment: None,
+            string_delimiters: vec!['"'],
+        }
+    }
+
+    pub fn keywords(mut self, keywords: &[&str]) -> Self {
+        self.keywords = keywords.iter().map(|keyword| keyword.to_string()).collect();
+        self
+    }
+
+    pub fn line_comment(mut self, prefix: Option<&str>) -> Self {
+        self.line_comment = prefix.map(str::to_string);
+        self
+    }
+
+    pub fn block_comment(mut self, open: &str, close: &str) -> Self {
+        self.block_comment = Some((open.to_string(), close.to_string()));
+        self
+    }
+
+    pub fn string_delimiters(mut self, quotes: &[char]) -> Self {
+        self.string_delimiters = quotes.to_vec();
+        self
+    }
+
+    /// The definition the editor comes with for `language`. Languages it
+    /// doesn't know get the plain one `new` makes.
+    pub fn built_in(language: &str) -> Self {
+        let definition = Self::new(language);
+        let c_like = |keywords: &[&str]| {
+            Self::new(language).keywords(keywords).block_comment("/*", "*/").string_delimiters(&['"', '\''])
+        };
+
+        match language {
+            // Rust's `'` starts a char literal or a lifetime, never a string
+            "rust" => definition.keywords(RUST_KEYWORDS).block_comment("/*", "*/"),
+            "javascript" => c_like(JAVASCRIPT_KEYWORDS),
+            "go" => c_like(GO_KEYWORDS),
+            "c" => c_like(C_KEYWORDS),
+            "cpp" => c_like(&[C_KEYWORDS, CPP_KEYWORDS].concat()),
+            "python" => definition.keywords(PYTHON_KEYWORDS).line_comment(Some("#")).string_delimiters(&['"', '\'']),
+            "shell" => definition.keywords(SHELL_KEYWORDS).line_comment(Some("#")).string_delimiters(&['"', '\'']),
+            "json" => definition.keywords(JSON_KEYWORDS).line_comment(None),
+            "toml" => definition.keywords(TOML_KEYWORDS).line_comment(Some("#")),
+            "yaml" => definition.keywords(YAML_KEYWORDS).line_comment(Some("#")),
+            // Markup and CSS only comment in blocks
+            "html" => definition.line_comment(None).block_comment("<!--", "-->"),
+            "css" => definition.line_comment(None).block_comment("/*", "*/"),
+            "markdown" => definition.line_comment(None),
+            _ => definition,
+        }
+    }
+
+    pub fn is_keyword(&self, word: &str) -> bool {
+        self.keywords.iter().any(|keyword| keyword == word)
+    }
+}
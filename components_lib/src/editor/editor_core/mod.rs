@@ -1,5 +1,50 @@
+pub mod brackets;
 pub mod buffer;
+pub mod chars;
+pub mod comments;
+pub mod completion;
 pub mod cursor;
+pub mod diagnostics;
+pub mod diff;
+pub mod edit;
+pub mod encoding;
+pub mod folding;
+pub mod font;
+pub mod hidden_chars;
+pub mod highlighter;
+pub mod indent_guides;
+pub mod indent_rules;
+pub mod language;
+pub mod language_definition;
+pub mod open_documents;
+pub mod search;
+pub mod settings;
+pub mod stats;
+pub mod templates;
+pub mod validation;
 
+pub use brackets::{AUTO_CLOSE_PAIRS, auto_close, bracket_jump, closing_for, find_matching_bracket, steps_over};
 pub use buffer::Buffer;
-pub use cursor::CursorPosition;
\ No newline at end of file
+pub use comments::{toggle_comment, toggle_comment_lines};
+pub use completion::{WordIndex, word_before};
+pub use cursor::{CursorPosition, Selection};
+pub use diagnostics::{Diagnostic, Severity};
+pub use diff::{DiffKind, DiffLine, line_diff};
+pub use edit::Edit;
+pub use encoding::BufferEncoding;
+pub use folding::{FoldRegion, FoldedText, fold_regions, shift_folds};
+pub use font::{EditorFont, MONOSPACE_FONTS, next_zoom};
+pub use hidden_chars::find_suspicious_chars;
+pub use highlighter::{HighlightCache, SyntaxHighlighter, escape_html, highlight_stylesheet, highlight_to_html};
+pub use indent_guides::indent_guides;
+pub use indent_rules::{LanguageRules, dedent_lines, indent_lines, language_rules};
+pub use language::{
+    LANGUAGES, detect_language, extension_for, is_prose, is_text_file_name, language_for_extension, language_from_filename,
+};
+pub use language_definition::LanguageDefinition;
+pub use open_documents::{OpenDocument, OpenDocuments};
+pub use search::{find_all, find_all_ci};
+pub use settings::EditorSettings;
+pub use stats::{BufferStats, IndentStyle};
+pub use templates::{Template, template_for};
+pub use validation::{ValidationError, Validator, validator_for};
\ No newline at end of file
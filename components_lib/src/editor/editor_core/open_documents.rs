@@ -0,0 +1,253 @@
+use super::Buffer;
+
+/// One document open in its own tab
+#[derive(Clone, PartialEq)]
+pub struct OpenDocument {
+    /// Stable for as long as the tab is open, unlike its index
+    pub id: u32,
+    pub buffer: Buffer,
+    pub filename: Option<String>,
+    pub language: String,
+    /// Shown without letting the user edit it
+    pub read_only: bool,
+    /// A throwaway buffer that's never saved, autosaved or asked about on close
+    pub scratch: bool,
+    /// Kept at the left of the tab bar with the other pinned tabs, and left
+    /// open by "Close Others" and "Close to the Right"
+    pub pinned: bool,
+}
+
+impl OpenDocument {
+    /// The name shown on the document's tab
+    pub fn title(&self) -> String {
+        self.filename.clone().unwrap_or_else(|| "untitled".to_string())
+    }
+
+    /// Whether the document has changes that would be lost on closing it.
+    /// Scratch buffers never do.
+    pub fn has_unsaved_changes(&self) -> bool {
+        !self.scratch && self.buffer.is_modified()
+    }
+
+    /// An untitled document nobody has typed into yet
+    fn is_pristine(&self) -> bool {
+        self.filename.is_none() && !self.buffer.is_modified() && self.buffer.text().is_empty()
+    }
+}
+
+/// Every open document and which one is being edited. There is always at
+/// least one document open.
+#[derive(Clone, PartialEq)]
+pub struct OpenDocuments {
+    documents: Vec<OpenDocument>,
+    active: usize,
+    next_id: u32,
+}
+
+impl Default for OpenDocuments {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OpenDocuments {
+    pub fn new() -> Self {
+        let mut documents = Self {
+            documents: Vec::new(),
+            active: 0,
+            next_id: 0,
+        };
+        documents.push(Buffer::new(), None, "plain".to_string());
+        documents
+    }
+
+    fn push(&mut self, buffer: Buffer, filename: Option<String>, language: String) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.documents.push(OpenDocument { id, buffer, filename, language, read_only: false, scratch: false, pinned: false });
+        self.active = self.documents.len() - 1;
+        id
+    }
+
+    /// Open a document in a new tab and switch to it. An empty untitled tab is
+    /// reused rather than left behind.
+    pub fn open(&mut self, buffer: Buffer, filename: Option<String>, language: String) -> u32 {
+        if self.active().is_pristine() {
+            let active = self.active_mut();
+            active.buffer = buffer;
+            active.filename = filename;
+            active.language = language;
+            active.read_only = false;
+            active.scratch = false;
+            return active.id;
+        }
+
+        self.push(buffer, filename, language)
+    }
+
+    /// Open a new, empty tab and switch to it
+    pub fn new_document(&mut self, filename: Option<String>, language: String) -> u32 {
+        self.open(Buffer::new(), filename, language)
+    }
+
+    /// Open a new, empty scratch buffer and switch to it
+    pub fn new_scratch(&mut self) -> u32 {
+        let id = self.new_document(None, "plain".to_string());
+        if let Some(document) = self.get_mut(id) {
+            document.scratch = true;
+        }
+        id
+    }
+
+    pub fn documents(&self) -> &[OpenDocument] {
+        &self.documents
+    }
+
+    pub fn active_index(&self) -> usize {
+        self.active
+    }
+
+    pub fn active(&self) -> &OpenDocument {
+        &self.documents[self.active]
+    }
+
+    pub fn active_mut(&mut self) -> &mut OpenDocument {
+        &mut self.documents[self.active]
+    }
+
+    pub fn get_mut(&mut self, id: u32) -> Option<&mut OpenDocument> {
+        self.documents.iter_mut().find(|document| document.id == id)
+    }
+
+    pub fn set_active(&mut self, index: usize) {
+        if index < self.documents.len() {
+            self.active = index;
+        }
+    }
+
+    /// Close the tab at `index`. Closing the last tab leaves a fresh untitled one.
+    pub fn close(&mut self, index: usize) -> Option<OpenDocument> {
+        if index >= self.documents.len() {
+            return None;
+        }
+
+        let closed = self.documents.remove(index);
+
+        if self.documents.is_empty() {
+            self.push(Buffer::new(), None, "plain".to_string());
+        } else if self.active > index || self.active == self.documents.len() {
+            self.active -= 1;
+        }
+
+        Some(closed)
+    }
+
+    /// How many tabs are pinned, which are always the first ones
+    fn pinned_count(&self) -> usize {
+        self.documents.iter().filter(|document| document.pinned).count()
+    }
+
+    /// Move the tab at `from` to `to`, keeping the same document active.
+    /// Pinned tabs only move among the pinned ones and the rest only after them.
+    pub fn move_tab(&mut self, from: usize, to: usize) {
+        if from >= self.documents.len() {
+            return;
+        }
+
+        let pinned_count = self.pinned_count();
+        let to = if self.documents[from].pinned {
+            to.min(pinned_count - 1)
+        } else {
+            to.clamp(pinned_count, self.documents.len() - 1)
+        };
+        self.reposition(from, to);
+    }
+
+    /// Pin or unpin the tab at `index`, moving it to the end of the pinned
+    /// tabs or just after them
+    pub fn set_pinned(&mut self, index: usize, pinned: bool) {
+        let Some(document) = self.documents.get_mut(index) else {
+            return;
+        };
+        if document.pinned == pinned {
+            return;
+        }
+
+        document.pinned = pinned;
+        // Not counting the tab itself, wherever it was
+        let boundary = self.pinned_count() - usize::from(pinned);
+        self.reposition(index, boundary);
+    }
+
+    fn reposition(&mut self, from: usize, to: usize) {
+        let active_id = self.active().id;
+        let document = self.documents.remove(from);
+        self.documents.insert(to, document);
+        self.active = self.documents.iter().position(|document| document.id == active_id).unwrap_or(0);
+    }
+
+    /// The documents `close_others(index)` would close
+    pub fn others(&self, index: usize) -> Vec<&OpenDocument> {
+        self.documents
+            .iter()
+            .enumerate()
+            .filter(|(other, document)| *other != index && !document.pinned)
+            .map(|(_, document)| document)
+            .collect()
+    }
+
+    /// The documents `close_to_right(index)` would close
+    pub fn to_right(&self, index: usize) -> Vec<&OpenDocument> {
+        self.documents.iter().skip(index + 1).filter(|document| !document.pinned).collect()
+    }
+
+    /// Close every tab `closes` picks by index. The active document stays
+    /// active if it's left open, otherwise the one at `index` takes over.
+    /// Returns the closed documents.
+    fn close_where(&mut self, index: usize, closes: impl Fn(usize, &OpenDocument) -> bool) -> Vec<OpenDocument> {
+        let Some(fallback_id) = self.documents.get(index).map(|document| document.id) else {
+            return Vec::new();
+        };
+        let active_id = self.active().id;
+
+        let mut closed = Vec::new();
+        for (idx, document) in std::mem::take(&mut self.documents).into_iter().enumerate() {
+            if closes(idx, &document) {
+                closed.push(document);
+            } else {
+                self.documents.push(document);
+            }
+        }
+
+        self.active = self
+            .documents
+            .iter()
+            .position(|document| document.id == active_id)
+            .or_else(|| self.documents.iter().position(|document| document.id == fallback_id))
+            .unwrap_or(0);
+        closed
+    }
+
+    /// Close every tab but the one at `index` and the pinned ones, switching
+    /// to the one at `index`. Returns the closed documents.
+    pub fn close_others(&mut self, index: usize) -> Vec<OpenDocument> {
+        let Some(kept_id) = self.documents.get(index).map(|document| document.id) else {
+            return Vec::new();
+        };
+
+        let closed = self.close_where(index, |other, document| other != index && !document.pinned);
+        self.active = self.documents.iter().position(|document| document.id == kept_id).unwrap_or(0);
+        closed
+    }
+
+    /// Close every tab after the one at `index` except pinned ones, switching
+    /// to it if the active one is closed. Returns the closed documents.
+    pub fn close_to_right(&mut self, index: usize) -> Vec<OpenDocument> {
+        self.close_where(index, |other, document| other > index && !document.pinned)
+    }
+
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.documents.iter().any(OpenDocument::has_unsaved_changes)
+    }
+}
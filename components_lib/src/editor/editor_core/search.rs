@@ -0,0 +1,73 @@
+/// Char offsets of every match of `needle` in `text`, left to right. Matches
+/// don't overlap, so searching "aa" in "aaa" finds only the first.
+pub fn find_all(text: &str, needle: &str) -> Vec<usize> {
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches = Vec::new();
+    let mut chars_before = 0;
+    let mut searched_to = 0;
+
+    for (byte_idx, _) in text.match_indices(needle) {
+        chars_before += text[searched_to..byte_idx].chars().count();
+        matches.push(chars_before);
+        searched_to = byte_idx;
+    }
+
+    matches
+}
+
+/// Char offsets of every match of `needle` in `text` ignoring case, left to
+/// right and without overlaps. Chars match when their lowercase forms do, so
+/// a match is always as many chars long as `needle`.
+pub fn find_all_ci(text: &str, needle: &str) -> Vec<usize> {
+    let needle: Vec<char> = needle.chars().collect();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    let text: Vec<char> = text.chars().collect();
+    let same = |a: char, b: char| a == b || a.to_lowercase().eq(b.to_lowercase());
+
+    let mut matches = Vec::new();
+    let mut idx = 0;
+    while idx + needle.len() <= text.len() {
+        if text[idx..idx + needle.len()].iter().zip(&needle).all(|(a, b)| same(*a, *b)) {
+            matches.push(idx);
+            idx += needle.len();
+        } else {
+            idx += 1;
+        }
+    }
+
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_every_match_in_chars() {
+        assert_eq!(find_all("let x = x + 1;", "x"), [4, 8]);
+        assert_eq!(find_all("é x é x", "x"), [2, 6]);
+        assert_eq!(find_all("abc", "z"), Vec::<usize>::new());
+        assert_eq!(find_all("abc", ""), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn matches_do_not_overlap() {
+        assert_eq!(find_all("aaaa", "aa"), [0, 2]);
+        assert_eq!(find_all("aaa", "aa"), [0]);
+        assert_eq!(find_all_ci("AaA", "aa"), [0]);
+    }
+
+    #[test]
+    fn case_insensitive_matches_ignore_case() {
+        assert_eq!(find_all_ci("Value value VALUE", "value"), [0, 6, 12]);
+        assert_eq!(find_all_ci("Straße STRASSE", "straße"), [0]);
+        assert_eq!(find_all_ci("ÉCOLE école", "École"), [0, 6]);
+        assert_eq!(find_all("Value value", "value"), [6]);
+    }
+}
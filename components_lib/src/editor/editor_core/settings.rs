@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use super::EditorFont;
+use super::font::DEFAULT_ZOOM;
+
+pub const MIN_TAB_WIDTH: usize = 1;
+pub const MAX_TAB_WIDTH: usize = 8;
+
+/// Everything the user can configure about the editor, saved as one piece.
+/// Settings missing from what was saved take their default.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditorSettings {
+    /// Columns between tab stops, and spaces in one indent level
+    pub tab_width: usize,
+    /// Indent with spaces rather than tab characters
+    pub insert_spaces: bool,
+    pub word_wrap: bool,
+    pub show_line_numbers: bool,
+    pub show_minimap: bool,
+    /// Faint lines down through each level of indentation
+    pub show_indent_guides: bool,
+    /// Offer words from the document to finish the one being typed
+    pub word_completion: bool,
+    pub font: EditorFont,
+    /// How far the text is zoomed in or out from the font's size, in percent
+    pub zoom: u32,
+    /// Remove spaces and tabs from the ends of lines when saving
+    pub trim_trailing_whitespace: bool,
+    /// End the text with exactly one line break when saving
+    pub insert_final_newline: bool,
+    /// The theme the user picked, or None to follow the system's light or dark mode
+    pub theme: Option<String>,
+    /// Reopen the tabs that were open when the page was last closed
+    pub restore_session: bool,
+    /// Send documents to the server's linters and mark what they find
+    pub server_linting: bool,
+}
+
+impl Default for EditorSettings {
+    fn default() -> Self {
+        Self {
+            tab_width: 4,
+            insert_spaces: true,
+            word_wrap: false,
+            show_line_numbers: true,
+            show_minimap: false,
+            show_indent_guides: true,
+            word_completion: true,
+            font: EditorFont::default(),
+            zoom: DEFAULT_ZOOM,
+            trim_trailing_whitespace: false,
+            insert_final_newline: false,
+            theme: None,
+            restore_session: true,
+            server_linting: true,
+        }
+    }
+}
+
+impl EditorSettings {
+    /// The font the text is shown in, zoomed
+    pub fn display_font(&self) -> EditorFont {
+        self.font.zoomed(self.zoom)
+    }
+
+    /// What one level of indentation is made of
+    pub fn indent_unit(&self) -> String {
+        if self.insert_spaces {
+            " ".repeat(self.tab_width)
+        } else {
+            "\t".to_string()
+        }
+    }
+}
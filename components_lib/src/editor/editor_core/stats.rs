@@ -0,0 +1,144 @@
+use ropey::RopeSlice;
+use std::collections::HashMap;
+
+/// Indented lines looked at when working out a document's indentation
+const INDENT_SAMPLE_LINES: usize = 1000;
+
+/// How a document's lines are indented
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndentStyle {
+    Tabs,
+    /// Each level is this many spaces
+    Spaces(usize),
+    /// Some lines start with tabs and others with spaces
+    Mixed,
+}
+
+impl IndentStyle {
+    pub fn label(&self) -> String {
+        match self {
+            IndentStyle::Tabs => "Tabs".to_string(),
+            IndentStyle::Spaces(1) => "1 space".to_string(),
+            IndentStyle::Spaces(width) => format!("{width} spaces"),
+            IndentStyle::Mixed => "Mixed tabs and spaces".to_string(),
+        }
+    }
+}
+
+/// Facts about a document's text, worked out in one pass over it
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BufferStats {
+    /// In chars, not counting the line break
+    pub longest_line_len: usize,
+    pub total_chars: usize,
+    /// None when no line is indented
+    pub detected_indent: Option<IndentStyle>,
+    /// Lines with spaces or tabs before their line break
+    pub trailing_whitespace_lines: usize,
+}
+
+/// The indentation of lines whose leading whitespace is `indents`, as (tabs,
+/// spaces) counts. A level of spaces is the step seen most often between one
+/// line's indent and the next.
+fn detect_indent(indents: &[(usize, usize)]) -> Option<IndentStyle> {
+    let tab_lines = indents.iter().filter(|(tabs, _)| *tabs > 0).count();
+    let space_lines = indents.iter().filter(|(tabs, spaces)| *tabs == 0 && *spaces > 0).count();
+
+    match (tab_lines, space_lines) {
+        (0, 0) => None,
+        (_, 0) => Some(IndentStyle::Tabs),
+        (0, _) => {
+            let mut steps = HashMap::<usize, usize>::new();
+            let mut previous = 0;
+            for (_, spaces) in indents {
+                if *spaces != previous {
+                    *steps.entry(spaces.abs_diff(previous)).or_default() += 1;
+                }
+                previous = *spaces;
+            }
+
+            // The most common step, the smaller one on a tie
+            let width = steps
+                .into_iter()
+                .max_by_key(|(step, count)| (*count, std::cmp::Reverse(*step)))
+                .map(|(step, _)| step)?;
+            Some(IndentStyle::Spaces(width))
+        },
+        _ => Some(IndentStyle::Mixed),
+    }
+}
+
+/// Stats for a text from its lines, without their line breaks. Counting its
+/// chars is left to the caller, who can do it without going over the lines.
+pub fn buffer_stats<'a>(lines: impl Iterator<Item = RopeSlice<'a>>) -> BufferStats {
+    let mut stats = BufferStats::default();
+    // Leading (tabs, spaces) of the non-empty lines, with unindented ones as (0, 0)
+    let mut indents = Vec::new();
+    let mut indented_lines = 0;
+
+    for line in lines {
+        let line_len = line.len_chars();
+        stats.longest_line_len = stats.longest_line_len.max(line_len);
+
+        let Some(first) = line.chars().find(|c| !c.is_whitespace()) else {
+            continue;
+        };
+        if matches!(line.char(line_len - 1), ' ' | '\t') {
+            stats.trailing_whitespace_lines += 1;
+        }
+
+        // The ` * ` lines of a block comment line up with its `/*`, not with the code
+        if indented_lines < INDENT_SAMPLE_LINES && first != '*' {
+            let leading: Vec<char> = line.chars().take_while(|c| *c == ' ' || *c == '\t').collect();
+            let tabs = leading.iter().filter(|c| **c == '\t').count();
+            if !leading.is_empty() {
+                indented_lines += 1;
+            }
+            indents.push((tabs, leading.len() - tabs));
+        }
+    }
+
+    stats.detected_indent = detect_indent(&indents);
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::editor_core::Buffer;
+
+    fn stats(text: &str) -> BufferStats {
+        Buffer::from_str(text, None).stats()
+    }
+
+    #[test]
+    fn detects_tab_indentation() {
+        let stats = stats("fn main() {\n\tif x {\n\t\ty();\n\t}\n}\n");
+        assert_eq!(stats.detected_indent, Some(IndentStyle::Tabs));
+    }
+
+    #[test]
+    fn detects_the_width_of_space_indentation() {
+        assert_eq!(stats("def f():\n    if x:\n        y()\n    return\n").detected_indent, Some(IndentStyle::Spaces(4)));
+        assert_eq!(stats("a:\n  b:\n    c: 1\n  d: 2\n").detected_indent, Some(IndentStyle::Spaces(2)));
+    }
+
+    #[test]
+    fn detects_mixed_indentation() {
+        let stats = stats("fn main() {\n\tx();\n    y();\n}\n");
+        assert_eq!(stats.detected_indent, Some(IndentStyle::Mixed));
+    }
+
+    #[test]
+    fn unindented_text_has_no_indent_style() {
+        assert_eq!(stats("one\ntwo\n").detected_indent, None);
+    }
+
+    #[test]
+    fn counts_long_lines_and_trailing_whitespace() {
+        let stats = stats("short \nthe longest line\t\nend");
+        assert_eq!(stats.longest_line_len, 17);
+        assert_eq!(stats.total_chars, 28);
+        assert_eq!(stats.trailing_whitespace_lines, 2);
+    }
+}
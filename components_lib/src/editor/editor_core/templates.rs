@@ -0,0 +1,38 @@
+/// Where the caret goes in a template, the way snippets mark it
+const CURSOR_MARKER: &str = "$0";
+
+/// What a new file in each language starts with, as (language id, template).
+/// Each template marks where the caret goes with `CURSOR_MARKER`.
+const TEMPLATES: &[(&str, &str)] = &[
+    ("rust", "fn main() {\n    $0\n}\n"),
+    ("python", "def main():\n    $0\n\n\nif __name__ == \"__main__\":\n    main()\n"),
+    ("go", "package main\n\nfunc main() {\n\t$0\n}\n"),
+    ("c", "#include <stdio.h>\n\nint main(void) {\n    $0\n    return 0;\n}\n"),
+    ("cpp", "#include <iostream>\n\nint main() {\n    $0\n    return 0;\n}\n"),
+    (
+        "html",
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n    <meta charset=\"UTF-8\">\n    \
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\">\n    \
+         <title></title>\n</head>\n<body>\n    $0\n</body>\n</html>\n",
+    ),
+    ("markdown", "# $0\n"),
+    ("json", "{\n    $0\n}\n"),
+];
+
+/// The text a new file starts with, and the char offset the caret starts at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pub text: String,
+    pub cursor: usize,
+}
+
+/// The template for new files in `language`, if it has one
+pub fn template_for(language: &str) -> Option<Template> {
+    let (_, template) = TEMPLATES.iter().find(|(id, _)| *id == language)?;
+    let (before, after) = template.split_once(CURSOR_MARKER).unwrap_or((template, ""));
+
+    Some(Template {
+        text: format!("{before}{after}"),
+        cursor: before.chars().count(),
+    })
+}
@@ -0,0 +1,98 @@
+/// Where and why a document failed to parse
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    pub message: String,
+    /// 0-based line and column of the first char at fault
+    pub line: usize,
+    pub column: usize,
+    /// Char offset of the first char at fault, and how many chars from there
+    /// on the same line are
+    pub offset: usize,
+    pub len: usize,
+}
+
+impl ValidationError {
+    /// The error spanning the byte range `start..end` of `text`, kept to the line it starts on
+    fn at_bytes(text: &str, start: usize, end: usize, message: String) -> Self {
+        let start = floor_char_boundary(text, start.min(text.len()));
+        let end = floor_char_boundary(text, end.clamp(start, text.len()));
+
+        let line_start = text[..start].rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+        let line_end = text[start..].find('\n').map(|idx| start + idx).unwrap_or(text.len());
+        let offset = text[..start].chars().count();
+
+        Self {
+            message,
+            line: text[..start].matches('\n').count(),
+            column: text[line_start..start].chars().count(),
+            offset,
+            len: text[start..end.min(line_end)].chars().count().max(1),
+        }
+    }
+}
+
+/// The closest char boundary at or before the byte offset `idx`
+fn floor_char_boundary(text: &str, mut idx: usize) -> usize {
+    while !text.is_char_boundary(idx) {
+        idx -= 1;
+    }
+    idx
+}
+
+/// Checks that a document is well formed in its language
+pub trait Validator {
+    fn validate(&self, text: &str) -> Result<(), ValidationError>;
+}
+
+pub struct JsonValidator;
+
+impl Validator for JsonValidator {
+    fn validate(&self, text: &str) -> Result<(), ValidationError> {
+        let err = match serde_json::from_str::<serde::de::IgnoredAny>(text) {
+            Ok(_) => return Ok(()),
+            Err(err) => err,
+        };
+
+        // serde_json counts 1-based lines and the bytes into them. Column 0
+        // means it had just read a line break, so the fault is at the end of
+        // whatever came before.
+        let line_start: usize = text
+            .split_inclusive('\n')
+            .take(err.line().saturating_sub(1))
+            .map(str::len)
+            .sum();
+        let start = match err.column() {
+            0 => text[..line_start].trim_end().len().saturating_sub(1),
+            column => line_start + column - 1,
+        };
+
+        // Its messages end with where the error is, which is shown separately
+        let message = err.to_string();
+        let message = message.split(" at line ").next().unwrap_or(&message).to_string();
+        Err(ValidationError::at_bytes(text, start, start + 1, message))
+    }
+}
+
+pub struct TomlValidator;
+
+impl Validator for TomlValidator {
+    fn validate(&self, text: &str) -> Result<(), ValidationError> {
+        let err = match text.parse::<toml::Table>() {
+            Ok(_) => return Ok(()),
+            Err(err) => err,
+        };
+
+        let span = err.span().unwrap_or(text.len()..text.len());
+        let message = err.message().lines().collect::<Vec<_>>().join(": ");
+        Err(ValidationError::at_bytes(text, span.start, span.end, message))
+    }
+}
+
+/// The validator for documents in `language`, if there is one
+pub fn validator_for(language: &str) -> Option<&'static dyn Validator> {
+    match language {
+        "json" => Some(&JsonValidator),
+        "toml" => Some(&TomlValidator),
+        _ => None,
+    }
+}
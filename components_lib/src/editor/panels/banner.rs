@@ -0,0 +1,34 @@
+use dioxus::prelude::*;
+use crate::core::Theme;
+
+#[component]
+pub fn Banner(
+    theme: Theme,
+    message: String,
+    action_label: String,
+    on_action: EventHandler<()>,
+) -> Element {
+    let style = format!(
+        "display: flex; align-items: center; padding: 0.25rem 0.5rem; font-size: 12px;
+         background-color: {}; color: {};",
+         theme.ui.toolbar_bg, theme.ui.toolbar_fg
+    );
+
+    let button_style = format!(
+        "margin-left: auto; padding: 0.1rem 0.5rem; border: none; border-radius: 4px;
+         cursor: pointer; background-color: {}; color: {};",
+         theme.ui.button, theme.ui.toolbar_fg
+    );
+
+    rsx! {
+        div {
+            style: style,
+            span { "{message}" }
+            button {
+                style: button_style,
+                onclick: move |_| on_action.call(()),
+                "{action_label}"
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use dioxus::prelude::*;
+use crate::core::Theme;
+use crate::editor::editor_core::{DiffKind, line_diff};
+
+/// What a line of the diff starts with
+fn marker(kind: DiffKind) -> &'static str {
+    match kind {
+        DiffKind::Added => "+ ",
+        DiffKind::Removed => "- ",
+        DiffKind::Unchanged => "  ",
+    }
+}
+
+/// The changes made to a document since it was loaded or last saved
+#[component]
+pub fn DiffView(
+    theme: Theme,
+    saved_text: String,
+    current_text: String,
+    on_close: EventHandler<()>,
+) -> Element {
+    let diff = line_diff(&saved_text, &current_text);
+    let added = diff.iter().filter(|line| line.kind == DiffKind::Added).count();
+    let removed = diff.iter().filter(|line| line.kind == DiffKind::Removed).count();
+
+    let dialog_style = "position: fixed; top: 0; left: 0; right: 0; bottom: 0;
+         background-color: rgba(0, 0, 0, 0.7);
+         display: flex; align-items: center; justify-content: center;
+         z-index: 100;";
+
+    let panel_style = format!(
+        "background-color: {}; color: {}; padding: 1.5rem;
+         border-radius: 4px; width: 80vw; max-height: 80vh;
+         display: flex; flex-direction: column;",
+         theme.background, theme.foreground
+    );
+
+    let lines_style = "flex: 1; overflow: auto; margin: 0.5rem 0 1rem; border: 1px solid #555;
+         font-family: 'Fira Code', monospace; font-size: 13px; line-height: 1.5;
+         white-space: pre;";
+
+    let button_style = "padding: 0.5rem 1rem; border: none; border-radius: 3px;
+         cursor: pointer; background-color: #0078d7; color: white;";
+
+    rsx! {
+        div {
+            style: dialog_style,
+            tabindex: "-1",
+            onmounted: move |event| async move {
+                let _ = event.set_focus(true).await;
+            },
+            onkeydown: move |event: Event<KeyboardData>| {
+                if event.key() == Key::Escape {
+                    on_close.call(());
+                }
+            },
+            div {
+                style: panel_style,
+                h3 { "Changes Since Last Save" }
+                div {
+                    style: "font-size: 12px; opacity: 0.8;",
+                    "{added} added, {removed} removed"
+                }
+
+                div {
+                    style: lines_style,
+                    if added == 0 && removed == 0 {
+                        div {
+                            style: "padding: 0.5rem; font-style: italic; opacity: 0.7;",
+                            "No changes"
+                        }
+                    } else {
+                        for line in diff {
+                            div {
+                                style: match line.kind {
+                                    DiffKind::Added => "padding: 0 0.5rem; background-color: rgba(46, 160, 67, 0.25);",
+                                    DiffKind::Removed => "padding: 0 0.5rem; background-color: rgba(248, 81, 73, 0.25);",
+                                    DiffKind::Unchanged => "padding: 0 0.5rem;",
+                                },
+                                "{marker(line.kind)}{line.text}"
+                            }
+                        }
+                    }
+                }
+
+                div {
+                    style: "display: flex; justify-content: flex-end;",
+                    button {
+                        style: button_style,
+                        onclick: move |_| on_close.call(()),
+                        "Close"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::{BTreeSet, HashMap};
+use dioxus::prelude::*;
+use serde::Deserialize;
+use crate::core::Theme;
+use crate::editor::editor_core::is_text_file_name;
+
+/// A file or folder in the open folder
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileTreeEntry {
+    /// Where it is from the open folder, its names joined with `/`
+    pub path: String,
+    pub name: String,
+    pub is_dir: bool,
+}
+
+/// One entry as the browser lists it
+#[derive(Deserialize)]
+struct ListedEntry {
+    name: String,
+    is_dir: bool,
+}
+
+/// The entries of the folder at `path` from the JSON list of them read in the
+/// browser, folders first and then by name. Files that don't look like text
+/// are left out.
+pub fn folder_entries(path: &str, listing: &str) -> Vec<FileTreeEntry> {
+    let listed: Vec<ListedEntry> = serde_json::from_str(listing).unwrap_or_default();
+
+    let mut entries: Vec<FileTreeEntry> = listed
+        .into_iter()
+        .filter(|entry| entry.is_dir || is_text_file_name(&entry.name))
+        .map(|entry| FileTreeEntry {
+            path: if path.is_empty() { entry.name.clone() } else { format!("{path}/{}", entry.name) },
+            name: entry.name,
+            is_dir: entry.is_dir,
+        })
+        .collect();
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase())));
+    entries
+}
+
+/// The entries showing under the folder at `path`, each with how deeply it's nested
+fn visible_entries<'a>(
+    entries: &'a HashMap<String, Vec<FileTreeEntry>>,
+    expanded: &BTreeSet<String>,
+    path: &str,
+    depth: usize,
+    rows: &mut Vec<(usize, &'a FileTreeEntry)>,
+) {
+    for entry in entries.get(path).into_iter().flatten() {
+        rows.push((depth, entry));
+        if entry.is_dir && expanded.contains(&entry.path) {
+            visible_entries(entries, expanded, &entry.path, depth + 1, rows);
+        }
+    }
+}
+
+/// The folder the user opened, as a tree to open its files from. Folders are
+/// read the first time they're expanded, so `entries` only has those.
+#[component]
+pub fn FileTree(
+    theme: Theme,
+    // Name of the open folder
+    root: String,
+    // The entries of each folder read so far, by path. The open folder's path is "".
+    entries: HashMap<String, Vec<FileTreeEntry>>,
+    // Paths of the folders showing their entries
+    expanded: BTreeSet<String>,
+    on_toggle: EventHandler<String>,
+    on_open: EventHandler<String>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let panel_style = format!(
+        "width: 220px; flex-shrink: 0; overflow: auto; font-size: 13px;
+         background-color: {}; color: {}; border-right: 1px solid {};",
+         theme.ui.toolbar_bg, theme.ui.toolbar_fg, theme.selection
+    );
+
+    let header_style = "display: flex; align-items: center; justify-content: space-between;
+         padding: 0.25rem 0.5rem; font-weight: bold;";
+
+    let close_style = "background: none; border: none; color: inherit; cursor: pointer; padding: 0 0.25rem;";
+
+    let mut rows = Vec::new();
+    visible_entries(&entries, &expanded, "", 0, &mut rows);
+    let rows: Vec<(usize, FileTreeEntry)> = rows.into_iter().map(|(depth, entry)| (depth, entry.clone())).collect();
+
+    rsx! {
+        div {
+            style: panel_style,
+            role: "tree",
+            aria_label: "{root}",
+            div {
+                style: header_style,
+                span {
+                    style: "overflow: hidden; text-overflow: ellipsis; white-space: nowrap;",
+                    title: "{root}",
+                    "{root}"
+                }
+                button {
+                    style: close_style,
+                    title: "Close Folder",
+                    onclick: move |_| on_close.call(()),
+                    "×"
+                }
+            }
+            for (depth, entry) in rows {
+                div {
+                    key: "{entry.path}",
+                    role: "treeitem",
+                    aria_expanded: entry.is_dir.then(|| expanded.contains(&entry.path).to_string()),
+                    title: "{entry.path}",
+                    style: format!(
+                        "padding: 0.125rem 0.5rem 0.125rem {}rem; cursor: pointer; white-space: nowrap;
+                         overflow: hidden; text-overflow: ellipsis;",
+                        0.5 + depth as f32
+                    ),
+                    onclick: move |_| {
+                        if entry.is_dir {
+                            on_toggle.call(entry.path.clone());
+                        } else {
+                            on_open.call(entry.path.clone());
+                        }
+                    },
+                    span {
+                        style: "display: inline-block; width: 1em;",
+                        if entry.is_dir {
+                            if expanded.contains(&entry.path) { "▾" } else { "▸" }
+                        }
+                    }
+                    "{entry.name}"
+                }
+            }
+        }
+    }
+}
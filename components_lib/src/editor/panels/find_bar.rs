@@ -0,0 +1,121 @@
+use dioxus::prelude::*;
+use crate::core::Theme;
+
+/// Searches the active document as the user types, and replaces every match
+#[component]
+pub fn FindBar(
+    theme: Theme,
+    match_count: usize,
+    // Index of the match the editor is on
+    active_match: Option<usize>,
+    // Whether the document can't be changed, so nothing can be replaced
+    read_only: bool,
+    on_search: EventHandler<String>,
+    on_next: EventHandler<()>,
+    on_previous: EventHandler<()>,
+    // Called with the text to replace every match with
+    on_replace_all: EventHandler<String>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let mut term = use_signal(String::new);
+    let mut replacement = use_signal(String::new);
+
+    let style = format!(
+        "display: flex; align-items: center; gap: 0.5rem; padding: 0.25rem 0.5rem; font-size: 12px;
+         background-color: {}; color: {};",
+         theme.ui.toolbar_bg, theme.ui.toolbar_fg
+    );
+
+    let input_style = format!(
+        "width: 16rem; padding: 0.25rem 0.5rem; border-radius: 3px;
+         background-color: {}; color: {}; border: 1px solid {};",
+         theme.background, theme.foreground,
+         if !term.read().is_empty() && match_count == 0 { "#E06C75" } else { "#555" }
+    );
+
+    let button_style = format!(
+        "padding: 0.25rem 0.5rem; border: none; border-radius: 3px; cursor: pointer;
+         background-color: {}; color: {};",
+         theme.ui.button, theme.ui.toolbar_fg
+    );
+
+    let status = match (term.read().is_empty(), active_match) {
+        (true, _) => String::new(),
+        (false, _) if match_count == 0 => "No results".to_string(),
+        (false, Some(active)) => format!("{} of {match_count}", active + 1),
+        (false, None) => format!("{match_count} matches"),
+    };
+
+    let handle_keydown = move |event: Event<KeyboardData>| match event.key() {
+        Key::Enter if event.modifiers().shift() => on_previous.call(()),
+        Key::Enter => on_next.call(()),
+        Key::Escape => on_close.call(()),
+        _ => {}
+    };
+
+    let can_replace = match_count > 0 && !read_only;
+    let handle_replace_keydown = move |event: Event<KeyboardData>| match event.key() {
+        Key::Enter if can_replace => on_replace_all.call(replacement()),
+        Key::Escape => on_close.call(()),
+        _ => {}
+    };
+
+    rsx! {
+        div {
+            style: style,
+            input {
+                id: "find-input",
+                style: input_style.clone(),
+                placeholder: "Find",
+                value: term(),
+                onmounted: move |event| async move {
+                    let _ = event.set_focus(true).await;
+                },
+                oninput: move |event| {
+                    term.set(event.value());
+                    on_search.call(event.value());
+                },
+                onkeydown: handle_keydown,
+            }
+            input {
+                style: input_style.clone(),
+                placeholder: "Replace",
+                aria_label: "Replace with",
+                value: replacement(),
+                oninput: move |event| replacement.set(event.value()),
+                onkeydown: handle_replace_keydown,
+            }
+            span {
+                style: "min-width: 5rem;",
+                "{status}"
+            }
+            button {
+                style: button_style.clone(),
+                disabled: match_count == 0,
+                title: "Previous match (Shift+Enter)",
+                onclick: move |_| on_previous.call(()),
+                "↑"
+            }
+            button {
+                style: button_style.clone(),
+                disabled: match_count == 0,
+                title: "Next match (Enter)",
+                onclick: move |_| on_next.call(()),
+                "↓"
+            }
+            button {
+                style: button_style.clone(),
+                disabled: !can_replace,
+                title: "Replace every match (Enter in the replace box)",
+                onclick: move |_| on_replace_all.call(replacement()),
+                "Replace All"
+            }
+            button {
+                style: format!("{button_style} margin-left: auto;"),
+                title: "Close (Escape)",
+                onclick: move |_| on_close.call(()),
+                "✕"
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use dioxus::prelude::*;
+use crate::core::Theme;
+use super::{MenuHandler, MenuItem};
+
+/// Rough size of the menu, used to keep it on screen near the viewport's edges
+const MENU_WIDTH: f64 = 200.0;
+const ITEM_HEIGHT: f64 = 32.0;
+const SEPARATOR_HEIGHT: f64 = 9.0;
+
+/// A menu opened at the mouse with a right-click. Items run through the same
+/// handler as the menu bar. Clicking anywhere else closes it.
+#[component]
+pub fn ContextMenu<H: MenuHandler + Clone + PartialEq + 'static>(
+    theme: Theme,
+    items: Vec<MenuItem>,
+    handler: H,
+    x: f64,
+    y: f64,
+    on_close: EventHandler<()>,
+) -> Element {
+    let (viewport_width, viewport_height) = web_sys::window()
+        .map(|window| (
+            window.inner_width().ok().and_then(|width| width.as_f64()).unwrap_or(f64::MAX),
+            window.inner_height().ok().and_then(|height| height.as_f64()).unwrap_or(f64::MAX),
+        ))
+        .unwrap_or((f64::MAX, f64::MAX));
+
+    let menu_height: f64 = items.iter()
+        .map(|item| if item.label == "-" { SEPARATOR_HEIGHT } else { ITEM_HEIGHT })
+        .sum();
+
+    // Open up and to the left of the mouse when there's no room below or to the right
+    let left = if x + MENU_WIDTH > viewport_width { (x - MENU_WIDTH).max(0.0) } else { x };
+    let top = if y + menu_height > viewport_height { (y - menu_height).max(0.0) } else { y };
+
+    let menu_style = format!(
+        "position: fixed; left: {left}px; top: {top}px; width: {MENU_WIDTH}px; z-index: 1000;
+         background-color: {}; color: {}; box-shadow: 0 2px 5px rgba(0, 0, 0, 0.3);
+         display: flex; flex-direction: column; padding: 0.25rem 0; font-size: 14px;",
+        theme.ui.toolbar_bg, theme.ui.toolbar_fg
+    );
+
+    let item_style = "padding: 0.5rem 1rem; display: flex; justify-content: space-between; cursor: pointer;";
+    let disabled_style = "opacity: 0.5; cursor: default;";
+    let separator_style = format!(
+        "height: 1px; margin: 0.25rem 0; background-color: {};",
+        theme.ui.button
+    );
+
+    rsx! {
+        // Catches the click (or right-click) that lands anywhere outside the menu
+        div {
+            style: "position: fixed; top: 0; left: 0; right: 0; bottom: 0; z-index: 999;",
+            onclick: move |_| on_close.call(()),
+            oncontextmenu: move |event| {
+                event.prevent_default();
+                on_close.call(());
+            },
+        }
+
+        div {
+            style: menu_style,
+
+            for item in items {
+                if item.label == "-" {
+                    div { key: "{item.id}", style: separator_style.clone() }
+                } else {
+                    div {
+                        key: "{item.id}",
+                        style: format!(
+                            "{} {}",
+                            item_style,
+                            if handler.is_item_enabled(&item.id) { "" } else { disabled_style }
+                        ),
+                        // Keep focus, and with it the selection, in the editor
+                        onmousedown: move |event| event.prevent_default(),
+                        onclick: {
+                            let mut handler = handler.clone();
+                            let item_id = item.id.clone();
+
+                            move |_| {
+                                if handler.is_item_enabled(&item_id) {
+                                    handler.handle_menu_action(&item_id);
+                                    on_close.call(());
+                                }
+                            }
+                        },
+
+                        span { "{item.label}" }
+                        if let Some(shortcut) = &item.shortcut {
+                            span {
+                                style: "color: #999; font-size: 0.9em; margin-left: 1rem",
+                                "{shortcut}"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
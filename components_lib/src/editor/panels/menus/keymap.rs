@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use super::MenuItem;
+
+/// Keys that only modify others, never bound on their own
+const MODIFIER_KEYS: [&str; 6] = ["Control", "Shift", "Alt", "Meta", "AltGraph", "CapsLock"];
+
+/// How a key is written in a combination: letters in capitals, arrow keys
+/// without the `Arrow` their key names start with and the space bar as `Space`
+fn key_name(key: &str) -> String {
+    let key = key.strip_prefix("Arrow").unwrap_or(key);
+    match key {
+        " " => "Space".to_string(),
+        key if key.chars().count() == 1 => key.to_uppercase(),
+        key => key.to_string(),
+    }
+}
+
+/// A key combination written the way menus display it, e.g. "Ctrl+Shift+S".
+/// Ctrl also matches the Cmd key, so the same shortcuts work on a Mac.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyCombo {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub key: String,
+}
+
+impl KeyCombo {
+    pub fn parse(combo: &str) -> Option<Self> {
+        // "Ctrl++" binds the plus key itself
+        let (modifiers, key) = match combo.strip_suffix("++") {
+            Some(modifiers) => (modifiers, "+"),
+            None => combo.rsplit_once('+').unwrap_or(("", combo)),
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            return None;
+        }
+
+        let mut parsed = Self {
+            ctrl: false,
+            shift: false,
+            alt: false,
+            key: key_name(key),
+        };
+
+        for modifier in modifiers.split('+').map(str::trim).filter(|modifier| !modifier.is_empty()) {
+            match modifier.to_lowercase().as_str() {
+                "ctrl" | "cmd" => parsed.ctrl = true,
+                "shift" => parsed.shift = true,
+                "alt" => parsed.alt = true,
+                _ => return None,
+            }
+        }
+
+        Some(parsed)
+    }
+
+    /// The combination pressed, with `ctrl` set for either Ctrl or Cmd. None
+    /// while only modifiers are down.
+    pub fn from_key_press(key: &str, ctrl: bool, shift: bool, alt: bool) -> Option<Self> {
+        if key.is_empty() || MODIFIER_KEYS.contains(&key) {
+            return None;
+        }
+        Some(Self { ctrl, shift, alt, key: key_name(key) })
+    }
+
+    /// Whether a key press matches, with `ctrl` set for either Ctrl or Cmd
+    pub fn matches(&self, key: &str, ctrl: bool, shift: bool, alt: bool) -> bool {
+        self.ctrl == ctrl && self.shift == shift && self.alt == alt && self.key.eq_ignore_ascii_case(&key_name(key))
+    }
+
+    /// Whether the combination can be a shortcut without getting in the way of
+    /// typing: it needs Ctrl or Alt, unless it's a function key
+    pub fn is_bindable(&self) -> bool {
+        let function_key = self
+            .key
+            .strip_prefix('F')
+            .is_some_and(|number| number.parse::<u8>().is_ok_and(|number| (1..=24).contains(&number)));
+        self.ctrl || self.alt || function_key
+    }
+}
+
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+impl TryFrom<String> for KeyCombo {
+    type Error = String;
+
+    fn try_from(combo: String) -> Result<Self, Self::Error> {
+        Self::parse(&combo).ok_or_else(|| format!("Not a key combination: {combo}"))
+    }
+}
+
+impl From<KeyCombo> for String {
+    fn from(combo: KeyCombo) -> Self {
+        combo.to_string()
+    }
+}
+
+/// What the user changed from the default keymap, by command id. None
+/// leaves the command without a shortcut.
+pub type KeyOverrides = BTreeMap<String, Option<KeyCombo>>;
+
+/// Which key combination runs each command, by the id of the menu item the
+/// command is
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Keymap {
+    bindings: BTreeMap<String, KeyCombo>,
+}
+
+impl Keymap {
+    /// The default keymap, the shortcuts the menus are written with
+    pub fn from_menus(menus: &[MenuItem]) -> Self {
+        fn collect(menus: &[MenuItem], bindings: &mut BTreeMap<String, KeyCombo>) {
+            for item in menus {
+                if item.action
+                    && let Some(combo) = item.shortcut.as_deref().and_then(KeyCombo::parse)
+                {
+                    bindings.insert(item.id.clone(), combo);
+                }
+                if let Some(submenu) = &item.submenu {
+                    collect(submenu, bindings);
+                }
+            }
+        }
+
+        let mut bindings = BTreeMap::new();
+        collect(menus, &mut bindings);
+        Self { bindings }
+    }
+
+    /// The keymap with the user's changes made to it. A combination bound to
+    /// another command as well is taken from the command it was bound to by
+    /// default.
+    pub fn with_overrides(mut self, overrides: &KeyOverrides) -> Self {
+        for (command, combo) in overrides {
+            match combo {
+                Some(combo) => {
+                    self.bindings.retain(|bound, bound_combo| bound_combo != combo || overrides.contains_key(bound));
+                    self.bindings.insert(command.clone(), combo.clone());
+                }
+                None => {
+                    self.bindings.remove(command);
+                }
+            }
+        }
+        self
+    }
+
+    pub fn combo_for(&self, command: &str) -> Option<&KeyCombo> {
+        self.bindings.get(command)
+    }
+
+    /// The command a key press runs, with `ctrl` set for either Ctrl or Cmd
+    pub fn command_for(&self, key: &str, ctrl: bool, shift: bool, alt: bool) -> Option<&str> {
+        self.bindings
+            .iter()
+            .find(|(_, combo)| combo.matches(key, ctrl, shift, alt))
+            .map(|(command, _)| command.as_str())
+    }
+
+    /// The command other than `except` that `combo` already runs
+    pub fn command_bound_to(&self, combo: &KeyCombo, except: &str) -> Option<&str> {
+        self.bindings
+            .iter()
+            .find(|(command, bound)| *bound == combo && command.as_str() != except)
+            .map(|(command, _)| command.as_str())
+    }
+
+    /// Show each menu item's shortcut from the keymap rather than the default
+    pub fn label_menus(&self, menus: &mut [MenuItem]) {
+        for item in menus {
+            if item.action {
+                item.shortcut = self.combo_for(&item.id).map(KeyCombo::to_string);
+            }
+            if let Some(submenu) = &mut item.submenu {
+                self.label_menus(submenu);
+            }
+        }
+    }
+}
+
+/// Every command in the menus a shortcut can be bound to, by id, with where
+/// it is in the menus like "File › Save". Recent files come and go, so
+/// they're left out.
+pub fn menu_commands(menus: &[MenuItem]) -> Vec<(String, String)> {
+    fn collect(menus: &[MenuItem], path: &str, commands: &mut Vec<(String, String)>) {
+        for item in menus {
+            let label = if path.is_empty() { item.label.clone() } else { format!("{path} › {}", item.label) };
+            if item.action && !item.id.starts_with("file.open_recent.") {
+                commands.push((item.id.clone(), label.clone()));
+            }
+            if let Some(submenu) = &item.submenu {
+                collect(submenu, &label, commands);
+            }
+        }
+    }
+
+    let mut commands = Vec::new();
+    collect(menus, "", &mut commands);
+    commands
+}
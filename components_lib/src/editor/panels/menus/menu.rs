@@ -1,6 +1,5 @@
 use dioxus::prelude::*;
 use crate::core::Theme;
-use std::collections::HashMap;
 use wasm_bindgen::prelude::*;
 
 // Menu item structure
@@ -22,6 +21,41 @@ pub trait MenuHandler {
     fn is_item_checked(&self, item_id: &str) -> Option<bool>;
 }
 
+/// Whether `item` is only there to divide its menu
+fn is_separator(item: &MenuItem) -> bool {
+    item.label == "-"
+}
+
+/// The id of the element rendering the menu item `id`, for `aria-activedescendant`
+fn menu_element_id(id: &str) -> String {
+    format!("menu-item-{id}")
+}
+
+/// The role of a menu item for screen readers
+fn item_role(item: &MenuItem, checked: Option<bool>) -> &'static str {
+    if is_separator(item) {
+        "separator"
+    } else if checked.is_some() {
+        "menuitemcheckbox"
+    } else {
+        "menuitem"
+    }
+}
+
+/// The index of the item after (or before) `from` in `items`, wrapping around
+/// and skipping separators. From no item, the first (or last) one.
+fn step_item(items: &[MenuItem], from: Option<usize>, forward: bool) -> Option<usize> {
+    let len = items.len();
+    (1..=len)
+        .map(|step| match (from, forward) {
+            (Some(from), true) => (from + step) % len,
+            (Some(from), false) => (from + len - step) % len,
+            (None, true) => step - 1,
+            (None, false) => len - step,
+        })
+        .find(|&idx| !is_separator(&items[idx]))
+}
+
 // Component for rendering a nested submenu
 #[component]
 fn NestedSubmenu<H: MenuHandler + Clone + PartialEq + 'static>(
@@ -31,40 +65,51 @@ fn NestedSubmenu<H: MenuHandler + Clone + PartialEq + 'static>(
     handler: H,
     dropdown_item_style: String,
     disabled_style: String,
+    // Shown by the keyboard rather than by hovering over its parent
+    open: bool,
+    // The item picked out with the keyboard, styled with `highlight_style`
+    highlighted: Option<usize>,
+    highlight_style: String,
 ) -> Element {
     let container_style = format!(
         "position: absolute; left: 100%; top: 0; background-color: {}; color: {}; \
          min-width: 200px; box-shadow: 0 2px 5px rgba(0, 0, 0, 0.3); z-index: 1000; \
-         display: none; flex-direction: column; padding: 0.25rem 0;",
-        theme.ui.toolbar_bg, theme.ui.toolbar_fg
+         display: {}; flex-direction: column; padding: 0.25rem 0;",
+        theme.ui.toolbar_bg, theme.ui.toolbar_fg, if open { "flex" } else { "none" }
     );
 
     rsx! {
         div {
             class: "submenu-container",
             "data-submenu-id": "{parent_id}",
+            role: "menu",
             style: container_style,
             
             {
-                submenu.iter().map(|item| {
+                submenu.iter().enumerate().map(|(index, item)| {
                     let item_id = item.id.clone();
                     let item_id_clone = item_id.clone();
                     let item_label = item.label.clone();
                     let is_enabled = handler.is_item_enabled(&item_id);
                     let is_checked = handler.is_item_checked(&item_id);
-                    let has_shortcut = item.shortcut.is_some();
                     let has_submenu = item.submenu.is_some();
                     let is_action = item.action;
                     
-                    let item_style = format!("{} {}", dropdown_item_style, 
-                                         if !is_enabled { &disabled_style } else { "" });
+                    let item_style = format!("{} {} {}", dropdown_item_style,
+                                         if !is_enabled { &disabled_style } else { "" },
+                                         if highlighted == Some(index) { &highlight_style } else { "" });
+                    let role = item_role(item, is_checked);
 
                     let mut handler_clone = handler.clone();
                     
                     rsx! {
                         div {
                             key: "{item_id.clone()}",
+                            id: menu_element_id(&item_id),
                             "data-menu-id": "{item_id.clone()}",
+                            role: role,
+                            aria_disabled: "{!is_enabled}",
+                            aria_checked: is_checked.map(|checked| checked.to_string()),
                             style: item_style,
                             onclick: move |event: MouseEvent| {
                                 if !is_enabled {
@@ -132,13 +177,30 @@ pub fn MenuBar<H: MenuHandler + Clone + PartialEq + 'static> (
     theme: Theme,
     menus: Vec<MenuItem>,
     handler: H,
+    // Called when the menu bar is done with the keyboard, after an action or
+    // on Escape, to give focus back to wherever it belongs
+    on_close: EventHandler<()>,
 ) -> Element {
     // Track which menu is currently open
     let mut active_menu = use_signal(|| None::<String>);
 
+    // Keyboard focus: the top-level menu, the item in its dropdown, and the
+    // item in that item's own submenu, which is open while this is set
+    let mut focused_menu = use_signal(|| None::<usize>);
+    let mut highlighted_item = use_signal(|| None::<usize>);
+    let mut highlighted_nested = use_signal(|| None::<usize>);
+
+    // Close every menu and forget the keyboard focus
+    let mut close_menus = move || {
+        active_menu.set(None);
+        focused_menu.set(None);
+        highlighted_item.set(None);
+        highlighted_nested.set(None);
+    };
+
     // Styles for the menu bar
     let menu_bar_style = format!(
-        "display: flex; background-color: {}; color: {}; padding: 0;",
+        "display: flex; background-color: {}; color: {}; padding: 0; outline: none;",
         theme.ui.toolbar_bg, theme.ui.toolbar_fg
     );
 
@@ -177,8 +239,10 @@ pub fn MenuBar<H: MenuHandler + Clone + PartialEq + 'static> (
         let mut handler = handler.clone();
 
         move |item_id: &str| {
-            // Close the menu
-            active_menu.set(None);
+            // Close the menu and hand focus back before the action, which
+            // may want to move it somewhere else
+            close_menus();
+            on_close.call(());
 
             // Call the action handler
             handler.handle_menu_action(item_id);
@@ -188,11 +252,11 @@ pub fn MenuBar<H: MenuHandler + Clone + PartialEq + 'static> (
     // Set up global JS handler for nested menu items
     let click_handler = {
         let mut handler_clone = handler.clone();
-        let mut active_menu_clone = active_menu.clone();
         
         Closure::wrap(Box::new(move |action_id: String| {
             // Close the menu
-            active_menu_clone.set(None);
+            close_menus();
+            on_close.call(());
             
             // Call the action handler
             handler_clone.handle_menu_action(&action_id);
@@ -206,19 +270,174 @@ pub fn MenuBar<H: MenuHandler + Clone + PartialEq + 'static> (
     js_sys::Reflect::set(
         &window_obj,
         &JsValue::from_str("_handleMenuAction"),
-        &click_handler.as_ref()
+        click_handler.as_ref()
     ).expect("Failed to set menu handler");
     
     // Prevent the callback from being dropped
     click_handler.forget();
+
+    // Alt on its own moves focus to the menu bar, and back again
+    use_hook(move || {
+        let focus_handler = Closure::wrap(Box::new(move || {
+            if focused_menu().is_some() || active_menu().is_some() {
+                close_menus();
+                on_close.call(());
+            } else {
+                focused_menu.set(Some(0));
+                let _ = js_sys::eval("document.getElementById('menu-bar')?.focus()");
+            }
+        }) as Box<dyn FnMut()>);
+
+        let window = web_sys::window().expect("no global window exists");
+        js_sys::Reflect::set(
+            &window,
+            &JsValue::from_str("_focusMenuBar"),
+            focus_handler.as_ref()
+        ).expect("Failed to set menu focus handler");
+        focus_handler.forget();
+    });
+
+    // Open the top-level menu at `index` and pick out its first item, or
+    // just move focus to it while no menu is open
+    let mut focus_menu = {
+        let menus = menus.clone();
+        move |index: usize, open: bool| {
+            let _ = js_sys::eval("document.querySelectorAll('.submenu-container').forEach(m => m.style.display = 'none');");
+            focused_menu.set(Some(index));
+            highlighted_nested.set(None);
+            if open {
+                let items = menus[index].submenu.as_deref().unwrap_or_default();
+                active_menu.set(Some(menus[index].id.clone()));
+                highlighted_item.set(step_item(items, None, true));
+            } else {
+                active_menu.set(None);
+                highlighted_item.set(None);
+            }
+        }
+    };
+
+    let handle_keydown = {
+        let menus = menus.clone();
+        let handler = handler.clone();
+        let mut on_action = handle_menu_action.clone();
+
+        move |event: Event<KeyboardData>| {
+            // Arrows work from a menu opened with the mouse too
+            let top = focused_menu()
+                .or_else(|| active_menu().and_then(|id| menus.iter().position(|menu| menu.id == id)));
+            let Some(top) = top.filter(|top| *top < menus.len()) else {
+                return;
+            };
+
+            let is_open = active_menu().is_some();
+            let items = menus[top].submenu.clone().unwrap_or_default();
+            let nested = highlighted_item()
+                .and_then(|idx| items.get(idx))
+                .and_then(|item| item.submenu.clone())
+                .unwrap_or_default();
+            let in_nested = highlighted_nested().is_some();
+
+            let key = event.key();
+            let activates = key == Key::Enter || key == Key::Character(" ".to_string());
+
+            match key {
+                Key::ArrowLeft if in_nested => highlighted_nested.set(None),
+                Key::ArrowRight if is_open && !in_nested && !nested.is_empty() => {
+                    highlighted_nested.set(step_item(&nested, None, true));
+                },
+                Key::ArrowLeft => focus_menu((top + menus.len() - 1) % menus.len(), is_open),
+                Key::ArrowRight => focus_menu((top + 1) % menus.len(), is_open),
+                Key::ArrowDown | Key::ArrowUp => {
+                    let forward = key == Key::ArrowDown;
+                    if !is_open {
+                        focus_menu(top, true);
+                        if !forward {
+                            highlighted_item.set(step_item(&items, None, false));
+                        }
+                    } else if in_nested {
+                        highlighted_nested.set(step_item(&nested, highlighted_nested(), forward));
+                    } else {
+                        highlighted_item.set(step_item(&items, highlighted_item(), forward));
+                    }
+                },
+                _ if activates => {
+                    let (list, highlighted) = if in_nested {
+                        (&nested, highlighted_nested())
+                    } else {
+                        (&items, highlighted_item())
+                    };
+                    let item = highlighted.and_then(|idx| list.get(idx));
+
+                    if !is_open {
+                        focus_menu(top, true);
+                    } else if let Some(item) = item {
+                        if !in_nested && item.submenu.is_some() {
+                            highlighted_nested.set(step_item(&nested, None, true));
+                        } else if item.action && handler.is_item_enabled(&item.id) {
+                            on_action(&item.id);
+                        }
+                    }
+                },
+                Key::Escape if in_nested => highlighted_nested.set(None),
+                Key::Escape if is_open => focus_menu(top, false),
+                Key::Escape | Key::Tab => {
+                    close_menus();
+                    on_close.call(());
+                },
+                _ => return,
+            }
+
+            event.prevent_default();
+            event.stop_propagation();
+        }
+    };
+
+    // The element screen readers should treat as focused
+    let active_descendant = {
+        let items = focused_menu()
+            .and_then(|top| menus.get(top))
+            .filter(|_| active_menu().is_some())
+            .and_then(|menu| menu.submenu.as_ref());
+        let item = highlighted_item().and_then(|idx| items?.get(idx));
+        let nested = highlighted_nested().and_then(|idx| item?.submenu.as_ref()?.get(idx));
+
+        nested
+            .or(item)
+            .or_else(|| focused_menu().and_then(|top| menus.get(top)))
+            .map(|item| menu_element_id(&item.id))
+    };
     
     // Set up general menu event handlers using JavaScript
     use_effect(move || {
         let menu_js = r#"
             // Setup function to handle menu events
             function setupMenuEvents() {
+                // Alt pressed and released without another key in between
+                // toggles focus to the menu bar
+                if (!window._menuAltBound) {
+                    window._menuAltBound = true;
+                    let altAlone = false;
+                    document.addEventListener('keydown', event => {
+                        altAlone = event.key === 'Alt' && !event.repeat;
+                    }, true);
+                    document.addEventListener('mousedown', () => {
+                        altAlone = false;
+                    }, true);
+                    document.addEventListener('keyup', event => {
+                        if (event.key === 'Alt' && altAlone && window._focusMenuBar) {
+                            event.preventDefault();
+                            window._focusMenuBar();
+                        }
+                        altAlone = false;
+                    }, true);
+                }
+
                 // Handle clicks on menu items
                 document.querySelectorAll('[data-menu-id]').forEach(item => {
+                    // This runs on every DOM change, so only bind each item once
+                    if (item.dataset.menuBound) return;
+                    item.dataset.menuBound = 'true';
+
                     // Click handler for menu actions
                     item.addEventListener('click', event => {
                         if (window._handleMenuAction) {
@@ -268,20 +487,29 @@ pub fn MenuBar<H: MenuHandler + Clone + PartialEq + 'static> (
         let _ = js_sys::eval(menu_js);
         
         // Cleanup on unmount
-        (move || {
-            let _ = js_sys::eval(r#"
-                // Clean up the observer when menu is unmounted
-                if (window.menuObserver) {
-                    window.menuObserver.disconnect();
-                }
-            "#);
-        })()
+        let _ = js_sys::eval(r#"
+            // Clean up the observer when menu is unmounted
+            if (window.menuObserver) {
+                window.menuObserver.disconnect();
+            }
+        "#);
     });
 
     // Render the menu bar
     rsx! {
         div {
+            id: "menu-bar",
+            role: "menubar",
+            tabindex: "-1",
+            aria_activedescendant: active_descendant,
             style: menu_bar_style,
+            onkeydown: handle_keydown,
+            onblur: move |_| {
+                // Focus went elsewhere, so menus opened from the keyboard go too
+                if focused_menu().is_some() {
+                    close_menus();
+                }
+            },
             onmousedown: move |_| {
                 // This prevents text selection when clicking the menu
                 let _ = js_sys::eval("document.getSelection().removeAllRanges();");
@@ -289,20 +517,25 @@ pub fn MenuBar<H: MenuHandler + Clone + PartialEq + 'static> (
 
             // Render top-level menu items
             {
-                menus.iter().map(|item| {
+                menus.iter().enumerate().map(|(index, item)| {
                     let item_id = item.id.clone();
                     let item_id_onmouseover = item_id.clone();
                     let item_id_onclick = item_id.clone();
                     let item_label = item.label.clone();
                     let has_submenu = item.submenu.is_some();
                     let is_active = active_menu() == Some(item_id.clone());
+                    let is_focused = is_active || (active_menu().is_none() && focused_menu() == Some(index));
                     let item_style = format!("{} {}", menu_item_style, 
-                                          if is_active { &menu_item_hover_style } else { "" });
-                    let mut active_menu_clone = active_menu.clone();
+                                          if is_focused { &menu_item_hover_style } else { "" });
+                    let mut active_menu_clone = active_menu;
                     
                     rsx! {
                         div {
                             key: item_id.clone(),
+                            id: menu_element_id(&item_id),
+                            role: "menuitem",
+                            aria_haspopup: has_submenu.then_some("menu"),
+                            aria_expanded: has_submenu.then(|| is_active.to_string()),
                             style: item_style,
                             onmouseover: move |_| {
                                 // If a menu is already open, switch to this one immediately on hover
@@ -311,6 +544,11 @@ pub fn MenuBar<H: MenuHandler + Clone + PartialEq + 'static> (
                                     let _ = js_sys::eval("document.querySelectorAll('.submenu-container').forEach(m => m.style.display = 'none');");
                                     // Set the new active menu
                                     active_menu_clone.set(Some(item_id_onmouseover.clone()));
+                                    if focused_menu().is_some() {
+                                        focused_menu.set(Some(index));
+                                        highlighted_item.set(None);
+                                        highlighted_nested.set(None);
+                                    }
                                 }
                             },
                             onclick: move |_| {
@@ -323,19 +561,24 @@ pub fn MenuBar<H: MenuHandler + Clone + PartialEq + 'static> (
                             // Render dropdown if this menu is active
                             if is_active && has_submenu {
                                 div {
+                                    role: "menu",
+                                    aria_label: item_label.clone(),
                                     style: dropdown_style.clone(),
                                     onclick: move |event| { event.stop_propagation(); },
                                     
                                     {
-                                        item.submenu.as_ref().unwrap().iter().map(|submenu_item| {
+                                        item.submenu.as_ref().unwrap().iter().enumerate().map(|(sub_index, submenu_item)| {
                                             let sub_id = submenu_item.id.clone();
                                             let sub_label = submenu_item.label.clone();
                                             let is_enabled = handler.is_item_enabled(&sub_id);
                                             let is_checked = handler.is_item_checked(&sub_id);
                                             let has_shortcut = submenu_item.shortcut.is_some();
                                             let shortcut = submenu_item.shortcut.clone();
-                                            let sub_style = format!("{} {}", dropdown_item_style, 
-                                                               if !is_enabled { disabled_style } else { "" });
+                                            let is_highlighted = highlighted_item() == Some(sub_index);
+                                            let sub_style = format!("{} {} {}", dropdown_item_style,
+                                                               if !is_enabled { disabled_style } else { "" },
+                                                               if is_highlighted { &menu_item_hover_style } else { "" });
+                                            let role = item_role(submenu_item, is_checked);
                                             let mut on_action = handle_menu_action.clone();
                                             let is_action = submenu_item.action;
                                             
@@ -345,6 +588,12 @@ pub fn MenuBar<H: MenuHandler + Clone + PartialEq + 'static> (
                                             rsx! {
                                                 div {
                                                     key: sub_id.clone(),
+                                                    id: menu_element_id(&sub_id),
+                                                    role: role,
+                                                    aria_disabled: "{!is_enabled}",
+                                                    aria_checked: is_checked.map(|checked| checked.to_string()),
+                                                    aria_haspopup: has_nested_submenu.then_some("menu"),
+                                                    aria_expanded: has_nested_submenu.then(|| (is_highlighted && highlighted_nested().is_some()).to_string()),
                                                     style: sub_style,
                                                     "attr:data_menu_id": sub_id.clone(),
                                                     "attr:data_has_submenu": if has_nested_submenu { Some(sub_id.clone()) } else { None },
@@ -433,6 +682,9 @@ pub fn MenuBar<H: MenuHandler + Clone + PartialEq + 'static> (
                                                             handler: handler.clone(),
                                                             dropdown_item_style: dropdown_item_style.to_string(),
                                                             disabled_style: disabled_style.to_string(),
+                                                            open: is_highlighted && highlighted_nested().is_some(),
+                                                            highlighted: highlighted_nested().filter(|_| is_highlighted),
+                                                            highlight_style: menu_item_hover_style.clone(),
                                                         }
                                                     }
                                                 }
@@ -451,7 +703,7 @@ pub fn MenuBar<H: MenuHandler + Clone + PartialEq + 'static> (
         if active_menu().is_some() {
             div {
                 style: "position: fixed; top: 0; left: 0; right: 0; bottom: 0; z-index: 999;",
-                onclick: move |_| active_menu.set(None),
+                onclick: move |_| close_menus(),
             }
         }
     }
@@ -1,7 +1,107 @@
 use super::menu::MenuItem;
+use crate::editor::editor_core::MONOSPACE_FONTS;
 
-pub fn get_default_editor_menus() -> Vec<MenuItem> {
-    vec![
+/// The File → Open Recent submenu, one entry per recent file name, newest first
+fn open_recent_submenu(recent_files: &[String]) -> Vec<MenuItem> {
+    if recent_files.is_empty() {
+        return vec![MenuItem {
+            id: "file.open_recent.none".to_string(),
+            label: "No Recent Files".to_string(),
+            action: false,
+            shortcut: None,
+            enabled: false,
+            checked: None,
+            submenu: None,
+        }];
+    }
+
+    let mut submenu: Vec<MenuItem> = recent_files
+        .iter()
+        .enumerate()
+        .map(|(index, name)| MenuItem {
+            id: format!("file.open_recent.{index}"),
+            label: name.clone(),
+            action: true,
+            shortcut: None,
+            enabled: true,
+            checked: None,
+            submenu: None,
+        })
+        .collect();
+
+    submenu.push(MenuItem {
+        id: "file.open_recent.separator".to_string(),
+        label: "-".to_string(),
+        action: false,
+        shortcut: None,
+        enabled: false,
+        checked: None,
+        submenu: None,
+    });
+    submenu.push(MenuItem {
+        id: "file.clear_recent".to_string(),
+        label: "Clear Recent".to_string(),
+        action: true,
+        shortcut: None,
+        enabled: true,
+        checked: None,
+        submenu: None,
+    });
+
+    submenu
+}
+
+/// The View → Font submenu, one checkable entry per font, then the size controls
+fn font_submenu() -> Vec<MenuItem> {
+    let mut submenu: Vec<MenuItem> = MONOSPACE_FONTS
+        .iter()
+        .enumerate()
+        .map(|(index, (name, _))| MenuItem {
+            id: format!("view.font.{index}"),
+            label: name.to_string(),
+            action: true,
+            shortcut: None,
+            enabled: true,
+            checked: Some(false),
+            submenu: None,
+        })
+        .collect();
+
+    submenu.push(MenuItem {
+        id: "view.font.separator".to_string(),
+        label: "-".to_string(),
+        action: false,
+        shortcut: None,
+        enabled: false,
+        checked: None,
+        submenu: None,
+    });
+    submenu.push(MenuItem {
+        id: "view.font_bigger".to_string(),
+        label: "Increase Font Size".to_string(),
+        action: true,
+        shortcut: None,
+        enabled: true,
+        checked: None,
+        submenu: None,
+    });
+    submenu.push(MenuItem {
+        id: "view.font_smaller".to_string(),
+        label: "Decrease Font Size".to_string(),
+        action: true,
+        shortcut: None,
+        enabled: true,
+        checked: None,
+        submenu: None,
+    });
+
+    submenu
+}
+
+/// The menu bar's menus. `recent_files` fills File → Open Recent, and File →
+/// Open Folder is only there if `can_open_folders`.
+pub fn get_default_editor_menus(recent_files: &[String], can_open_folders: bool) -> Vec<MenuItem> {
+    let mut menus = vec![
         MenuItem {
             id: "file".to_string(),
             label: "File".to_string(),
@@ -19,6 +119,15 @@ pub fn get_default_editor_menus() -> Vec<MenuItem> {
                     checked: None,
                     submenu: None,
                 },
+                MenuItem {
+                    id: "file.new_scratch".to_string(),
+                    label: "New Scratch".to_string(),
+                    action: true,
+                    shortcut: None,
+                    enabled: true,
+                    checked: None,
+                    submenu: None,
+                },
                 MenuItem {
                     id: "file.open".to_string(),
                     label: "Open...".to_string(),
@@ -28,6 +137,33 @@ pub fn get_default_editor_menus() -> Vec<MenuItem> {
                     checked: None,
                     submenu: None,
                 },
+                MenuItem {
+                    id: "file.open_folder".to_string(),
+                    label: "Open Folder...".to_string(),
+                    action: true,
+                    shortcut: None,
+                    enabled: true,
+                    checked: None,
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "file.open_recent".to_string(),
+                    label: "Open Recent".to_string(),
+                    action: false,
+                    shortcut: None,
+                    enabled: true,
+                    checked: None,
+                    submenu: Some(open_recent_submenu(recent_files)),
+                },
+                MenuItem {
+                    id: "file.save".to_string(),
+                    label: "Save".to_string(),
+                    action: true,
+                    shortcut: Some("Ctrl+S".to_string()),
+                    enabled: true,
+                    checked: None,
+                    submenu: None,
+                },
                 MenuItem {
                     id: "file.save_as".to_string(),
                     label: "Save As...".to_string(),
@@ -37,6 +173,24 @@ pub fn get_default_editor_menus() -> Vec<MenuItem> {
                     checked: None,
                     submenu: None,
                 },
+                MenuItem {
+                    id: "file.save_all".to_string(),
+                    label: "Save All".to_string(),
+                    action: true,
+                    shortcut: Some("Ctrl+Alt+S".to_string()),
+                    enabled: true,
+                    checked: None,
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "file.save_to_server".to_string(),
+                    label: "Save to Server".to_string(),
+                    action: true,
+                    shortcut: None,
+                    enabled: true,
+                    checked: None,
+                    submenu: None,
+                },
                 MenuItem {
                     id: "file.exit".to_string(),
                     label: "Exit".to_string(),
@@ -69,7 +223,7 @@ pub fn get_default_editor_menus() -> Vec<MenuItem> {
                     id: "edit.redo".to_string(),
                     label: "Redo".to_string(),
                     action: true,
-                    shortcut: Some("Ctrl+Y".to_string()),
+                    shortcut: Some("Ctrl+Shift+Z".to_string()),
                     enabled: true,
                     checked: None,
                     submenu: None,
@@ -110,6 +264,78 @@ pub fn get_default_editor_menus() -> Vec<MenuItem> {
                     checked: None,
                     submenu: None,
                 },
+                MenuItem {
+                    id: "edit.separator2".to_string(),
+                    label: "-".to_string(),
+                    action: false,
+                    shortcut: None,
+                    enabled: false,
+                    checked: None,
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "edit.duplicate_lines".to_string(),
+                    label: "Duplicate Line".to_string(),
+                    action: true,
+                    shortcut: Some("Ctrl+Shift+D".to_string()),
+                    enabled: true,
+                    checked: None,
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "edit.move_lines_up".to_string(),
+                    label: "Move Line Up".to_string(),
+                    action: true,
+                    shortcut: Some("Alt+Up".to_string()),
+                    enabled: true,
+                    checked: None,
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "edit.move_lines_down".to_string(),
+                    label: "Move Line Down".to_string(),
+                    action: true,
+                    shortcut: Some("Alt+Down".to_string()),
+                    enabled: true,
+                    checked: None,
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "edit.separator3".to_string(),
+                    label: "-".to_string(),
+                    action: false,
+                    shortcut: None,
+                    enabled: false,
+                    checked: None,
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "edit.find".to_string(),
+                    label: "Find...".to_string(),
+                    action: true,
+                    shortcut: Some("Ctrl+F".to_string()),
+                    enabled: true,
+                    checked: None,
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "edit.goto_line".to_string(),
+                    label: "Go to Line...".to_string(),
+                    action: true,
+                    shortcut: Some("Ctrl+G".to_string()),
+                    enabled: true,
+                    checked: None,
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "edit.goto_bracket".to_string(),
+                    label: "Go to Bracket".to_string(),
+                    action: true,
+                    shortcut: Some("Ctrl+M".to_string()),
+                    enabled: true,
+                    checked: None,
+                    submenu: None,
+                },
             ]),
         },
         MenuItem {
@@ -148,6 +374,187 @@ pub fn get_default_editor_menus() -> Vec<MenuItem> {
                         },
                     ]),
                 },
+                MenuItem {
+                    id: "view.import_theme".to_string(),
+                    label: "Import Theme...".to_string(),
+                    action: true,
+                    shortcut: None,
+                    enabled: true,
+                    checked: None,
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "view.export_theme".to_string(),
+                    label: "Export Theme...".to_string(),
+                    action: true,
+                    shortcut: None,
+                    enabled: true,
+                    checked: None,
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "view.separator1".to_string(),
+                    label: "-".to_string(),
+                    action: false,
+                    shortcut: None,
+                    enabled: false,
+                    checked: None,
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "view.font".to_string(),
+                    label: "Font".to_string(),
+                    action: false,
+                    shortcut: None,
+                    enabled: true,
+                    checked: None,
+                    submenu: Some(font_submenu()),
+                },
+                MenuItem {
+                    id: "view.zoom".to_string(),
+                    label: "Zoom".to_string(),
+                    action: false,
+                    shortcut: None,
+                    enabled: true,
+                    checked: None,
+                    submenu: Some(vec![
+                        MenuItem {
+                            id: "view.zoom_in".to_string(),
+                            label: "Zoom In".to_string(),
+                            action: true,
+                            shortcut: Some("Ctrl+=".to_string()),
+                            enabled: true,
+                            checked: None,
+                            submenu: None,
+                        },
+                        MenuItem {
+                            id: "view.zoom_out".to_string(),
+                            label: "Zoom Out".to_string(),
+                            action: true,
+                            shortcut: Some("Ctrl+-".to_string()),
+                            enabled: true,
+                            checked: None,
+                            submenu: None,
+                        },
+                        MenuItem {
+                            id: "view.zoom_reset".to_string(),
+                            label: "Reset Zoom".to_string(),
+                            action: true,
+                            shortcut: Some("Ctrl+0".to_string()),
+                            enabled: true,
+                            checked: None,
+                            submenu: None,
+                        },
+                    ]),
+                },
+                MenuItem {
+                    id: "view.word_wrap".to_string(),
+                    label: "Word Wrap".to_string(),
+                    action: true,
+                    shortcut: None,
+                    enabled: true,
+                    checked: Some(false),
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "view.read_only".to_string(),
+                    label: "Read Only".to_string(),
+                    action: true,
+                    shortcut: None,
+                    enabled: true,
+                    checked: Some(false),
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "view.minimap".to_string(),
+                    label: "Minimap".to_string(),
+                    action: true,
+                    shortcut: None,
+                    enabled: true,
+                    checked: Some(false),
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "view.split_editor".to_string(),
+                    label: "Split Editor".to_string(),
+                    action: true,
+                    shortcut: Some("Ctrl+\\".to_string()),
+                    enabled: true,
+                    checked: Some(false),
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "view.stack_panes".to_string(),
+                    label: "Stack Split Panes".to_string(),
+                    action: true,
+                    shortcut: None,
+                    enabled: false,
+                    checked: Some(false),
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "view.reveal_hidden_chars".to_string(),
+                    label: "Reveal Hidden Characters".to_string(),
+                    action: true,
+                    shortcut: None,
+                    enabled: true,
+                    checked: Some(false),
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "view.render_whitespace".to_string(),
+                    label: "Render Whitespace".to_string(),
+                    action: true,
+                    shortcut: None,
+                    enabled: true,
+                    checked: Some(false),
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "view.spellcheck".to_string(),
+                    label: "Check Spelling".to_string(),
+                    action: true,
+                    shortcut: None,
+                    enabled: true,
+                    checked: Some(false),
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "view.separator2".to_string(),
+                    label: "-".to_string(),
+                    action: false,
+                    shortcut: None,
+                    enabled: false,
+                    checked: None,
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "view.show_changes".to_string(),
+                    label: "Show Changes Since Save".to_string(),
+                    action: true,
+                    shortcut: None,
+                    enabled: true,
+                    checked: None,
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "view.separator3".to_string(),
+                    label: "-".to_string(),
+                    action: false,
+                    shortcut: None,
+                    enabled: false,
+                    checked: None,
+                    submenu: None,
+                },
+                MenuItem {
+                    id: "view.settings".to_string(),
+                    label: "Settings...".to_string(),
+                    action: true,
+                    shortcut: Some("Ctrl+,".to_string()),
+                    enabled: true,
+                    checked: None,
+                    submenu: None,
+                },
             ]),
         },
         MenuItem {
@@ -169,5 +576,123 @@ pub fn get_default_editor_menus() -> Vec<MenuItem> {
                 },
             ]),
         },
+    ];
+
+    // Where the browser can't list folders there's no opening one
+    if !can_open_folders && let Some(file) = menus[0].submenu.as_mut() {
+        file.retain(|item| item.id != "file.open_folder");
+    }
+
+    menus
+}
+/// Items for the menu opened by right-clicking a tab, which offers to unpin
+/// it if it's `pinned`
+pub fn get_tab_context_menu(pinned: bool) -> Vec<MenuItem> {
+    vec![
+        MenuItem {
+            id: "tab.pin".to_string(),
+            label: if pinned { "Unpin" } else { "Pin" }.to_string(),
+            action: true,
+            shortcut: None,
+            enabled: true,
+            checked: None,
+            submenu: None,
+        },
+        MenuItem {
+            id: "tab.separator".to_string(),
+            label: "-".to_string(),
+            action: false,
+            shortcut: None,
+            enabled: false,
+            checked: None,
+            submenu: None,
+        },
+        MenuItem {
+            id: "tab.close".to_string(),
+            label: "Close".to_string(),
+            action: true,
+            shortcut: None,
+            enabled: true,
+            checked: None,
+            submenu: None,
+        },
+        MenuItem {
+            id: "tab.close_others".to_string(),
+            label: "Close Others".to_string(),
+            action: true,
+            shortcut: None,
+            enabled: true,
+            checked: None,
+            submenu: None,
+        },
+        MenuItem {
+            id: "tab.close_to_right".to_string(),
+            label: "Close to the Right".to_string(),
+            action: true,
+            shortcut: None,
+            enabled: true,
+            checked: None,
+            submenu: None,
+        },
+    ]
+}
+
+/// Items for the menu opened by right-clicking in the editor
+pub fn get_editor_context_menu() -> Vec<MenuItem> {
+    vec![
+        MenuItem {
+            id: "edit.cut".to_string(),
+            label: "Cut".to_string(),
+            action: true,
+            shortcut: Some("Ctrl+X".to_string()),
+            enabled: true,
+            checked: None,
+            submenu: None,
+        },
+        MenuItem {
+            id: "edit.copy".to_string(),
+            label: "Copy".to_string(),
+            action: true,
+            shortcut: Some("Ctrl+C".to_string()),
+            enabled: true,
+            checked: None,
+            submenu: None,
+        },
+        MenuItem {
+            id: "edit.paste".to_string(),
+            label: "Paste".to_string(),
+            action: true,
+            shortcut: Some("Ctrl+V".to_string()),
+            enabled: true,
+            checked: None,
+            submenu: None,
+        },
+        MenuItem {
+            id: "edit.select_all".to_string(),
+            label: "Select All".to_string(),
+            action: true,
+            shortcut: Some("Ctrl+A".to_string()),
+            enabled: true,
+            checked: None,
+            submenu: None,
+        },
+        MenuItem {
+            id: "edit.context_separator".to_string(),
+            label: "-".to_string(),
+            action: false,
+            shortcut: None,
+            enabled: false,
+            checked: None,
+            submenu: None,
+        },
+        MenuItem {
+            id: "edit.goto_definition".to_string(),
+            label: "Go to Definition".to_string(),
+            action: true,
+            shortcut: None,
+            enabled: false,
+            checked: None,
+            submenu: None,
+        },
     ]
-}
\ No newline at end of file
+}
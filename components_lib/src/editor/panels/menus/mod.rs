@@ -1,4 +1,8 @@
+pub mod context_menu;
+pub mod keymap;
 pub mod menu;
 pub mod menu_config;
 
-pub use menu::{MenuBar, MenuItem, MenuHandler};
\ No newline at end of file
+pub use context_menu::ContextMenu;
+pub use menu::{MenuBar, MenuItem, MenuHandler};
+pub use keymap::{KeyCombo, KeyOverrides, Keymap, menu_commands};
\ No newline at end of file
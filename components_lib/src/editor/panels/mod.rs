@@ -1,6 +1,18 @@
+pub mod banner;
+pub mod diff_view;
+pub mod file_tree;
+pub mod find_bar;
 pub mod menus;
+pub mod presence_panel;
 pub mod status_bar;
+pub mod tab_bar;
 pub mod toolbar;
 
+pub use banner::Banner;
+pub use diff_view::DiffView;
+pub use file_tree::{FileTree, FileTreeEntry, folder_entries};
+pub use find_bar::FindBar;
+pub use presence_panel::PresencePanel;
 pub use status_bar::StatusBar;
+pub use tab_bar::TabBar;
 pub use toolbar::Toolbar;
\ No newline at end of file
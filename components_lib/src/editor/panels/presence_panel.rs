@@ -0,0 +1,44 @@
+use dioxus::prelude::*;
+use crate::core::Theme;
+use crate::core::users::User;
+
+/// Everyone connected to the shared document
+#[component]
+pub fn PresencePanel(
+    theme: Theme,
+    users: Vec<User>,
+    // Ids of the users who edited in the last few seconds
+    editing: Vec<String>,
+) -> Element {
+    let style = format!(
+        "display: flex; align-items: center; gap: 0.75rem; padding: 0.25rem 0.5rem; font-size: 12px;
+         background-color: {}; color: {};",
+         theme.ui.toolbar_bg, theme.ui.toolbar_fg
+    );
+
+    rsx! {
+        div {
+            style: style,
+            span { "{users.len()} connected" }
+            for user in users {
+                div {
+                    key: "{user.id}",
+                    style: "display: flex; align-items: center; gap: 0.25rem;",
+                    span {
+                        style: format!(
+                            "display: inline-block; width: 0.6rem; height: 0.6rem; border-radius: 50%; background-color: {};",
+                            user.color()
+                        ),
+                    }
+                    "{user.name}"
+                    if editing.contains(&user.id) {
+                        span {
+                            style: "font-style: italic; opacity: 0.7;",
+                            " editing…"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,14 +1,36 @@
 use dioxus::prelude::*;
 use crate::core::Theme;
+use crate::editor::editor_core::{LANGUAGES, ValidationError, font::DEFAULT_ZOOM};
 
 #[component]
 pub fn StatusBar(
     theme: Theme,
     filename: Option<String>,
+    modified: bool,
     language: Option<String>,
+    encoding: String,
     cursor_line: usize,
+    // In grapheme clusters, so an emoji or an accented letter is one column
     cursor_column: usize,
     total_lines: usize,
+    word_count: usize,
+    char_count: usize,
+    // Whether the counts are for the selection rather than the whole document
+    counts_selection: bool,
+    hidden_char_count: usize,
+    // How the document is indented, e.g. "Spaces: 4"
+    indentation: String,
+    // Whether some lines are indented with tabs and others with spaces
+    mixed_indentation: bool,
+    // How far the text is zoomed, in percent. Only shown when it's zoomed.
+    zoom: u32,
+    // Whether the document parses, for languages that can be checked
+    validation: Option<Result<(), ValidationError>>,
+    // Shown for a moment when something asked for couldn't be done
+    message: Option<String>,
+    on_jump_to_hidden_char: EventHandler<()>,
+    on_jump_to_error: EventHandler<()>,
+    on_language_change: EventHandler<String>,
 ) -> Element {
     let style = format!(
         "display: flex; padding: 0.25rem 0.5rem; font-size: 12px;
@@ -17,14 +39,94 @@ pub fn StatusBar(
     );
 
     let display_filename = filename.clone().unwrap_or_else(|| "untitled".to_string());
-    let display_language = language.clone().unwrap_or_else(|| "plain text".to_string());
+    let language = language.unwrap_or_else(|| "plain".to_string());
+    let select_style = format!(
+        "margin-left: 0.25rem; padding: 0; border: none; font-size: 12px;
+         background-color: transparent; color: {}; cursor: pointer;",
+        theme.ui.statusbar_fg
+    );
+    let modified_marker = if modified { " ●" } else { "" };
+    let counts_suffix = if counts_selection { " selected" } else { "" };
 
     rsx! {
         div {
             style: style,
             div {
                 style: "flex: 1;",
-                "{display_filename} - {display_language}"
+                "{display_filename}{modified_marker} -"
+                select {
+                    style: select_style,
+                    title: "Highlight as",
+                    value: language.clone(),
+                    onchange: move |e| on_language_change.call(e.value()),
+
+                    for (id, name) in LANGUAGES {
+                        option {
+                            value: *id,
+                            selected: *id == language,
+                            style: "color: initial;",
+                            "{name}"
+                        }
+                    }
+                }
+            }
+            if let Some(message) = message {
+                div {
+                    style: "margin-right: 1rem; color: #E06C75;",
+                    role: "status",
+                    "{message}"
+                }
+            }
+            if hidden_char_count > 0 {
+                div {
+                    style: "margin-right: 1rem; cursor: pointer; text-decoration: underline;",
+                    title: "Jump to the next hidden character",
+                    onclick: move |_| on_jump_to_hidden_char.call(()),
+                    "⚠ {hidden_char_count} hidden chars"
+                }
+            }
+            match validation {
+                Some(Ok(())) => rsx! {
+                    div {
+                        style: "margin-right: 1rem; color: #98C379;",
+                        "✓ Valid"
+                    }
+                },
+                Some(Err(error)) => rsx! {
+                    div {
+                        style: "margin-right: 1rem; color: #E06C75; cursor: pointer;",
+                        title: "Jump to the error",
+                        onclick: move |_| on_jump_to_error.call(()),
+                        "✗ Ln {error.line + 1}, Col {error.column + 1}: {error.message}"
+                    }
+                },
+                None => rsx! {},
+            }
+            div {
+                style: "margin-right: 1rem;",
+                "{word_count} words, {char_count} chars{counts_suffix}"
+            }
+            if mixed_indentation {
+                div {
+                    style: "margin-right: 1rem;",
+                    title: "Some lines are indented with tabs and others with spaces",
+                    "⚠ Mixed indentation"
+                }
+            }
+            div {
+                style: "margin-right: 1rem;",
+                "{indentation}"
+            }
+            div {
+                style: "margin-right: 1rem;",
+                "{encoding}"
+            }
+            if zoom != DEFAULT_ZOOM {
+                div {
+                    style: "margin-right: 1rem;",
+                    title: "Ctrl+0 resets the zoom",
+                    "{zoom}%"
+                }
             }
             div {
                 "Ln {cursor_line + 1}, Col {cursor_column + 1} | {total_lines} lines"
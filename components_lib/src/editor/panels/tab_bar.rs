@@ -0,0 +1,196 @@
+use dioxus::prelude::*;
+use crate::core::Theme;
+use crate::editor::editor_core::OpenDocuments;
+use super::menus::{ContextMenu, MenuHandler, menu_config::get_tab_context_menu};
+
+/// Runs the tab context menu's items on the tab it was opened for
+#[derive(Clone, PartialEq)]
+struct TabMenuHandler {
+    index: usize,
+    pinned: bool,
+    // How many tabs "Close Others" and "Close to the Right" would close
+    others: usize,
+    to_right: usize,
+    on_pin: EventHandler<usize>,
+    on_close: EventHandler<usize>,
+    on_close_others: EventHandler<usize>,
+    on_close_to_right: EventHandler<usize>,
+}
+
+impl MenuHandler for TabMenuHandler {
+    fn handle_menu_action(&mut self, action_id: &str) {
+        match action_id {
+            "tab.pin" => self.on_pin.call(self.index),
+            "tab.close" => self.on_close.call(self.index),
+            "tab.close_others" => self.on_close_others.call(self.index),
+            "tab.close_to_right" => self.on_close_to_right.call(self.index),
+            _ => {}
+        }
+    }
+
+    fn is_item_enabled(&self, item_id: &str) -> bool {
+        match item_id {
+            // Pinned tabs have to be unpinned first
+            "tab.close" => !self.pinned,
+            "tab.close_others" => self.others > 0,
+            "tab.close_to_right" => self.to_right > 0,
+            _ => true,
+        }
+    }
+
+    fn is_item_checked(&self, _item_id: &str) -> Option<bool> {
+        None
+    }
+}
+
+/// A border on the side of the tab at `index` that a tab dropped on it would
+/// go, while one is being dragged over it
+fn drop_indicator(dragging: Option<usize>, drop_target: Option<usize>, index: usize, color: &str) -> String {
+    match (dragging, drop_target) {
+        (Some(from), Some(target)) if target == index && from != index => {
+            let offset = if from < index { "-2px" } else { "2px" };
+            format!("box-shadow: inset {offset} 0 0 {color};")
+        },
+        _ => String::new(),
+    }
+}
+
+#[component]
+pub fn TabBar(
+    theme: Theme,
+    documents: OpenDocuments,
+    on_select: EventHandler<usize>,
+    on_close: EventHandler<usize>,
+    // Close every tab but this one
+    on_close_others: EventHandler<usize>,
+    // Close every tab after this one
+    on_close_to_right: EventHandler<usize>,
+    on_new: EventHandler<()>,
+    // Pin or unpin this tab
+    on_pin: EventHandler<usize>,
+    // Move the tab being dragged from the first index to the second
+    on_move: EventHandler<(usize, usize)>,
+) -> Element {
+    // The tab whose context menu is open, and where, in viewport coordinates
+    let mut menu_at = use_signal(|| None::<(usize, f64, f64)>);
+    // The tab being dragged, and the one it's over
+    let mut dragging = use_signal(|| None::<usize>);
+    let mut drop_target = use_signal(|| None::<usize>);
+
+    let is_pinned = |index: usize| documents.documents().get(index).is_some_and(|document| document.pinned);
+
+    let style = format!(
+        "display: flex; overflow-x: auto; font-size: 12px;
+         background-color: {}; color: {};",
+         theme.ui.toolbar_bg, theme.ui.toolbar_fg
+    );
+
+    let button_style = "padding: 0 0.25rem; border: none; cursor: pointer;
+                        background-color: transparent; color: inherit;";
+
+    rsx! {
+        div {
+            style: style,
+            for (index, document) in documents.documents().iter().enumerate() {
+                div {
+                    key: "{document.id}",
+                    draggable: "true",
+                    style: format!(
+                        "display: flex; align-items: center; padding: 0.25rem {}; cursor: pointer;
+                         white-space: nowrap; background-color: {}; {}",
+                        if document.pinned { "0.375rem" } else { "0.5rem" },
+                        if index == documents.active_index() { theme.background.clone() } else { "transparent".to_string() },
+                        drop_indicator(dragging(), drop_target(), index, &theme.cursor),
+                    ),
+                    title: document.title(),
+                    aria_label: document.title(),
+                    onclick: move |_| on_select.call(index),
+                    oncontextmenu: move |event: Event<MouseData>| {
+                        event.prevent_default();
+                        let point = event.client_coordinates();
+                        menu_at.set(Some((index, point.x, point.y)));
+                    },
+                    ondragstart: move |event: Event<DragData>| {
+                        // Firefox won't start a drag without any data
+                        if let Some(transfer) = event.data().downcast::<web_sys::DragEvent>().and_then(|drag| drag.data_transfer()) {
+                            transfer.set_effect_allowed("move");
+                            let _ = transfer.set_data("text/plain", "");
+                        }
+                        dragging.set(Some(index));
+                    },
+                    ondragover: move |event: Event<DragData>| {
+                        // Only tabs are dropped here, files are opened by the page
+                        if dragging().is_some() {
+                            event.prevent_default();
+                            if drop_target() != Some(index) {
+                                drop_target.set(Some(index));
+                            }
+                        }
+                    },
+                    ondrop: move |event: Event<DragData>| {
+                        if let Some(from) = dragging() {
+                            event.prevent_default();
+                            dragging.set(None);
+                            drop_target.set(None);
+                            if from != index {
+                                on_move.call((from, index));
+                            }
+                        }
+                    },
+                    ondragend: move |_| {
+                        dragging.set(None);
+                        drop_target.set(None);
+                    },
+                    if document.pinned {
+                        span {
+                            "📌"
+                            if document.has_unsaved_changes() { "●" }
+                        }
+                    } else {
+                        span {
+                            "{document.title()}"
+                            if document.scratch { " (scratch)" }
+                            if document.has_unsaved_changes() { " ●" }
+                        }
+                        button {
+                            style: button_style,
+                            title: "Close",
+                            onclick: move |event| {
+                                // Don't also select the tab being closed
+                                event.stop_propagation();
+                                on_close.call(index);
+                            },
+                            "×"
+                        }
+                    }
+                }
+            }
+            button {
+                style: "{button_style} padding: 0.25rem 0.5rem;",
+                title: "New Tab",
+                onclick: move |_| on_new.call(()),
+                "+"
+            }
+        }
+
+        if let Some((index, x, y)) = menu_at() {
+            ContextMenu {
+                theme: theme.clone(),
+                items: get_tab_context_menu(is_pinned(index)),
+                handler: TabMenuHandler {
+                    index,
+                    pinned: is_pinned(index),
+                    others: documents.others(index).len(),
+                    to_right: documents.to_right(index).len(),
+                    on_pin,
+                    on_close,
+                    on_close_others,
+                    on_close_to_right,
+                },
+                x: x,
+                y: y,
+                on_close: move |_| menu_at.set(None),
+            }
+        }
+    }
+}
@@ -11,6 +11,8 @@ pub fn Toolbar(
     on_open_file: EventHandler<()>,
     on_save_file: EventHandler<()>,
     on_save_as: EventHandler<()>,
+    on_import_theme: EventHandler<()>,
+    on_export_theme: EventHandler<()>,
 ) -> Element {
     let toolbar_style = format!(
         "display: flex; padding: 0.5rem; gap: 0.5rem;
@@ -53,6 +55,16 @@ pub fn Toolbar(
                 onclick: move |_| on_save_as.call(()),
                 "Save As"
             }
+            button {
+                style: button_style.clone(),
+                onclick: move |_| on_import_theme.call(()),
+                "Import Theme"
+            }
+            button {
+                style: button_style.clone(),
+                onclick: move |_| on_export_theme.call(()),
+                "Export Theme"
+            }
 
             select {
                 style: select_style,
@@ -2,4 +2,4 @@ pub mod editor;
 pub mod core;
 
 // Rexport the editor component
-pub use crate::core::themes::{Theme, available_themes};
+pub use crate::core::themes::{Theme, available_themes, available_themes_with};